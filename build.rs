@@ -11,6 +11,7 @@ fn main() {
     iced_fontello::build("assets/icons.toml").expect("Generate icons font");
 
     println!("cargo::rerun-if-changed=assets/komorice.ico");
+    #[cfg(windows)]
     {
         let mut res = winres::WindowsResource::new();
         res.set_icon("assets/komorice.ico");
@@ -0,0 +1,331 @@
+//! The application specific configuration files, like `applications.json`, that the config points
+//! to with `app_specific_configuration_path`. They are where komorebi keeps the rules for each
+//! known application.
+//!
+//! The files are maps from the name of each application to its rules, grouped by what komorebi
+//! should do with the windows they match. They can also be written as YAML, with the same shape.
+
+use crate::apperror::{AppError, AppErrorKind};
+use crate::backup;
+use crate::config::paths;
+use crate::utils;
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use komorebi_client::{AppSpecificConfigurationPath, MatchingRule, StaticConfig};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// What komorebi does with the windows matched by a list of rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleKind {
+    Ignore,
+    Manage,
+    Floating,
+    TrayAndMultiWindow,
+    ObjectNameChange,
+    Layered,
+    BorderOverflow,
+    SlowApplication,
+}
+
+impl RuleKind {
+    pub const ALL: [RuleKind; 8] = [
+        RuleKind::Ignore,
+        RuleKind::Manage,
+        RuleKind::Floating,
+        RuleKind::TrayAndMultiWindow,
+        RuleKind::ObjectNameChange,
+        RuleKind::Layered,
+        RuleKind::BorderOverflow,
+        RuleKind::SlowApplication,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RuleKind::Ignore => "Windows that komorebi should not manage at all.",
+            RuleKind::Manage => {
+                "Windows that komorebi should manage even if it wouldn't by default."
+            }
+            RuleKind::Floating => "Windows that should be managed as floating windows.",
+            RuleKind::TrayAndMultiWindow => {
+                "Windows of applications that close or minimize to the tray or that open \
+                multiple windows."
+            }
+            RuleKind::ObjectNameChange => {
+                "Windows of applications that send EVENT_OBJECT_NAME_CHANGE on launch."
+            }
+            RuleKind::Layered => "Windows that have the WS_EXLAYERED extended window style.",
+            RuleKind::BorderOverflow => "Windows that draw over the border.",
+            RuleKind::SlowApplication => {
+                "Windows of applications that are slow to send their initial event notifications."
+            }
+        }
+    }
+}
+
+impl Display for RuleKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleKind::Ignore => write!(f, "Ignore"),
+            RuleKind::Manage => write!(f, "Manage"),
+            RuleKind::Floating => write!(f, "Float"),
+            RuleKind::TrayAndMultiWindow => write!(f, "Tray and Multi Window"),
+            RuleKind::ObjectNameChange => write!(f, "Object Name Change"),
+            RuleKind::Layered => write!(f, "Layered"),
+            RuleKind::BorderOverflow => write!(f, "Border Overflow"),
+            RuleKind::SlowApplication => write!(f, "Slow Application"),
+        }
+    }
+}
+
+/// The rules of a single application.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApplicationRules {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore: Option<Vec<MatchingRule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manage: Option<Vec<MatchingRule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none", alias = "float")]
+    pub floating: Option<Vec<MatchingRule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tray_and_multi_window: Option<Vec<MatchingRule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object_name_change: Option<Vec<MatchingRule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layered: Option<Vec<MatchingRule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_overflow: Option<Vec<MatchingRule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_application: Option<Vec<MatchingRule>>,
+    /// Any other options of the application, like `transparency_ignore`, which are kept as they
+    /// were.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+impl ApplicationRules {
+    pub fn rules(&self, kind: RuleKind) -> &Option<Vec<MatchingRule>> {
+        match kind {
+            RuleKind::Ignore => &self.ignore,
+            RuleKind::Manage => &self.manage,
+            RuleKind::Floating => &self.floating,
+            RuleKind::TrayAndMultiWindow => &self.tray_and_multi_window,
+            RuleKind::ObjectNameChange => &self.object_name_change,
+            RuleKind::Layered => &self.layered,
+            RuleKind::BorderOverflow => &self.border_overflow,
+            RuleKind::SlowApplication => &self.slow_application,
+        }
+    }
+
+    pub fn rules_mut(&mut self, kind: RuleKind) -> &mut Option<Vec<MatchingRule>> {
+        match kind {
+            RuleKind::Ignore => &mut self.ignore,
+            RuleKind::Manage => &mut self.manage,
+            RuleKind::Floating => &mut self.floating,
+            RuleKind::TrayAndMultiWindow => &mut self.tray_and_multi_window,
+            RuleKind::ObjectNameChange => &mut self.object_name_change,
+            RuleKind::Layered => &mut self.layered,
+            RuleKind::BorderOverflow => &mut self.border_overflow,
+            RuleKind::SlowApplication => &mut self.slow_application,
+        }
+    }
+
+    /// The amount of rules of `kind`.
+    pub fn count(&self, kind: RuleKind) -> usize {
+        self.rules(kind).as_ref().map_or(0, Vec::len)
+    }
+
+    /// Removes the lists left without any rule, which don't need to be on the file.
+    pub fn remove_empty(&mut self) {
+        for kind in RuleKind::ALL {
+            let rules = self.rules_mut(kind);
+            if rules.as_ref().is_some_and(Vec::is_empty) {
+                *rules = None;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// The format of the file at `path`, from its extension.
+    pub fn of(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml") => {
+                Format::Yaml
+            }
+            _ => Format::Json,
+        }
+    }
+}
+
+/// The contents of an application specific configuration file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Applications {
+    pub schema: Option<String>,
+    /// The rules of each application, by its name.
+    pub applications: BTreeMap<String, ApplicationRules>,
+}
+
+impl Applications {
+    pub fn parse(contents: &str, format: Format) -> Result<Self, String> {
+        let fields =
+            match format {
+                Format::Json => serde_json::from_str::<Map<String, Value>>(contents)
+                    .map_err(|e| e.to_string())?,
+                Format::Yaml => serde_yaml::from_str::<Map<String, Value>>(contents)
+                    .map_err(|e| e.to_string())?,
+            };
+        let mut applications = Applications::default();
+        for (name, value) in fields {
+            if name == "$schema" {
+                applications.schema = value.as_str().map(String::from);
+                continue;
+            }
+            let rules = serde_json::from_value::<ApplicationRules>(value)
+                .map_err(|e| format!("{name}: {e}"))?;
+            applications.applications.insert(name, rules);
+        }
+        Ok(applications)
+    }
+
+    pub fn to_string(&self, format: Format) -> Result<String, String> {
+        let mut fields = Map::new();
+        if let Some(schema) = &self.schema {
+            fields.insert("$schema".into(), Value::String(schema.clone()));
+        }
+        for (name, rules) in &self.applications {
+            let value = serde_json::to_value(rules).map_err(|e| e.to_string())?;
+            fields.insert(name.clone(), value);
+        }
+        match format {
+            Format::Json => serde_json::to_string_pretty(&fields).map_err(|e| e.to_string()),
+            Format::Yaml => serde_yaml::to_string(&fields).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// The paths of the application specific configuration files of `config`, as written on it.
+pub fn paths(config: &StaticConfig) -> Vec<PathBuf> {
+    match &config.app_specific_configuration_path {
+        Some(AppSpecificConfigurationPath::Single(path)) => vec![path.clone()],
+        Some(AppSpecificConfigurationPath::Multiple(paths)) => paths.clone(),
+        None => Vec::new(),
+    }
+}
+
+/// The `path` as written on the config with its variables resolved.
+fn resolve(path: &Path) -> Result<PathBuf, AppError> {
+    let resolved = paths::resolve(&path.to_string_lossy());
+    if resolved.missing.is_empty() {
+        Ok(resolved.path)
+    } else {
+        Err(AppError {
+            title: format!("Failed to find '{}'", path.display()),
+            description: Some(format!(
+                "These variables are not set: {}",
+                resolved.missing.join(", ")
+            )),
+            kind: AppErrorKind::Error,
+        })
+    }
+}
+
+/// Loads the application specific configuration file at `path`, as written on the config.
+pub async fn load(path: PathBuf) -> Result<Applications, AppError> {
+    let resolved = resolve(&path)?;
+    let contents = smol::fs::read_to_string(&resolved)
+        .await
+        .map_err(|e| AppError {
+            title: format!("Error opening '{}'", path.display()),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })?;
+    Applications::parse(&contents, Format::of(&resolved)).map_err(|e| AppError {
+        title: format!("Error reading '{}'", path.display()),
+        description: Some(e),
+        kind: AppErrorKind::Error,
+    })
+}
+
+/// Saves `applications` to the file at `path`, as written on the config, backing up the previous
+/// file first.
+pub async fn save(
+    path: PathBuf,
+    applications: Applications,
+    backups_to_keep: usize,
+) -> Result<(), AppError> {
+    let resolved = resolve(&path)?;
+    let format = Format::of(&resolved);
+    let contents = applications.to_string(format).map_err(|e| AppError {
+        title: format!("Error writing to '{}'", path.display()),
+        description: Some(e),
+        kind: AppErrorKind::Error,
+    })?;
+    let file_name = resolved.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into(),
+    );
+    backup::create_file(&resolved, backups_to_keep).await?;
+    utils::write_verified(
+        &resolved,
+        contents.as_bytes(),
+        &file_name,
+        move |temp_path| {
+            let contents = std::fs::read_to_string(&temp_path).map_err(|e| e.to_string())?;
+            Applications::parse(&contents, format).map(|_| ())
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APPLICATIONS: &str = r#"{
+        "$schema": "https://raw.githubusercontent.com/LGUG2Z/komorebi/master/schema.asc.json",
+        "Steam": {
+            "ignore": [{ "kind": "Class", "id": "vguiPopupWindow", "matching_strategy": "Equals" }],
+            "tray_and_multi_window": [{ "kind": "Exe", "id": "steam.exe", "matching_strategy": "Equals" }],
+            "transparency_ignore": [{ "kind": "Exe", "id": "steam.exe" }]
+        },
+        "1Password": {
+            "float": [{ "kind": "Exe", "id": "1Password.exe" }]
+        }
+    }"#;
+
+    #[test]
+    fn parses_the_rules_of_each_application() {
+        let applications = Applications::parse(APPLICATIONS, Format::Json).unwrap();
+        assert!(applications.schema.is_some());
+        let steam = &applications.applications["Steam"];
+        assert_eq!(steam.count(RuleKind::Ignore), 1);
+        assert_eq!(steam.count(RuleKind::TrayAndMultiWindow), 1);
+        assert!(steam.other.contains_key("transparency_ignore"));
+        assert_eq!(
+            applications.applications["1Password"].count(RuleKind::Floating),
+            1
+        );
+    }
+
+    #[test]
+    fn keeps_everything_when_written_back() {
+        let applications = Applications::parse(APPLICATIONS, Format::Json).unwrap();
+        for format in [Format::Json, Format::Yaml] {
+            let contents = applications.to_string(format).unwrap();
+            assert_eq!(
+                Applications::parse(&contents, format).unwrap(),
+                applications
+            );
+        }
+    }
+}
@@ -0,0 +1,453 @@
+use crate::{
+    apperror::{AppError, AppErrorKind},
+    config,
+    screen::ConfigType,
+    utils,
+};
+
+use std::path::{Path, PathBuf};
+
+/// Format used on the backup file names. It sorts chronologically as a string and has millisecond
+/// precision, so that saving twice on the same second doesn't overwrite the first backup.
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+const TIMESTAMP_LEN: usize = "YYYYmmdd-HHMMSS-fff".len();
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub config_type: ConfigType,
+    pub path: PathBuf,
+    pub timestamp: String,
+    pub size: u64,
+}
+
+impl Backup {
+    /// The timestamp in a more human readable format.
+    pub fn date(&self) -> String {
+        self.timestamp
+            .get(..TIMESTAMP_LEN)
+            .and_then(|date| chrono::NaiveDateTime::parse_from_str(date, TIMESTAMP_FORMAT).ok())
+            .map(|date| date.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| self.timestamp.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Added(String),
+    Removed(String),
+    Changed(String),
+}
+
+/// The folder where the backups of the `config_type` file at `path` are kept. Each file has its
+/// own folder, so that the backups of a loaded file or of a profile don't mix with the ones of
+/// the active config.
+pub fn backups_dir(config_type: ConfigType, path: &Path) -> Result<PathBuf, AppError> {
+    Ok(utils::data_dir()?
+        .join("backups")
+        .join(config_type.as_str())
+        .join(path_key(path)))
+}
+
+/// Copies the file at `path` to the backups folder, if it exists, and then removes the oldest
+/// backups so that only `keep` backups remain. If `keep` is `0` no backup is made.
+pub async fn create(config_type: ConfigType, path: &Path, keep: usize) -> Result<(), AppError> {
+    if keep == 0 || !matches!(smol::fs::metadata(path).await, Ok(m) if m.is_file()) {
+        return Ok(());
+    }
+
+    let dir = backups_dir(config_type, path)?;
+    smol::fs::create_dir_all(&dir).await.map_err(|e| AppError {
+        title: format!(
+            "Error creating backups folder for '{}'",
+            config_type.file_name()
+        ),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    })?;
+
+    let (stem, extension) = (file_stem(config_type), file_extension(config_type));
+    let backup_path = new_backup_path(&dir, stem, extension).await;
+    smol::fs::copy(path, &backup_path)
+        .await
+        .map_err(|e| AppError {
+            title: format!("Error creating backup of '{}'", config_type.file_name()),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })?;
+
+    prune(&dir, stem, extension, keep)
+        .await
+        .map_err(|e| AppError {
+            title: "Error reading backups folder".into(),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })
+}
+
+/// Copies the file at `path` to the backups folder, if it exists, like `create` but for any other
+/// file, such as the ones written when importing a bundle. Only the newest `keep` backups of it
+/// are kept.
+pub async fn create_file(path: &Path, keep: usize) -> Result<(), AppError> {
+    if keep == 0 || !matches!(smol::fs::metadata(path).await, Ok(m) if m.is_file()) {
+        return Ok(());
+    }
+
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "file".into(), |stem| stem.to_string_lossy().into_owned());
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let map_error = |e: std::io::Error| AppError {
+        title: format!("Error creating backup of '{}'", path.display()),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    };
+    let dir = utils::data_dir()?
+        .join("backups")
+        .join("files")
+        .join(path_key(path));
+    smol::fs::create_dir_all(&dir).await.map_err(map_error)?;
+
+    let backup_path = new_backup_path(&dir, &stem, &extension).await;
+    smol::fs::copy(path, &backup_path)
+        .await
+        .map_err(map_error)?;
+
+    prune(&dir, &stem, &extension, keep)
+        .await
+        .map_err(map_error)
+}
+
+/// Lists all the backups of the `config_type` file at `path`, from the newest to the oldest.
+pub async fn list(config_type: ConfigType, path: PathBuf) -> Result<Vec<Backup>, AppError> {
+    let dir = backups_dir(config_type, &path)?;
+    if !matches!(smol::fs::metadata(&dir).await, Ok(m) if m.is_dir()) {
+        return Ok(Vec::new());
+    }
+
+    let found = backups_in(&dir, file_stem(config_type), file_extension(config_type))
+        .await
+        .map_err(|e| AppError {
+            title: "Error reading backups folder".into(),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })?;
+    let mut backups = Vec::new();
+    for (path, timestamp) in found {
+        let size = smol::fs::metadata(&path)
+            .await
+            .map(|m| m.len())
+            .unwrap_or_default();
+        backups.push(Backup {
+            config_type,
+            path,
+            timestamp,
+            size,
+        });
+    }
+
+    Ok(backups)
+}
+
+/// A path on `dir` for a new backup of the file with `stem` and `extension`. A backup made on the
+/// same millisecond as another one gets a count after its timestamp, so it still sorts after it.
+async fn new_backup_path(dir: &Path, stem: &str, extension: &str) -> PathBuf {
+    let timestamp = chrono::Local::now().format(TIMESTAMP_FORMAT).to_string();
+    let mut backup_path = dir.join(backup_file_name(stem, extension, &timestamp));
+    let mut count = 0;
+    while smol::fs::metadata(&backup_path).await.is_ok() {
+        count += 1;
+        backup_path = dir.join(backup_file_name(
+            stem,
+            extension,
+            &format!("{timestamp}-{count}"),
+        ));
+    }
+    backup_path
+}
+
+/// Removes the oldest backups on `dir` of the file with `stem` and `extension`, so that only the
+/// newest `keep` remain.
+async fn prune(dir: &Path, stem: &str, extension: &str, keep: usize) -> std::io::Result<()> {
+    for (backup, _) in backups_in(dir, stem, extension).await?.iter().skip(keep) {
+        if let Err(e) = smol::fs::remove_file(backup).await {
+            println!(
+                "Failed to remove old backup '{}'.\nError: {}",
+                backup.display(),
+                e
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The backups on `dir` of the file with `stem` and `extension`, with their timestamps, from the
+/// newest to the oldest.
+async fn backups_in(
+    dir: &Path,
+    stem: &str,
+    extension: &str,
+) -> std::io::Result<Vec<(PathBuf, String)>> {
+    use smol::stream::StreamExt;
+
+    let mut entries = smol::fs::read_dir(dir).await?;
+    let mut backups = Vec::new();
+    while let Some(entry) = entries.next().await {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        let Some(timestamp) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| backup_timestamp(name, stem, extension))
+        else {
+            continue;
+        };
+        let timestamp = timestamp.to_string();
+        backups.push((path, timestamp));
+    }
+    backups.sort_by(|(_, a), (_, b)| timestamp_order(b).cmp(&timestamp_order(a)));
+    Ok(backups)
+}
+
+/// The timestamp of the backup named `name` of the file with `stem` and `extension`, if it is one.
+fn backup_timestamp<'a>(name: &'a str, stem: &str, extension: &str) -> Option<&'a str> {
+    name.strip_prefix(stem)?
+        .strip_prefix('-')?
+        .strip_suffix(extension)
+}
+
+/// The key to sort the backups by their `timestamp`, which can have a count after it when several
+/// backups were made on the same millisecond.
+fn timestamp_order(timestamp: &str) -> (&str, u32) {
+    match timestamp.split_at_checked(TIMESTAMP_LEN) {
+        Some((time, count)) => (
+            time,
+            count
+                .strip_prefix('-')
+                .and_then(|count| count.parse().ok())
+                .unwrap_or_default(),
+        ),
+        None => (timestamp, 0),
+    }
+}
+
+/// Restores `backup` by writing it over the file at `target`. The current `target` file is backed
+/// up first so that restoring can also be undone.
+pub async fn restore(backup: Backup, target: PathBuf, keep: usize) -> Result<(), AppError> {
+    let contents = smol::fs::read(&backup.path).await.map_err(|e| AppError {
+        title: format!("Error reading backup '{}'", backup.path.display()),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    })?;
+    create(backup.config_type, &target, keep).await?;
+    let config_type = backup.config_type;
+    utils::write_config(config_type, &target, &contents).await
+}
+
+/// Computes what would change on the file at `current` if `backup` was restored.
+pub async fn diff(backup: Backup, current: PathBuf) -> Result<Vec<DiffLine>, AppError> {
+    diff_files(backup.config_type, current, backup.path).await
+}
+
+/// Computes the differences from the file at `old` to the file at `new`. For a komorebi config
+/// the differences are computed field by field, for a whkdrc they are computed line by line.
+pub async fn diff_files(
+    config_type: ConfigType,
+    old: PathBuf,
+    new: PathBuf,
+) -> Result<Vec<DiffLine>, AppError> {
+    match config_type {
+        ConfigType::Komorebi => {
+            let old_config = config::load(old).await?;
+            let new_config = config::load(new).await?;
+            let sections = config::diff::diff(
+                &config::merge_default(old_config),
+                &config::merge_default(new_config),
+            );
+            Ok(sections
+                .iter()
+                .flat_map(|section| {
+                    section.changes.iter().map(move |change| {
+                        let path = change.relative_path(&section.section);
+                        let line = if path.is_empty() {
+                            section.section.to_string()
+                        } else {
+                            format!("{} > {}", section.section, path)
+                        };
+                        match change.kind {
+                            config::diff::ChangeKind::Added => DiffLine::Added(format!(
+                                "{line}: {}",
+                                config::diff::short_value(change.new.as_ref())
+                            )),
+                            config::diff::ChangeKind::Removed => DiffLine::Removed(format!(
+                                "{line}: {}",
+                                config::diff::short_value(change.old.as_ref())
+                            )),
+                            config::diff::ChangeKind::Changed => DiffLine::Changed(format!(
+                                "{line}: {} → {}",
+                                config::diff::short_value(change.old.as_ref()),
+                                config::diff::short_value(change.new.as_ref())
+                            )),
+                        }
+                    })
+                })
+                .collect())
+        }
+        ConfigType::Whkd => {
+            let read = |path: PathBuf| async move {
+                smol::fs::read_to_string(&path).await.map_err(|e| AppError {
+                    title: format!("Error reading '{}'", path.display()),
+                    description: Some(e.to_string()),
+                    kind: AppErrorKind::Error,
+                })
+            };
+            let old_contents = read(old).await?;
+            let new_contents = read(new).await?;
+            Ok(line_diff(&old_contents, &new_contents))
+        }
+    }
+}
+
+/// A simple line diff based on the longest common subsequence. Only the lines that differ are
+/// returned. Lines from `new` that are missing on `old` are added and vice versa.
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    lines.extend(new[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    lines
+}
+
+/// The name of the folder with the backups of the file at `path`: its file name followed by a hash
+/// of the whole path. The hash is computed here (FNV-1a) instead of using the std hasher, which
+/// isn't guaranteed to give the same result between versions.
+fn path_key(path: &Path) -> String {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut key = absolute.to_string_lossy().into_owned();
+    if cfg!(windows) {
+        key = key.to_lowercase();
+    }
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    let name = path
+        .file_name()
+        .map_or_else(|| "config".into(), |name| name.to_string_lossy());
+    format!("{name}-{hash:016x}")
+}
+
+fn backup_file_name(stem: &str, extension: &str, timestamp: &str) -> String {
+    format!("{stem}-{timestamp}{extension}")
+}
+
+fn file_stem(config_type: ConfigType) -> &'static str {
+    match config_type {
+        ConfigType::Komorebi => "komorebi",
+        ConfigType::Whkd => "whkdrc",
+    }
+}
+
+fn file_extension(config_type: ConfigType) -> &'static str {
+    match config_type {
+        ConfigType::Komorebi => ".json",
+        ConfigType::Whkd => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backups_of_different_files_are_kept_apart() {
+        let active = path_key(Path::new("/home/user/.config/komorebi/komorebi.json"));
+        let profile = path_key(Path::new("/home/user/profiles/work/komorebi.json"));
+        assert_ne!(active, profile);
+        assert!(active.starts_with("komorebi.json-"));
+        assert_eq!(
+            active,
+            path_key(Path::new("/home/user/.config/komorebi/komorebi.json"))
+        );
+    }
+
+    #[test]
+    fn dates_are_read_from_timestamps() {
+        let backup = |timestamp: &str| Backup {
+            config_type: ConfigType::Komorebi,
+            path: PathBuf::new(),
+            timestamp: timestamp.into(),
+            size: 0,
+        };
+        assert_eq!(backup("20250102-030405-678").date(), "2025-01-02 03:04:05");
+        assert_eq!(
+            backup("20250102-030405-678-1").date(),
+            "2025-01-02 03:04:05"
+        );
+        assert_eq!(backup("garbage").date(), "garbage");
+    }
+
+    #[test]
+    fn backups_of_the_same_millisecond_sort_after_the_first() {
+        let mut timestamps = vec![
+            "20250102-030405-678-1",
+            "20250102-030405-678-10",
+            "20250102-030405-678",
+            "20250102-030405-679",
+            "20250102-030405-678-2",
+        ];
+        timestamps.sort_by_key(|timestamp| timestamp_order(*timestamp));
+        assert_eq!(
+            timestamps,
+            [
+                "20250102-030405-678",
+                "20250102-030405-678-1",
+                "20250102-030405-678-2",
+                "20250102-030405-678-10",
+                "20250102-030405-679",
+            ]
+        );
+    }
+
+    #[test]
+    fn timestamps_are_read_from_backup_names() {
+        let timestamp = |name| backup_timestamp(name, "komorebi", ".json");
+        assert_eq!(
+            timestamp("komorebi-20250102-030405-678-1.json"),
+            Some("20250102-030405-678-1")
+        );
+        assert_eq!(timestamp("komorebi.bar-20250102-030405-678.json"), None);
+        assert_eq!(
+            backup_timestamp("whkdrc-20250102-030405-678", "whkdrc", ""),
+            Some("20250102-030405-678")
+        );
+    }
+}
@@ -0,0 +1,359 @@
+//! Portable bundles with a whole setup on a single zip archive.
+//!
+//! A bundle has the `komorebi.json` and `whkdrc` files on its root and every file referenced from
+//! the komorebi config (applications, bar configs, wallpapers and custom layouts) inside the
+//! `files` folder. The paths on the bundled `komorebi.json` are relative to the bundle, so they
+//! can be pointed to the komorebi config folder of whatever machine the bundle is imported on.
+
+use crate::{
+    apperror::{AppError, AppErrorKind},
+    backup, config,
+    screen::ConfigType,
+    utils,
+    whkd::{self, Whkdrc, unparser::unparse_whkdrc},
+};
+
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use komorebi_client::{AppSpecificConfigurationPath, PathExt, StaticConfig};
+
+const KOMOREBI_ENTRY: &str = "komorebi.json";
+const WHKD_ENTRY: &str = "whkdrc";
+const FILES_DIR: &str = "files";
+
+/// Exports `config` and `whkdrc` together with all the files referenced by `config` to a bundle
+/// at `target`. Returns the referenced files that couldn't be found, which keep their original
+/// path on the bundled config.
+pub async fn export(
+    mut config: StaticConfig,
+    whkdrc: Whkdrc,
+    target: PathBuf,
+) -> Result<Vec<PathBuf>, AppError> {
+    let mut files = Vec::new();
+    let mut missing = Vec::new();
+    let mut used_names = HashSet::new();
+    // The same file can be referenced more than once, like a wallpaper used on many workspaces.
+    let mut entries: HashMap<PathBuf, String> = HashMap::new();
+    for path in config_paths(&mut config) {
+        let resolved = path.clone().replace_env();
+        if let Some(entry) = entries.get(&resolved) {
+            *path = PathBuf::from(entry);
+            continue;
+        }
+        match smol::fs::read(&resolved).await {
+            Ok(contents) => {
+                let entry = bundle_entry(&resolved, &mut used_names);
+                files.push((entry.clone(), contents));
+                *path = PathBuf::from(&entry);
+                entries.insert(resolved, entry);
+            }
+            Err(_) if !missing.contains(&resolved) => missing.push(resolved),
+            Err(_) => {}
+        }
+    }
+
+    let komorebi_json = config::to_json(config)?;
+    let whkdrc = smol::unblock(move || unparse_whkdrc(&whkdrc)).await;
+
+    let target_clone = target.clone();
+    smol::unblock(move || {
+        let file = std::fs::File::create(&target_clone)?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(KOMOREBI_ENTRY, options)?;
+        zip.write_all(komorebi_json.as_bytes())?;
+        zip.start_file(WHKD_ENTRY, options)?;
+        zip.write_all(whkdrc.as_bytes())?;
+        for (entry, contents) in files {
+            zip.start_file(entry, options)?;
+            zip.write_all(&contents)?;
+        }
+        zip.finish()?;
+        Ok::<_, zip::result::ZipError>(())
+    })
+    .await
+    .map_err(|e| AppError {
+        title: format!("Error exporting bundle to '{}'", target.display()),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    })?;
+
+    Ok(missing)
+}
+
+/// A bundle read from a zip archive, ready to be imported.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub source: PathBuf,
+    /// The bundled config, with its paths already pointing to where the bundled files are written.
+    config: StaticConfig,
+    whkdrc: Option<Vec<u8>>,
+    /// The bundled files together with the path each one is written to.
+    files: Vec<(PathBuf, Vec<u8>)>,
+    /// Every file that importing the bundle writes, and whether it already exists and would be
+    /// overwritten.
+    pub writes: Vec<(PathBuf, bool)>,
+}
+
+/// Reads the bundle at `source` so it can be imported. Nothing is written yet.
+pub async fn read(source: PathBuf) -> Result<Bundle, AppError> {
+    let source_clone = source.clone();
+    let entries = smol::unblock(move || read_entries(&source_clone))
+        .await
+        .map_err(|e| AppError {
+            title: format!("Error reading bundle '{}'", source.display()),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })?;
+
+    let komorebi_home = config::home_path()?;
+    let mut bundle = unpack(source, entries, &komorebi_home)?;
+    for path in config_paths(&mut bundle.config) {
+        if path.starts_with(&komorebi_home) {
+            *path = config::unresolve_home_path(path.clone());
+        }
+    }
+
+    let mut targets = vec![config::config_path()?];
+    if bundle.whkdrc.is_some() {
+        targets.push(whkd::config_path()?);
+    }
+    targets.extend(bundle.files.iter().map(|(target, _)| target.clone()));
+    for target in targets {
+        let exists = smol::fs::metadata(&target).await.is_ok();
+        bundle.writes.push((target, exists));
+    }
+
+    Ok(bundle)
+}
+
+/// Creates the bundle read from `source` with its zip `entries`. The bundled files are written to
+/// `komorebi_home`, where the paths of the bundled config point to as well.
+fn unpack(
+    source: PathBuf,
+    entries: Vec<(PathBuf, Vec<u8>)>,
+    komorebi_home: &Path,
+) -> Result<Bundle, AppError> {
+    let mut komorebi_json = None;
+    let mut whkdrc = None;
+    let mut files = Vec::new();
+    for (entry, contents) in entries {
+        match entry.to_str() {
+            Some(KOMOREBI_ENTRY) => komorebi_json = Some(contents),
+            Some(WHKD_ENTRY) => whkdrc = Some(contents),
+            _ if entry.starts_with(FILES_DIR) => files.push((entry, contents)),
+            _ => {}
+        }
+    }
+    let komorebi_json = komorebi_json.ok_or_else(|| AppError {
+        title: format!("Invalid bundle '{}'", source.display()),
+        description: Some(format!(
+            "The bundle doesn't have a '{KOMOREBI_ENTRY}' file."
+        )),
+        kind: AppErrorKind::Error,
+    })?;
+    let mut config: StaticConfig =
+        serde_json::from_slice(&komorebi_json).map_err(|e| AppError {
+            title: format!(
+                "Invalid '{KOMOREBI_ENTRY}' on bundle '{}'",
+                source.display()
+            ),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })?;
+
+    let files = files
+        .into_iter()
+        .map(|(entry, contents)| {
+            let target = komorebi_home.join(entry.strip_prefix(FILES_DIR).unwrap_or(&entry));
+            (target, contents)
+        })
+        .collect::<Vec<_>>();
+    for path in config_paths(&mut config) {
+        if let Ok(rest) = path.strip_prefix(FILES_DIR) {
+            *path = komorebi_home.join(rest);
+        }
+    }
+
+    Ok(Bundle {
+        source,
+        config,
+        whkdrc,
+        files,
+        writes: Vec::new(),
+    })
+}
+
+/// Imports `bundle`. The bundled files are written to the komorebi config folder and the bundled
+/// `komorebi.json` and `whkdrc` replace the active ones. Every file that is overwritten is backed
+/// up first.
+pub async fn import(bundle: Bundle, backups_to_keep: usize) -> Result<(), AppError> {
+    for (target, contents) in bundle.files {
+        if let Some(parent) = target.parent() {
+            let _ = smol::fs::create_dir_all(parent).await;
+        }
+        backup::create_file(&target, backups_to_keep).await?;
+        let file_name = target.file_name().map_or_else(
+            || target.display().to_string(),
+            |name| name.to_string_lossy().into(),
+        );
+        utils::write_verified(&target, &contents, &file_name, |_| Ok(())).await?;
+    }
+
+    let komorebi_path = config::config_path()?;
+    backup::create(ConfigType::Komorebi, &komorebi_path, backups_to_keep).await?;
+    let komorebi_json = config::to_json(config::merge_default(bundle.config))?;
+    utils::write_config(
+        ConfigType::Komorebi,
+        &komorebi_path,
+        komorebi_json.as_bytes(),
+    )
+    .await?;
+
+    if let Some(whkdrc) = bundle.whkdrc {
+        let whkd_path = whkd::config_path()?;
+        backup::create(ConfigType::Whkd, &whkd_path, backups_to_keep).await?;
+        utils::write_config(ConfigType::Whkd, &whkd_path, &whkdrc).await?;
+    }
+
+    Ok(())
+}
+
+/// All the file paths referenced by `config`.
+fn config_paths(config: &mut StaticConfig) -> Vec<&mut PathBuf> {
+    let mut paths = Vec::new();
+    match &mut config.app_specific_configuration_path {
+        Some(AppSpecificConfigurationPath::Single(path)) => paths.push(path),
+        Some(AppSpecificConfigurationPath::Multiple(list)) => paths.extend(list.iter_mut()),
+        None => {}
+    }
+    paths.extend(config.bar_configurations.iter_mut().flatten());
+    for monitor in config.monitors.iter_mut().flatten() {
+        if let Some(wallpaper) = &mut monitor.wallpaper {
+            paths.push(&mut wallpaper.path);
+        }
+        for workspace in &mut monitor.workspaces {
+            if let Some(wallpaper) = &mut workspace.wallpaper {
+                paths.push(&mut wallpaper.path);
+            }
+            paths.extend(workspace.custom_layout.as_mut());
+            paths.extend(
+                workspace
+                    .custom_layout_rules
+                    .iter_mut()
+                    .flat_map(|rules| rules.values_mut()),
+            );
+        }
+    }
+    paths
+}
+
+/// Returns the name of the bundle entry for the file at `path`, making sure it isn't used yet.
+fn bundle_entry(path: &Path, used_names: &mut HashSet<String>) -> String {
+    let stem = path
+        .file_stem()
+        .map_or_else(|| "file".into(), |stem| stem.to_string_lossy());
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let mut name = format!("{stem}{extension}");
+    let mut idx = 1;
+    while used_names.contains(&name.to_lowercase()) {
+        name = format!("{stem}-{idx}{extension}");
+        idx += 1;
+    }
+    used_names.insert(name.to_lowercase());
+    format!("{FILES_DIR}/{name}")
+}
+
+fn read_entries(path: &Path) -> Result<Vec<(PathBuf, Vec<u8>)>, zip::result::ZipError> {
+    let file = std::fs::File::open(path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    let mut entries = Vec::new();
+    for idx in 0..archive.len() {
+        let mut entry = archive.by_index(idx)?;
+        // Entries with paths that would escape the bundle are ignored.
+        let Some(name) = entry.enclosed_name() else {
+            continue;
+        };
+        if entry.is_dir() {
+            continue;
+        }
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        entries.push((name, contents));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::empty_config;
+    use crate::whkd::DEFAULT_WHKDRC;
+
+    /// An empty folder for the test with `name`.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn exported_bundles_are_read_back() {
+        let dir = test_dir("komorice-bundle-test");
+        let applications = dir.join("applications.json");
+        std::fs::write(&applications, "[]").unwrap();
+        let missing = dir.join("missing.json");
+        let mut config = empty_config();
+        config.app_specific_configuration_path =
+            Some(AppSpecificConfigurationPath::Single(applications.clone()));
+        config.bar_configurations = Some(vec![applications.clone(), missing.clone()]);
+        let target = dir.join("bundle.zip");
+
+        let not_found =
+            smol::block_on(export(config, DEFAULT_WHKDRC.clone(), target.clone())).unwrap();
+        assert_eq!(not_found, vec![missing.clone()]);
+
+        let home = dir.join("home");
+        let bundle = unpack(target.clone(), read_entries(&target).unwrap(), &home).unwrap();
+        let imported = home.join("applications.json");
+        assert_eq!(bundle.files, vec![(imported.clone(), b"[]".to_vec())]);
+        assert_eq!(
+            bundle.config.app_specific_configuration_path,
+            Some(AppSpecificConfigurationPath::Single(imported.clone()))
+        );
+        assert_eq!(
+            bundle.config.bar_configurations,
+            Some(vec![imported, missing])
+        );
+        assert_eq!(
+            bundle.whkdrc,
+            Some(unparse_whkdrc(&DEFAULT_WHKDRC).into_bytes())
+        );
+    }
+
+    #[test]
+    fn entries_outside_of_the_bundle_are_ignored() {
+        let path = test_dir("komorice-bundle-escape-test").join("bundle.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("files/layout.json", options).unwrap();
+        zip.write_all(b"{}").unwrap();
+        zip.start_file("../layout.json", options).unwrap();
+        zip.write_all(b"{}").unwrap();
+        zip.finish().unwrap();
+
+        let names = read_entries(&path)
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![PathBuf::from("files/layout.json")]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Headless commands that run without opening the Komorice window, so the config files can be
+//! checked from scripts and pre-commit hooks.
+
+use crate::{
+    KOMOREBI_VERSION,
+    apperror::{AppError, AppErrorKind},
+    backup::{self, DiffLine},
+    config::{self, DEFAULT_CONFIG, migration::Version},
+    screen::ConfigType,
+    utils,
+    whkd::{self, DEFAULT_WHKDRC, unparser::unparse_whkdrc},
+};
+
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+Usage: komorice [COMMAND]
+
+Runs the Komorice GUI when no command is given or the first argument isn't one of these commands.
+
+Commands:
+  validate <FILE>...     Loads each file and reports any errors found
+  fmt [--check|--write] [--keep-comments] <FILE>...
+                         Prints each file the way Komorice would save it. With '--check' it only
+                         reports the files that aren't formatted and with '--write' it formats
+                         them in place. Komorebi configs are written as a whole new file unless
+                         '--keep-comments' is used, which only edits the values that change and
+                         keeps their comments, unknown keys and formatting
+  diff <OLD> <NEW>       Prints the differences between two config files
+  defaults [FILE]        Prints the komorebi config with all the default values filled in. If a
+                         FILE is given, its values are used over the defaults. With '--whkd' it
+                         prints the default whkdrc instead
+  help                   Prints this message
+
+Options:
+  --komorebi             Treat the files as komorebi configs
+  --whkd                 Treat the files as whkdrc configs
+
+Files ending with '.json' are treated as komorebi configs and any other files as whkdrc configs,
+unless '--komorebi' or '--whkd' is used.
+
+Exit codes: 0 on success, 1 if some file has errors, isn't formatted or has differences, 2 if
+the command is wrong.";
+
+const COMMANDS: &[&str] = &[
+    "validate", "fmt", "diff", "defaults", "help", "--help", "-h",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FmtMode {
+    Print,
+    Check,
+    Write,
+}
+
+/// Runs the command given by `args`, if any, and returns its exit code. Returns `None` if there
+/// is no command, in which case the GUI should be started.
+pub fn run(args: Vec<String>) -> Option<i32> {
+    let command = args
+        .first()
+        .filter(|arg| COMMANDS.contains(&arg.as_str()))?
+        .clone();
+    attach_console();
+
+    let mut config_type = None;
+    let mut fmt_mode = FmtMode::Print;
+    let mut keep_comments = false;
+    let mut files = Vec::new();
+    for arg in &args[1..] {
+        match arg.as_str() {
+            "--komorebi" => config_type = Some(ConfigType::Komorebi),
+            "--whkd" => config_type = Some(ConfigType::Whkd),
+            "--check" if command == "fmt" => fmt_mode = FmtMode::Check,
+            "--write" if command == "fmt" => fmt_mode = FmtMode::Write,
+            "--keep-comments" if command == "fmt" => keep_comments = true,
+            _ if arg.starts_with("--") => {
+                return Some(usage_error(&format!("unknown option '{arg}'")));
+            }
+            _ => files.push(PathBuf::from(arg)),
+        }
+    }
+    let type_of = |path: &PathBuf| {
+        config_type.unwrap_or_else(|| {
+            if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+            {
+                ConfigType::Komorebi
+            } else {
+                ConfigType::Whkd
+            }
+        })
+    };
+
+    let code = match command.as_str() {
+        "validate" if !files.is_empty() => smol::block_on(async {
+            let mut code = 0;
+            for path in files {
+                match validate(type_of(&path), path.clone()).await {
+                    Ok(()) => println!("{}: ok", path.display()),
+                    Err(apperror) => {
+                        eprintln!("{}: {}", path.display(), describe(&apperror));
+                        code = 1;
+                    }
+                }
+            }
+            code
+        }),
+        "fmt" if !files.is_empty() => smol::block_on(async {
+            let mut code = 0;
+            for path in files {
+                match fmt(type_of(&path), path.clone(), fmt_mode, keep_comments).await {
+                    Ok(true) => {}
+                    Ok(false) => code = 1,
+                    Err(apperror) => {
+                        eprintln!("{}: {}", path.display(), describe(&apperror));
+                        code = 1;
+                    }
+                }
+            }
+            code
+        }),
+        "diff" if files.len() == 2 => {
+            let config_type = type_of(&files[0]);
+            let (old, new) = (files[0].clone(), files[1].clone());
+            match smol::block_on(backup::diff_files(config_type, old, new)) {
+                Ok(lines) if lines.is_empty() => 0,
+                Ok(lines) => {
+                    for line in lines {
+                        match line {
+                            DiffLine::Added(line) => println!("+ {line}"),
+                            DiffLine::Removed(line) => println!("- {line}"),
+                            DiffLine::Changed(line) => println!("~ {line}"),
+                        }
+                    }
+                    1
+                }
+                Err(apperror) => {
+                    eprintln!("{}", describe(&apperror));
+                    1
+                }
+            }
+        }
+        "defaults" if files.len() <= 1 => {
+            let res = smol::block_on(async {
+                match (files.pop(), config_type) {
+                    (None, Some(ConfigType::Whkd)) => Ok(unparse_whkdrc(&DEFAULT_WHKDRC)),
+                    (None, _) => Ok(serialize_merged(DEFAULT_CONFIG.clone())),
+                    (Some(path), _) if type_of(&path) == ConfigType::Whkd => Err(AppError {
+                        title: "The 'defaults' command only works with komorebi configs".into(),
+                        description: None,
+                        kind: AppErrorKind::Error,
+                    }),
+                    (Some(path), _) => config::load(path)
+                        .await
+                        .map(|config| serialize_merged(config::merge_default(config))),
+                }
+            });
+            match res {
+                Ok(contents) => {
+                    println!("{contents}");
+                    0
+                }
+                Err(apperror) => {
+                    eprintln!("{}", describe(&apperror));
+                    1
+                }
+            }
+        }
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            0
+        }
+        _ => usage_error(&format!("wrong arguments for '{command}'")),
+    };
+
+    Some(code)
+}
+
+async fn validate(config_type: ConfigType, path: PathBuf) -> Result<(), AppError> {
+    match config_type {
+        ConfigType::Komorebi => {
+            let (_, violations) = config::load_validated(path).await?;
+            if violations.is_empty() {
+                return Ok(());
+            }
+            Err(AppError {
+                title: format!("doesn't match the komorebi {} schema", *KOMOREBI_VERSION),
+                description: Some(
+                    violations
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+                kind: AppErrorKind::Warning,
+            })
+        }
+        ConfigType::Whkd => whkd::load(path).await.map(|_| ()),
+    }
+}
+
+/// Formats the file at `path` according to `mode`, the same way Komorice saves it as a whole new
+/// file, or the way it edits an existing one with `keep_comments`. Returns `false` if the file
+/// isn't formatted when checking.
+async fn fmt(
+    config_type: ConfigType,
+    path: PathBuf,
+    mode: FmtMode,
+    keep_comments: bool,
+) -> Result<bool, AppError> {
+    let current = smol::fs::read_to_string(&path).await.unwrap_or_default();
+    let formatted = match config_type {
+        ConfigType::Komorebi => {
+            let config = config::merge_default(config::load(path.clone()).await?);
+            if keep_comments {
+                let (formatted, warnings) =
+                    config::file_contents(config, Some(&current), Version::current())?;
+                for warning in warnings {
+                    eprintln!("{}: {}", path.display(), describe(&warning));
+                }
+                formatted
+            } else {
+                config::to_json(config)?
+            }
+        }
+        ConfigType::Whkd => unparse_whkdrc(&whkd::load(path.clone()).await?),
+    };
+    match mode {
+        FmtMode::Print => print!("{formatted}"),
+        FmtMode::Check => {
+            if current != formatted {
+                println!("{}: not formatted", path.display());
+                return Ok(false);
+            }
+        }
+        FmtMode::Write => {
+            if current != formatted {
+                utils::write_config(config_type, &path, formatted.as_bytes()).await?;
+                println!("{}: formatted", path.display());
+            }
+        }
+    }
+    Ok(true)
+}
+
+fn serialize_merged(config: komorebi_client::StaticConfig) -> String {
+    serde_json::to_string_pretty(&config).unwrap_or_default()
+}
+
+fn describe(apperror: &AppError) -> String {
+    match &apperror.description {
+        Some(description) => format!("{}\n{description}", apperror.title),
+        None => apperror.title.clone(),
+    }
+}
+
+fn usage_error(message: &str) -> i32 {
+    eprintln!("error: {message}\n\n{USAGE}");
+    2
+}
+
+/// On release builds the app uses the windows subsystem, so it has no console of its own. Attach
+/// to the console of the parent process so the output of the commands can be seen.
+fn attach_console() {
+    #[cfg(all(windows, not(debug_assertions)))]
+    unsafe {
+        windows_sys::Win32::System::Console::AttachConsole(
+            windows_sys::Win32::System::Console::ATTACH_PARENT_PROCESS,
+        );
+    }
+}
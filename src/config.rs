@@ -1,13 +1,21 @@
+pub mod diff;
+mod document;
+pub mod merge;
+pub mod migration;
+pub mod paths;
+pub mod schema;
+
 use crate::{
     KOMOREBI_VERSION, Message,
     apperror::{AppError, AppErrorKind},
-    screen::{
-        monitors::DisplayInfo,
-        wallpaper::{DEFAULT_THEME_OPTIONS, DEFAULT_WALLPAPER},
-    },
+    backup,
+    platform::{self, KomorebiIpc, Native, new_debouncer},
+    screen::{ConfigType, monitors::DisplayInfo, wallpaper::DEFAULT_WALLPAPER},
+    utils,
 };
 
-use std::sync::Arc;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use std::{collections::HashMap, path::PathBuf};
 
@@ -20,15 +28,16 @@ use komorebi_client::{
     BorderImplementation, BorderStyle, Colour, CrossBoundaryBehaviour, DefaultLayout,
     FloatingLayerBehaviour, HidingBehaviour, KomorebiTheme, MonitorConfig, MoveBehaviour,
     OperationBehaviour, PerAnimationPrefixConfig, Placement, PredefinedAspectRatio, Rgb,
-    StackbarConfig, StackbarLabel, StackbarMode, StaticConfig, TabsConfig, ThemeOptions, Wallpaper,
+    StackbarConfig, StackbarLabel, StackbarMode, StaticConfig, TabsConfig,
     WindowContainerBehaviour, WindowHandlingBehaviour, WorkspaceConfig,
 };
 use komorebi_themes::{Base16, Base16Value, Catppuccin, CatppuccinValue};
 use lazy_static::lazy_static;
+use migration::{TargetVersion, Version};
 use notify_debouncer_mini::{
-    DebounceEventResult, DebouncedEvent, DebouncedEventKind, Debouncer, new_debouncer,
-    notify::{ReadDirectoryChangesWatcher, RecursiveMode},
+    DebounceEventResult, DebouncedEvent, DebouncedEventKind, Debouncer, notify::RecursiveMode,
 };
+use serde_json::{Map, Value};
 use smol::channel::{self, Receiver};
 
 lazy_static! {
@@ -50,7 +59,11 @@ lazy_static! {
         focus_follows_mouse: None,
         mouse_follows_focus: Some(true),
         app_specific_configuration_path: Some(AppSpecificConfigurationPath::Single(
-            unresolve_home_path(home_path().0.join("applications.json"))
+            unresolve_home_path(
+                home_path()
+                    .unwrap_or_else(|_| PathBuf::from("$Env:USERPROFILE"))
+                    .join("applications.json")
+            )
         )),
         border_width: Some(8),
         border_offset: Some(-1),
@@ -261,921 +274,176 @@ pub fn fill_monitors(config: &mut StaticConfig, monitors: &HashMap<usize, Displa
     true
 }
 
-/// Merge the `DEFAULT_CONFIG` values on `config`. For each value that is `None` on `config`
-/// it uses the corresponding value from `DEFAULT_CONFIG`.
-/// It returns a new `StaticConfig` with the result.
-pub fn merge_default(config: StaticConfig) -> StaticConfig {
-    StaticConfig {
-        invisible_borders: config
-            .invisible_borders
-            .or(DEFAULT_CONFIG.invisible_borders),
-        minimum_window_width: config
-            .minimum_window_width
-            .or(DEFAULT_CONFIG.minimum_window_width),
-        minimum_window_height: config
-            .minimum_window_height
-            .or(DEFAULT_CONFIG.minimum_window_height),
-        resize_delta: config.resize_delta.or(DEFAULT_CONFIG.resize_delta),
-        window_container_behaviour: config
-            .window_container_behaviour
-            .or(DEFAULT_CONFIG.window_container_behaviour),
-        float_override: config.float_override.or(DEFAULT_CONFIG.float_override),
-        floating_layer_behaviour: config
-            .floating_layer_behaviour
-            .or(DEFAULT_CONFIG.floating_layer_behaviour),
-        toggle_float_placement: config
-            .toggle_float_placement
-            .or(DEFAULT_CONFIG.toggle_float_placement),
-        floating_layer_placement: config
-            .floating_layer_placement
-            .or(DEFAULT_CONFIG.floating_layer_placement),
-        float_override_placement: config
-            .float_override_placement
-            .or(DEFAULT_CONFIG.float_override_placement),
-        float_rule_placement: config
-            .float_rule_placement
-            .or(DEFAULT_CONFIG.float_rule_placement),
-        cross_monitor_move_behaviour: config
-            .cross_monitor_move_behaviour
-            .or(DEFAULT_CONFIG.cross_monitor_move_behaviour),
-        cross_boundary_behaviour: config
-            .cross_boundary_behaviour
-            .or(DEFAULT_CONFIG.cross_boundary_behaviour),
-        unmanaged_window_operation_behaviour: config
-            .unmanaged_window_operation_behaviour
-            .or(DEFAULT_CONFIG.unmanaged_window_operation_behaviour),
-        focus_follows_mouse: config
-            .focus_follows_mouse
-            .or(DEFAULT_CONFIG.focus_follows_mouse),
-        mouse_follows_focus: config
-            .mouse_follows_focus
-            .or(DEFAULT_CONFIG.mouse_follows_focus),
-        app_specific_configuration_path: config
-            .app_specific_configuration_path
-            .as_ref()
-            .cloned()
-            .map(|asc| match asc {
-                AppSpecificConfigurationPath::Single(path) => {
-                    AppSpecificConfigurationPath::Single(unresolve_home_path(path))
-                }
-                AppSpecificConfigurationPath::Multiple(paths) => {
-                    AppSpecificConfigurationPath::Multiple(
-                        paths.into_iter().map(unresolve_home_path).collect(),
-                    )
-                }
-            })
-            .or(DEFAULT_CONFIG.app_specific_configuration_path.clone()),
-        border_width: config.border_width.or(DEFAULT_CONFIG.border_width),
-        border_offset: config.border_offset.or(DEFAULT_CONFIG.border_offset),
-        border: config.border.or(DEFAULT_CONFIG.border),
-        border_colours: config
-            .border_colours
-            .map(|bc| BorderColours {
-                single: bc.single.or(DEFAULT_CONFIG
-                    .border_colours
-                    .as_ref()
-                    .and_then(|bc| bc.single)),
-                stack: bc.stack.or(DEFAULT_CONFIG
-                    .border_colours
-                    .as_ref()
-                    .and_then(|bc| bc.stack)),
-                monocle: bc.monocle.or(DEFAULT_CONFIG
-                    .border_colours
-                    .as_ref()
-                    .and_then(|bc| bc.monocle)),
-                floating: bc.floating.or(DEFAULT_CONFIG
-                    .border_colours
-                    .as_ref()
-                    .and_then(|bc| bc.floating)),
-                unfocused: bc.unfocused.or(DEFAULT_CONFIG
-                    .border_colours
-                    .as_ref()
-                    .and_then(|bc| bc.unfocused)),
-                unfocused_locked: bc.unfocused_locked.or(DEFAULT_CONFIG
-                    .border_colours
-                    .as_ref()
-                    .and_then(|bc| bc.unfocused_locked)),
+/// Fields that are always written to the config file, even when they have the default value. The
+/// default `app_specific_configuration_path` is only a default for Komorice, komorebi doesn't use
+/// any applications file when this field is missing.
+const ALWAYS_WRITTEN: &[&str] = &["app_specific_configuration_path"];
+
+lazy_static! {
+    static ref DEFAULTS: Defaults = {
+        let wallpaper = || {
+            let wallpaper = Defaults::new(&*DEFAULT_WALLPAPER, &["path"]);
+            let theme_options = wallpaper.field("theme_options", &[]);
+            wallpaper.with("theme_options", theme_options)
+        };
+        // A workspace without `layout` uses its `custom_layout`, so it can't be filled in.
+        let workspace = Defaults::new(&*DEFAULT_WORKSPACE_CONFIG, &["name", "layout"])
+            .with("wallpaper", wallpaper());
+        let monitor = Defaults::new(&*DEFAULT_MONITOR_CONFIG, &["workspaces"])
+            .with("workspaces", workspace)
+            .with("wallpaper", wallpaper());
+
+        let config = Defaults::new(&*DEFAULT_CONFIG, &[]);
+        let border_colours = config.field("border_colours", &[]);
+        let stackbar = config.field("stackbar", &[]);
+        let tabs = stackbar.field("tabs", &[]);
+        let animation = config.field("animation", &["enabled"]);
+        config
+            .with("border_colours", border_colours)
+            .with("stackbar", stackbar.with("tabs", tabs))
+            .with("animation", animation)
+            .with("monitors", monitor)
+    };
+}
+
+/// The default values of some part of the config, as serialized values.
+struct Defaults {
+    /// The default value of each field. The fields that are `None` by default and the required
+    /// fields, which are never filled in or removed, aren't here.
+    values: Map<String, Value>,
+    /// The default value of each required field. They are only used to tell whether a nested
+    /// config is the same as its default one as a whole.
+    required: Map<String, Value>,
+    /// The defaults for the fields with nested configs, or lists of them, which are merged field
+    /// by field instead of as a whole.
+    nested: Vec<(&'static str, Defaults)>,
+}
+
+impl Defaults {
+    fn new(value: &impl serde::Serialize, required: &[&str]) -> Self {
+        let (required, values) = match serde_json::to_value(value) {
+            Ok(Value::Object(map)) => map
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .partition(|(key, _)| required.contains(&key.as_str())),
+            _ => (Map::new(), Map::new()),
+        };
+        Defaults {
+            values,
+            required,
+            nested: Vec::new(),
+        }
+    }
+
+    /// The defaults of the nested config on the `key` field.
+    fn field(&self, key: &str, required: &[&str]) -> Self {
+        Defaults::new(&self.values.get(key), required)
+    }
+
+    fn with(mut self, key: &'static str, defaults: Defaults) -> Self {
+        self.nested.push((key, defaults));
+        self
+    }
+
+    fn merge(&self, fields: &mut Map<String, Value>) {
+        for (key, default) in &self.values {
+            if fields.get(key).is_none_or(Value::is_null) {
+                fields.insert(key.clone(), default.clone());
+            }
+        }
+        for (key, nested) in &self.nested {
+            for_each_nested(fields.get_mut(*key), |fields| nested.merge(fields));
+        }
+    }
+
+    fn unmerge(&self, fields: &mut Map<String, Value>) {
+        // The nested configs are compared field by field below instead.
+        let is_nested = |key: &str| self.nested.iter().any(|(nested, _)| *nested == key);
+        let defaults = fields
+            .iter()
+            .filter(|(key, value)| {
+                value.is_null() || (!is_nested(key) && self.values.get(*key) == Some(value))
             })
-            .or_else(|| DEFAULT_CONFIG.border_colours.clone()),
-        border_style: config.border_style.or(DEFAULT_CONFIG.border_style),
-        border_z_order: config.border_z_order.or(DEFAULT_CONFIG.border_z_order),
-        border_implementation: config
-            .border_implementation
-            .or(DEFAULT_CONFIG.border_implementation),
-        transparency: config.transparency.or(DEFAULT_CONFIG.transparency),
-        transparency_alpha: config
-            .transparency_alpha
-            .or(DEFAULT_CONFIG.transparency_alpha),
-        transparency_ignore_rules: config
-            .transparency_ignore_rules
-            .or(DEFAULT_CONFIG.transparency_ignore_rules.clone()),
-        default_workspace_padding: config
-            .default_workspace_padding
-            .or(DEFAULT_CONFIG.default_workspace_padding),
-        default_container_padding: config
-            .default_container_padding
-            .or(DEFAULT_CONFIG.default_container_padding),
-        monitors: config.monitors.map(|ms| {
-            ms.into_iter()
-                .map(|m| MonitorConfig {
-                    container_padding: m
-                        .container_padding
-                        .or(DEFAULT_MONITOR_CONFIG.container_padding),
-                    workspace_padding: m
-                        .workspace_padding
-                        .or(DEFAULT_MONITOR_CONFIG.workspace_padding),
-                    workspaces: m
-                        .workspaces
-                        .into_iter()
-                        .map(|w| WorkspaceConfig {
-                            name: w.name,
-                            layout: w.layout,
-                            layout_options: w
-                                .layout_options
-                                .or(DEFAULT_WORKSPACE_CONFIG.layout_options),
-                            custom_layout: w
-                                .custom_layout
-                                .or(DEFAULT_WORKSPACE_CONFIG.custom_layout.clone()),
-                            layout_rules: w
-                                .layout_rules
-                                .or(DEFAULT_WORKSPACE_CONFIG.layout_rules.clone()),
-                            custom_layout_rules: w
-                                .custom_layout_rules
-                                .or(DEFAULT_WORKSPACE_CONFIG.custom_layout_rules.clone()),
-                            container_padding: w
-                                .container_padding
-                                .or(DEFAULT_WORKSPACE_CONFIG.container_padding),
-                            workspace_padding: w
-                                .workspace_padding
-                                .or(DEFAULT_WORKSPACE_CONFIG.workspace_padding),
-                            initial_workspace_rules: w
-                                .initial_workspace_rules
-                                .or(DEFAULT_WORKSPACE_CONFIG.initial_workspace_rules.clone()),
-                            workspace_rules: w
-                                .workspace_rules
-                                .or(DEFAULT_WORKSPACE_CONFIG.workspace_rules.clone()),
-                            work_area_offset: w
-                                .work_area_offset
-                                .or(DEFAULT_WORKSPACE_CONFIG.work_area_offset),
-                            apply_window_based_work_area_offset: w
-                                .apply_window_based_work_area_offset
-                                .or(DEFAULT_WORKSPACE_CONFIG.apply_window_based_work_area_offset),
-                            window_container_behaviour: w
-                                .window_container_behaviour
-                                .or(DEFAULT_WORKSPACE_CONFIG.window_container_behaviour),
-                            window_container_behaviour_rules: w
-                                .window_container_behaviour_rules
-                                .or(DEFAULT_WORKSPACE_CONFIG
-                                    .window_container_behaviour_rules
-                                    .clone()),
-                            float_override: w
-                                .float_override
-                                .or(DEFAULT_WORKSPACE_CONFIG.float_override),
-                            tile: w.tile.or(DEFAULT_WORKSPACE_CONFIG.tile),
-                            layout_flip: w.layout_flip.or(DEFAULT_WORKSPACE_CONFIG.layout_flip),
-                            floating_layer_behaviour: w
-                                .floating_layer_behaviour
-                                .or(DEFAULT_WORKSPACE_CONFIG.floating_layer_behaviour),
-                            wallpaper: w
-                                .wallpaper
-                                .map(|wp| Wallpaper {
-                                    path: wp.path,
-                                    generate_theme: wp
-                                        .generate_theme
-                                        .or(DEFAULT_WALLPAPER.generate_theme),
-                                    theme_options: wp
-                                        .theme_options
-                                        .map(|to| ThemeOptions {
-                                            theme_variant: to
-                                                .theme_variant
-                                                .or(DEFAULT_THEME_OPTIONS.theme_variant),
-                                            single_border: to
-                                                .single_border
-                                                .or(DEFAULT_THEME_OPTIONS.single_border),
-                                            stack_border: to
-                                                .stack_border
-                                                .or(DEFAULT_THEME_OPTIONS.stack_border),
-                                            monocle_border: to
-                                                .monocle_border
-                                                .or(DEFAULT_THEME_OPTIONS.monocle_border),
-                                            floating_border: to
-                                                .floating_border
-                                                .or(DEFAULT_THEME_OPTIONS.floating_border),
-                                            unfocused_border: to
-                                                .unfocused_border
-                                                .or(DEFAULT_THEME_OPTIONS.unfocused_border),
-                                            unfocused_locked_border: to
-                                                .unfocused_locked_border
-                                                .or(DEFAULT_THEME_OPTIONS.unfocused_locked_border),
-                                            stackbar_focused_text: to
-                                                .stackbar_focused_text
-                                                .or(DEFAULT_THEME_OPTIONS.stackbar_focused_text),
-                                            stackbar_unfocused_text: to
-                                                .stackbar_unfocused_text
-                                                .or(DEFAULT_THEME_OPTIONS.stackbar_unfocused_text),
-                                            stackbar_background: to
-                                                .stackbar_background
-                                                .or(DEFAULT_THEME_OPTIONS.stackbar_background),
-                                            bar_accent: to
-                                                .bar_accent
-                                                .or(DEFAULT_THEME_OPTIONS.bar_accent),
-                                        })
-                                        .or_else(|| DEFAULT_WALLPAPER.theme_options.clone()),
-                                })
-                                .or_else(|| DEFAULT_WORKSPACE_CONFIG.wallpaper.clone()),
-                        })
-                        .collect(),
-                    work_area_offset: m
-                        .work_area_offset
-                        .or(DEFAULT_MONITOR_CONFIG.work_area_offset),
-                    window_based_work_area_offset: m
-                        .window_based_work_area_offset
-                        .or(DEFAULT_MONITOR_CONFIG.window_based_work_area_offset),
-                    window_based_work_area_offset_limit: m
-                        .window_based_work_area_offset_limit
-                        .or(DEFAULT_MONITOR_CONFIG.window_based_work_area_offset_limit),
-                    wallpaper: m
-                        .wallpaper
-                        .map(|wp| Wallpaper {
-                            path: wp.path,
-                            generate_theme: wp.generate_theme.or(DEFAULT_WALLPAPER.generate_theme),
-                            theme_options: wp
-                                .theme_options
-                                .map(|to| ThemeOptions {
-                                    theme_variant: to
-                                        .theme_variant
-                                        .or(DEFAULT_THEME_OPTIONS.theme_variant),
-                                    single_border: to
-                                        .single_border
-                                        .or(DEFAULT_THEME_OPTIONS.single_border),
-                                    stack_border: to
-                                        .stack_border
-                                        .or(DEFAULT_THEME_OPTIONS.stack_border),
-                                    monocle_border: to
-                                        .monocle_border
-                                        .or(DEFAULT_THEME_OPTIONS.monocle_border),
-                                    floating_border: to
-                                        .floating_border
-                                        .or(DEFAULT_THEME_OPTIONS.floating_border),
-                                    unfocused_border: to
-                                        .unfocused_border
-                                        .or(DEFAULT_THEME_OPTIONS.unfocused_border),
-                                    unfocused_locked_border: to
-                                        .unfocused_locked_border
-                                        .or(DEFAULT_THEME_OPTIONS.unfocused_locked_border),
-                                    stackbar_focused_text: to
-                                        .stackbar_focused_text
-                                        .or(DEFAULT_THEME_OPTIONS.stackbar_focused_text),
-                                    stackbar_unfocused_text: to
-                                        .stackbar_unfocused_text
-                                        .or(DEFAULT_THEME_OPTIONS.stackbar_unfocused_text),
-                                    stackbar_background: to
-                                        .stackbar_background
-                                        .or(DEFAULT_THEME_OPTIONS.stackbar_background),
-                                    bar_accent: to.bar_accent.or(DEFAULT_THEME_OPTIONS.bar_accent),
-                                })
-                                .or_else(|| DEFAULT_WALLPAPER.theme_options.clone()),
-                        })
-                        .or_else(|| DEFAULT_MONITOR_CONFIG.wallpaper.clone()),
-                    floating_layer_behaviour: m
-                        .floating_layer_behaviour
-                        .or(DEFAULT_MONITOR_CONFIG.floating_layer_behaviour),
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        for key in defaults {
+            fields.remove(&key);
+        }
+        for (key, nested) in &self.nested {
+            for_each_nested(fields.get_mut(*key), |fields| nested.unmerge(fields));
+            // Only the required fields are left when all the other nested fields have the
+            // default values, so the whole nested config is the default one if they have the
+            // default values as well.
+            if fields
+                .get(*key)
+                .and_then(Value::as_object)
+                .is_some_and(|object| {
+                    object
+                        .iter()
+                        .all(|(k, v)| nested.required.get(k) == Some(v))
                 })
-                .collect()
-        }),
-        window_hiding_behaviour: config
-            .window_hiding_behaviour
-            .or(DEFAULT_CONFIG.window_hiding_behaviour),
-        global_work_area_offset: config
-            .global_work_area_offset
-            .or(DEFAULT_CONFIG.global_work_area_offset),
-        ignore_rules: config.ignore_rules.or(DEFAULT_CONFIG.ignore_rules.clone()),
-        manage_rules: config.manage_rules.or(DEFAULT_CONFIG.manage_rules.clone()),
-        floating_applications: config
-            .floating_applications
-            .or(DEFAULT_CONFIG.floating_applications.clone()),
-        border_overflow_applications: config
-            .border_overflow_applications
-            .or(DEFAULT_CONFIG.border_overflow_applications.clone()),
-        tray_and_multi_window_applications: config
-            .tray_and_multi_window_applications
-            .or(DEFAULT_CONFIG.tray_and_multi_window_applications.clone()),
-        layered_applications: config
-            .layered_applications
-            .or(DEFAULT_CONFIG.layered_applications.clone()),
-        object_name_change_applications: config
-            .object_name_change_applications
-            .or(DEFAULT_CONFIG.object_name_change_applications.clone()),
-        object_name_change_title_ignore_list: config
-            .object_name_change_title_ignore_list
-            .or(DEFAULT_CONFIG.object_name_change_title_ignore_list.clone()),
-        monitor_index_preferences: config
-            .monitor_index_preferences
-            .or(DEFAULT_CONFIG.monitor_index_preferences.clone()),
-        display_index_preferences: config
-            .display_index_preferences
-            .or(DEFAULT_CONFIG.display_index_preferences.clone()),
-        stackbar: config
-            .stackbar
-            .map(|s| StackbarConfig {
-                height: s
-                    .height
-                    .or(DEFAULT_CONFIG.stackbar.as_ref().and_then(|s| s.height)),
-                label: s
-                    .label
-                    .or(DEFAULT_CONFIG.stackbar.as_ref().and_then(|s| s.label)),
-                mode: s
-                    .mode
-                    .or(DEFAULT_CONFIG.stackbar.as_ref().and_then(|s| s.mode)),
-                tabs: s
-                    .tabs
-                    .map(|t| TabsConfig {
-                        width: t.width.or(DEFAULT_CONFIG
-                            .stackbar
-                            .as_ref()
-                            .and_then(|s| s.tabs.as_ref().and_then(|t| t.width))),
-                        focused_text: t.focused_text.or(DEFAULT_CONFIG
-                            .stackbar
-                            .as_ref()
-                            .and_then(|s| s.tabs.as_ref().and_then(|t| t.focused_text))),
-                        unfocused_text: t.unfocused_text.or(DEFAULT_CONFIG
-                            .stackbar
-                            .as_ref()
-                            .and_then(|s| s.tabs.as_ref().and_then(|t| t.unfocused_text))),
-                        background: t.background.or(DEFAULT_CONFIG
-                            .stackbar
-                            .as_ref()
-                            .and_then(|s| s.tabs.as_ref().and_then(|t| t.background))),
-                        font_family: t.font_family.as_ref().cloned().or(DEFAULT_CONFIG
-                            .stackbar
-                            .as_ref()
-                            .and_then(|s| s.tabs.as_ref().and_then(|t| t.font_family.clone()))),
-                        font_size: t.font_size.or(DEFAULT_CONFIG
-                            .stackbar
-                            .as_ref()
-                            .and_then(|s| s.tabs.as_ref().and_then(|t| t.font_size))),
-                    })
-                    .or_else(|| {
-                        DEFAULT_CONFIG
-                            .stackbar
-                            .as_ref()
-                            .and_then(|s| s.tabs.clone())
-                    }),
-            })
-            .or_else(|| DEFAULT_CONFIG.stackbar.clone()),
-        animation: config
-            .animation
-            .map(|a| AnimationsConfig {
-                enabled: a.enabled,
-                duration: a.duration.or(DEFAULT_CONFIG
-                    .animation
-                    .as_ref()
-                    .and_then(|a| a.duration.clone())),
-                style: a.style.or(DEFAULT_CONFIG
-                    .animation
-                    .as_ref()
-                    .and_then(|a| a.style.clone())),
-                fps: a
-                    .fps
-                    .or(DEFAULT_CONFIG.animation.as_ref().and_then(|a| a.fps)),
-            })
-            .or_else(|| DEFAULT_CONFIG.animation.clone()),
-        theme: config.theme.or(DEFAULT_CONFIG.theme.clone()),
-        slow_application_identifiers: config
-            .slow_application_identifiers
-            .or(DEFAULT_CONFIG.slow_application_identifiers.clone()),
-        slow_application_compensation_time: config
-            .slow_application_compensation_time
-            .or(DEFAULT_CONFIG.slow_application_compensation_time),
-        bar_configurations: config
-            .bar_configurations
-            .or(DEFAULT_CONFIG.bar_configurations.clone()),
-        remove_titlebar_applications: config
-            .remove_titlebar_applications
-            .or(DEFAULT_CONFIG.remove_titlebar_applications.clone()),
-        floating_window_aspect_ratio: config
-            .floating_window_aspect_ratio
-            .or(DEFAULT_CONFIG.floating_window_aspect_ratio),
-        window_handling_behaviour: config
-            .window_handling_behaviour
-            .or(DEFAULT_CONFIG.window_handling_behaviour),
+            {
+                fields.remove(*key);
+            }
+        }
+    }
+}
+
+/// Calls `f` with the fields of the nested config on `value`, or of each config if it is a list.
+fn for_each_nested(value: Option<&mut Value>, mut f: impl FnMut(&mut Map<String, Value>)) {
+    match value {
+        Some(Value::Object(fields)) => f(fields),
+        Some(Value::Array(items)) => items
+            .iter_mut()
+            .filter_map(Value::as_object_mut)
+            .for_each(f),
+        _ => {}
     }
 }
 
+/// Calls `f` with the fields of the serialized `config` and deserializes the result back. The
+/// `config` is returned as it was if it can't be done.
+fn map_fields(config: StaticConfig, f: impl FnOnce(&mut Map<String, Value>)) -> StaticConfig {
+    let mut fields = match serde_json::to_value(&config) {
+        Ok(Value::Object(fields)) => fields,
+        _ => return config,
+    };
+    f(&mut fields);
+    serde_json::from_value(Value::Object(fields)).unwrap_or_else(|e| {
+        println!("Error deserializing the config after merging the defaults: {e}");
+        config
+    })
+}
+
+/// Merge the `DEFAULT_CONFIG` values on `config`. For each value that is `None` on `config`
+/// it uses the corresponding value from `DEFAULT_CONFIG`. The nested configs, like the
+/// `stackbar` or each of the monitors and workspaces, are merged field by field against their own
+/// defaults, while any other value is used as a whole.
+/// It returns a new `StaticConfig` with the result.
+pub fn merge_default(config: StaticConfig) -> StaticConfig {
+    map_fields(config, |fields| DEFAULTS.merge(fields))
+}
+
 /// Unmerge the `DEFAULT_CONFIG` values from `config`. For each value that is equal to
 /// `DEFAULT_CONFIG` on `config` it changes it to `None`, this way we simplify the `config` so that
-/// when written to file it will only have the necessary lines.
+/// when written to file it will only have the necessary lines. It is the opposite of
+/// `merge_default`, so the nested configs are unmerged field by field as well.
 /// It returns a new `StaticConfig` with the result.
 pub fn unmerge_default(config: StaticConfig) -> StaticConfig {
-    StaticConfig {
-        invisible_borders: config
-            .invisible_borders
-            .and_then(|v| (DEFAULT_CONFIG.invisible_borders != Some(v)).then_some(v)),
-        minimum_window_width: config
-            .minimum_window_width
-            .and_then(|v| (DEFAULT_CONFIG.minimum_window_width != Some(v)).then_some(v)),
-        minimum_window_height: config
-            .minimum_window_height
-            .and_then(|v| (DEFAULT_CONFIG.minimum_window_height != Some(v)).then_some(v)),
-        resize_delta: config
-            .resize_delta
-            .and_then(|v| (DEFAULT_CONFIG.resize_delta != Some(v)).then_some(v)),
-        window_container_behaviour: config
-            .window_container_behaviour
-            .and_then(|v| (DEFAULT_CONFIG.window_container_behaviour != Some(v)).then_some(v)),
-        float_override: config
-            .float_override
-            .and_then(|v| (DEFAULT_CONFIG.float_override != Some(v)).then_some(v)),
-        floating_layer_behaviour: config
-            .floating_layer_behaviour
-            .and_then(|v| (DEFAULT_CONFIG.floating_layer_behaviour != Some(v)).then_some(v)),
-        toggle_float_placement: config
-            .toggle_float_placement
-            .and_then(|v| (DEFAULT_CONFIG.toggle_float_placement != Some(v)).then_some(v)),
-        floating_layer_placement: config
-            .floating_layer_placement
-            .and_then(|v| (DEFAULT_CONFIG.floating_layer_placement != Some(v)).then_some(v)),
-        float_override_placement: config
-            .float_override_placement
-            .and_then(|v| (DEFAULT_CONFIG.float_override_placement != Some(v)).then_some(v)),
-        float_rule_placement: config
-            .float_rule_placement
-            .and_then(|v| (DEFAULT_CONFIG.float_rule_placement != Some(v)).then_some(v)),
-        cross_monitor_move_behaviour: config
-            .cross_monitor_move_behaviour
-            .and_then(|v| (DEFAULT_CONFIG.cross_monitor_move_behaviour != Some(v)).then_some(v)),
-        cross_boundary_behaviour: config
-            .cross_boundary_behaviour
-            .and_then(|v| (DEFAULT_CONFIG.cross_boundary_behaviour != Some(v)).then_some(v)),
-        unmanaged_window_operation_behaviour: config.unmanaged_window_operation_behaviour.and_then(
-            |v| (DEFAULT_CONFIG.unmanaged_window_operation_behaviour != Some(v)).then_some(v),
-        ),
-        focus_follows_mouse: config
-            .focus_follows_mouse
-            .and_then(|v| (DEFAULT_CONFIG.focus_follows_mouse != Some(v)).then_some(v)),
-        mouse_follows_focus: config
-            .mouse_follows_focus
-            .and_then(|v| (DEFAULT_CONFIG.mouse_follows_focus != Some(v)).then_some(v)),
-        app_specific_configuration_path: config
-            .app_specific_configuration_path
-            .map(|asc| match asc {
-                AppSpecificConfigurationPath::Single(path) => {
-                    AppSpecificConfigurationPath::Single(unresolve_home_path(path))
-                }
-                AppSpecificConfigurationPath::Multiple(paths) => {
-                    AppSpecificConfigurationPath::Multiple(
-                        paths.into_iter().map(unresolve_home_path).collect(),
-                    )
-                }
-            })
-            .or(DEFAULT_CONFIG.app_specific_configuration_path.clone()),
-        border_width: config
-            .border_width
-            .and_then(|v| (DEFAULT_CONFIG.border_width != Some(v)).then_some(v)),
-        border_offset: config
-            .border_offset
-            .and_then(|v| (DEFAULT_CONFIG.border_offset != Some(v)).then_some(v)),
-        border: config
-            .border
-            .and_then(|v| (DEFAULT_CONFIG.border != Some(v)).then_some(v)),
-        border_colours: config.border_colours.as_ref().and_then(|bc| {
-            (DEFAULT_CONFIG.border_colours.as_ref() != Some(bc)).then(|| BorderColours {
-                single: bc.single.and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .border_colours
-                        .as_ref()
-                        .and_then(|bc| bc.single)
-                        != Some(v))
-                    .then_some(v)
-                }),
-                stack: bc.stack.and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .border_colours
-                        .as_ref()
-                        .and_then(|bc| bc.stack)
-                        != Some(v))
-                    .then_some(v)
-                }),
-                monocle: bc.monocle.and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .border_colours
-                        .as_ref()
-                        .and_then(|bc| bc.monocle)
-                        != Some(v))
-                    .then_some(v)
-                }),
-                floating: bc.floating.and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .border_colours
-                        .as_ref()
-                        .and_then(|bc| bc.floating)
-                        != Some(v))
-                    .then_some(v)
-                }),
-                unfocused: bc.unfocused.and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .border_colours
-                        .as_ref()
-                        .and_then(|bc| bc.unfocused)
-                        != Some(v))
-                    .then_some(v)
-                }),
-                unfocused_locked: bc.unfocused_locked.and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .border_colours
-                        .as_ref()
-                        .and_then(|bc| bc.unfocused_locked)
-                        != Some(v))
-                    .then_some(v)
-                }),
+    map_fields(config, |fields| {
+        let always_written = ALWAYS_WRITTEN
+            .iter()
+            .filter_map(|key| {
+                let value = fields
+                    .get(*key)
+                    .filter(|value| !value.is_null())
+                    .or_else(|| DEFAULTS.values.get(*key))?;
+                Some((key.to_string(), value.clone()))
             })
-        }),
-        border_style: config
-            .border_style
-            .and_then(|v| (DEFAULT_CONFIG.border_style != Some(v)).then_some(v)),
-        border_z_order: config
-            .border_z_order
-            .and_then(|v| (DEFAULT_CONFIG.border_z_order != Some(v)).then_some(v)),
-        border_implementation: config
-            .border_implementation
-            .and_then(|v| (DEFAULT_CONFIG.border_implementation != Some(v)).then_some(v)),
-        transparency: config
-            .transparency
-            .and_then(|v| (DEFAULT_CONFIG.transparency != Some(v)).then_some(v)),
-        transparency_alpha: config
-            .transparency_alpha
-            .and_then(|v| (DEFAULT_CONFIG.transparency_alpha != Some(v)).then_some(v)),
-        transparency_ignore_rules: config.transparency_ignore_rules.and_then(|v| {
-            (DEFAULT_CONFIG.transparency_ignore_rules.as_ref() != Some(&v)).then_some(v)
-        }),
-        default_workspace_padding: config
-            .default_workspace_padding
-            .and_then(|v| (DEFAULT_CONFIG.default_workspace_padding != Some(v)).then_some(v)),
-        default_container_padding: config
-            .default_container_padding
-            .and_then(|v| (DEFAULT_CONFIG.default_container_padding != Some(v)).then_some(v)),
-        monitors: config.monitors.map(|ms| {
-            ms.into_iter()
-                .map(|m| MonitorConfig {
-                    container_padding: m.container_padding.and_then(|v| {
-                        (DEFAULT_MONITOR_CONFIG.container_padding != Some(v)).then_some(v)
-                    }),
-                    workspace_padding: m.workspace_padding.and_then(|v| {
-                        (DEFAULT_MONITOR_CONFIG.workspace_padding != Some(v)).then_some(v)
-                    }),
-                    workspaces: m
-                        .workspaces
-                        .into_iter()
-                        .map(|ws| WorkspaceConfig {
-                            name: ws.name,
-                            layout: ws.layout,
-                            layout_options: ws.layout_options.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.layout_options.as_ref() != Some(&v))
-                                    .then_some(v)
-                            }),
-                            custom_layout: ws.custom_layout.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.custom_layout.as_ref() != Some(&v))
-                                    .then_some(v)
-                            }),
-                            layout_rules: ws.layout_rules.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.layout_rules.as_ref() != Some(&v))
-                                    .then_some(v)
-                            }),
-                            custom_layout_rules: ws.custom_layout_rules.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.custom_layout_rules.as_ref() != Some(&v))
-                                    .then_some(v)
-                            }),
-                            container_padding: ws.container_padding.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.container_padding != Some(v)).then_some(v)
-                            }),
-                            workspace_padding: ws.workspace_padding.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.workspace_padding != Some(v)).then_some(v)
-                            }),
-                            initial_workspace_rules: ws.initial_workspace_rules.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.initial_workspace_rules.as_ref()
-                                    != Some(&v))
-                                .then_some(v)
-                            }),
-                            workspace_rules: ws.workspace_rules.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.workspace_rules.as_ref() != Some(&v))
-                                    .then_some(v)
-                            }),
-                            work_area_offset: ws.work_area_offset.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.work_area_offset.as_ref() != Some(&v))
-                                    .then_some(v)
-                            }),
-                            apply_window_based_work_area_offset: ws
-                                .apply_window_based_work_area_offset
-                                .and_then(|v| {
-                                    (DEFAULT_WORKSPACE_CONFIG.apply_window_based_work_area_offset
-                                        != Some(v))
-                                    .then_some(v)
-                                }),
-                            window_container_behaviour: ws.window_container_behaviour.and_then(
-                                |v| {
-                                    (DEFAULT_WORKSPACE_CONFIG.window_container_behaviour != Some(v))
-                                        .then_some(v)
-                                },
-                            ),
-                            window_container_behaviour_rules: ws
-                                .window_container_behaviour_rules
-                                .and_then(|v| {
-                                    (DEFAULT_WORKSPACE_CONFIG
-                                        .window_container_behaviour_rules
-                                        .as_ref()
-                                        != Some(&v))
-                                    .then_some(v)
-                                }),
-                            float_override: ws.float_override.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.float_override != Some(v)).then_some(v)
-                            }),
-                            tile: ws.tile.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.tile != Some(v)).then_some(v)
-                            }),
-                            layout_flip: ws.layout_flip.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.layout_flip != Some(v)).then_some(v)
-                            }),
-                            floating_layer_behaviour: ws.floating_layer_behaviour.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.floating_layer_behaviour != Some(v))
-                                    .then_some(v)
-                            }),
-                            wallpaper: ws.wallpaper.and_then(|v| {
-                                (DEFAULT_WORKSPACE_CONFIG.wallpaper.as_ref() != Some(&v))
-                                    .then_some(v)
-                            }),
-                        })
-                        .collect(),
-                    work_area_offset: m.work_area_offset.and_then(|v| {
-                        (DEFAULT_MONITOR_CONFIG.work_area_offset != Some(v)).then_some(v)
-                    }),
-                    window_based_work_area_offset: m.window_based_work_area_offset.and_then(|v| {
-                        (DEFAULT_MONITOR_CONFIG.window_based_work_area_offset != Some(v))
-                            .then_some(v)
-                    }),
-                    window_based_work_area_offset_limit: m
-                        .window_based_work_area_offset_limit
-                        .and_then(|v| {
-                            (DEFAULT_MONITOR_CONFIG.window_based_work_area_offset_limit != Some(v))
-                                .then_some(v)
-                        }),
-                    wallpaper: m.wallpaper.and_then(|wp| {
-                        (DEFAULT_MONITOR_CONFIG.wallpaper.as_ref() != Some(&wp)).then(|| {
-                            Wallpaper {
-                                path: wp.path,
-                                generate_theme: wp.generate_theme.and_then(|v| {
-                                    (DEFAULT_WALLPAPER.generate_theme.as_ref() != Some(&v))
-                                        .then_some(v)
-                                }),
-                                theme_options: wp.theme_options.and_then(|to| {
-                                    (DEFAULT_WALLPAPER.theme_options.as_ref() != Some(&to)).then(
-                                        || ThemeOptions {
-                                            theme_variant: to.theme_variant.and_then(|v| {
-                                                (DEFAULT_THEME_OPTIONS.theme_variant.as_ref()
-                                                    != Some(&v))
-                                                .then_some(v)
-                                            }),
-                                            single_border: to.single_border.and_then(|v| {
-                                                (DEFAULT_THEME_OPTIONS.single_border.as_ref()
-                                                    != Some(&v))
-                                                .then_some(v)
-                                            }),
-                                            stack_border: to.stack_border.and_then(|v| {
-                                                (DEFAULT_THEME_OPTIONS.stack_border.as_ref()
-                                                    != Some(&v))
-                                                .then_some(v)
-                                            }),
-                                            monocle_border: to.monocle_border.and_then(|v| {
-                                                (DEFAULT_THEME_OPTIONS.monocle_border.as_ref()
-                                                    != Some(&v))
-                                                .then_some(v)
-                                            }),
-                                            floating_border: to.floating_border.and_then(|v| {
-                                                (DEFAULT_THEME_OPTIONS.floating_border.as_ref()
-                                                    != Some(&v))
-                                                .then_some(v)
-                                            }),
-                                            unfocused_border: to.unfocused_border.and_then(|v| {
-                                                (DEFAULT_THEME_OPTIONS.unfocused_border.as_ref()
-                                                    != Some(&v))
-                                                .then_some(v)
-                                            }),
-                                            unfocused_locked_border: to
-                                                .unfocused_locked_border
-                                                .and_then(|v| {
-                                                    (DEFAULT_THEME_OPTIONS
-                                                        .unfocused_locked_border
-                                                        .as_ref()
-                                                        != Some(&v))
-                                                    .then_some(v)
-                                                }),
-                                            stackbar_focused_text: to
-                                                .stackbar_focused_text
-                                                .and_then(|v| {
-                                                    (DEFAULT_THEME_OPTIONS
-                                                        .stackbar_focused_text
-                                                        .as_ref()
-                                                        != Some(&v))
-                                                    .then_some(v)
-                                                }),
-                                            stackbar_unfocused_text: to
-                                                .stackbar_unfocused_text
-                                                .and_then(|v| {
-                                                    (DEFAULT_THEME_OPTIONS
-                                                        .stackbar_unfocused_text
-                                                        .as_ref()
-                                                        != Some(&v))
-                                                    .then_some(v)
-                                                }),
-                                            stackbar_background: to.stackbar_background.and_then(
-                                                |v| {
-                                                    (DEFAULT_THEME_OPTIONS
-                                                        .stackbar_background
-                                                        .as_ref()
-                                                        != Some(&v))
-                                                    .then_some(v)
-                                                },
-                                            ),
-                                            bar_accent: to.bar_accent.and_then(|v| {
-                                                (DEFAULT_THEME_OPTIONS.bar_accent.as_ref()
-                                                    != Some(&v))
-                                                .then_some(v)
-                                            }),
-                                        },
-                                    )
-                                }),
-                            }
-                        })
-                    }),
-                    floating_layer_behaviour: m.floating_layer_behaviour.and_then(|v| {
-                        (DEFAULT_MONITOR_CONFIG.floating_layer_behaviour != Some(v)).then_some(v)
-                    }),
-                })
-                .collect()
-        }),
-        window_hiding_behaviour: config
-            .window_hiding_behaviour
-            .and_then(|v| (DEFAULT_CONFIG.window_hiding_behaviour != Some(v)).then_some(v)),
-        global_work_area_offset: config
-            .global_work_area_offset
-            .and_then(|v| (DEFAULT_CONFIG.global_work_area_offset != Some(v)).then_some(v)),
-        ignore_rules: config
-            .ignore_rules
-            .and_then(|v| (DEFAULT_CONFIG.ignore_rules.as_ref() != Some(&v)).then_some(v)),
-        manage_rules: config
-            .manage_rules
-            .and_then(|v| (DEFAULT_CONFIG.manage_rules.as_ref() != Some(&v)).then_some(v)),
-        floating_applications: config
-            .floating_applications
-            .and_then(|v| (DEFAULT_CONFIG.floating_applications.as_ref() != Some(&v)).then_some(v)),
-        border_overflow_applications: config.border_overflow_applications.and_then(|v| {
-            (DEFAULT_CONFIG.border_overflow_applications.as_ref() != Some(&v)).then_some(v)
-        }),
-        tray_and_multi_window_applications: config.tray_and_multi_window_applications.and_then(
-            |v| {
-                (DEFAULT_CONFIG.tray_and_multi_window_applications.as_ref() != Some(&v))
-                    .then_some(v)
-            },
-        ),
-        layered_applications: config
-            .layered_applications
-            .and_then(|v| (DEFAULT_CONFIG.layered_applications.as_ref() != Some(&v)).then_some(v)),
-        object_name_change_applications: config.object_name_change_applications.and_then(|v| {
-            (DEFAULT_CONFIG.object_name_change_applications.as_ref() != Some(&v)).then_some(v)
-        }),
-        object_name_change_title_ignore_list: config.object_name_change_title_ignore_list.and_then(
-            |v| {
-                (DEFAULT_CONFIG.object_name_change_title_ignore_list.as_ref() != Some(&v))
-                    .then_some(v)
-            },
-        ),
-        monitor_index_preferences: config.monitor_index_preferences.and_then(|v| {
-            (DEFAULT_CONFIG.monitor_index_preferences.as_ref() != Some(&v)).then_some(v)
-        }),
-        display_index_preferences: config.display_index_preferences.and_then(|v| {
-            (DEFAULT_CONFIG.display_index_preferences.as_ref() != Some(&v)).then_some(v)
-        }),
-        stackbar: config.stackbar.as_ref().and_then(|s| {
-            (DEFAULT_CONFIG.stackbar.as_ref() != Some(s)).then(|| StackbarConfig {
-                height: s.height.and_then(|v| {
-                    (DEFAULT_CONFIG.stackbar.as_ref().and_then(|s| s.height) != Some(v))
-                        .then_some(v)
-                }),
-                label: s.label.and_then(|v| {
-                    (DEFAULT_CONFIG.stackbar.as_ref().and_then(|s| s.label) != Some(v)).then_some(v)
-                }),
-                mode: s.mode.and_then(|v| {
-                    (DEFAULT_CONFIG.stackbar.as_ref().and_then(|s| s.mode) != Some(v)).then_some(v)
-                }),
-                tabs: s.tabs.as_ref().and_then(|t| {
-                    (DEFAULT_CONFIG
-                        .stackbar
-                        .as_ref()
-                        .and_then(|ds| ds.tabs.as_ref())
-                        != Some(t))
-                    .then(|| TabsConfig {
-                        width: t.width.and_then(|v| {
-                            (DEFAULT_CONFIG
-                                .stackbar
-                                .as_ref()
-                                .and_then(|s| s.tabs.as_ref().and_then(|t| t.width))
-                                != Some(v))
-                            .then_some(v)
-                        }),
-                        focused_text: t.focused_text.and_then(|v| {
-                            (DEFAULT_CONFIG
-                                .stackbar
-                                .as_ref()
-                                .and_then(|s| s.tabs.as_ref().and_then(|t| t.focused_text))
-                                != Some(v))
-                            .then_some(v)
-                        }),
-                        unfocused_text: t.unfocused_text.and_then(|v| {
-                            (DEFAULT_CONFIG
-                                .stackbar
-                                .as_ref()
-                                .and_then(|s| s.tabs.as_ref().and_then(|t| t.unfocused_text))
-                                != Some(v))
-                            .then_some(v)
-                        }),
-                        background: t.background.and_then(|v| {
-                            (DEFAULT_CONFIG
-                                .stackbar
-                                .as_ref()
-                                .and_then(|s| s.tabs.as_ref().and_then(|t| t.background))
-                                != Some(v))
-                            .then_some(v)
-                        }),
-                        font_family: t.font_family.as_ref().and_then(|v| {
-                            (DEFAULT_CONFIG
-                                .stackbar
-                                .as_ref()
-                                .and_then(|s| s.tabs.as_ref().and_then(|t| t.font_family.as_ref()))
-                                != Some(v))
-                            .then(|| v.clone())
-                        }),
-                        font_size: t.font_size.and_then(|v| {
-                            (DEFAULT_CONFIG
-                                .stackbar
-                                .as_ref()
-                                .and_then(|s| s.tabs.as_ref().and_then(|t| t.font_size))
-                                != Some(v))
-                            .then_some(v)
-                        }),
-                    })
-                }),
-            })
-        }),
-        animation: config.animation.as_ref().and_then(|a| {
-            (DEFAULT_CONFIG.animation.as_ref() != Some(a)).then(|| AnimationsConfig {
-                enabled: a.enabled.clone(),
-                duration: a.duration.as_ref().and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .animation
-                        .as_ref()
-                        .and_then(|a| a.duration.as_ref())
-                        != Some(v))
-                    .then(|| v.clone())
-                }),
-                style: a.style.as_ref().and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .animation
-                        .as_ref()
-                        .and_then(|a| a.style.as_ref())
-                        != Some(v))
-                    .then(|| v.clone())
-                }),
-                fps: a.fps.and_then(|v| {
-                    (DEFAULT_CONFIG
-                        .animation
-                        .as_ref()
-                        .and_then(|a| a.fps.as_ref())
-                        != Some(&v))
-                    .then_some(v)
-                }),
-            })
-        }),
-        theme: config
-            .theme
-            .and_then(|v| (DEFAULT_CONFIG.theme.as_ref() != Some(&v)).then_some(v)),
-        slow_application_identifiers: config.slow_application_identifiers.and_then(|v| {
-            (DEFAULT_CONFIG.slow_application_identifiers.as_ref() != Some(&v)).then_some(v)
-        }),
-        slow_application_compensation_time: config.slow_application_compensation_time.and_then(
-            |v| (DEFAULT_CONFIG.slow_application_compensation_time != Some(v)).then_some(v),
-        ),
-        bar_configurations: config
-            .bar_configurations
-            .and_then(|v| (DEFAULT_CONFIG.bar_configurations.as_ref() != Some(&v)).then_some(v)),
-        remove_titlebar_applications: config.remove_titlebar_applications.and_then(|v| {
-            (DEFAULT_CONFIG.remove_titlebar_applications.as_ref() != Some(&v)).then_some(v)
-        }),
-        floating_window_aspect_ratio: config
-            .floating_window_aspect_ratio
-            .and_then(|v| (DEFAULT_CONFIG.floating_window_aspect_ratio != Some(v)).then_some(v)),
-        window_handling_behaviour: config
-            .window_handling_behaviour
-            .and_then(|v| (DEFAULT_CONFIG.window_handling_behaviour != Some(v)).then_some(v)),
-    }
+            .collect::<Vec<_>>();
+        DEFAULTS.unmerge(fields);
+        fields.extend(always_written);
+    })
 }
 
 #[allow(dead_code)]
@@ -1311,7 +579,7 @@ enum State {
 }
 
 struct Data {
-    debouncer: Debouncer<ReadDirectoryChangesWatcher>,
+    debouncer: Debouncer<platform::Watcher>,
     receiver: Receiver<Input>,
     ignore_event: usize,
 }
@@ -1471,10 +739,13 @@ fn handle_event(
         if *ignore_event == 0 {
             println!("FileWatcher: loading options");
             smol::block_on(async {
-                match load(path).await {
-                    Ok(loaded_config) => {
+                match load_validated(path).await {
+                    Ok((loaded_config, violations)) => {
                         let _ = output
-                            .send(Message::LoadedConfig(Arc::new(loaded_config)))
+                            .send(Message::ConfigChangedOnDisk(
+                                Arc::new(loaded_config),
+                                violations,
+                            ))
                             .await;
                     }
                     Err(e) => {
@@ -1490,13 +761,22 @@ fn handle_event(
 }
 
 pub fn load_task(path: PathBuf) -> Task<Message> {
-    Task::perform(load(path), |res| match res {
-        Ok(config) => Message::LoadedConfig(Arc::new(config)),
+    Task::perform(load_validated(path), |res| match res {
+        Ok((config, violations)) => Message::LoadedConfig(Arc::new(config), violations),
         Err(apperror) => Message::FailedToLoadConfig(apperror),
     })
 }
 
-async fn load(path: PathBuf) -> Result<StaticConfig, AppError> {
+pub async fn load(path: PathBuf) -> Result<StaticConfig, AppError> {
+    load_validated(path).await.map(|(config, _)| config)
+}
+
+/// Loads the config at `path` together with the places where it doesn't follow the komorebi
+/// schema. The violations don't stop the config from loading, komorebi itself accepts some of
+/// them, so they are only reported.
+pub async fn load_validated(
+    path: PathBuf,
+) -> Result<(StaticConfig, Vec<schema::Violation>), AppError> {
     use smol::prelude::*;
 
     let mut contents = String::new();
@@ -1523,34 +803,116 @@ async fn load(path: PathBuf) -> Result<StaticConfig, AppError> {
             kind: AppErrorKind::Error,
         })?;
 
-    serde_json::from_str(&contents).map_err(|e| AppError {
+    let mut value: serde_json::Value = serde_json::from_str(&contents).map_err(|e| AppError {
         title: "Error reading 'komorebi.json' file.".into(),
         description: Some(e.to_string()),
         kind: AppErrorKind::Error,
+    })?;
+
+    // Files written for older komorebi versions are read as if they were written for the
+    // supported one.
+    let version = migration::detect(Some(&value)).await;
+    migration::upgrade(&mut value, version);
+
+    let violations = schema::validate(&contents, &value, version);
+
+    // The config can't be used when it can't be read, but every place that doesn't follow the
+    // schema is still reported, not only the first one serde finds.
+    let config = serde_json::from_value(value).map_err(|e| {
+        let description = if violations.is_empty() {
+            e.to_string()
+        } else {
+            std::iter::once(e.to_string())
+                .chain(violations.iter().map(ToString::to_string))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        AppError {
+            title: "Error reading 'komorebi.json' file.".into(),
+            description: Some(description),
+            kind: AppErrorKind::Error,
+        }
+    })?;
+    Ok((config, violations))
+}
+
+/// What happened when trying to save a config.
+#[derive(Debug)]
+pub enum SaveOutcome {
+    /// The config was saved, with warnings about anything else that had to be changed on the file.
+    Saved(Vec<AppError>),
+    /// Saving for the older `Version` makes these changes on the config, so nothing was written
+    /// until they are confirmed.
+    NeedsDowngrade(Version, Vec<String>),
+}
+
+/// Saves `config` to `path` for the komorebi `version`. Saving for an older version needs
+/// `downgrade_confirmed` if it changes the config, otherwise the changes are sent to be confirmed
+/// first.
+pub fn save_task(
+    config: StaticConfig,
+    path: PathBuf,
+    backups_to_keep: usize,
+    version: TargetVersion,
+    downgrade_confirmed: bool,
+) -> Task<Message> {
+    let saved = Arc::new(config.clone());
+    Task::future(save(
+        config,
+        path,
+        backups_to_keep,
+        version,
+        downgrade_confirmed,
+    ))
+    .then(move |res| match res {
+        Ok(SaveOutcome::Saved(warnings)) => {
+            Task::done(Message::Saved(saved.clone())).chain(Task::batch(
+                warnings
+                    .into_iter()
+                    .map(|warning| Task::done(Message::SaveWarning(warning))),
+            ))
+        }
+        Ok(SaveOutcome::NeedsDowngrade(version, changes)) => {
+            Task::done(Message::ConfirmDowngrade(version, changes))
+        }
+        Err(apperror) => Task::done(Message::AppError(apperror)),
     })
 }
 
-pub fn save_task(config: StaticConfig, path: PathBuf) -> Task<Message> {
-    Task::future(save(config, path)).map(|res| match res {
-        Ok(_) => Message::Saved,
-        Err(apperror) => Message::AppError(apperror),
+/// Tells komorebi to reload its config from `path`, so that the changes saved to it are applied.
+pub fn reload_task(path: PathBuf) -> Task<Message> {
+    Task::future(async move {
+        Native::send_message(&komorebi_client::SocketMessage::ReloadStaticConfiguration(
+            path,
+        ))
+    })
+    .then(|res| match res {
+        Ok(_) => Task::none(),
+        Err(error) => Task::done(Message::AppError(AppError {
+            title: "Failed to tell komorebi to reload the config".into(),
+            description: Some(error.to_string()),
+            kind: AppErrorKind::Warning,
+        })),
     })
 }
 
-async fn save(config: StaticConfig, path: PathBuf) -> Result<(), AppError> {
-    use smol::prelude::*;
+async fn save(
+    config: StaticConfig,
+    path: PathBuf,
+    backups_to_keep: usize,
+    version: TargetVersion,
+    downgrade_confirmed: bool,
+) -> Result<SaveOutcome, AppError> {
+    let version = migration::save_version(version).await;
+    if version < Version::current() && !downgrade_confirmed {
+        let (_, changes) = to_json_for(config.clone(), version)?;
+        if !changes.is_empty() {
+            return Ok(SaveOutcome::NeedsDowngrade(version, changes));
+        }
+    }
 
-    let unmerged_config = unmerge_default(config);
-    let json = serde_json::to_string_pretty(&unmerged_config).map_err(|e| AppError {
-        title: "Error writing to 'komorebi.json' file".into(),
-        description: Some(e.to_string()),
-        kind: AppErrorKind::Error,
-    })?;
-    let schema = format!(
-        "{{\n  \"$schema\": \"https://raw.githubusercontent.com/LGUG2Z/komorebi/blob/{}/schema.json\",\n",
-        *KOMOREBI_VERSION,
-    );
-    let json = [schema.as_bytes(), &json.as_bytes()[2..]].concat();
+    let original = smol::fs::read_to_string(&path).await.ok();
+    let (json, warnings) = file_contents(config, original.as_deref(), version)?;
 
     // if let Some(dir) = path.parent() {
     //     smol::fs::create_dir_all(dir)
@@ -1562,50 +924,213 @@ async fn save(config: StaticConfig, path: PathBuf) -> Result<(), AppError> {
     //         })?;
     // }
 
-    let mut file = smol::fs::File::create(path).await.map_err(|e| AppError {
-        title: "Error creating 'komorebi.json' file.".into(),
-        description: Some(e.to_string()),
-        kind: AppErrorKind::Error,
-    })?;
+    backup::create(ConfigType::Komorebi, &path, backups_to_keep).await?;
+
+    utils::write_config(ConfigType::Komorebi, &path, json.as_bytes()).await?;
+
+    // This is a simple way to save at most once every couple seconds
+    // smol::Timer::after(std::time::Duration::from_secs(2)).await;
 
-    file.write_all(&json).await.map_err(|e| AppError {
-        title: "Error saving 'komorebi.json' file".into(),
+    Ok(SaveOutcome::Saved(warnings))
+}
+
+/// The contents to write to a `komorebi.json` file with the `original` contents so that it has
+/// `config` for the komorebi `version`, along with warnings about anything else that had to be
+/// changed on the file. The changes made to save for an older version are expected to be
+/// confirmed already.
+pub fn file_contents(
+    config: StaticConfig,
+    original: Option<&str>,
+    version: Version,
+) -> Result<(String, Vec<AppError>), AppError> {
+    let mut warnings = Vec::new();
+    let json = if version < Version::current() {
+        // Older versions get a whole new file, since the existing one might not even be valid
+        // for them anymore.
+        to_json_for(config, version)?.0
+    } else if let Some(json) =
+        original.and_then(|original| document::update(original, &config, version))
+    {
+        // Keep the comments, unknown keys and order of the existing file by only editing the
+        // values that changed on it.
+        return Ok((json, warnings));
+    } else {
+        to_json(config)?
+    };
+
+    if original.is_some_and(|original| !original.trim().is_empty()) {
+        warnings.push(AppError {
+            title: "Rewrote 'komorebi.json' as a whole".into(),
+            description: Some(
+                "The file couldn't be edited in place, so any comments, unknown keys and \
+                formatting it had were not kept."
+                    .into(),
+            ),
+            kind: AppErrorKind::Warning,
+        });
+    }
+    Ok((json, warnings))
+}
+
+/// Serializes the unmerged `config` as a whole new `komorebi.json` file, with the `$schema`
+/// header pointing to the supported komorebi version.
+pub fn to_json(config: StaticConfig) -> Result<String, AppError> {
+    let unmerged_config = unmerge_default(config);
+    let json = serde_json::to_string_pretty(&unmerged_config).map_err(|e| AppError {
+        title: "Error writing to 'komorebi.json' file".into(),
         description: Some(e.to_string()),
         kind: AppErrorKind::Error,
     })?;
+    let schema = format!(
+        "{{\n  \"$schema\": \"{}\",\n",
+        schema_url(*KOMOREBI_VERSION)
+    );
+    Ok(format!("{schema}{}", &json[2..]))
+}
 
-    file.close().await.map_err(|e| AppError {
-        title: "Error closing 'komorebi.json' file".into(),
+/// Serializes the unmerged `config` as a whole new `komorebi.json` file for the older komorebi
+/// `version`. Returns the file contents and the changes made to the config for that version.
+pub fn to_json_for(
+    config: StaticConfig,
+    version: Version,
+) -> Result<(String, Vec<String>), AppError> {
+    let map_error = |e: serde_json::Error| AppError {
+        title: "Error writing to 'komorebi.json' file".into(),
         description: Some(e.to_string()),
         kind: AppErrorKind::Error,
-    })?;
-
-    // This is a simple way to save at most once every couple seconds
-    // smol::Timer::after(std::time::Duration::from_secs(2)).await;
+    };
+    let mut value = serde_json::to_value(unmerge_default(config)).map_err(map_error)?;
+    let changes = migration::downgrade(&mut value, version);
+    let json = serde_json::to_string_pretty(&value).map_err(map_error)?;
+    let schema = format!("{{\n  \"$schema\": \"{}\",\n", schema_url(version));
+    Ok((format!("{schema}{}", &json[2..]), changes))
+}
 
-    Ok(())
+/// The URL of the `schema.json` of the komorebi `version`, which is written as the `$schema` of
+/// the config files.
+pub fn schema_url(version: impl std::fmt::Display) -> String {
+    format!("https://raw.githubusercontent.com/LGUG2Z/komorebi/blob/{version}/schema.json")
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum HomePathType {
     #[default]
     Default,
     EnvVar,
+    /// The folder picked on Komorice, which is kept on the settings.
+    Override,
 }
 
-pub fn home_path() -> (PathBuf, HomePathType) {
-    std::env::var("KOMOREBI_CONFIG_HOME").map_or_else(
-        |_| (dirs::home_dir().expect("there is no home directory"), HomePathType::Default),
-        |home_path| {
-            let home = PathBuf::from(&home_path);
+/// The reason why the folder of a config can't be used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HomePathError {
+    /// The folder set on the `variable` or on the settings isn't a directory.
+    NotADirectory {
+        path: PathBuf,
+        kind: HomePathType,
+        variable: &'static str,
+    },
+    NoHomeDirectory,
+}
 
-            if home.as_path().is_dir() {
-                (home, HomePathType::EnvVar)
-            } else {
-                panic!("$Env:KOMOREBI_CONFIG_HOME is set to '{home_path}', which is not a valid directory");
+impl From<HomePathError> for AppError {
+    fn from(error: HomePathError) -> Self {
+        error.to_app_error()
+    }
+}
+
+impl HomePathError {
+    /// The folder that can't be used, if there is one.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            HomePathError::NotADirectory { path, .. } => Some(path),
+            HomePathError::NoHomeDirectory => None,
+        }
+    }
+
+    pub fn to_app_error(&self) -> AppError {
+        match self {
+            HomePathError::NotADirectory {
+                path,
+                kind,
+                variable,
+            } => AppError {
+                title: if matches!(kind, HomePathType::Override) {
+                    format!("The folder picked for '{variable}' is not a valid directory")
+                } else {
+                    format!("$Env:{variable} is not a valid directory")
+                },
+                description: Some(format!(
+                    "It is set to '{}'. Until that folder is created or another one is picked, \
+                    the default config is used and it can't be saved.",
+                    path.display()
+                )),
+                kind: AppErrorKind::Error,
+            },
+            HomePathError::NoHomeDirectory => AppError {
+                title: "There is no home directory".into(),
+                description: Some(
+                    "Pick the folder where the config should be kept. Until then, the default \
+                    config is used."
+                        .into(),
+                ),
+                kind: AppErrorKind::Error,
+            },
+        }
+    }
+}
+
+/// The folder picked on Komorice to use instead of `KOMOREBI_CONFIG_HOME`.
+static HOME_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the folder to use instead of `KOMOREBI_CONFIG_HOME`, if any.
+pub fn set_home_override(path: Option<PathBuf>) {
+    if let Ok(mut home_override) = HOME_OVERRIDE.write() {
+        *home_override = path;
+    }
+}
+
+/// Finds the folder of a config, which is the `home_override` if there is one, or the one set on
+/// the `variable`, or the `default` folder inside the home directory.
+pub fn find_config_home(
+    home_override: &RwLock<Option<PathBuf>>,
+    variable: &'static str,
+    default: impl FnOnce(PathBuf) -> PathBuf,
+) -> Result<(PathBuf, HomePathType), HomePathError> {
+    let home_override = home_override
+        .read()
+        .map(|home_override| home_override.clone())
+        .unwrap_or_default();
+    let (path, kind) = match home_override {
+        Some(path) => (path, HomePathType::Override),
+        None => match std::env::var(variable) {
+            Ok(home_path) => (PathBuf::from(home_path), HomePathType::EnvVar),
+            Err(_) => {
+                return dirs::home_dir()
+                    .map(|home| (default(home), HomePathType::Default))
+                    .ok_or(HomePathError::NoHomeDirectory);
             }
         },
-    )
+    };
+    if path.is_dir() {
+        Ok((path, kind))
+    } else {
+        Err(HomePathError::NotADirectory {
+            path,
+            kind,
+            variable,
+        })
+    }
+}
+
+/// Finds the folder of the komorebi config.
+pub fn find_home_path() -> Result<(PathBuf, HomePathType), HomePathError> {
+    find_config_home(&HOME_OVERRIDE, "KOMOREBI_CONFIG_HOME", |home| home)
+}
+
+/// The folder of the komorebi config, or why it can't be used.
+pub fn home_path() -> Result<PathBuf, HomePathError> {
+    find_home_path().map(|(path, _)| path)
 }
 
 pub fn unresolve_home_path(path: PathBuf) -> PathBuf {
@@ -1628,8 +1153,466 @@ pub fn unresolve_home_path(path: PathBuf) -> PathBuf {
     }
 }
 
-pub fn config_path() -> PathBuf {
-    let (home_dir, _) = home_path();
+/// The path of the active `komorebi.json`, which can't be read or written while its folder can't
+/// be used.
+pub fn config_path() -> Result<PathBuf, HomePathError> {
+    home_path().map(|home_dir| home_dir.join("komorebi.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use komorebi_client::AnimationPrefix;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    /// The colours of the Catppuccin and Base16 themes.
+    const THEME_COLOURS: &[&str] = &[
+        "single_border",
+        "stack_border",
+        "monocle_border",
+        "floating_border",
+        "unfocused_border",
+        "unfocused_locked_border",
+        "stackbar_focused_text",
+        "stackbar_unfocused_text",
+        "stackbar_background",
+        "bar_accent",
+    ];
+
+    /// One of `values`.
+    fn one_of(values: Vec<Value>) -> BoxedStrategy<Value> {
+        proptest::sample::select(values).boxed()
+    }
+
+    /// An object with the given fields. Optional fields are missing from some of the objects.
+    fn object(fields: Vec<(&'static str, bool, BoxedStrategy<Value>)>) -> BoxedStrategy<Value> {
+        fields
+            .into_iter()
+            .map(|(key, required, value)| {
+                let value = if required {
+                    value.prop_map(Some).boxed()
+                } else {
+                    proptest::option::of(value).boxed()
+                };
+                value.prop_map(move |value| (key, value)).boxed()
+            })
+            .collect::<Vec<_>>()
+            .prop_map(|fields| {
+                Value::Object(
+                    fields
+                        .into_iter()
+                        .filter_map(|(key, value)| Some((key.to_string(), value?)))
+                        .collect(),
+                )
+            })
+            .boxed()
+    }
+
+    fn list(item: BoxedStrategy<Value>, max: usize) -> BoxedStrategy<Value> {
+        proptest::collection::vec(item, 0..=max)
+            .prop_map(Value::Array)
+            .boxed()
+    }
+
+    fn colour() -> BoxedStrategy<Value> {
+        one_of(vec![
+            json!({ "r": 66, "g": 165, "b": 245 }),
+            json!({ "r": 0, "g": 165, "b": 66 }),
+            json!({ "r": 10, "g": 20, "b": 30 }),
+        ])
+    }
+
+    fn wallpaper() -> BoxedStrategy<Value> {
+        object(vec![
+            (
+                "path",
+                true,
+                one_of(vec![json!("wallpaper.png"), json!("other.jpg")]),
+            ),
+            (
+                "generate_theme",
+                false,
+                one_of(vec![json!(true), json!(false)]),
+            ),
+            (
+                "theme_options",
+                false,
+                object(vec![
+                    (
+                        "theme_variant",
+                        false,
+                        one_of(vec![json!("Dark"), json!("Light")]),
+                    ),
+                    (
+                        "single_border",
+                        false,
+                        one_of(vec![json!("Base0D"), json!("Base08")]),
+                    ),
+                    (
+                        "bar_accent",
+                        false,
+                        one_of(vec![json!("Base0D"), json!("Base0A")]),
+                    ),
+                ]),
+            ),
+        ])
+    }
+
+    fn offset() -> BoxedStrategy<Value> {
+        one_of(vec![
+            json!({ "left": 0, "top": 0, "right": 0, "bottom": 0 }),
+            json!({ "left": 10, "top": 40, "right": 0, "bottom": 40 }),
+        ])
+    }
+
+    /// An animation option, either global or per type of animation.
+    fn per_animation<T: serde::Serialize>(
+        global: T,
+        movement: T,
+        transparency: T,
+    ) -> BoxedStrategy<Value> {
+        let per_type = HashMap::from([
+            (AnimationPrefix::Movement, movement),
+            (AnimationPrefix::Transparency, transparency),
+        ]);
+        one_of(vec![
+            serde_json::to_value(PerAnimationPrefixConfig::Global(global)).unwrap(),
+            serde_json::to_value(PerAnimationPrefixConfig::Prefix(per_type)).unwrap(),
+        ])
+    }
+
+    /// The `theme` with each of its colours either missing or set to the value of any of them.
+    /// The values are taken from the serialized `theme`, so they are always valid for it.
+    fn theme(theme: &KomorebiTheme) -> BoxedStrategy<Value> {
+        let Value::Object(mut base) = serde_json::to_value(theme).unwrap() else {
+            unreachable!("themes are serialized as objects");
+        };
+        let colours = THEME_COLOURS
+            .iter()
+            .filter_map(|key| base.remove(*key))
+            .collect::<Vec<_>>();
+        object(
+            THEME_COLOURS
+                .iter()
+                .map(|key| (*key, false, one_of(colours.clone())))
+                .collect(),
+        )
+        .prop_map(move |colours| {
+            let mut theme = base.clone();
+            if let Value::Object(colours) = colours {
+                theme.extend(colours);
+            }
+            Value::Object(theme)
+        })
+        .boxed()
+    }
+
+    fn workspace() -> BoxedStrategy<Value> {
+        object(vec![
+            ("name", true, one_of(vec![json!("I"), json!("II")])),
+            (
+                "layout",
+                false,
+                one_of(vec![json!("BSP"), json!("Columns")]),
+            ),
+            ("custom_layout", false, one_of(vec![json!("layout.json")])),
+            (
+                "container_padding",
+                false,
+                one_of(vec![json!(10), json!(0)]),
+            ),
+            ("initial_workspace_rules", false, one_of(vec![json!([])])),
+            (
+                "apply_window_based_work_area_offset",
+                false,
+                one_of(vec![json!(true), json!(false)]),
+            ),
+            ("tile", false, one_of(vec![json!(true), json!(false)])),
+            ("wallpaper", false, wallpaper()),
+            (
+                "layout_options",
+                false,
+                object(vec![
+                    ("grid", false, one_of(vec![json!({ "rows": 2 })])),
+                    (
+                        "scrolling",
+                        false,
+                        one_of(vec![json!({ "columns": 3 }), json!({ "columns": 2 })]),
+                    ),
+                ]),
+            ),
+            (
+                "layout_rules",
+                false,
+                one_of(vec![json!({}), json!({ "4": "Columns", "8": "Grid" })]),
+            ),
+            (
+                "window_container_behaviour",
+                false,
+                one_of(vec![json!("Create"), json!("Append")]),
+            ),
+            (
+                "floating_layer_behaviour",
+                false,
+                one_of(vec![json!("Tile"), json!("Float")]),
+            ),
+            (
+                "float_override",
+                false,
+                one_of(vec![json!(true), json!(false)]),
+            ),
+            (
+                "workspace_padding",
+                false,
+                one_of(vec![json!(10), json!(0)]),
+            ),
+            ("work_area_offset", false, offset()),
+        ])
+    }
 
-    home_dir.join("komorebi.json")
+    fn monitor() -> BoxedStrategy<Value> {
+        object(vec![
+            ("workspaces", true, list(workspace(), 3)),
+            (
+                "workspace_padding",
+                false,
+                one_of(vec![json!(10), json!(5)]),
+            ),
+            (
+                "window_based_work_area_offset_limit",
+                false,
+                one_of(vec![json!(1), json!(2)]),
+            ),
+            ("wallpaper", false, wallpaper()),
+            (
+                "container_padding",
+                false,
+                one_of(vec![json!(10), json!(5)]),
+            ),
+            ("work_area_offset", false, offset()),
+            ("window_based_work_area_offset", false, offset()),
+            (
+                "floating_layer_behaviour",
+                false,
+                one_of(vec![json!("Tile"), json!("Float")]),
+            ),
+        ])
+    }
+
+    /// Any config, with some of the fields of each kind having either their default value or some
+    /// other value.
+    fn config() -> impl Strategy<Value = StaticConfig> {
+        object(vec![
+            ("resize_delta", false, one_of(vec![json!(50), json!(20)])),
+            (
+                "window_container_behaviour",
+                false,
+                one_of(vec![json!("Create"), json!("Append")]),
+            ),
+            (
+                "mouse_follows_focus",
+                false,
+                one_of(vec![json!(true), json!(false)]),
+            ),
+            (
+                "app_specific_configuration_path",
+                false,
+                one_of(vec![
+                    json!("applications.json"),
+                    json!(["a.json", "b.json"]),
+                ]),
+            ),
+            ("border", false, one_of(vec![json!(true), json!(false)])),
+            ("border_width", false, one_of(vec![json!(8), json!(2)])),
+            (
+                "border_colours",
+                false,
+                object(vec![
+                    ("single", false, colour()),
+                    ("stack", false, colour()),
+                    ("monocle", false, colour()),
+                    ("floating", false, colour()),
+                    ("unfocused", false, colour()),
+                    ("unfocused_locked", false, colour()),
+                ]),
+            ),
+            ("global_work_area_offset", false, offset()),
+            (
+                "transparency_alpha",
+                false,
+                one_of(vec![json!(200), json!(100)]),
+            ),
+            (
+                "ignore_rules",
+                false,
+                one_of(vec![json!([]), json!([{ "kind": "Exe", "id": "app.exe" }])]),
+            ),
+            (
+                "stackbar",
+                false,
+                object(vec![
+                    ("height", false, one_of(vec![json!(40), json!(30)])),
+                    (
+                        "mode",
+                        false,
+                        one_of(vec![json!("OnStack"), json!("Always")]),
+                    ),
+                    (
+                        "tabs",
+                        false,
+                        object(vec![
+                            ("width", false, one_of(vec![json!(200), json!(100)])),
+                            ("focused_text", false, colour()),
+                            ("unfocused_text", false, colour()),
+                            ("background", false, colour()),
+                            ("font_size", false, one_of(vec![json!(12)])),
+                        ]),
+                    ),
+                ]),
+            ),
+            (
+                "animation",
+                false,
+                object(vec![
+                    ("enabled", true, per_animation(false, true, false)),
+                    ("duration", false, per_animation(250u64, 250, 100)),
+                    ("fps", false, one_of(vec![json!(60), json!(30)])),
+                ]),
+            ),
+            (
+                "theme",
+                false,
+                prop_oneof![
+                    theme(&DEFAULT_CATPPUCCIN_THEME),
+                    theme(&DEFAULT_BASE16_THEME),
+                    Just(serde_json::to_value(&*DEFAULT_CUSTOM_THEME).unwrap()),
+                ]
+                .boxed(),
+            ),
+            ("monitors", false, list(monitor(), 2)),
+        ])
+        .prop_map(|value| {
+            serde_json::from_value::<StaticConfig>(value).expect("generated config is valid")
+        })
+    }
+
+    #[test]
+    fn config_home_override_must_be_a_directory() {
+        let dir = std::env::temp_dir();
+        let home_override = RwLock::new(Some(dir.clone()));
+        assert_eq!(
+            find_config_home(&home_override, "KOMORICE_TEST_HOME", |home| home),
+            Ok((dir.clone(), HomePathType::Override))
+        );
+
+        let missing = dir.join("komorice-missing-config-home");
+        let home_override = RwLock::new(Some(missing.clone()));
+        let error = find_config_home(&home_override, "KOMORICE_TEST_HOME", |home| home)
+            .expect_err("the folder doesn't exist");
+        assert_eq!(error.path(), Some(missing.as_path()));
+        assert_eq!(error.to_app_error().kind, AppErrorKind::Error);
+    }
+
+    #[test]
+    fn warns_when_the_file_is_rewritten_as_a_whole() {
+        let mut config = merge_default(crate::testing::empty_config());
+        config.resize_delta = Some(20);
+        let whole = to_json(config.clone()).unwrap();
+
+        let (json, warnings) = file_contents(config.clone(), None, Version::current()).unwrap();
+        assert_eq!(json, whole);
+        assert!(warnings.is_empty());
+
+        let (json, warnings) = file_contents(
+            config.clone(),
+            Some("{ // not a config"),
+            Version::current(),
+        )
+        .unwrap();
+        assert_eq!(json, whole);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, AppErrorKind::Warning);
+
+        let original = "{\n  // Keep me\n  \"resize_delta\": 10\n}";
+        let (json, warnings) = file_contents(config, Some(original), Version::current()).unwrap();
+        assert!(json.contains("// Keep me"));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn nested_configs_with_required_fields_are_unmerged_as_a_whole() {
+        let config = |value: Value| serde_json::from_value::<StaticConfig>(value).unwrap();
+        // `enabled` is required, so it is kept when the other fields are removed, unless it has
+        // the default value as well.
+        let enabled = config(json!({ "animation": { "enabled": true, "fps": 60 } }));
+        assert_eq!(
+            unmerge_default(enabled).animation,
+            config(json!({ "animation": { "enabled": true } })).animation
+        );
+        let disabled = config(json!({ "animation": { "enabled": false, "fps": 60 } }));
+        assert_eq!(unmerge_default(disabled).animation, None);
+
+        let merged = merge_default(config(json!({})));
+        assert_eq!(unmerge_default(merged).animation, None);
+    }
+
+    #[test]
+    fn app_specific_paths_are_kept_as_written() {
+        let paths = [
+            "~/applications.json",
+            "%APPDATA%\\komorebi\\applications.json",
+            "$Env:KOMOREBI_CONFIG_HOME/apps/applications.json",
+        ];
+        for path in paths {
+            let config = serde_json::from_value::<StaticConfig>(
+                json!({ "app_specific_configuration_path": path }),
+            )
+            .unwrap();
+            let expected = Some(AppSpecificConfigurationPath::Single(PathBuf::from(path)));
+
+            let merged = merge_default(config);
+            assert_eq!(merged.app_specific_configuration_path, expected);
+            assert_eq!(
+                unmerge_default(merged).app_specific_configuration_path,
+                expected
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn unmerge_inverts_merge(config in config()) {
+            // An arbitrary config can set some options to their default value explicitly, which
+            // `unmerge_default` removes, since it can't tell them apart from the merged ones. So
+            // merging and unmerging gives back the config in its normalized form, without them,
+            // and a normalized config is given back as it is.
+            let normalized = unmerge_default(config.clone());
+            prop_assert_eq!(unmerge_default(merge_default(config)), normalized.clone());
+            prop_assert_eq!(
+                unmerge_default(merge_default(normalized.clone())),
+                normalized
+            );
+        }
+
+        #[test]
+        fn merge_inverts_unmerge(config in config()) {
+            let merged = merge_default(config);
+            prop_assert_eq!(merge_default(unmerge_default(merged.clone())), merged);
+        }
+
+        #[test]
+        fn merge_fills_nested_configs(config in config()) {
+            let merged = merge_default(config);
+            prop_assert!(merged.stackbar.as_ref().and_then(|s| s.tabs.as_ref()).is_some());
+            for workspace in merged.monitors.iter().flatten().flat_map(|m| &m.workspaces) {
+                prop_assert!(workspace.tile.is_some());
+                if let Some(wallpaper) = &workspace.wallpaper {
+                    prop_assert!(wallpaper.generate_theme.is_some());
+                    prop_assert!(wallpaper.theme_options.as_ref().and_then(|t| t.stack_border).is_some());
+                }
+            }
+        }
+    }
 }
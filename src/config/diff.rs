@@ -0,0 +1,725 @@
+use super::{merge_default, unmerge_default};
+use crate::{
+    BOLD_FONT, Message,
+    apperror::{AppError, AppErrorKind},
+    widget::opt_helpers,
+};
+
+use std::fmt::{Display, Formatter};
+
+use iced::{
+    Center, Element, Fill, padding,
+    widget::{column, container, row, scrollable, text},
+};
+use komorebi_client::StaticConfig;
+use serde_json::Value;
+
+/// Top level keys of the config that hold a list of rules.
+pub const RULE_LISTS: [&str; 10] = [
+    "ignore_rules",
+    "manage_rules",
+    "floating_applications",
+    "tray_and_multi_window_applications",
+    "object_name_change_applications",
+    "slow_application_identifiers",
+    "layered_applications",
+    "border_overflow_applications",
+    "transparency_ignore_rules",
+    "remove_titlebar_applications",
+];
+
+/// Keys of lists that should be compared by position (each element is walked on its own) instead
+/// of by value.
+const POSITIONAL_LISTS: [&str; 2] = ["monitors", "workspaces"];
+
+/// Max length of a value when shown on the changes view.
+const MAX_VALUE_LEN: usize = 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+impl Display for Segment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Segment::Key(key) => write!(f, "{key}"),
+            Segment::Index(idx) => write!(f, "[{idx}]"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub path: Vec<Segment>,
+    pub kind: ChangeKind,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+    /// If true, this change refers to a single element that was added or removed from the list
+    /// at `path`. These lists are compared by value (like the rule lists) so the position of the
+    /// element doesn't matter.
+    pub is_element: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Section {
+    General,
+    Monitor(usize),
+    Workspace(usize, usize),
+    Rules(String),
+}
+
+impl Section {
+    fn from_path(path: &[Segment]) -> Self {
+        match path {
+            [
+                Segment::Key(m),
+                Segment::Index(m_idx),
+                Segment::Key(w),
+                Segment::Index(w_idx),
+                ..,
+            ] if m == "monitors" && w == "workspaces" => Section::Workspace(*m_idx, *w_idx),
+            [Segment::Key(m), Segment::Index(m_idx), ..] if m == "monitors" => {
+                Section::Monitor(*m_idx)
+            }
+            [Segment::Key(key), ..] if RULE_LISTS.contains(&key.as_str()) => {
+                Section::Rules(key.clone())
+            }
+            _ => Section::General,
+        }
+    }
+
+    /// The amount of path segments that are already described by the section itself.
+    fn prefix_len(&self) -> usize {
+        match self {
+            Section::General => 0,
+            Section::Monitor(_) => 2,
+            Section::Workspace(_, _) => 4,
+            Section::Rules(_) => 1,
+        }
+    }
+
+    fn sort_key(&self) -> (usize, usize, usize, usize, &str) {
+        match self {
+            Section::General => (0, 0, 0, 0, ""),
+            Section::Monitor(m) => (1, *m, 0, 0, ""),
+            Section::Workspace(m, w) => (1, *m, 1, *w, ""),
+            Section::Rules(name) => (2, 0, 0, 0, name.as_str()),
+        }
+    }
+}
+
+impl Display for Section {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Section::General => write!(f, "General"),
+            Section::Monitor(m) => write!(f, "Monitor [{m}]"),
+            Section::Workspace(m, w) => write!(f, "Monitor [{m}] > Workspace [{w}]"),
+            Section::Rules(name) => write!(f, "Rules > {name}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionChanges {
+    pub section: Section,
+    pub changes: Vec<Change>,
+}
+
+impl Change {
+    /// The path of this change without the segments described by its section.
+    pub fn relative_path(&self, section: &Section) -> String {
+        path_to_string(&self.path[section.prefix_len().min(self.path.len())..])
+    }
+
+    fn view(
+        &self,
+        section: &Section,
+        section_idx: usize,
+        change_idx: usize,
+    ) -> Element<'_, Message> {
+        let (sign, style): (&str, fn(&iced::Theme) -> text::Style) = match self.kind {
+            ChangeKind::Added => ("+", text::success),
+            ChangeKind::Removed => ("-", text::danger),
+            ChangeKind::Changed => ("~", text::primary),
+        };
+        let path = self.relative_path(section);
+        let path = if path.is_empty() {
+            String::from("(entire section)")
+        } else {
+            path
+        };
+        let values = match self.kind {
+            ChangeKind::Added => short_value(self.new.as_ref()),
+            ChangeKind::Removed => short_value(self.old.as_ref()),
+            ChangeKind::Changed => format!(
+                "{} → {}",
+                short_value(self.old.as_ref()),
+                short_value(self.new.as_ref())
+            ),
+        };
+        row![
+            text(sign).font(*BOLD_FONT).style(style).width(15),
+            text(path).style(style),
+            container(opt_helpers::to_description_text(text(values))).width(Fill),
+            opt_helpers::reset_button(Some(Message::RevertChange(section_idx, change_idx))),
+        ]
+        .spacing(10)
+        .align_y(Center)
+        .into()
+    }
+}
+
+/// Computes the changes between the `old` and `new` configs. Both configs are unmerged from the
+/// default config first so that only the values that would actually be written are compared.
+pub fn diff(old: &StaticConfig, new: &StaticConfig) -> Vec<SectionChanges> {
+    diff_values(&to_value(old), &to_value(new))
+}
+
+/// Computes the changes between two config values, grouped by section.
+pub fn diff_values(old: &Value, new: &Value) -> Vec<SectionChanges> {
+    let mut sections: Vec<SectionChanges> = Vec::new();
+    for change in changes(old, new) {
+        let section = Section::from_path(&change.path);
+        if let Some(s) = sections.iter_mut().find(|s| s.section == section) {
+            s.changes.push(change);
+        } else {
+            sections.push(SectionChanges {
+                section,
+                changes: vec![change],
+            });
+        }
+    }
+    sections.sort_by(|a, b| a.section.sort_key().cmp(&b.section.sort_key()));
+    sections
+}
+
+/// Computes the changes between two config values, in the order they show up on the values.
+pub fn changes(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_value(&mut Vec::new(), Some(old), Some(new), &mut changes);
+    changes
+}
+
+/// Returns a short description of the changes between the `old` and `new` configs, to be used as
+/// a label for the change (e.g. on the undo history).
+pub fn describe(old: &StaticConfig, new: &StaticConfig) -> String {
+    let sections = diff(old, new);
+    let count = sections.iter().map(|s| s.changes.len()).sum::<usize>();
+    let Some((section, change)) = sections
+        .first()
+        .and_then(|s| s.changes.first().map(|c| (&s.section, c)))
+    else {
+        return String::from("Edit");
+    };
+    let path = change.relative_path(section);
+    let label = match (section, path.is_empty()) {
+        (Section::General, _) => path,
+        (_, true) => section.to_string(),
+        (_, false) => format!("{section} > {path}"),
+    };
+    if count > 1 {
+        format!("{label} (+{} more)", count - 1)
+    } else {
+        label
+    }
+}
+
+/// Returns a new config which is the same as `config` but with `change` reverted.
+pub fn revert(config: &StaticConfig, change: &Change) -> Result<StaticConfig, AppError> {
+    let mut value = to_value(config);
+    if !revert_value(&mut value, change) {
+        return Err(AppError {
+            title: "Failed to revert change".into(),
+            description: Some(format!(
+                "'{}' is past the end of its list now. Revert the changes to the items before it \
+                first.",
+                path_to_string(&change.path)
+            )),
+            kind: AppErrorKind::Error,
+        });
+    }
+    serde_json::from_value::<StaticConfig>(value)
+        .map(merge_default)
+        .map_err(|e| AppError {
+            title: "Failed to revert change".into(),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })
+}
+
+/// Reverts `change` on the `root` value. Returns `false` if the old value couldn't be set back,
+/// see `set`.
+pub fn revert_value(root: &mut Value, change: &Change) -> bool {
+    if change.is_element {
+        let Some(list) = get_or_create(root, &change.path, Value::Array(Vec::new())) else {
+            return true;
+        };
+        if let Value::Array(list) = list {
+            match change.kind {
+                ChangeKind::Added => {
+                    if let Some(new) = &change.new
+                        && let Some(idx) = list.iter().position(|v| v == new)
+                    {
+                        list.remove(idx);
+                    }
+                }
+                ChangeKind::Removed => {
+                    if let Some(old) = &change.old {
+                        list.push(old.clone());
+                    }
+                }
+                ChangeKind::Changed => {}
+            }
+        }
+        true
+    } else {
+        match &change.old {
+            Some(old) => set(root, &change.path, old.clone()),
+            None => {
+                remove(root, &change.path);
+                true
+            }
+        }
+    }
+}
+
+/// Applies `change` on the `root` value, the opposite of `revert_value`. Returns `false` if the
+/// new value couldn't be set, see `set`.
+pub fn apply_value(root: &mut Value, change: &Change) -> bool {
+    if change.is_element {
+        let Some(list) = get_or_create(root, &change.path, Value::Array(Vec::new())) else {
+            return true;
+        };
+        if let Value::Array(list) = list {
+            match change.kind {
+                ChangeKind::Added => {
+                    if let Some(new) = &change.new
+                        && !list.contains(new)
+                    {
+                        list.push(new.clone());
+                    }
+                }
+                ChangeKind::Removed => {
+                    if let Some(old) = &change.old
+                        && let Some(idx) = list.iter().position(|v| v == old)
+                    {
+                        list.remove(idx);
+                    }
+                }
+                ChangeKind::Changed => {}
+            }
+        }
+        true
+    } else {
+        match &change.new {
+            Some(new) => set(root, &change.path, new.clone()),
+            None => {
+                remove(root, &change.path);
+                true
+            }
+        }
+    }
+}
+
+/// Returns the value at `path`, if there is one.
+pub fn get<'a>(root: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter()
+        .try_fold(root, |value, segment| match segment {
+            Segment::Key(key) => value.get(key.as_str()),
+            Segment::Index(idx) => value.get(*idx),
+        })
+        .filter(|value| !value.is_null())
+}
+
+/// Joins the segments of `path`, like `monitors[0].workspaces[1].layout`.
+pub fn path_to_string(path: &[Segment]) -> String {
+    path.iter().fold(String::new(), |mut s, segment| {
+        if !s.is_empty() && matches!(segment, Segment::Key(_)) {
+            s.push('.');
+        }
+        s.push_str(&segment.to_string());
+        s
+    })
+}
+
+/// Serializes the unmerged `config` into a `Value`.
+pub fn to_value(config: &StaticConfig) -> Value {
+    serde_json::to_value(unmerge_default(config.clone())).unwrap_or(Value::Null)
+}
+
+/// Returns a short string representation of `value` to be shown to the user.
+pub fn short_value(value: Option<&Value>) -> String {
+    let s = match value {
+        Some(v) => serde_json::to_string(v).unwrap_or_default(),
+        None => String::from("[None]"),
+    };
+    if s.chars().count() > MAX_VALUE_LEN {
+        let truncated: String = s.chars().take(MAX_VALUE_LEN).collect();
+        format!("{truncated}…")
+    } else {
+        s
+    }
+}
+
+fn diff_value(
+    path: &mut Vec<Segment>,
+    old: Option<&Value>,
+    new: Option<&Value>,
+    changes: &mut Vec<Change>,
+) {
+    let old = old.filter(|v| !v.is_null());
+    let new = new.filter(|v| !v.is_null());
+    match (old, new) {
+        (None, None) => {}
+        (None, Some(new)) => changes.push(Change {
+            path: path.clone(),
+            kind: ChangeKind::Added,
+            old: None,
+            new: Some(new.clone()),
+            is_element: false,
+        }),
+        (Some(old), None) => changes.push(Change {
+            path: path.clone(),
+            kind: ChangeKind::Removed,
+            old: Some(old.clone()),
+            new: None,
+            is_element: false,
+        }),
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            let keys = old
+                .keys()
+                .chain(new.keys().filter(|k| !old.contains_key(*k)))
+                .cloned()
+                .collect::<Vec<_>>();
+            for key in keys {
+                path.push(Segment::Key(key.clone()));
+                diff_value(path, old.get(&key), new.get(&key), changes);
+                path.pop();
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) if is_positional(path) => {
+            for idx in 0..old.len().max(new.len()) {
+                path.push(Segment::Index(idx));
+                diff_value(path, old.get(idx), new.get(idx), changes);
+                path.pop();
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            let mut matched = vec![false; new.len()];
+            for o in old {
+                let found = new
+                    .iter()
+                    .enumerate()
+                    .position(|(i, n)| !matched[i] && n == o);
+                match found {
+                    Some(i) => matched[i] = true,
+                    None => changes.push(Change {
+                        path: path.clone(),
+                        kind: ChangeKind::Removed,
+                        old: Some(o.clone()),
+                        new: None,
+                        is_element: true,
+                    }),
+                }
+            }
+            for (n, _) in new.iter().zip(matched).filter(|(_, m)| !m) {
+                changes.push(Change {
+                    path: path.clone(),
+                    kind: ChangeKind::Added,
+                    old: None,
+                    new: Some(n.clone()),
+                    is_element: true,
+                });
+            }
+        }
+        (Some(old), Some(new)) => {
+            if old != new {
+                changes.push(Change {
+                    path: path.clone(),
+                    kind: ChangeKind::Changed,
+                    old: Some(old.clone()),
+                    new: Some(new.clone()),
+                    is_element: false,
+                });
+            }
+        }
+    }
+}
+
+fn is_positional(path: &[Segment]) -> bool {
+    matches!(path.last(), Some(Segment::Key(key)) if POSITIONAL_LISTS.contains(&key.as_str()))
+}
+
+/// Gets the value at `path`, creating any missing objects along the way and using `default` for
+/// the final value if it doesn't exist.
+fn get_or_create<'a>(
+    root: &'a mut Value,
+    path: &[Segment],
+    default: Value,
+) -> Option<&'a mut Value> {
+    let Some((last, parents)) = path.split_last() else {
+        return Some(root);
+    };
+    let parent = parents
+        .iter()
+        .try_fold(root, |value, segment| match segment {
+            Segment::Key(key) => {
+                if !value.is_object() {
+                    *value = Value::Object(serde_json::Map::new());
+                }
+                value
+                    .as_object_mut()
+                    .map(|obj| obj.entry(key.clone()).or_insert(Value::Null))
+            }
+            Segment::Index(idx) => value.as_array_mut().and_then(|arr| arr.get_mut(*idx)),
+        })?;
+    match last {
+        Segment::Key(key) => {
+            if !parent.is_object() {
+                *parent = Value::Object(serde_json::Map::new());
+            }
+            let value = parent
+                .as_object_mut()?
+                .entry(key.clone())
+                .or_insert(Value::Null);
+            if value.is_null() {
+                *value = default;
+            }
+            Some(value)
+        }
+        Segment::Index(idx) => parent.as_array_mut().and_then(|arr| arr.get_mut(*idx)),
+    }
+}
+
+/// Sets the value at `path` to `new_value`, creating any missing parents. Returns whether it was
+/// set. An index past the end of its list isn't, since pushing the value would put it on another
+/// index, like when only the last of several removed monitors is reverted.
+pub fn set(root: &mut Value, path: &[Segment], new_value: Value) -> bool {
+    let Some((last, parents)) = path.split_last() else {
+        *root = new_value;
+        return true;
+    };
+    let container = match last {
+        Segment::Key(_) => Value::Object(serde_json::Map::new()),
+        Segment::Index(_) => Value::Array(Vec::new()),
+    };
+    let Some(parent) = get_or_create(root, parents, container) else {
+        return false;
+    };
+    match (last, parent) {
+        (Segment::Key(key), Value::Object(obj)) => {
+            obj.insert(key.clone(), new_value);
+            true
+        }
+        (Segment::Index(idx), Value::Array(arr)) if *idx < arr.len() => {
+            arr[*idx] = new_value;
+            true
+        }
+        (Segment::Index(idx), Value::Array(arr)) if *idx == arr.len() => {
+            arr.push(new_value);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Removes the value at `path`, if there is one.
+pub fn remove(root: &mut Value, path: &[Segment]) {
+    let Some((last, parents)) = path.split_last() else {
+        *root = Value::Null;
+        return;
+    };
+    let parent = parents
+        .iter()
+        .try_fold(root, |value, segment| match segment {
+            Segment::Key(key) => value.get_mut(key.as_str()),
+            Segment::Index(idx) => value.get_mut(*idx),
+        });
+    match (last, parent) {
+        (Segment::Key(key), Some(Value::Object(obj))) => {
+            obj.remove(key);
+        }
+        (Segment::Index(idx), Some(Value::Array(arr))) => {
+            if *idx < arr.len() {
+                arr.remove(*idx);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Creates the view with all the pending changes grouped by section, each change with a button to
+/// revert it.
+pub fn view(sections: &[SectionChanges]) -> Element<'_, Message> {
+    if sections.is_empty() {
+        return opt_helpers::description_text("There are no pending changes.").into();
+    }
+    let initial_col = column![].spacing(10).padding(padding::all(5.0).right(20.0));
+    let sections_col =
+        sections
+            .iter()
+            .enumerate()
+            .fold(initial_col, |col, (section_idx, section)| {
+                let changes = section.changes.iter().enumerate().fold(
+                    column![].spacing(5).padding(padding::left(20)),
+                    |col, (change_idx, change)| {
+                        col.push(change.view(&section.section, section_idx, change_idx))
+                    },
+                );
+                col.push(column![text!("{}", section.section).font(*BOLD_FONT), changes].spacing(5))
+            });
+    container(scrollable(sections_col)).max_height(350.0).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::empty_config;
+
+    use serde_json::json;
+
+    fn key(key: &str) -> Segment {
+        Segment::Key(key.into())
+    }
+
+    #[test]
+    fn finds_changes_on_nested_objects() {
+        let old = json!({ "animation": { "enabled": true, "fps": 60 }, "resize_delta": 50 });
+        let new = json!({ "animation": { "enabled": false, "fps": 60, "duration": 250 } });
+
+        let changes = changes(&old, &new);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].path, vec![key("animation"), key("enabled")]);
+        assert_eq!(changes[0].kind, ChangeKind::Changed);
+        assert_eq!(changes[1].path, vec![key("animation"), key("duration")]);
+        assert_eq!(changes[1].kind, ChangeKind::Added);
+        assert_eq!(changes[1].new, Some(json!(250)));
+        assert_eq!(changes[2].path, vec![key("resize_delta")]);
+        assert_eq!(changes[2].kind, ChangeKind::Removed);
+    }
+
+    #[test]
+    fn groups_changes_by_section() {
+        let old = json!({ "monitors": [{ "workspaces": [{ "name": "I" }] }] });
+        let new = json!({
+            "monitors": [{ "workspaces": [{ "name": "II" }], "container_padding": 5 }],
+            "ignore_rules": [{ "kind": "Exe", "id": "a.exe" }],
+        });
+
+        let sections = diff_values(&old, &new)
+            .into_iter()
+            .map(|s| s.section)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sections,
+            vec![
+                Section::Monitor(0),
+                Section::Workspace(0, 0),
+                Section::Rules("ignore_rules".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_elements_added_and_removed_from_lists() {
+        let a = json!({ "kind": "Exe", "id": "a.exe" });
+        let b = json!({ "kind": "Exe", "id": "b.exe" });
+        let c = json!({ "kind": "Exe", "id": "c.exe" });
+        let old = json!({ "ignore_rules": [a, b] });
+        let new = json!({ "ignore_rules": [b, c] });
+
+        let changes = changes(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|c| c.is_element));
+        assert_eq!(changes[0].kind, ChangeKind::Removed);
+        assert_eq!(changes[0].old, Some(a));
+        assert_eq!(changes[1].kind, ChangeKind::Added);
+        assert_eq!(changes[1].new, Some(c));
+
+        let mut reverted = new.clone();
+        for change in changes.iter().rev() {
+            revert_value(&mut reverted, change);
+        }
+        let mut list = reverted["ignore_rules"].as_array().unwrap().clone();
+        list.sort_by_key(|v| v["id"].as_str().unwrap().to_string());
+        assert_eq!(Value::Array(list), old["ignore_rules"]);
+
+        let mut applied = old.clone();
+        for change in &changes {
+            apply_value(&mut applied, change);
+        }
+        assert_eq!(applied, new);
+    }
+
+    #[test]
+    fn positional_lists_are_compared_element_by_element() {
+        let old = json!({ "monitors": [{ "workspace_padding": 10 }] });
+        let new = json!({ "monitors": [{ "workspace_padding": 20 }, { "workspace_padding": 5 }] });
+
+        let changes = changes(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|c| !c.is_element));
+        assert_eq!(
+            changes[0].path,
+            vec![key("monitors"), Segment::Index(0), key("workspace_padding")]
+        );
+        assert_eq!(changes[1].path, vec![key("monitors"), Segment::Index(1)]);
+        assert_eq!(changes[1].kind, ChangeKind::Added);
+
+        let mut reverted = new.clone();
+        for change in changes.iter().rev() {
+            revert_value(&mut reverted, change);
+        }
+        assert_eq!(reverted, old);
+    }
+
+    #[test]
+    fn reverting_a_removed_item_keeps_its_index() {
+        let old = json!({ "monitors": [{ "container_padding": 0 }, { "container_padding": 1 }, { "container_padding": 2 }] });
+        let new = json!({ "monitors": [{ "container_padding": 0 }] });
+
+        let changes = changes(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].path, vec![key("monitors"), Segment::Index(2)]);
+
+        // The monitor at index 2 can't be put back before the one at index 1.
+        let mut reverted = new.clone();
+        assert!(!revert_value(&mut reverted, &changes[1]));
+        assert_eq!(reverted, new);
+
+        assert!(revert_value(&mut reverted, &changes[0]));
+        assert!(revert_value(&mut reverted, &changes[1]));
+        assert_eq!(reverted, old);
+    }
+
+    #[test]
+    fn reverting_every_change_restores_the_loaded_config() {
+        let loaded = merge_default(empty_config());
+        let mut edited = loaded.clone();
+        edited.resize_delta = Some(10);
+        edited.default_workspace_padding = Some(3);
+
+        let changes = diff(&loaded, &edited)
+            .into_iter()
+            .flat_map(|s| s.changes)
+            .collect::<Vec<_>>();
+        assert_eq!(changes.len(), 2);
+
+        let reverted = changes
+            .iter()
+            .try_fold(edited, |config, change| revert(&config, change))
+            .unwrap();
+        assert_eq!(to_value(&reverted), to_value(&loaded));
+        assert!(diff(&loaded, &reverted).is_empty());
+    }
+}
@@ -0,0 +1,567 @@
+//! Document preserving updates of `komorebi.json` files.
+//!
+//! Instead of serializing the whole config again, only the values that changed are edited on the
+//! original text, so comments, unknown keys, key order and formatting are kept as they were.
+
+use super::{diff, merge_default, migration::Version, schema_url};
+
+use komorebi_client::StaticConfig;
+use serde_json::{Map, Value};
+
+/// Returns the `original` file contents updated to match `config`, changing only the values that
+/// differ from what `original` already has. A `$schema` for another version than `version` is
+/// changed as well, so that the file isn't taken for one written for that other version.
+///
+/// Returns `None` if `original` can't be read as a config or if the edited document doesn't end
+/// up matching `config`, in which case the caller should serialize the whole config instead.
+pub fn update(original: &str, config: &StaticConfig, version: Version) -> Option<String> {
+    let original_config: StaticConfig = serde_json::from_str(original).ok()?;
+    let old = diff::to_value(&merge_default(original_config));
+    let new = diff::to_value(config);
+
+    let root = Parser::new(original).parse()?;
+    let mut edits = schema_edit(original, &root, version)
+        .into_iter()
+        .collect::<Vec<_>>();
+    if old == new && edits.is_empty() {
+        return Some(original.to_string());
+    }
+    edit_value(original, &root, &old, &new, &mut edits);
+
+    edits.sort_by_key(|edit| (edit.start, edit.end));
+    if edits.windows(2).any(|w| w[0].end > w[1].start) {
+        return None;
+    }
+    let mut updated = original.to_string();
+    for edit in edits.iter().rev() {
+        updated.replace_range(edit.start..edit.end, &edit.text);
+    }
+
+    // Make sure the edits got us exactly the new config.
+    let updated_config: StaticConfig = serde_json::from_str(&updated).ok()?;
+    (diff::to_value(&merge_default(updated_config)) == new).then_some(updated)
+}
+
+/// The edit changing the `$schema` of the `root` object to the one of `version`, if it has a
+/// `$schema` for another version. A file without `$schema` is left without one.
+fn schema_edit(original: &str, root: &Node, version: Version) -> Option<Edit> {
+    let Node::Object { members, .. } = root else {
+        return None;
+    };
+    let schema = members.iter().find(|member| member.key == "$schema")?;
+    let (start, end) = (schema.value.start(), schema.value.end());
+    let current = serde_json::from_str::<String>(&original[start..end]).ok();
+    if current.as_deref().and_then(Version::parse) == Some(version) {
+        return None;
+    }
+    Some(Edit {
+        start,
+        end,
+        text: Value::String(schema_url(version)).to_string(),
+    })
+}
+
+/// A replacement of the text between `start` and `end` with `text`.
+#[derive(Debug)]
+struct Edit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+#[derive(Debug)]
+enum Node {
+    Object {
+        start: usize,
+        end: usize,
+        members: Vec<Member>,
+    },
+    Array {
+        start: usize,
+        end: usize,
+        items: Vec<Node>,
+    },
+    Scalar {
+        start: usize,
+        end: usize,
+    },
+}
+
+#[derive(Debug)]
+struct Member {
+    key: String,
+    key_start: usize,
+    value: Node,
+    /// Position of the comma after the value, if any.
+    comma: Option<usize>,
+}
+
+impl Node {
+    fn start(&self) -> usize {
+        match self {
+            Node::Object { start, .. } | Node::Array { start, .. } | Node::Scalar { start, .. } => {
+                *start
+            }
+        }
+    }
+
+    fn end(&self) -> usize {
+        match self {
+            Node::Object { end, .. } | Node::Array { end, .. } | Node::Scalar { end, .. } => *end,
+        }
+    }
+}
+
+fn edit_value(text: &str, node: &Node, old: &Value, new: &Value, edits: &mut Vec<Edit>) {
+    if old == new {
+        return;
+    }
+    match (node, old, new) {
+        (Node::Object { members, end, .. }, Value::Object(old), Value::Object(new)) => {
+            edit_object(text, members, *end, old, new, edits)
+                .unwrap_or_else(|| replace(text, node, &Value::Object(new.clone()), edits));
+        }
+        (Node::Array { items, .. }, Value::Array(old), Value::Array(new))
+            if items.len() == old.len() && old.len() == new.len() =>
+        {
+            for ((item, old), new) in items.iter().zip(old).zip(new) {
+                edit_value(text, item, old, new, edits);
+            }
+        }
+        _ => replace(text, node, new, edits),
+    }
+}
+
+/// Edits the members of an object in place. Returns `None` if the object has to be replaced as a
+/// whole instead.
+fn edit_object(
+    text: &str,
+    members: &[Member],
+    end: usize,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    edits: &mut Vec<Edit>,
+) -> Option<()> {
+    let get = |map: &'_ Map<String, Value>, key: &str| map.get(key).cloned().filter(is_present);
+    // If a key is repeated only the last one is used by the parser.
+    let member = |key: &str| members.iter().rev().find(|member| member.key == key);
+
+    let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for key in keys {
+        match (get(old, key), get(new, key), member(key)) {
+            (_, None, Some(member)) => removed.push(member),
+            (old, Some(new), Some(member)) => changed.push((member, old, new)),
+            (_, Some(new), None) => added.push((key, new)),
+            (_, None, None) => {}
+        }
+    }
+
+    let remaining = members
+        .iter()
+        .filter(|member| {
+            !removed
+                .iter()
+                .any(|removed| std::ptr::eq(*removed, *member))
+        })
+        .collect::<Vec<_>>();
+    if remaining.is_empty() && (!removed.is_empty() || !added.is_empty()) {
+        return None;
+    }
+
+    for (member, old, new) in changed {
+        match old {
+            Some(old) => edit_value(text, &member.value, &old, &new, edits),
+            None => replace(text, &member.value, &new, edits),
+        }
+    }
+
+    if removed.is_empty() && added.is_empty() {
+        return Some(());
+    }
+
+    for member in &removed {
+        edits.push(Edit {
+            start: line_start(text, member.key_start),
+            end: member_end(text, member),
+            text: String::new(),
+        });
+    }
+
+    let last = remaining.last()?;
+    let last_is_removed_tail = members
+        .last()
+        .is_some_and(|member| !std::ptr::eq(member, *last));
+    if added.is_empty() {
+        if last_is_removed_tail && let Some(comma) = last.comma {
+            edits.push(Edit {
+                start: comma,
+                end: comma + 1,
+                text: String::new(),
+            });
+        }
+    } else {
+        if last.comma.is_none() {
+            let end = last.value.end();
+            edits.push(Edit {
+                start: end,
+                end,
+                text: ",".into(),
+            });
+        }
+        let indent = indentation(text, last.key_start);
+        let members_text = added
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{indent}{}: {}",
+                    Value::String(key.to_string()),
+                    pretty(value, &indent)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+        // `end` is just after the closing brace.
+        let close = end - 1;
+        let insert_at = line_start(text, close);
+        let members_text = if insert_at == 0 || text.as_bytes()[insert_at - 1] == b'\n' {
+            format!("{members_text}\n")
+        } else {
+            format!("\n{members_text}\n")
+        };
+        // Insert after the end of the last member so it doesn't overlap the removed members.
+        let at = insert_at.max(member_end(text, last));
+        edits.push(Edit {
+            start: at,
+            end: at,
+            text: members_text,
+        });
+    }
+
+    Some(())
+}
+
+fn replace(text: &str, node: &Node, value: &Value, edits: &mut Vec<Edit>) {
+    let indent = indentation(text, node.start());
+    edits.push(Edit {
+        start: node.start(),
+        end: node.end(),
+        text: pretty(value, &indent),
+    });
+}
+
+fn is_present(value: &Value) -> bool {
+    !value.is_null()
+}
+
+/// Pretty prints `value` with every line after the first one indented by `indent`.
+fn pretty(value: &Value, indent: &str) -> String {
+    serde_json::to_string_pretty(value)
+        .unwrap_or_default()
+        .replace('\n', &format!("\n{indent}"))
+}
+
+/// Returns the whitespace at the start of the line that contains `position`.
+fn indentation(text: &str, position: usize) -> String {
+    text[line_start(text, position)..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+/// Returns the start of the line that contains `position` if there is only whitespace between
+/// them, otherwise it returns `position`.
+fn line_start(text: &str, position: usize) -> usize {
+    let start = text[..position].rfind('\n').map_or(0, |idx| idx + 1);
+    if text[start..position].trim().is_empty() {
+        start
+    } else {
+        position
+    }
+}
+
+/// Returns the end of a member, including its comma and the rest of the line if there is only
+/// whitespace or a comment after it.
+fn member_end(text: &str, member: &Member) -> usize {
+    let end = member.comma.map_or(member.value.end(), |comma| comma + 1);
+    let line_end = text[end..]
+        .find('\n')
+        .map_or(text.len(), |idx| end + idx + 1);
+    let rest = text[end..line_end].trim();
+    if rest.is_empty() || rest.starts_with("//") {
+        line_end
+    } else {
+        end
+    }
+}
+
+/// A small JSON parser which keeps the position of every value. It accepts comments and trailing
+/// commas like the lenient parser used to load the config.
+struct Parser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            bytes: text.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn parse(mut self) -> Option<Node> {
+        let node = self.value()?;
+        self.skip_whitespace();
+        (self.position == self.bytes.len()).then_some(node)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else if self.text[self.position..].starts_with("//") {
+                self.position = self.text[self.position..]
+                    .find('\n')
+                    .map_or(self.bytes.len(), |idx| self.position + idx + 1);
+            } else if self.text[self.position..].starts_with("/*") {
+                self.position = self.text[self.position + 2..]
+                    .find("*/")
+                    .map_or(self.bytes.len(), |idx| self.position + 2 + idx + 2);
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn value(&mut self) -> Option<Node> {
+        self.skip_whitespace();
+        let start = self.position;
+        match self.peek()? {
+            b'{' => {
+                self.position += 1;
+                let mut members: Vec<Member> = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b'}' => {
+                            self.position += 1;
+                            return Some(Node::Object {
+                                start,
+                                end: self.position,
+                                members,
+                            });
+                        }
+                        b',' => {
+                            let last = members.last_mut()?;
+                            if last.comma.is_some() {
+                                return None;
+                            }
+                            last.comma = Some(self.position);
+                            self.position += 1;
+                        }
+                        b'"' => {
+                            let key_start = self.position;
+                            let key = self.string()?;
+                            self.skip_whitespace();
+                            if self.peek()? != b':' {
+                                return None;
+                            }
+                            self.position += 1;
+                            let value = self.value()?;
+                            members.push(Member {
+                                key,
+                                key_start,
+                                value,
+                                comma: None,
+                            });
+                        }
+                        _ => return None,
+                    }
+                }
+            }
+            b'[' => {
+                self.position += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        b']' => {
+                            self.position += 1;
+                            return Some(Node::Array {
+                                start,
+                                end: self.position,
+                                items,
+                            });
+                        }
+                        b',' => self.position += 1,
+                        _ => items.push(self.value()?),
+                    }
+                }
+            }
+            b'"' => {
+                self.string()?;
+                Some(Node::Scalar {
+                    start,
+                    end: self.position,
+                })
+            }
+            _ => {
+                while self
+                    .peek()
+                    .is_some_and(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'+' | b'.'))
+                {
+                    self.position += 1;
+                }
+                (self.position > start).then_some(Node::Scalar {
+                    start,
+                    end: self.position,
+                })
+            }
+        }
+    }
+
+    /// Parses a string and returns its contents.
+    fn string(&mut self) -> Option<String> {
+        let start = self.position;
+        self.position += 1;
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.position += 1;
+                    return serde_json::from_str(&self.text[start..self.position]).ok();
+                }
+                b'\\' => self.position += 2,
+                _ => self.position += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use komorebi_client::{StackbarLabel, StackbarMode};
+
+    const ORIGINAL: &str = r#"{
+  "$schema": "https://raw.githubusercontent.com/LGUG2Z/komorebi/v0.1.39/schema.json",
+  "app_specific_configuration_path": "applications.json",
+  // How much to resize windows by
+  "resize_delta": 100,
+  "border_width": 4, // thinner than the default
+  "stackbar": {
+    "height": 30,
+    "mode": "Always",
+  },
+}
+"#;
+
+    /// The config on `text`, merged with the defaults like the loaded configs are.
+    fn config(text: &str) -> StaticConfig {
+        merge_default(serde_json::from_str(text).unwrap())
+    }
+
+    fn stackbar(config: &mut StaticConfig) -> &mut komorebi_client::StackbarConfig {
+        config.stackbar.as_mut().unwrap()
+    }
+
+    #[test]
+    fn keeps_the_file_as_it_was_when_nothing_changed() {
+        assert_eq!(
+            update(ORIGINAL, &config(ORIGINAL), Version::current()).as_deref(),
+            Some(ORIGINAL)
+        );
+    }
+
+    #[test]
+    fn only_edits_the_changed_value() {
+        let mut config = config(ORIGINAL);
+        config.resize_delta = Some(20);
+        assert_eq!(
+            update(ORIGINAL, &config, Version::current()),
+            Some(ORIGINAL.replace("\"resize_delta\": 100", "\"resize_delta\": 20"))
+        );
+    }
+
+    #[test]
+    fn keeps_comments_and_trailing_commas() {
+        let mut config = config(ORIGINAL);
+        config.border_width = Some(2);
+        let updated = update(ORIGINAL, &config, Version::current()).unwrap();
+        assert_eq!(
+            updated,
+            ORIGINAL.replace("\"border_width\": 4,", "\"border_width\": 2,")
+        );
+        assert!(updated.contains("// How much to resize windows by"));
+        assert!(updated.contains("// thinner than the default"));
+        assert!(updated.contains("\"mode\": \"Always\",\n  },\n}"));
+    }
+
+    #[test]
+    fn adds_nested_keys() {
+        let mut config = config(ORIGINAL);
+        stackbar(&mut config).label = Some(StackbarLabel::Process);
+        assert_eq!(
+            update(ORIGINAL, &config, Version::current()),
+            Some(ORIGINAL.replace(
+                "\"mode\": \"Always\",\n",
+                "\"mode\": \"Always\",\n    \"label\": \"Process\"\n"
+            ))
+        );
+    }
+
+    #[test]
+    fn removes_nested_keys() {
+        let mut config = config(ORIGINAL);
+        // The default mode isn't written to the file.
+        stackbar(&mut config).mode = Some(StackbarMode::OnStack);
+        assert_eq!(
+            update(ORIGINAL, &config, Version::current()),
+            Some(ORIGINAL.replace(
+                "\"height\": 30,\n    \"mode\": \"Always\",\n",
+                "\"height\": 30\n"
+            ))
+        );
+    }
+
+    #[test]
+    fn gives_up_on_files_that_cant_be_read() {
+        let config = config(ORIGINAL);
+        assert_eq!(
+            update("{ \"resize_delta\": ", &config, Version::current()),
+            None
+        );
+        assert_eq!(update("", &config, Version::current()), None);
+    }
+
+    #[test]
+    fn rewrites_the_schema_of_other_versions() {
+        let old = ORIGINAL.replace("v0.1.39", "v0.1.38");
+        let updated = update(&old, &config(&old), Version::current()).unwrap();
+        assert_eq!(
+            updated,
+            old.replace(
+                "https://raw.githubusercontent.com/LGUG2Z/komorebi/v0.1.38/schema.json",
+                &schema_url(Version::current())
+            )
+        );
+
+        let mut config = config(&old);
+        config.resize_delta = Some(20);
+        let updated = update(&old, &config, Version::current()).unwrap();
+        assert!(updated.contains(&schema_url(Version::current())));
+        assert!(updated.contains("\"resize_delta\": 20"));
+    }
+}
@@ -0,0 +1,304 @@
+//! Three-way merge of configs, used when the config file changes on disk while there are unsaved
+//! changes on Komorice.
+//!
+//! The changes are compared field by field against the last loaded config, which is the version
+//! both sides started from. The changes made on only one side are kept, while the fields that were
+//! changed on both sides to different values become conflicts, which use the version on disk until
+//! the user resolves them.
+
+use super::diff::{self, ChangeKind, Segment};
+use super::merge_default;
+use crate::apperror::{AppError, AppErrorKind};
+
+use komorebi_client::StaticConfig;
+use serde_json::Value;
+
+/// Which version to keep when resolving a conflict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The version with the changes made on Komorice.
+    Mine,
+    /// The version on disk.
+    Disk,
+}
+
+/// A field that was changed both on Komorice and on disk, to different values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub path: Vec<Segment>,
+    pub mine: Option<Value>,
+    pub disk: Option<Value>,
+}
+
+impl Conflict {
+    /// The path of the field, like `monitors[0].workspaces[1].layout`.
+    pub fn label(&self) -> String {
+        diff::path_to_string(&self.path)
+    }
+
+    pub fn mine_text(&self) -> String {
+        diff::short_value(self.mine.as_ref())
+    }
+
+    pub fn disk_text(&self) -> String {
+        diff::short_value(self.disk.as_ref())
+    }
+}
+
+#[derive(Debug)]
+pub struct Merged {
+    /// The config on disk with all the changes from Komorice that don't conflict with it.
+    pub config: StaticConfig,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Merges the changes made on Komorice (`mine`) and on disk (`disk`) since `base` was loaded.
+pub fn merge(
+    base: &StaticConfig,
+    mine: &StaticConfig,
+    disk: &StaticConfig,
+) -> Result<Merged, AppError> {
+    let mut base = diff::to_value(base);
+    let mut mine = diff::to_value(mine);
+    let mut disk = diff::to_value(disk);
+    // Rules added on both sides to a list that didn't exist should be merged instead of
+    // conflicting, so the missing lists are compared as empty ones.
+    let missing_on_disk = diff::RULE_LISTS
+        .into_iter()
+        .filter(|key| diff::get(&disk, &[Segment::Key(key.to_string())]).is_none())
+        .collect::<Vec<_>>();
+    for key in diff::RULE_LISTS {
+        let path = [Segment::Key(key.to_string())];
+        if [&base, &mine, &disk]
+            .iter()
+            .any(|value| diff::get(value, &path).is_some())
+        {
+            for value in [&mut base, &mut mine, &mut disk] {
+                if diff::get(value, &path).is_none() {
+                    diff::set(value, &path, Value::Array(Vec::new()));
+                }
+            }
+        }
+    }
+    let my_changes = diff::changes(&base, &mine);
+    let disk_changes = diff::changes(&base, &disk);
+
+    let mut merged = disk.clone();
+    let mut conflicts: Vec<Conflict> = Vec::new();
+    // Removing an item from a list shifts the items after it, so the removals are applied last,
+    // starting from the end of the list.
+    let (removals, others): (Vec<_>, Vec<_>) = my_changes
+        .iter()
+        .partition(|change| matches!(change.kind, ChangeKind::Removed) && !change.is_element);
+    for change in others.into_iter().chain(removals.into_iter().rev()) {
+        // Elements of the lists compared by value, like the rules, can always be added or removed
+        // on their own.
+        if change.is_element {
+            diff::apply_value(&mut merged, change);
+            continue;
+        }
+        let overlapping = disk_changes
+            .iter()
+            .filter(|d| !d.is_element && overlaps(&d.path, &change.path))
+            .collect::<Vec<_>>();
+        if overlapping.is_empty() {
+            diff::apply_value(&mut merged, change);
+        } else if !overlapping
+            .iter()
+            .all(|d| d.path == change.path && d.new == change.new)
+        {
+            let path = overlapping
+                .iter()
+                .map(|d| &d.path)
+                .chain([&change.path])
+                .min_by_key(|path| path.len())
+                .expect("there is at least one path")
+                .clone();
+            add_conflict(&mut conflicts, path, &mine, &disk);
+        }
+    }
+
+    for key in missing_on_disk {
+        let path = [Segment::Key(key.to_string())];
+        if diff::get(&merged, &path).is_some_and(|list| list == &Value::Array(Vec::new())) {
+            diff::remove(&mut merged, &path);
+        }
+    }
+
+    from_value(merged).map(|config| Merged { config, conflicts })
+}
+
+/// Returns the `config` resulting from the merge after resolving `conflicts` with the version on
+/// `side`. The merged config already uses the version on disk for all of them.
+pub fn resolve(
+    config: &StaticConfig,
+    conflicts: &[Conflict],
+    side: Side,
+) -> Result<StaticConfig, AppError> {
+    match side {
+        Side::Disk => Ok(config.clone()),
+        Side::Mine => {
+            let mut value = diff::to_value(config);
+            for conflict in conflicts {
+                match &conflict.mine {
+                    Some(mine) => {
+                        diff::set(&mut value, &conflict.path, mine.clone());
+                    }
+                    None => diff::remove(&mut value, &conflict.path),
+                }
+            }
+            from_value(value)
+        }
+    }
+}
+
+/// Whether one of the paths contains the other.
+fn overlaps(a: &[Segment], b: &[Segment]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a == b)
+}
+
+/// Adds a conflict on `path`, unless it is part of an existing conflict. Existing conflicts
+/// within `path` are replaced by it.
+fn add_conflict(conflicts: &mut Vec<Conflict>, path: Vec<Segment>, mine: &Value, disk: &Value) {
+    if conflicts
+        .iter()
+        .any(|c| overlaps(&c.path, &path) && c.path.len() <= path.len())
+    {
+        return;
+    }
+    conflicts.retain(|c| !overlaps(&c.path, &path));
+    conflicts.push(Conflict {
+        mine: diff::get(mine, &path).cloned(),
+        disk: diff::get(disk, &path).cloned(),
+        path,
+    });
+}
+
+fn from_value(value: Value) -> Result<StaticConfig, AppError> {
+    serde_json::from_value::<StaticConfig>(value)
+        .map(merge_default)
+        .map_err(|e| AppError {
+            title: "Failed to merge the changes on disk".into(),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::empty_config;
+
+    use komorebi_client::{MatchingRule, WindowContainerBehaviour};
+
+    fn config(f: impl FnOnce(&mut StaticConfig)) -> StaticConfig {
+        let mut config = merge_default(empty_config());
+        f(&mut config);
+        config
+    }
+
+    #[test]
+    fn changes_on_different_fields_are_both_kept() {
+        let base = config(|_| {});
+        let mine = config(|c| c.resize_delta = Some(10));
+        let disk = config(|c| c.border_width = Some(2));
+
+        let merged = merge(&base, &mine, &disk).unwrap();
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.config.resize_delta, Some(10));
+        assert_eq!(merged.config.border_width, Some(2));
+    }
+
+    #[test]
+    fn same_change_on_both_sides_is_not_a_conflict() {
+        let base = config(|_| {});
+        let mine = config(|c| c.resize_delta = Some(10));
+
+        let merged = merge(&base, &mine, &mine).unwrap();
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.config, mine);
+    }
+
+    #[test]
+    fn different_changes_on_same_field_conflict() {
+        let base = config(|_| {});
+        let mine = config(|c| {
+            c.resize_delta = Some(10);
+            c.window_container_behaviour = Some(WindowContainerBehaviour::Append);
+        });
+        let disk = config(|c| c.resize_delta = Some(30));
+
+        let merged = merge(&base, &mine, &disk).unwrap();
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].label(), "resize_delta");
+        assert_eq!(merged.config.resize_delta, Some(30));
+        assert_eq!(
+            merged.config.window_container_behaviour,
+            Some(WindowContainerBehaviour::Append)
+        );
+
+        let resolved = resolve(&merged.config, &merged.conflicts, Side::Mine).unwrap();
+        assert_eq!(resolved, mine);
+    }
+
+    #[test]
+    fn each_conflict_is_resolved_on_its_own() {
+        let base = config(|c| c.minimum_window_width = Some(100));
+        let mine = config(|c| {
+            c.resize_delta = Some(10);
+            c.border_width = Some(2);
+            c.minimum_window_width = None;
+        });
+        let disk = config(|c| {
+            c.resize_delta = Some(30);
+            c.border_width = Some(4);
+            c.minimum_window_width = Some(200);
+        });
+
+        let merged = merge(&base, &mine, &disk).unwrap();
+        assert_eq!(merged.conflicts.len(), 3);
+        let conflict = |label: &str| {
+            merged
+                .conflicts
+                .iter()
+                .find(|conflict| conflict.label() == label)
+                .cloned()
+                .unwrap()
+        };
+
+        let resolved = resolve(&merged.config, &[conflict("resize_delta")], Side::Mine).unwrap();
+        assert_eq!(resolved.resize_delta, Some(10));
+        assert_eq!(resolved.border_width, Some(4));
+        assert_eq!(resolved.minimum_window_width, Some(200));
+
+        // Keeping a field removed on Komorice removes it from the merged config
+        let conflicts = [conflict("minimum_window_width")];
+        let resolved = resolve(&resolved, &conflicts, Side::Mine).unwrap();
+        assert_eq!(resolved.minimum_window_width, None);
+        assert_eq!(resolved.resize_delta, Some(10));
+
+        let resolved = resolve(&resolved, &[conflict("border_width")], Side::Disk).unwrap();
+        assert_eq!(resolved.border_width, Some(4));
+        assert_eq!(resolved.resize_delta, Some(10));
+        assert_eq!(resolved.minimum_window_width, None);
+    }
+
+    #[test]
+    fn rules_added_on_both_sides_are_merged() {
+        let rule = |id: &str| {
+            serde_json::from_value::<MatchingRule>(serde_json::json!({ "kind": "Exe", "id": id }))
+                .unwrap()
+        };
+        let base = config(|_| {});
+        let mine = config(|c| c.ignore_rules = Some(vec![rule("a.exe")]));
+        let disk = config(|c| c.ignore_rules = Some(vec![rule("b.exe")]));
+
+        let merged = merge(&base, &mine, &disk).unwrap();
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(
+            merged.config.ignore_rules,
+            Some(vec![rule("b.exe"), rule("a.exe")])
+        );
+    }
+}
@@ -0,0 +1,530 @@
+//! Support for configs written for other komorebi versions.
+//!
+//! Komorice only knows the `StaticConfig` of `KOMOREBI_VERSION`. Configs written for older
+//! versions are upgraded before being read, by applying the changes made to the config on each
+//! newer version, and they can be downgraded again when saving for an older version.
+
+use crate::{
+    KOMOREBI_VERSION,
+    platform::{self, Native, ProcessControl},
+};
+
+use std::sync::OnceLock;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use smol::process::Stdio;
+
+lazy_static! {
+    static ref VERSION_REGEX: regex::Regex =
+        regex::Regex::new(r"v?(\d+)\.(\d+)\.(\d+)").expect("the version regex is valid");
+    /// The versions a config can be saved for, newest first.
+    pub static ref SAVE_VERSIONS: Vec<TargetVersion> = [
+        Version::current(),
+        Version::new(0, 1, 38),
+        Version::new(0, 1, 37),
+    ]
+    .into_iter()
+    .map(TargetVersion::Version)
+    .collect();
+}
+
+/// Version of the installed `komorebic`, it is only checked once.
+static INSTALLED: OnceLock<Option<Version>> = OnceLock::new();
+
+/// A change made to the config on some komorebi version.
+struct Migration {
+    /// The first version with the change.
+    version: Version,
+    /// Path to the objects with the changed field, where `*` stands for each item of a list.
+    path: &'static [&'static str],
+    change: Change,
+}
+
+enum Change {
+    /// The field was renamed from the first name to the second one.
+    Renamed(&'static str, &'static str),
+    /// The field was added, older versions fail to read configs with it.
+    Added(&'static str),
+    /// The field was removed, newer versions fail to read configs with it.
+    Removed(&'static str),
+}
+
+const ROOT: &[&str] = &[];
+const MONITOR: &[&str] = &["monitors", "*"];
+const WORKSPACE: &[&str] = &["monitors", "*", "workspaces", "*"];
+
+/// All the known changes, from the oldest to the newest.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: Version::new(0, 1, 25),
+        path: ROOT,
+        change: Change::Renamed("active_window_border", "border"),
+    },
+    Migration {
+        version: Version::new(0, 1, 25),
+        path: ROOT,
+        change: Change::Renamed("active_window_border_width", "border_width"),
+    },
+    Migration {
+        version: Version::new(0, 1, 25),
+        path: ROOT,
+        change: Change::Renamed("active_window_border_offset", "border_offset"),
+    },
+    Migration {
+        version: Version::new(0, 1, 25),
+        path: ROOT,
+        change: Change::Renamed("active_window_border_colours", "border_colours"),
+    },
+    Migration {
+        version: Version::new(0, 1, 25),
+        path: ROOT,
+        change: Change::Renamed("active_window_border_style", "border_style"),
+    },
+    Migration {
+        version: Version::new(0, 1, 25),
+        path: ROOT,
+        change: Change::Removed("active_window_border_z_order"),
+    },
+    Migration {
+        version: Version::new(0, 1, 35),
+        path: ROOT,
+        change: Change::Renamed("float_rules", "ignore_rules"),
+    },
+    Migration {
+        version: Version::new(0, 1, 38),
+        path: ROOT,
+        change: Change::Added("window_handling_behaviour"),
+    },
+    Migration {
+        version: Version::new(0, 1, 38),
+        path: ROOT,
+        change: Change::Added("floating_window_aspect_ratio"),
+    },
+    Migration {
+        version: Version::new(0, 1, 38),
+        path: ROOT,
+        change: Change::Added("remove_titlebar_applications"),
+    },
+    Migration {
+        version: Version::new(0, 1, 39),
+        path: ROOT,
+        change: Change::Added("toggle_float_placement"),
+    },
+    Migration {
+        version: Version::new(0, 1, 39),
+        path: ROOT,
+        change: Change::Added("floating_layer_placement"),
+    },
+    Migration {
+        version: Version::new(0, 1, 39),
+        path: ROOT,
+        change: Change::Added("float_override_placement"),
+    },
+    Migration {
+        version: Version::new(0, 1, 39),
+        path: ROOT,
+        change: Change::Added("float_rule_placement"),
+    },
+    Migration {
+        version: Version::new(0, 1, 39),
+        path: MONITOR,
+        change: Change::Added("wallpaper"),
+    },
+    Migration {
+        version: Version::new(0, 1, 39),
+        path: WORKSPACE,
+        change: Change::Added("wallpaper"),
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+    patch: u32,
+}
+
+impl Version {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// The version supported by Komorice.
+    pub fn current() -> Self {
+        Version::parse(&KOMOREBI_VERSION).expect("`KOMOREBI_VERSION` is a valid version")
+    }
+
+    /// Finds the first version on `text`, like `v0.1.39` or `0.1.39`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let captures = VERSION_REGEX.captures(text)?;
+        let number = |idx| -> Option<u32> { captures.get(idx)?.as_str().parse().ok() };
+        Some(Version::new(number(1)?, number(2)?, number(3)?))
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Version::parse(&text)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid version '{text}'")))
+    }
+}
+
+/// The komorebi version a config is saved for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetVersion {
+    /// The version of the installed `komorebic`.
+    #[default]
+    Detected,
+    Version(Version),
+}
+
+impl std::fmt::Display for TargetVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetVersion::Detected => write!(f, "Detected"),
+            TargetVersion::Version(version) if *version == Version::current() => {
+                write!(f, "{version} (latest supported)")
+            }
+            TargetVersion::Version(version) => write!(f, "{version}"),
+        }
+    }
+}
+
+/// The version on the `$schema` URL of a config, like
+/// `https://raw.githubusercontent.com/LGUG2Z/komorebi/v0.1.38/schema.json`.
+pub fn from_schema(value: &Value) -> Option<Version> {
+    value
+        .get("$schema")
+        .and_then(Value::as_str)
+        .and_then(Version::parse)
+}
+
+/// The version of the installed `komorebic`, if it can be found.
+pub async fn installed() -> Option<Version> {
+    if let Some(version) = INSTALLED.get() {
+        return *version;
+    }
+
+    let output = Native::command(&platform::executable("komorebic"))
+        .arg("--version")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await;
+    let version = output
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| Version::parse(&String::from_utf8_lossy(&output.stdout)));
+    *INSTALLED.get_or_init(|| version)
+}
+
+/// The version a config with `value` was written for. It uses the `$schema` of the config or the
+/// installed version otherwise. Versions newer than the supported one are treated as the
+/// supported one.
+pub async fn detect(value: Option<&Value>) -> Version {
+    let detected = match value.and_then(from_schema) {
+        Some(version) => Some(version),
+        None => installed().await,
+    };
+    detected.map_or_else(Version::current, |version| version.min(Version::current()))
+}
+
+/// The version to save configs for when saving for `TargetVersion::Detected`. It is the version
+/// of the installed `komorebic`, never the `$schema` of the file, so that a file written for an
+/// older version doesn't keep being saved for it. The supported version is used when komorebic
+/// can't be found or is newer.
+pub async fn save_version(target: TargetVersion) -> Version {
+    match target {
+        TargetVersion::Version(version) => version,
+        TargetVersion::Detected => installed()
+            .await
+            .map_or_else(Version::current, |version| version.min(Version::current())),
+    }
+}
+
+/// Upgrades a config written for `version` to the supported version. Returns a description of
+/// each change made.
+pub fn upgrade(value: &mut Value, version: Version) -> Vec<String> {
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.version > version) {
+        for_each_object(
+            value,
+            migration.path,
+            &mut |object| match migration.change {
+                Change::Renamed(old, new) => {
+                    if let Some(field) = object.remove(old) {
+                        if !object.contains_key(new) {
+                            object.insert(new.to_string(), field);
+                        }
+                        changes.push(format!("renamed '{old}' to '{new}'"));
+                    }
+                }
+                Change::Removed(field) => {
+                    if object.remove(field).is_some() {
+                        changes.push(format!("removed '{field}'"));
+                    }
+                }
+                Change::Added(_) => {}
+            },
+        );
+    }
+    changes.dedup();
+    changes
+}
+
+/// Downgrades a config for the supported version to `version`. Returns a description of each
+/// change made, including the fields that had to be removed.
+pub fn downgrade(value: &mut Value, version: Version) -> Vec<String> {
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().rev().filter(|m| m.version > version) {
+        for_each_object(
+            value,
+            migration.path,
+            &mut |object| match migration.change {
+                Change::Renamed(old, new) => {
+                    if let Some(field) = object.remove(new) {
+                        object.insert(old.to_string(), field);
+                        changes.push(format!("renamed '{new}' to '{old}'"));
+                    }
+                }
+                Change::Added(field) => {
+                    if object.remove(field).is_some() {
+                        changes.push(format!("removed '{field}'"));
+                    }
+                }
+                Change::Removed(_) => {}
+            },
+        );
+    }
+    changes.dedup();
+    changes
+}
+
+/// The JSON pointer that the value at `pointer`, on a config upgraded from `version`, had before
+/// the upgrade.
+pub fn original_pointer(pointer: &str, version: Version) -> String {
+    let mut segments = pointer
+        .split('/')
+        .skip(1)
+        .map(String::from)
+        .collect::<Vec<_>>();
+    for migration in MIGRATIONS.iter().rev().filter(|m| m.version > version) {
+        let Change::Renamed(old, new) = migration.change else {
+            continue;
+        };
+        let depth = migration.path.len();
+        let on_path = migration.path.iter().zip(&segments).all(|(key, segment)| {
+            *key == segment || (*key == "*" && segment.parse::<usize>().is_ok())
+        });
+        if on_path && segments.get(depth).is_some_and(|segment| segment == new) {
+            segments[depth] = old.to_string();
+        }
+    }
+    segments
+        .iter()
+        .map(|segment| format!("/{segment}"))
+        .collect()
+}
+
+fn for_each_object(value: &mut Value, path: &[&str], f: &mut impl FnMut(&mut Map<String, Value>)) {
+    match (path.split_first(), value) {
+        (None, Value::Object(object)) => f(object),
+        (Some((&"*", rest)), Value::Array(items)) => {
+            for item in items {
+                for_each_object(item, rest, f);
+            }
+        }
+        (Some((key, rest)), Value::Object(object)) => {
+            if let Some(value) = object.get_mut(*key) {
+                for_each_object(value, rest, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    #[test]
+    fn migrations_are_sorted_from_the_oldest_to_the_newest() {
+        assert!(MIGRATIONS.windows(2).all(|m| m[0].version <= m[1].version));
+        assert!(MIGRATIONS.iter().all(|m| m.version <= Version::current()));
+    }
+
+    #[test]
+    fn save_versions_are_sorted_from_the_newest_to_the_oldest() {
+        let versions = SAVE_VERSIONS
+            .iter()
+            .map(|target| match target {
+                TargetVersion::Version(version) => *version,
+                TargetVersion::Detected => panic!("only actual versions are listed"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(versions.first(), Some(&Version::current()));
+        assert!(versions.windows(2).all(|v| v[0] > v[1]));
+    }
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(Version::parse("v0.1.39"), Some(Version::new(0, 1, 39)));
+        assert_eq!(
+            Version::parse("komorebic 0.1.38\n"),
+            Some(Version::new(0, 1, 38))
+        );
+        assert_eq!(
+            Version::parse("https://raw.githubusercontent.com/LGUG2Z/komorebi/v0.1.37/schema.json"),
+            Some(Version::new(0, 1, 37))
+        );
+        assert_eq!(Version::parse("master"), None);
+        assert!(Version::new(0, 1, 9) < Version::new(0, 1, 10));
+    }
+
+    #[test]
+    fn upgrades_renamed_and_removed_fields() {
+        let mut value = json!({
+            "float_rules": [],
+            "active_window_border": true,
+            "active_window_border_z_order": "Top",
+        });
+        let changes = upgrade(&mut value, Version::new(0, 1, 24));
+
+        assert_eq!(
+            value,
+            json!({
+                "ignore_rules": [],
+                "border": true,
+            })
+        );
+        assert_eq!(
+            changes,
+            [
+                "renamed 'active_window_border' to 'border'",
+                "removed 'active_window_border_z_order'",
+                "renamed 'float_rules' to 'ignore_rules'",
+            ]
+        );
+    }
+
+    #[test]
+    fn upgrading_the_current_version_changes_nothing() {
+        let original = json!({
+            "border": true,
+            "ignore_rules": [],
+            "monitors": [{ "wallpaper": null, "workspaces": [{ "wallpaper": null }] }],
+        });
+        let mut value = original.clone();
+
+        assert!(upgrade(&mut value, Version::current()).is_empty());
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn downgrades_remove_fields_added_on_newer_versions() {
+        let mut value = json!({
+            "border": true,
+            "toggle_float_placement": "Center",
+            "monitors": [
+                { "wallpaper": { "path": "a.png" }, "workspaces": [{ "name": "I", "wallpaper": null }] },
+                { "workspaces": [{ "name": "II" }] },
+            ],
+        });
+        let changes = downgrade(&mut value, Version::new(0, 1, 38));
+
+        assert_eq!(
+            value,
+            json!({
+                "border": true,
+                "monitors": [
+                    { "workspaces": [{ "name": "I" }] },
+                    { "workspaces": [{ "name": "II" }] },
+                ],
+            })
+        );
+        assert_eq!(
+            changes,
+            ["removed 'wallpaper'", "removed 'toggle_float_placement'",]
+        );
+    }
+
+    #[test]
+    fn downgrades_renamed_fields_back_to_their_old_name() {
+        let mut value = json!({ "ignore_rules": [], "window_handling_behaviour": "Sync" });
+        let changes = downgrade(&mut value, Version::new(0, 1, 34));
+
+        assert_eq!(value, json!({ "float_rules": [] }));
+        assert_eq!(
+            changes,
+            [
+                "removed 'window_handling_behaviour'",
+                "renamed 'ignore_rules' to 'float_rules'",
+            ]
+        );
+    }
+
+    #[test]
+    fn upgrading_a_downgraded_config_gives_it_back() {
+        let original = json!({
+            "border": true,
+            "border_width": 8,
+            "ignore_rules": [],
+        });
+        let mut value = original.clone();
+        downgrade(&mut value, Version::new(0, 1, 24));
+        assert_eq!(
+            value,
+            json!({
+                "active_window_border": true,
+                "active_window_border_width": 8,
+                "float_rules": [],
+            })
+        );
+
+        upgrade(&mut value, Version::new(0, 1, 24));
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    fn finds_the_original_pointer_of_renamed_fields() {
+        assert_eq!(
+            original_pointer("/ignore_rules/0", Version::new(0, 1, 34)),
+            "/float_rules/0"
+        );
+        assert_eq!(
+            original_pointer("/ignore_rules/0", Version::new(0, 1, 35)),
+            "/ignore_rules/0"
+        );
+    }
+
+    #[test]
+    fn saves_for_the_chosen_version() {
+        let version = Version::new(0, 1, 37);
+        assert_eq!(
+            smol::block_on(save_version(TargetVersion::Version(version))),
+            version
+        );
+        assert!(smol::block_on(save_version(TargetVersion::Detected)) <= Version::current());
+    }
+}
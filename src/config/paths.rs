@@ -0,0 +1,177 @@
+//! Paths that use environment variables, the way komorebi reads them on its config.
+//!
+//! A component of a path can be a variable written as `$Env:VAR`, `$VAR` or `%VAR%`, and a path
+//! can start with `~` for the home folder.
+
+use std::path::PathBuf;
+
+/// The variables that absolute paths can be written with, from the most to the least specific.
+pub const KNOWN_VARIABLES: [&str; 6] = [
+    "KOMOREBI_CONFIG_HOME",
+    "LOCALAPPDATA",
+    "APPDATA",
+    "USERPROFILE",
+    "ProgramFiles",
+    "ProgramData",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolved {
+    /// The path with all the variables it uses replaced by their values.
+    pub path: PathBuf,
+    /// The variables used on the path that aren't set, which are kept as they were written.
+    pub missing: Vec<String>,
+}
+
+/// Resolves the variables used on `path` with the current environment.
+pub fn resolve(path: &str) -> Resolved {
+    resolve_with(path, env_var)
+}
+
+/// Returns the ways `path` can be written with the `KNOWN_VARIABLES` that are set. Only absolute
+/// paths without any variable can be written this way.
+pub fn express(path: &str) -> Vec<String> {
+    let variables = KNOWN_VARIABLES
+        .into_iter()
+        .filter_map(|name| env_var(name).map(|value| (name, value)))
+        .collect::<Vec<_>>();
+    express_with(path, &variables)
+}
+
+fn resolve_with(path: &str, lookup: impl Fn(&str) -> Option<String>) -> Resolved {
+    let mut resolved = String::new();
+    let mut missing = Vec::new();
+    for (idx, part) in path.split_inclusive(['/', '\\']).enumerate() {
+        let component = part.trim_end_matches(['/', '\\']);
+        let separator = &part[component.len()..];
+        match variable(component, idx == 0) {
+            Some(name) => match lookup(name) {
+                Some(value) => resolved.push_str(&value),
+                None => {
+                    missing.push(name.to_string());
+                    resolved.push_str(component);
+                }
+            },
+            None => resolved.push_str(component),
+        }
+        resolved.push_str(separator);
+    }
+    Resolved {
+        path: PathBuf::from(resolved),
+        missing,
+    }
+}
+
+fn express_with(path: &str, variables: &[(&str, String)]) -> Vec<String> {
+    let has_variables = path
+        .split(['/', '\\'])
+        .enumerate()
+        .any(|(idx, component)| variable(component, idx == 0).is_some());
+    if has_variables {
+        return Vec::new();
+    }
+    let normalize = |s: &str| s.replace('/', "\\").to_ascii_lowercase();
+    let normalized = normalize(path);
+    variables
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = normalize(value.trim_end_matches(['/', '\\']));
+            let rest = normalized.strip_prefix(&value)?;
+            if value.is_empty() || !(rest.is_empty() || rest.starts_with('\\')) {
+                return None;
+            }
+            Some(format!("$Env:{name}{}", &path[value.len()..]))
+        })
+        .collect()
+}
+
+/// The name of the variable used on `component`, if it is one. Only the first component of a path
+/// can be `~`.
+fn variable(component: &str, is_first: bool) -> Option<&str> {
+    let name = if is_first && component == "~" {
+        Some("USERPROFILE")
+    } else if component
+        .get(..5)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("$Env:"))
+    {
+        Some(&component[5..])
+    } else if let Some(name) = component.strip_prefix('$') {
+        Some(name)
+    } else {
+        component
+            .strip_prefix('%')
+            .and_then(|name| name.strip_suffix('%'))
+    };
+    name.filter(|name| !name.is_empty())
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .or_else(|| {
+            name.eq_ignore_ascii_case("USERPROFILE")
+                .then(dirs::home_dir)
+                .flatten()
+                .map(|home| home.display().to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "USERPROFILE" => Some(r"C:\Users\me".into()),
+            "KOMOREBI_CONFIG_HOME" => Some(r"C:\Users\me\.config\komorebi".into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn resolves_every_variable_form() {
+        for path in [
+            r"$Env:USERPROFILE\bar.json",
+            r"$env:USERPROFILE\bar.json",
+            r"$USERPROFILE\bar.json",
+            r"%USERPROFILE%\bar.json",
+            r"~\bar.json",
+        ] {
+            let resolved = resolve_with(path, lookup);
+            assert_eq!(
+                resolved.path,
+                PathBuf::from(r"C:\Users\me\bar.json"),
+                "{path}"
+            );
+            assert!(resolved.missing.is_empty());
+        }
+    }
+
+    #[test]
+    fn unset_variables_are_kept() {
+        let resolved = resolve_with(r"$Env:NOPE\bar.json", lookup);
+        assert_eq!(resolved.path, PathBuf::from(r"$Env:NOPE\bar.json"));
+        assert_eq!(resolved.missing, vec!["NOPE".to_string()]);
+    }
+
+    #[test]
+    fn absolute_paths_are_expressed_with_the_known_variables() {
+        let variables = [
+            (
+                "KOMOREBI_CONFIG_HOME",
+                lookup("KOMOREBI_CONFIG_HOME").unwrap(),
+            ),
+            ("USERPROFILE", lookup("USERPROFILE").unwrap()),
+        ];
+        assert_eq!(
+            express_with(r"c:\users\me\.config\komorebi\bar.json", &variables),
+            vec![
+                r"$Env:KOMOREBI_CONFIG_HOME\bar.json".to_string(),
+                r"$Env:USERPROFILE\.config\komorebi\bar.json".to_string(),
+            ]
+        );
+        assert!(express_with(r"C:\Users\meh\bar.json", &variables).is_empty());
+        assert!(express_with(r"$Env:USERPROFILE\bar.json", &variables).is_empty());
+    }
+}
@@ -0,0 +1,590 @@
+//! Validation of raw `komorebi.json` contents against the komorebi JSON Schema.
+//!
+//! The schema is generated from the same `StaticConfig` types that komorebi uses to generate its
+//! `schema.json`, so it always matches `KOMOREBI_VERSION` and works offline.
+
+use super::migration::{self, Version};
+
+use std::collections::HashMap;
+
+use komorebi_client::StaticConfig;
+use lazy_static::lazy_static;
+use serde_json::Value;
+
+lazy_static! {
+    static ref SCHEMA: Value = serde_json::to_value(schemars::schema_for!(StaticConfig))
+        .expect("the komorebi schema should be serializable");
+}
+
+/// Max amount of allowed values listed on an error message.
+const MAX_LISTED_VALUES: usize = 8;
+
+/// A place where the config doesn't follow the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// JSON pointer to the offending value, e.g. `/monitors/0/workspaces/1/layout`.
+    pub pointer: String,
+    pub message: String,
+    /// 1-based line of the offending value on the file, `0` if unknown.
+    pub line: usize,
+    /// 1-based column of the offending value on the file, `0` if unknown.
+    pub column: usize,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        if self.line > 0 {
+            write!(
+                f,
+                "line {}, column {} ({}): {}",
+                self.line, self.column, pointer, self.message
+            )
+        } else {
+            write!(f, "{}: {}", pointer, self.message)
+        }
+    }
+}
+
+/// Validates `value` against the komorebi schema. Returns every violation found, in the order they
+/// appear on the file.
+///
+/// The `value` is the one parsed from `contents` once upgraded from `version`, so the positions
+/// are looked up with the pointers the values had before the upgrade.
+pub fn validate(contents: &str, value: &Value, version: Version) -> Vec<Violation> {
+    let mut errors = Vec::new();
+    check(&SCHEMA, value, "", &mut errors);
+
+    let positions = positions(contents);
+    let mut violations = errors
+        .into_iter()
+        .map(|(pointer, message)| {
+            let (line, column) = positions
+                .get(&migration::original_pointer(&pointer, version))
+                .copied()
+                .unwrap_or((0, 0));
+            Violation {
+                pointer,
+                message,
+                line,
+                column,
+            }
+        })
+        .collect::<Vec<_>>();
+    violations.sort_by_key(|v| (v.line, v.column));
+    violations.dedup();
+    violations
+}
+
+fn check(schema: &Value, value: &Value, pointer: &str, errors: &mut Vec<(String, String)>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push((pointer.into(), "this value is not allowed".into()));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(Value::String(reference)) = schema.get("$ref")
+        && let Some(resolved) = resolve(reference)
+    {
+        check(resolved, value, pointer, errors);
+    }
+
+    if let Some(types) = schema.get("type")
+        && !matches_type(types, value)
+    {
+        errors.push((
+            pointer.into(),
+            format!("expected {}, found {}", type_names(types), type_of(value)),
+        ));
+        return;
+    }
+
+    if let Some(Value::Array(values)) = schema.get("enum")
+        && !values.contains(value)
+    {
+        errors.push((pointer.into(), one_of_message(values.iter(), value)));
+    }
+
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        errors.push((
+            pointer.into(),
+            format!("expected {expected}, found {value}"),
+        ));
+    }
+
+    if let Some(Value::Array(schemas)) = schema.get("allOf") {
+        for schema in schemas {
+            check(schema, value, pointer, errors);
+        }
+    }
+
+    // `oneOf` is checked the same as `anyOf` since serde picks the first variant that matches.
+    for key in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(schemas)) = schema.get(key) {
+            check_any_of(schemas, value, pointer, errors);
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(key) {
+                        errors.push((pointer.into(), format!("missing required property '{key}'")));
+                    }
+                }
+            }
+            for (key, item) in map {
+                let item_pointer = format!("{pointer}/{}", escape(key));
+                if let Some(property) = properties.and_then(|p| p.get(key)) {
+                    check(property, item, &item_pointer, errors);
+                } else if let Some(additional) = schema.get("additionalProperties") {
+                    if additional == &Value::Bool(false) {
+                        errors.push((item_pointer, format!("unknown property '{key}'")));
+                    } else {
+                        check(additional, item, &item_pointer, errors);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            match schema.get("items") {
+                Some(Value::Array(schemas)) => {
+                    for (idx, (schema, item)) in schemas.iter().zip(items).enumerate() {
+                        check(schema, item, &format!("{pointer}/{idx}"), errors);
+                    }
+                }
+                Some(schema) => {
+                    for (idx, item) in items.iter().enumerate() {
+                        check(schema, item, &format!("{pointer}/{idx}"), errors);
+                    }
+                }
+                None => {}
+            }
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+                && (items.len() as u64) < min
+            {
+                errors.push((pointer.into(), format!("expected at least {min} items")));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+                && (items.len() as u64) > max
+            {
+                errors.push((pointer.into(), format!("expected at most {max} items")));
+            }
+        }
+        Value::Number(number) => {
+            let Some(number) = number.as_f64() else {
+                return;
+            };
+            let (format_min, format_max) = schema
+                .get("format")
+                .and_then(Value::as_str)
+                .map_or((None, None), format_range);
+            let min = schema.get("minimum").and_then(Value::as_f64).or(format_min);
+            let max = schema.get("maximum").and_then(Value::as_f64).or(format_max);
+            if let Some(min) = min.filter(|min| number < *min) {
+                errors.push((pointer.into(), format!("{number} is lower than {min}")));
+            }
+            if let Some(max) = max.filter(|max| number > *max) {
+                errors.push((pointer.into(), format!("{number} is higher than {max}")));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Checks `value` against each of the `schemas` and only reports the errors of the closest one
+/// if none of them match.
+fn check_any_of(
+    schemas: &[Value],
+    value: &Value,
+    pointer: &str,
+    errors: &mut Vec<(String, String)>,
+) {
+    let mut best: Option<Vec<(String, String)>> = None;
+    for schema in schemas {
+        let mut branch_errors = Vec::new();
+        check(schema, value, pointer, &mut branch_errors);
+        if branch_errors.is_empty() {
+            return;
+        }
+        // The branch whose errors are the deepest on the value is the one that matched the most.
+        let depth = |errors: &[(String, String)]| {
+            errors
+                .iter()
+                .map(|(p, _)| p.matches('/').count())
+                .min()
+                .unwrap_or_default()
+        };
+        if best
+            .as_ref()
+            .is_none_or(|best| depth(&branch_errors) > depth(best))
+        {
+            best = Some(branch_errors);
+        }
+    }
+
+    let Some(best) = best else {
+        return;
+    };
+    if best.iter().all(|(p, _)| p == pointer) {
+        // No variant got past this value, list the allowed ones if they are simple values.
+        let allowed = schemas
+            .iter()
+            .filter_map(|schema| resolve_schema(schema).get("enum").and_then(Value::as_array))
+            .flatten()
+            .collect::<Vec<_>>();
+        if allowed.is_empty() {
+            errors.extend(best);
+        } else {
+            errors.push((pointer.into(), one_of_message(allowed.into_iter(), value)));
+        }
+    } else {
+        errors.extend(best);
+    }
+}
+
+fn resolve(reference: &str) -> Option<&'static Value> {
+    let name = reference.strip_prefix("#/definitions/")?;
+    SCHEMA.get("definitions")?.get(name)
+}
+
+fn resolve_schema(schema: &Value) -> &Value {
+    schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(resolve)
+        .unwrap_or(schema)
+}
+
+fn matches_type(types: &Value, value: &Value) -> bool {
+    match types {
+        Value::String(ty) => is_type(ty, value),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|ty| is_type(ty, value)),
+        _ => true,
+    }
+}
+
+fn is_type(ty: &str, value: &Value) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64()
+                || value.is_u64()
+                || value.as_f64().is_some_and(|number| number.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_names(types: &Value) -> String {
+    match types {
+        Value::String(ty) => article(ty),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .map(article)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        _ => String::new(),
+    }
+}
+
+fn type_of(value: &Value) -> String {
+    match value {
+        Value::Null => "null".into(),
+        Value::Bool(b) => format!("boolean {b}"),
+        Value::Number(n) => format!("number {n}"),
+        Value::String(s) => format!("string \"{s}\""),
+        Value::Array(_) => "an array".into(),
+        Value::Object(_) => "an object".into(),
+    }
+}
+
+fn article(ty: &str) -> String {
+    match ty {
+        "null" => "null".into(),
+        "array" | "object" | "integer" => format!("an {ty}"),
+        _ => format!("a {ty}"),
+    }
+}
+
+fn one_of_message<'a>(allowed: impl Iterator<Item = &'a Value>, value: &Value) -> String {
+    let allowed = allowed.map(ToString::to_string).collect::<Vec<_>>();
+    let mut listed = allowed
+        .iter()
+        .take(MAX_LISTED_VALUES)
+        .cloned()
+        .collect::<Vec<_>>();
+    if allowed.len() > MAX_LISTED_VALUES {
+        listed.push(format!("... ({} more)", allowed.len() - MAX_LISTED_VALUES));
+    }
+    format!("{} is not one of: {}", value, listed.join(", "))
+}
+
+/// The range of the integer formats generated by the schema.
+fn format_range(format: &str) -> (Option<f64>, Option<f64>) {
+    match format {
+        "uint8" => (Some(0.0), Some(u8::MAX as f64)),
+        "int8" => (Some(i8::MIN as f64), Some(i8::MAX as f64)),
+        "uint16" => (Some(0.0), Some(u16::MAX as f64)),
+        "int16" => (Some(i16::MIN as f64), Some(i16::MAX as f64)),
+        "uint32" => (Some(0.0), Some(u32::MAX as f64)),
+        "int32" => (Some(i32::MIN as f64), Some(i32::MAX as f64)),
+        "uint" | "uint64" => (Some(0.0), None),
+        _ => (None, None),
+    }
+}
+
+/// Escapes a key to be used on a JSON pointer.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Maps the JSON pointer of each value on `contents` to the line and column where it starts.
+///
+/// It accepts the same relaxed JSON as komorebi (comments and trailing commas). If the contents
+/// are malformed it stops and returns the positions found so far.
+fn positions(contents: &str) -> HashMap<String, (usize, usize)> {
+    let mut scanner = Scanner {
+        chars: contents.chars().peekable(),
+        line: 1,
+        column: 1,
+        positions: HashMap::new(),
+    };
+    scanner.value(String::new());
+    scanner.positions
+}
+
+struct Scanner<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+    column: usize,
+    positions: HashMap<String, (usize, usize)>,
+}
+
+impl Scanner<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.next();
+            } else if c == '/' {
+                self.next();
+                match self.next() {
+                    Some('/') => while self.next().is_some_and(|c| c != '\n') {},
+                    Some('*') => {
+                        let mut previous = None;
+                        while let Some(c) = self.next() {
+                            if previous == Some('*') && c == '/' {
+                                break;
+                            }
+                            previous = Some(c);
+                        }
+                    }
+                    _ => return,
+                }
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn value(&mut self, pointer: String) -> Option<()> {
+        self.skip_whitespace();
+        self.positions
+            .insert(pointer.clone(), (self.line, self.column));
+        match self.peek()? {
+            '{' => {
+                self.next();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        '}' => {
+                            self.next();
+                            return Some(());
+                        }
+                        ',' => {
+                            self.next();
+                        }
+                        '"' => {
+                            let key = self.string()?;
+                            self.skip_whitespace();
+                            if self.next()? != ':' {
+                                return None;
+                            }
+                            self.value(format!("{pointer}/{}", escape(&key)))?;
+                        }
+                        _ => return None,
+                    }
+                }
+            }
+            '[' => {
+                self.next();
+                let mut idx = 0;
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        ']' => {
+                            self.next();
+                            return Some(());
+                        }
+                        ',' => {
+                            self.next();
+                        }
+                        _ => {
+                            self.value(format!("{pointer}/{idx}"))?;
+                            idx += 1;
+                        }
+                    }
+                }
+            }
+            '"' => self.string().map(|_| ()),
+            _ => {
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_alphanumeric() || matches!(c, '-' | '+' | '.'))
+                {
+                    self.next();
+                }
+                Some(())
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.next();
+        let mut string = String::new();
+        loop {
+            match self.next()? {
+                '"' => return Some(string),
+                '\\' => {
+                    let escaped = self.next()?;
+                    string.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        'u' => {
+                            let hex = (0..4).filter_map(|_| self.next()).collect::<String>();
+                            u32::from_str_radix(&hex, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        c => c,
+                    });
+                }
+                c => string.push(c),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn validate_str(contents: &str, version: Version) -> Vec<Violation> {
+        let mut value = serde_json::from_str(contents).unwrap();
+        migration::upgrade(&mut value, version);
+        validate(contents, &value, version)
+    }
+
+    #[test]
+    fn valid_configs_have_no_violations() {
+        let contents = r#"{
+            // Some comment
+            "resize_delta": 50,
+            "window_container_behaviour": "Create",
+        }"#;
+        assert!(validate_str(contents, Version::current()).is_empty());
+    }
+
+    #[test]
+    fn violations_point_to_the_offending_values() {
+        let contents = "{\n  \"resize_delta\": 50,\n  \"window_container_behaviour\": \"Stack\"\n}";
+        let violations = validate_str(contents, Version::current());
+        assert!(!violations.is_empty());
+        assert_eq!(violations[0].pointer, "/window_container_behaviour");
+        assert_eq!((violations[0].line, violations[0].column), (3, 33));
+    }
+
+    #[test]
+    fn positions_follow_the_keys_renamed_by_migrations() {
+        let contents = "{\n  \"float_rules\": [\n    { \"kind\": \"Exe\", \"id\": 5 }\n  ]\n}";
+        let violations = validate_str(contents, Version::new(0, 1, 34));
+        assert!(!violations.is_empty());
+        assert!(violations[0].pointer.starts_with("/ignore_rules/0"));
+        assert_eq!(violations[0].line, 3);
+    }
+
+    #[test]
+    fn checks_types_enums_and_ranges() {
+        let schema = json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string" },
+                "kind": { "enum": ["A", "B"] },
+                "size": { "type": "integer", "format": "uint8" },
+            },
+            "additionalProperties": false,
+        });
+        let mut errors = Vec::new();
+        check(
+            &schema,
+            &json!({ "kind": "C", "size": 300, "other": true }),
+            "",
+            &mut errors,
+        );
+        let pointers = errors.iter().map(|(p, _)| p.as_str()).collect::<Vec<_>>();
+        assert_eq!(pointers.len(), 4, "{errors:?}");
+        for pointer in ["", "/kind", "/size", "/other"] {
+            assert!(pointers.contains(&pointer), "{pointer} in {errors:?}");
+        }
+    }
+
+    #[test]
+    fn positions_skip_comments() {
+        let positions = positions("{\n  /* a: 1 */ \"a\": [1, // two\n 2]\n}");
+        assert_eq!(positions.get("/a"), Some(&(2, 19)));
+        assert_eq!(positions.get("/a/1"), Some(&(3, 2)));
+    }
+}
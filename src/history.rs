@@ -0,0 +1,261 @@
+use crate::{BOLD_FONT, widget::opt_helpers};
+
+use std::time::{Duration, Instant};
+
+use iced::{
+    Center, Element, Fill, padding,
+    widget::{button, column, container, row, scrollable, text},
+};
+
+/// Max amount of entries kept on the undo stack.
+const MAX_ENTRIES: usize = 200;
+
+/// Changes with the same label done within this window of each other are merged into a single
+/// entry, so that typing on a text input doesn't create an entry for each character.
+const COALESCE_WINDOW: Duration = Duration::from_millis(1000);
+
+#[derive(Debug, Clone)]
+pub struct Entry<T> {
+    pub label: String,
+    pub state: T,
+}
+
+/// An undo/redo history of some state `T`.
+///
+/// The history is seen as a timeline where position `0` is the state before any change and each
+/// entry moves it one position forward. The current state is always at `position()`.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    undo: Vec<Entry<T>>,
+    redo: Vec<Entry<T>>,
+    last_push: Option<Instant>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last_push: None,
+        }
+    }
+}
+
+impl<T> History<T> {
+    /// Records a change described by `label`, where `previous` is the state before the change.
+    /// This clears any redo entries.
+    pub fn push(&mut self, label: String, previous: T) {
+        self.redo.clear();
+        let now = Instant::now();
+        let coalesce = self
+            .last_push
+            .is_some_and(|last| now.duration_since(last) < COALESCE_WINDOW)
+            && self.undo.last().is_some_and(|entry| entry.label == label);
+        if !coalesce {
+            self.undo.push(Entry {
+                label,
+                state: previous,
+            });
+            if self.undo.len() > MAX_ENTRIES {
+                self.undo.remove(0);
+            }
+        }
+        self.last_push = Some(now);
+    }
+
+    /// Undoes the last change. Returns the state to be used instead of `current`.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let entry = self.undo.pop()?;
+        self.redo.push(Entry {
+            label: entry.label,
+            state: current,
+        });
+        self.last_push = None;
+        Some(entry.state)
+    }
+
+    /// Redoes the last undone change. Returns the state to be used instead of `current`.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let entry = self.redo.pop()?;
+        self.undo.push(Entry {
+            label: entry.label,
+            state: current,
+        });
+        self.last_push = None;
+        Some(entry.state)
+    }
+
+    /// Undoes or redoes as many changes as needed to get to `position` on the timeline. Returns the
+    /// state to be used instead of `current` or `None` if it is already at `position`.
+    pub fn jump(&mut self, position: usize, current: T) -> Option<T> {
+        let position = position.min(self.undo.len() + self.redo.len());
+        if position == self.position() {
+            return None;
+        }
+        let mut state = current;
+        while self.position() > position {
+            state = self.undo(state)?;
+        }
+        while self.position() < position {
+            state = self.redo(state)?;
+        }
+        Some(state)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_push = None;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.undo.is_empty() && self.redo.is_empty()
+    }
+
+    /// The current position on the timeline.
+    pub fn position(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// The labels of each change on the timeline, from the oldest to the newest.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.undo
+            .iter()
+            .chain(self.redo.iter().rev())
+            .map(|entry| entry.label.as_str())
+    }
+
+    /// Creates the view with the history timeline where clicking an entry jumps to it.
+    pub fn view<'a, Message: Clone + 'a>(
+        &'a self,
+        on_jump: impl Fn(usize) -> Message + 'a,
+    ) -> Element<'a, Message> {
+        if self.is_empty() {
+            return opt_helpers::description_text("There are no changes yet.").into();
+        }
+        let position = self.position();
+        let entry = |pos: usize, label: &'a str| -> Element<'a, Message> {
+            let is_current = pos == position;
+            let label = if pos > position {
+                opt_helpers::description_text(label)
+            } else if is_current {
+                text(label).font(*BOLD_FONT)
+            } else {
+                text(label)
+            };
+            button(
+                row![text!("{pos}").width(30), label]
+                    .spacing(10)
+                    .align_y(Center),
+            )
+            .on_press_maybe((!is_current).then(|| on_jump(pos)))
+            .style(move |t, s| {
+                if is_current {
+                    button::primary(t, s)
+                } else {
+                    button::subtle(t, s)
+                }
+            })
+            .width(Fill)
+            .into()
+        };
+        let initial_col = column![entry(0, "Start")]
+            .spacing(5)
+            .padding(padding::all(5.0).right(20.0));
+        let entries = self
+            .labels()
+            .enumerate()
+            .fold(initial_col, |col, (idx, label)| {
+                col.push(entry(idx + 1, label))
+            });
+        container(scrollable(entries)).max_height(350.0).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_with_the_same_label_are_coalesced() {
+        let mut history = History::default();
+        history.push("padding".into(), 0);
+        history.push("padding".into(), 1);
+        history.push("padding".into(), 2);
+        assert_eq!(history.position(), 1);
+        assert_eq!(history.undo(3), Some(0));
+
+        history.push("padding".into(), 0);
+        history.push("gap".into(), 1);
+        assert_eq!(history.position(), 2);
+    }
+
+    #[test]
+    fn changes_outside_the_window_are_not_coalesced() {
+        let mut history = History::default();
+        history.push("padding".into(), 0);
+        history.last_push = Instant::now().checked_sub(COALESCE_WINDOW);
+        history.push("padding".into(), 1);
+        assert_eq!(history.position(), 2);
+        assert_eq!(history.undo(2), Some(1));
+        assert_eq!(history.undo(1), Some(0));
+    }
+
+    #[test]
+    fn undoing_stops_coalescing() {
+        let mut history = History::default();
+        history.push("padding".into(), 0);
+        history.push("gap".into(), 1);
+        assert_eq!(history.undo(2), Some(1));
+        history.push("gap".into(), 1);
+        assert_eq!(history.position(), 2);
+    }
+
+    #[test]
+    fn only_the_last_entries_are_kept() {
+        let mut history = History::default();
+        for state in 0..MAX_ENTRIES + 10 {
+            history.push(format!("change {state}"), state);
+        }
+        assert_eq!(history.position(), MAX_ENTRIES);
+        assert_eq!(history.labels().next(), Some("change 10"));
+        let oldest = std::iter::from_fn(|| history.undo(0)).last();
+        assert_eq!(oldest, Some(10));
+    }
+
+    #[test]
+    fn pushing_clears_redo() {
+        let mut history = History::default();
+        history.push("padding".into(), 0);
+        history.push("gap".into(), 1);
+        assert_eq!(history.undo(2), Some(1));
+        assert!(history.can_redo());
+
+        history.push("border".into(), 1);
+        assert!(!history.can_redo());
+        assert_eq!(history.labels().collect::<Vec<_>>(), ["padding", "border"]);
+        assert_eq!(history.redo(5), None);
+    }
+
+    #[test]
+    fn jumps_to_any_position_on_the_timeline() {
+        let mut history = History::default();
+        history.push("a".into(), 0);
+        history.push("b".into(), 1);
+        history.push("c".into(), 2);
+
+        assert_eq!(history.jump(1, 3), Some(1));
+        assert_eq!(history.position(), 1);
+        assert_eq!(history.jump(1, 1), None);
+        assert_eq!(history.jump(3, 1), Some(3));
+        assert_eq!(history.position(), 3);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod aspect_ratio;
+#[cfg(windows)]
 mod komorebi_connect;
 pub mod layout;
 
+#[cfg(windows)]
 pub use komorebi_connect::connect;
@@ -0,0 +1,652 @@
+pub mod conflicts;
+
+use crate::{
+    KOMOREBI_VERSION, Message,
+    apperror::{AppError, AppErrorKind},
+    config::schema::Violation,
+    screen::rules,
+    widget::{button_with_icon, icons, opt_helpers},
+};
+
+use std::collections::HashMap;
+
+use iced::{
+    Center, Element, Fill, padding,
+    widget::{button, column, container, row, scrollable},
+};
+use komorebi_client::{
+    IdWithIdentifier, MatchingRule, MatchingStrategy, StaticConfig, WorkspaceConfig,
+};
+
+/// A problem found on the config, together with the place where it can be fixed.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub error: AppError,
+    pub location: Location,
+    /// For the findings about a rule, its index on the list at `location` and a short summary to
+    /// show next to it.
+    pub rule: Option<(usize, String)>,
+}
+
+/// The screen where some problem can be fixed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    General,
+    Monitors,
+    Monitor(usize),
+    Workspace(usize, usize),
+    WorkspaceRules(usize, usize),
+    InitialWorkspaceRules(usize, usize),
+    Rules(rules::Screen),
+    TransparencyIgnoreRules,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::General => write!(f, "General"),
+            Location::Monitors => write!(f, "Monitors"),
+            Location::Monitor(m) => write!(f, "Monitor [{m}]"),
+            Location::Workspace(m, w) => write!(f, "Monitor [{m}] > Workspace [{w}]"),
+            Location::WorkspaceRules(m, w) => {
+                write!(f, "Monitor [{m}] > Workspace [{w}] > Workspace Rules")
+            }
+            Location::InitialWorkspaceRules(m, w) => {
+                write!(
+                    f,
+                    "Monitor [{m}] > Workspace [{w}] > Initial Workspace Rules"
+                )
+            }
+            Location::Rules(screen) => write!(f, "Rules > {screen}"),
+            Location::TransparencyIgnoreRules => write!(f, "Transparency > Ignore Rules"),
+        }
+    }
+}
+
+impl Problem {
+    fn new(
+        kind: AppErrorKind,
+        location: Location,
+        title: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            error: AppError {
+                title: title.into(),
+                description: Some(description.into()),
+                kind,
+            },
+            location,
+            rule: None,
+        }
+    }
+}
+
+/// The findings about the rules on the list at `location`, as the index of the rule and a short
+/// summary.
+pub fn rule_findings(problems: &[Problem], location: &Location) -> Vec<(usize, String)> {
+    problems
+        .iter()
+        .filter(|problem| problem.location == *location)
+        .filter_map(|problem| problem.rule.clone())
+        .collect()
+}
+
+/// Runs all the checks over `config` and returns the problems found, errors first.
+pub fn lint(config: &StaticConfig) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    check_rules(config, &mut problems);
+    check_conflicts(config, &mut problems);
+    check_paddings(config, &mut problems);
+    check_display_index_preferences(config, &mut problems);
+
+    if let Some(monitors) = &config.monitors {
+        let mut names: HashMap<&str, (usize, usize)> = HashMap::new();
+        for (m_idx, monitor) in monitors.iter().enumerate() {
+            for (w_idx, workspace) in monitor.workspaces.iter().enumerate() {
+                if !workspace.name.is_empty() {
+                    if let Some((first_m, first_w)) = names.get(workspace.name.as_str()) {
+                        problems.push(Problem::new(
+                            AppErrorKind::Warning,
+                            Location::Workspace(m_idx, w_idx),
+                            format!("Duplicate workspace name '{}'", workspace.name),
+                            format!(
+                                "Workspace [{w_idx}] of monitor [{m_idx}] has the same name as \
+                                workspace [{first_w}] of monitor [{first_m}]. Commands and rules \
+                                that target a workspace by name will only reach one of them."
+                            ),
+                        ));
+                    } else {
+                        names.insert(&workspace.name, (m_idx, w_idx));
+                    }
+                }
+                check_workspace(m_idx, w_idx, workspace, &mut problems);
+            }
+        }
+    }
+
+    problems.sort_by_key(|problem| match problem.error.kind {
+        AppErrorKind::Error => 0,
+        AppErrorKind::Warning => 1,
+        AppErrorKind::Info => 2,
+    });
+    problems
+}
+
+/// The problems for the places where the file doesn't follow the komorebi schema. They are only
+/// warnings, since komorebi itself accepts some of them.
+pub fn schema_problems(violations: &[Violation]) -> Vec<Problem> {
+    violations
+        .iter()
+        .map(|violation| {
+            let mut segments = violation.pointer.split('/').skip(1);
+            let location = match (
+                segments.next(),
+                segments.next().and_then(|m| m.parse().ok()),
+                segments.next(),
+                segments.next().and_then(|w| w.parse().ok()),
+            ) {
+                (Some("monitors"), Some(m), Some("workspaces"), Some(w)) => {
+                    Location::Workspace(m, w)
+                }
+                (Some("monitors"), Some(m), _, _) => Location::Monitor(m),
+                (Some("monitors"), None, _, _) => Location::Monitors,
+                (Some("transparency_ignore_rules"), _, _, _) => Location::TransparencyIgnoreRules,
+                _ => Location::General,
+            };
+            Problem::new(
+                AppErrorKind::Warning,
+                location,
+                format!(
+                    "Doesn't match the komorebi {} schema: {}",
+                    *KOMOREBI_VERSION, violation.message
+                ),
+                format!(
+                    "{violation}\n\nKomorice loaded the file anyway, but komorebi might refuse it \
+                    or ignore this value."
+                ),
+            )
+        })
+        .collect()
+}
+
+/// Checks the regex patterns of all rules lists.
+fn check_rules(config: &StaticConfig, problems: &mut Vec<Problem>) {
+    let global_rules = [
+        (&config.ignore_rules, rules::Screen::IgnoreRules),
+        (
+            &config.floating_applications,
+            rules::Screen::FloatingApplications,
+        ),
+        (&config.manage_rules, rules::Screen::ManageRules),
+        (
+            &config.tray_and_multi_window_applications,
+            rules::Screen::TrayAndMultiWindowApplications,
+        ),
+        (
+            &config.object_name_change_applications,
+            rules::Screen::ObjectNameChangeApplications,
+        ),
+        (
+            &config.slow_application_identifiers,
+            rules::Screen::SlowApplicationIdentifiers,
+        ),
+        (
+            &config.layered_applications,
+            rules::Screen::LayeredApplications,
+        ),
+        (
+            &config.border_overflow_applications,
+            rules::Screen::BorderOverflowApplications,
+        ),
+    ];
+    for (rules, screen) in global_rules {
+        check_regexes(rules.as_deref(), || Location::Rules(screen), problems);
+    }
+    check_regexes(
+        config.transparency_ignore_rules.as_deref(),
+        || Location::TransparencyIgnoreRules,
+        problems,
+    );
+}
+
+fn check_regexes(
+    rules: Option<&[MatchingRule]>,
+    location: impl Fn() -> Location,
+    problems: &mut Vec<Problem>,
+) {
+    for id in rules.unwrap_or_default().iter().flat_map(identifiers) {
+        if matches!(id.matching_strategy, Some(MatchingStrategy::Regex))
+            && let Err(error) = regex::Regex::new(&id.id)
+        {
+            problems.push(Problem::new(
+                AppErrorKind::Error,
+                location(),
+                format!("Invalid regex on rule {}", describe_id(id)),
+                format!("komorebi will never match this rule.\n\n{error}"),
+            ));
+        }
+    }
+}
+
+/// Reports the rules that conflict with other ones, are redundant or can never match.
+fn check_conflicts(config: &StaticConfig, problems: &mut Vec<Problem>) {
+    let lists = rule_lists(config);
+    for finding in conflicts::find(config) {
+        let rule = lists
+            .iter()
+            .find(|(_, location)| *location == finding.location)
+            .and_then(|(rules, _)| rules.as_ref()?.get(finding.rule))
+            .map(describe_rule)
+            .unwrap_or_default();
+        let title = match finding.kind {
+            conflicts::FindingKind::Conflict => format!("Conflicting rule {rule}"),
+            conflicts::FindingKind::Redundant => format!("Redundant rule {rule}"),
+            conflicts::FindingKind::NeverMatches => format!("Rule {rule} can never match"),
+        };
+        problems.push(Problem {
+            rule: Some((finding.rule, finding.summary)),
+            ..Problem::new(
+                AppErrorKind::Warning,
+                finding.location,
+                title,
+                finding.description,
+            )
+        });
+    }
+}
+
+fn check_paddings(config: &StaticConfig, problems: &mut Vec<Problem>) {
+    let paddings = [
+        (
+            "Default container padding",
+            config.default_container_padding,
+        ),
+        (
+            "Default workspace padding",
+            config.default_workspace_padding,
+        ),
+    ];
+    for (name, padding) in paddings {
+        push_negative_padding(name, padding, Location::General, problems);
+    }
+    for (idx, monitor) in config.monitors.iter().flatten().enumerate() {
+        let paddings = [
+            ("Container padding", monitor.container_padding),
+            ("Workspace padding", monitor.workspace_padding),
+        ];
+        for (name, padding) in paddings {
+            push_negative_padding(name, padding, Location::Monitor(idx), problems);
+        }
+    }
+}
+
+fn push_negative_padding(
+    name: &str,
+    padding: Option<i32>,
+    location: Location,
+    problems: &mut Vec<Problem>,
+) {
+    if let Some(padding) = padding.filter(|padding| *padding < 0) {
+        problems.push(Problem::new(
+            AppErrorKind::Error,
+            location,
+            format!("{name} is negative"),
+            format!(
+                "A padding of {padding} makes windows overlap each other and the screen edges."
+            ),
+        ));
+    }
+}
+
+fn check_display_index_preferences(config: &StaticConfig, problems: &mut Vec<Problem>) {
+    let monitors_count = config
+        .monitors
+        .as_ref()
+        .map_or(0, |monitors| monitors.len());
+    let Some(preferences) = &config.display_index_preferences else {
+        return;
+    };
+    let mut indices = preferences.keys().collect::<Vec<_>>();
+    indices.sort();
+    for idx in indices.into_iter().filter(|idx| **idx >= monitors_count) {
+        problems.push(Problem::new(
+            AppErrorKind::Warning,
+            Location::Monitors,
+            format!("Display index preference for missing monitor [{idx}]"),
+            format!(
+                "The display '{}' is set to monitor [{idx}] but there are only {monitors_count} \
+                monitors configured.",
+                preferences[idx]
+            ),
+        ));
+    }
+}
+
+fn check_workspace(
+    m_idx: usize,
+    w_idx: usize,
+    workspace: &WorkspaceConfig,
+    problems: &mut Vec<Problem>,
+) {
+    push_negative_padding(
+        "Container padding",
+        workspace.container_padding,
+        Location::Workspace(m_idx, w_idx),
+        problems,
+    );
+    push_negative_padding(
+        "Workspace padding",
+        workspace.workspace_padding,
+        Location::Workspace(m_idx, w_idx),
+        problems,
+    );
+
+    if let Some(grid) = workspace
+        .layout_options
+        .as_ref()
+        .and_then(|options| options.grid.as_ref())
+        && grid.rows == 0
+    {
+        problems.push(Problem::new(
+            AppErrorKind::Error,
+            Location::Workspace(m_idx, w_idx),
+            "Grid layout rows is zero",
+            "A grid with zero rows per column can't place any window. Remove the option to let \
+            komorebi choose the rows or set it to at least 1.",
+        ));
+    }
+
+    if let Some(layout_rules) = &workspace.layout_rules {
+        let mut thresholds = layout_rules.keys().copied().collect::<Vec<_>>();
+        thresholds.sort();
+        if let Some(threshold) = thresholds.first().filter(|threshold| **threshold <= 1) {
+            problems.push(Problem::new(
+                AppErrorKind::Warning,
+                Location::Workspace(m_idx, w_idx),
+                format!("Layout rule for {threshold} windows overrides the workspace layout"),
+                "Layout rules apply when the workspace has at least that amount of windows, so \
+                this rule always applies and the workspace layout is never used.",
+            ));
+        }
+        if let Some(custom_layout_rules) = &workspace.custom_layout_rules {
+            for threshold in thresholds
+                .iter()
+                .filter(|threshold| custom_layout_rules.contains_key(*threshold))
+            {
+                problems.push(Problem::new(
+                    AppErrorKind::Error,
+                    Location::Workspace(m_idx, w_idx),
+                    format!("Layout rules collide at {threshold} windows"),
+                    "There is a layout rule and a custom layout rule for the same amount of \
+                    windows, only one of them will be used.",
+                ));
+            }
+        }
+    }
+
+    check_regexes(
+        workspace.workspace_rules.as_deref(),
+        || Location::WorkspaceRules(m_idx, w_idx),
+        problems,
+    );
+    check_regexes(
+        workspace.initial_workspace_rules.as_deref(),
+        || Location::InitialWorkspaceRules(m_idx, w_idx),
+        problems,
+    );
+}
+
+/// All the lists of rules on `config`, each with the place where it can be edited.
+pub fn rule_lists(config: &StaticConfig) -> Vec<(&Option<Vec<MatchingRule>>, Location)> {
+    let mut lists = rules::Screen::ALL
+        .into_iter()
+        .map(|screen| {
+            (
+                rules::get_rules_from_config(config, &screen),
+                Location::Rules(screen),
+            )
+        })
+        .collect::<Vec<_>>();
+    lists.push((
+        &config.transparency_ignore_rules,
+        Location::TransparencyIgnoreRules,
+    ));
+    for (m, monitor) in config.monitors.iter().flatten().enumerate() {
+        for (w, workspace) in monitor.workspaces.iter().enumerate() {
+            lists.push((&workspace.workspace_rules, Location::WorkspaceRules(m, w)));
+            lists.push((
+                &workspace.initial_workspace_rules,
+                Location::InitialWorkspaceRules(m, w),
+            ));
+        }
+    }
+    lists
+}
+
+/// The list of rules at `location`, if there is one there.
+pub fn rule_list_mut<'a>(
+    config: &'a mut StaticConfig,
+    location: &Location,
+) -> Option<&'a mut Option<Vec<MatchingRule>>> {
+    match location {
+        Location::Rules(screen) => Some(rules::get_rules_from_config_mut(config, screen)),
+        Location::TransparencyIgnoreRules => Some(&mut config.transparency_ignore_rules),
+        Location::WorkspaceRules(m, w) | Location::InitialWorkspaceRules(m, w) => {
+            let workspace = config
+                .monitors
+                .as_mut()?
+                .get_mut(*m)?
+                .workspaces
+                .get_mut(*w)?;
+            if matches!(location, Location::WorkspaceRules(..)) {
+                Some(&mut workspace.workspace_rules)
+            } else {
+                Some(&mut workspace.initial_workspace_rules)
+            }
+        }
+        Location::General | Location::Monitors | Location::Monitor(_) | Location::Workspace(..) => {
+            None
+        }
+    }
+}
+
+fn identifiers(rule: &MatchingRule) -> &[IdWithIdentifier] {
+    match rule {
+        MatchingRule::Simple(id) => std::slice::from_ref(id),
+        MatchingRule::Composite(ids) => ids,
+    }
+}
+
+fn describe_id(id: &IdWithIdentifier) -> String {
+    format!("{} '{}'", id.kind, id.id)
+}
+
+pub fn describe_rule(rule: &MatchingRule) -> String {
+    identifiers(rule)
+        .iter()
+        .map(describe_id)
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Creates the view with the list of problems, each with a button to go where it can be fixed.
+pub fn view(problems: &[Problem]) -> Element<'_, Message> {
+    if problems.is_empty() {
+        return opt_helpers::description_text("No problems found on the config.").into();
+    }
+    let initial_col = column![].spacing(10).padding(padding::all(5.0).right(20.0));
+    let problems = problems.iter().fold(initial_col, |col, problem| {
+        col.push(
+            column![
+                problem.error.view(),
+                row![
+                    opt_helpers::to_description_text(iced::widget::text!("{}", problem.location))
+                        .width(Fill),
+                    button_with_icon(icons::edit(), "Go to")
+                        .on_press(Message::GoToProblem(problem.location.clone()))
+                        .style(button::secondary),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            ]
+            .spacing(5),
+        )
+    });
+    container(scrollable(problems)).max_height(450.0).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::{DEFAULT_MONITOR_CONFIG, DEFAULT_WORKSPACE_CONFIG};
+    use crate::testing;
+
+    use komorebi_client::{ApplicationIdentifier, MonitorConfig};
+    use serde_json::{Value, json};
+
+    /// The location and kind of each problem found on `config`.
+    fn found(config: &StaticConfig) -> Vec<(Location, AppErrorKind)> {
+        lint(config)
+            .into_iter()
+            .map(|problem| (problem.location, problem.error.kind))
+            .collect()
+    }
+
+    fn rule(id: &str, strategy: MatchingStrategy) -> MatchingRule {
+        MatchingRule::Simple(IdWithIdentifier {
+            kind: ApplicationIdentifier::Exe,
+            id: id.into(),
+            matching_strategy: Some(strategy),
+        })
+    }
+
+    /// A default workspace named `name` with the `fields` set over it.
+    fn workspace(name: &str, fields: Value) -> WorkspaceConfig {
+        let mut value = serde_json::to_value(&*DEFAULT_WORKSPACE_CONFIG).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.insert("name".into(), json!(name));
+        object.extend(fields.as_object().unwrap().clone());
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// A config with one monitor that has the `workspaces`.
+    fn with_workspaces(workspaces: Vec<WorkspaceConfig>) -> StaticConfig {
+        let mut config = testing::empty_config();
+        config.monitors = Some(vec![MonitorConfig {
+            workspaces,
+            ..DEFAULT_MONITOR_CONFIG.clone()
+        }]);
+        config
+    }
+
+    #[test]
+    fn finds_nothing_on_a_clean_config() {
+        let config = with_workspaces(vec![workspace("I", json!({})), workspace("II", json!({}))]);
+        assert_eq!(found(&config), vec![]);
+    }
+
+    #[test]
+    fn finds_invalid_regexes() {
+        let mut config = testing::empty_config();
+        config.ignore_rules = Some(vec![rule("fire(fox", MatchingStrategy::Regex)]);
+        assert_eq!(
+            found(&config),
+            vec![(
+                Location::Rules(rules::Screen::IgnoreRules),
+                AppErrorKind::Error
+            )]
+        );
+    }
+
+    #[test]
+    fn finds_rules_both_ignored_and_managed() {
+        let mut config = testing::empty_config();
+        config.ignore_rules = Some(vec![rule("firefox.exe", MatchingStrategy::Equals)]);
+        config.manage_rules = Some(vec![rule("firefox.exe", MatchingStrategy::Equals)]);
+        let problems = lint(&config);
+        assert_eq!(problems.len(), 2);
+        assert!(problems.iter().all(|problem| problem.rule.is_some()));
+        assert_eq!(
+            found(&config),
+            vec![
+                (
+                    Location::Rules(rules::Screen::IgnoreRules),
+                    AppErrorKind::Warning
+                ),
+                (
+                    Location::Rules(rules::Screen::ManageRules),
+                    AppErrorKind::Warning
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_duplicate_workspace_names() {
+        let config = with_workspaces(vec![workspace("I", json!({})), workspace("I", json!({}))]);
+        assert_eq!(
+            found(&config),
+            vec![(Location::Workspace(0, 1), AppErrorKind::Warning)]
+        );
+    }
+
+    #[test]
+    fn finds_display_index_preferences_without_monitor() {
+        let mut config = with_workspaces(vec![workspace("I", json!({}))]);
+        config.display_index_preferences = Some(HashMap::from([
+            (0, "DISPLAY1".to_string()),
+            (1, "DISPLAY2".to_string()),
+        ]));
+        assert_eq!(
+            found(&config),
+            vec![(Location::Monitors, AppErrorKind::Warning)]
+        );
+    }
+
+    #[test]
+    fn finds_negative_paddings() {
+        let mut config = with_workspaces(vec![workspace("I", json!({ "container_padding": -1 }))]);
+        config.default_workspace_padding = Some(-5);
+        if let Some(monitor) = config.monitors.as_mut().and_then(|m| m.first_mut()) {
+            monitor.workspace_padding = Some(-2);
+        }
+        assert_eq!(
+            found(&config),
+            vec![
+                (Location::General, AppErrorKind::Error),
+                (Location::Monitor(0), AppErrorKind::Error),
+                (Location::Workspace(0, 0), AppErrorKind::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_grids_with_zero_rows() {
+        let config = with_workspaces(vec![workspace(
+            "I",
+            json!({ "layout_options": { "grid": { "rows": 0 } } }),
+        )]);
+        assert_eq!(
+            found(&config),
+            vec![(Location::Workspace(0, 0), AppErrorKind::Error)]
+        );
+    }
+
+    #[test]
+    fn finds_layout_rules_that_collide() {
+        let config = with_workspaces(vec![workspace(
+            "I",
+            json!({
+                "layout_rules": { "4": "Columns" },
+                "custom_layout_rules": { "4": "custom.yaml" }
+            }),
+        )]);
+        assert_eq!(
+            found(&config),
+            vec![(Location::Workspace(0, 0), AppErrorKind::Error)]
+        );
+    }
+}
@@ -0,0 +1,417 @@
+//! Finds rules that get in each other's way, within a list or across lists.
+//!
+//! Komorebi checks the lists of rules one after the other and uses the first rule that matches a
+//! window, so when two conflicting lists match the same windows only one of them takes effect.
+//! Only the overlaps that are certain are reported: one rule has to match every window of the
+//! other one.
+
+use super::{Location, describe_id, describe_rule, identifiers, rule_lists};
+use crate::screen::rules;
+
+use komorebi_client::{
+    ApplicationIdentifier, IdWithIdentifier, MatchingRule, MatchingStrategy, StaticConfig,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FindingKind {
+    /// The rule matches the same windows as a rule on a list that handles them differently.
+    Conflict,
+    /// The rule is already covered by another rule on the same list.
+    Redundant,
+    /// The identifiers of the composite rule can never match the same window.
+    NeverMatches,
+}
+
+/// Something found about a rule.
+#[derive(Clone, Debug)]
+pub struct Finding {
+    pub kind: FindingKind,
+    /// The list of the rule.
+    pub location: Location,
+    /// The index of the rule on its list.
+    pub rule: usize,
+    /// A short summary, to show next to the rule.
+    pub summary: String,
+    pub description: String,
+}
+
+/// Finds the conflicting, redundant and impossible rules on every list of `config`.
+pub fn find(config: &StaticConfig) -> Vec<Finding> {
+    let rules = rule_lists(config)
+        .into_iter()
+        .flat_map(|(rules, location)| {
+            rules
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(move |(idx, rule)| (location.clone(), idx, rule))
+        })
+        .collect::<Vec<_>>();
+
+    let mut findings = Vec::new();
+    let mut can_match = Vec::new();
+    for (location, idx, rule) in &rules {
+        if identifiers(rule).is_empty() {
+            findings.push(Finding {
+                kind: FindingKind::NeverMatches,
+                location: location.clone(),
+                rule: *idx,
+                summary: "Can never match".into(),
+                description: "The composite rule has no identifiers, so it never matches any \
+                    window."
+                    .into(),
+            });
+            continue;
+        }
+        match contradiction(rule) {
+            Some((a, b)) => findings.push(Finding {
+                kind: FindingKind::NeverMatches,
+                location: location.clone(),
+                rule: *idx,
+                summary: "Can never match".into(),
+                description: format!(
+                    "No window can match both {} and {}, so this rule never matches any window.",
+                    describe_id(a),
+                    describe_id(b)
+                ),
+            }),
+            None => can_match.push((location, *idx, *rule)),
+        }
+    }
+
+    for (i, (a_location, a_idx, a)) in can_match.iter().enumerate() {
+        for (b_location, b_idx, b) in &can_match[i + 1..] {
+            let a_covers_b = covers_rule(a, b);
+            let b_covers_a = covers_rule(b, a);
+            if !a_covers_b && !b_covers_a {
+                continue;
+            }
+            if a_location == b_location {
+                let (broad, broad_idx, narrow, narrow_idx) = if a_covers_b {
+                    (a, a_idx, b, b_idx)
+                } else {
+                    (b, b_idx, a, a_idx)
+                };
+                let summary = if a_covers_b && b_covers_a {
+                    format!("Same as rule [{broad_idx}]")
+                } else {
+                    format!("Covered by rule [{broad_idx}]")
+                };
+                findings.push(Finding {
+                    kind: FindingKind::Redundant,
+                    location: (*a_location).clone(),
+                    rule: *narrow_idx,
+                    summary,
+                    description: format!(
+                        "Every window matched by {} is already matched by {} on the same list, \
+                        so it can be removed.",
+                        describe_rule(narrow),
+                        describe_rule(broad)
+                    ),
+                });
+            } else if conflicting(a_location, b_location) {
+                let mut push = |location: &Location, idx: usize, other_location: &Location| {
+                    findings.push(Finding {
+                        kind: FindingKind::Conflict,
+                        location: location.clone(),
+                        rule: idx,
+                        summary: format!("Overlaps with {other_location}"),
+                        description: format!(
+                            "{} on '{}' and {} on '{}' match the same windows. Komorebi uses \
+                            whichever it checks first, so only one of them takes effect.",
+                            describe_rule(a),
+                            a_location,
+                            describe_rule(b),
+                            b_location
+                        ),
+                    })
+                };
+                push(a_location, *a_idx, b_location);
+                push(b_location, *b_idx, a_location);
+            }
+        }
+    }
+    findings
+}
+
+/// Whether the windows matched by the lists at `a` and at `b` are handled in ways that can't both
+/// apply.
+fn conflicting(a: &Location, b: &Location) -> bool {
+    use rules::Screen::{FloatingApplications, IgnoreRules, ManageRules};
+
+    let workspace = |location: &Location| match location {
+        Location::WorkspaceRules(m, w) | Location::InitialWorkspaceRules(m, w) => Some((*m, *w)),
+        _ => None,
+    };
+    match (a, b) {
+        (Location::Rules(IgnoreRules), Location::Rules(ManageRules | FloatingApplications))
+        | (Location::Rules(ManageRules | FloatingApplications), Location::Rules(IgnoreRules)) => {
+            true
+        }
+        (Location::Rules(IgnoreRules), other) | (other, Location::Rules(IgnoreRules)) => {
+            workspace(other).is_some()
+        }
+        _ => matches!((workspace(a), workspace(b)), (Some(a), Some(b)) if a != b),
+    }
+}
+
+/// The values of a property matched by an identifier.
+#[derive(Clone, Debug)]
+enum Pattern<'a> {
+    Equals(&'a str),
+    StartsWith(&'a str),
+    EndsWith(&'a str),
+    Contains(&'a str),
+    /// The legacy strategy for titles and classes, which match from the start or from the end.
+    StartsOrEndsWith(&'a str),
+    Not(Box<Pattern<'a>>),
+    /// A pattern that can't be compared with others, like a regex.
+    Other,
+}
+
+impl<'a> Pattern<'a> {
+    fn new(id: &'a IdWithIdentifier) -> Self {
+        let value = id.id.as_str();
+        if value.is_empty() {
+            return Pattern::Other;
+        }
+        let not = |pattern| Pattern::Not(Box::new(pattern));
+        match id.matching_strategy {
+            None | Some(MatchingStrategy::Legacy) => match id.kind {
+                ApplicationIdentifier::Title | ApplicationIdentifier::Class => {
+                    Pattern::StartsOrEndsWith(value)
+                }
+                ApplicationIdentifier::Exe | ApplicationIdentifier::Path => Pattern::Equals(value),
+            },
+            Some(MatchingStrategy::Equals) => Pattern::Equals(value),
+            Some(MatchingStrategy::StartsWith) => Pattern::StartsWith(value),
+            Some(MatchingStrategy::EndsWith) => Pattern::EndsWith(value),
+            Some(MatchingStrategy::Contains) => Pattern::Contains(value),
+            Some(MatchingStrategy::Regex) => Pattern::Other,
+            Some(MatchingStrategy::DoesNotEqual) => not(Pattern::Equals(value)),
+            Some(MatchingStrategy::DoesNotStartWith) => not(Pattern::StartsWith(value)),
+            Some(MatchingStrategy::DoesNotEndWith) => not(Pattern::EndsWith(value)),
+            Some(MatchingStrategy::DoesNotContain) => not(Pattern::Contains(value)),
+        }
+    }
+
+    /// Whether `value` is matched. The `Other` patterns are never known to match.
+    fn matches(&self, value: &str) -> Option<bool> {
+        match self {
+            Pattern::Equals(v) => Some(value == *v),
+            Pattern::StartsWith(v) => Some(value.starts_with(*v)),
+            Pattern::EndsWith(v) => Some(value.ends_with(*v)),
+            Pattern::Contains(v) => Some(value.contains(*v)),
+            Pattern::StartsOrEndsWith(v) => Some(value.starts_with(*v) || value.ends_with(*v)),
+            Pattern::Not(pattern) => pattern.matches(value).map(|matches| !matches),
+            Pattern::Other => None,
+        }
+    }
+
+    /// Whether every value matched by `other` is matched by this pattern too.
+    fn covers(&self, other: &Pattern) -> bool {
+        match (self, other) {
+            (_, Pattern::Equals(v)) => self.matches(v) == Some(true),
+            (Pattern::StartsWith(a), Pattern::StartsWith(b)) => b.starts_with(*a),
+            (Pattern::EndsWith(a), Pattern::EndsWith(b)) => b.ends_with(*a),
+            (
+                Pattern::Contains(a),
+                Pattern::StartsWith(b)
+                | Pattern::EndsWith(b)
+                | Pattern::Contains(b)
+                | Pattern::StartsOrEndsWith(b),
+            ) => b.contains(*a),
+            (Pattern::StartsOrEndsWith(a), _) => {
+                Pattern::StartsWith(*a).covers(other) || Pattern::EndsWith(*a).covers(other)
+            }
+            (_, Pattern::StartsOrEndsWith(b)) => {
+                self.covers(&Pattern::StartsWith(*b)) && self.covers(&Pattern::EndsWith(*b))
+            }
+            (Pattern::Not(a), Pattern::Not(b)) => b.covers(a),
+            (Pattern::Not(a), _) => a.is_disjoint(other),
+            _ => false,
+        }
+    }
+
+    /// Whether no value can be matched by both this pattern and `other`.
+    fn is_disjoint(&self, other: &Pattern) -> bool {
+        match (self, other) {
+            (Pattern::Equals(v), pattern) | (pattern, Pattern::Equals(v)) => {
+                pattern.matches(v) == Some(false)
+            }
+            (Pattern::StartsWith(a), Pattern::StartsWith(b)) => {
+                !a.starts_with(*b) && !b.starts_with(*a)
+            }
+            (Pattern::EndsWith(a), Pattern::EndsWith(b)) => !a.ends_with(*b) && !b.ends_with(*a),
+            (Pattern::Not(a), pattern) | (pattern, Pattern::Not(a)) => a.covers(pattern),
+            _ => false,
+        }
+    }
+}
+
+/// Whether every window matched by `b` is matched by `a` too. Each identifier of `a` has to cover
+/// an identifier of `b` on the same property. A rule without identifiers never matches, so it
+/// covers nothing.
+fn covers_rule(a: &MatchingRule, b: &MatchingRule) -> bool {
+    let a_ids = identifiers(a);
+    !a_ids.is_empty()
+        && a_ids.iter().all(|a_id| {
+            let a_pattern = Pattern::new(a_id);
+            identifiers(b)
+                .iter()
+                .filter(|b_id| b_id.kind == a_id.kind)
+                .any(|b_id| a_pattern.covers(&Pattern::new(b_id)))
+        })
+}
+
+/// Two identifiers of `rule` that can't match the same window, if there are any.
+fn contradiction(rule: &MatchingRule) -> Option<(&IdWithIdentifier, &IdWithIdentifier)> {
+    let ids = identifiers(rule);
+    ids.iter().enumerate().find_map(|(i, a)| {
+        ids[i + 1..]
+            .iter()
+            .filter(|b| b.kind == a.kind)
+            .find(|b| Pattern::new(a).is_disjoint(&Pattern::new(b)))
+            .map(|b| (a, b))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::{DEFAULT_MONITOR_CONFIG, DEFAULT_WORKSPACE_CONFIG};
+    use crate::testing;
+
+    use komorebi_client::{MonitorConfig, WorkspaceConfig};
+
+    fn id(kind: ApplicationIdentifier, id: &str, strategy: MatchingStrategy) -> IdWithIdentifier {
+        IdWithIdentifier {
+            kind,
+            id: id.into(),
+            matching_strategy: Some(strategy),
+        }
+    }
+
+    fn exe(value: &str, strategy: MatchingStrategy) -> MatchingRule {
+        MatchingRule::Simple(id(ApplicationIdentifier::Exe, value, strategy))
+    }
+
+    #[test]
+    fn finds_the_same_exe_ignored_and_managed() {
+        let mut config = testing::empty_config();
+        config.ignore_rules = Some(vec![exe("firefox.exe", MatchingStrategy::Equals)]);
+        config.manage_rules = Some(vec![exe("firefox.exe", MatchingStrategy::Legacy)]);
+        let findings = find(&config);
+        assert_eq!(findings.len(), 2);
+        assert!(findings.iter().all(|f| f.kind == FindingKind::Conflict));
+    }
+
+    #[test]
+    fn finds_rules_covered_by_broader_ones() {
+        let mut config = testing::empty_config();
+        config.floating_applications = Some(vec![
+            exe("fire", MatchingStrategy::StartsWith),
+            exe("firefox.exe", MatchingStrategy::Equals),
+            exe("firefox", MatchingStrategy::StartsWith),
+            exe("chrome.exe", MatchingStrategy::Equals),
+        ]);
+        let findings = find(&config);
+        let mut redundant = findings
+            .iter()
+            .filter(|f| f.kind == FindingKind::Redundant)
+            .map(|f| f.rule)
+            .collect::<Vec<_>>();
+        redundant.sort();
+        redundant.dedup();
+        assert_eq!(redundant, vec![1, 2]);
+    }
+
+    #[test]
+    fn finds_workspace_rules_on_two_workspaces() {
+        let mut config = testing::empty_config();
+        let workspace = |name: &str| WorkspaceConfig {
+            name: name.into(),
+            workspace_rules: Some(vec![exe("code", MatchingStrategy::Contains)]),
+            ..DEFAULT_WORKSPACE_CONFIG.clone()
+        };
+        config.monitors = Some(vec![MonitorConfig {
+            workspaces: vec![workspace("I"), workspace("II")],
+            ..DEFAULT_MONITOR_CONFIG.clone()
+        }]);
+        let findings = find(&config);
+        assert_eq!(findings.len(), 2);
+        assert!(
+            findings
+                .iter()
+                .any(|f| f.location == Location::WorkspaceRules(0, 1))
+        );
+    }
+
+    #[test]
+    fn finds_composite_rules_that_never_match() {
+        let mut config = testing::empty_config();
+        config.ignore_rules = Some(vec![
+            MatchingRule::Composite(vec![
+                id(
+                    ApplicationIdentifier::Exe,
+                    "a.exe",
+                    MatchingStrategy::Equals,
+                ),
+                id(
+                    ApplicationIdentifier::Exe,
+                    "b.exe",
+                    MatchingStrategy::Equals,
+                ),
+            ]),
+            MatchingRule::Composite(vec![
+                id(
+                    ApplicationIdentifier::Title,
+                    "Save",
+                    MatchingStrategy::StartsWith,
+                ),
+                id(
+                    ApplicationIdentifier::Title,
+                    "Sa",
+                    MatchingStrategy::DoesNotStartWith,
+                ),
+            ]),
+            MatchingRule::Composite(vec![
+                id(
+                    ApplicationIdentifier::Exe,
+                    "a.exe",
+                    MatchingStrategy::Equals,
+                ),
+                id(
+                    ApplicationIdentifier::Title,
+                    "Save",
+                    MatchingStrategy::StartsWith,
+                ),
+            ]),
+        ]);
+        let never_matches = find(&config)
+            .into_iter()
+            .filter(|f| f.kind == FindingKind::NeverMatches)
+            .map(|f| f.rule)
+            .collect::<Vec<_>>();
+        assert_eq!(never_matches, vec![0, 1]);
+    }
+
+    #[test]
+    fn empty_composite_rules_never_match_and_cover_nothing() {
+        let mut config = testing::empty_config();
+        config.ignore_rules = Some(vec![
+            MatchingRule::Composite(vec![]),
+            exe("firefox.exe", MatchingStrategy::Equals),
+        ]);
+        config.manage_rules = Some(vec![exe("chrome.exe", MatchingStrategy::Equals)]);
+        let findings = find(&config);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, FindingKind::NeverMatches);
+        assert_eq!(
+            findings[0].location,
+            Location::Rules(rules::Screen::IgnoreRules)
+        );
+        assert_eq!(findings[0].rule, 0);
+    }
+}
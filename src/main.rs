@@ -3,33 +3,54 @@
     windows_subsystem = "windows"
 )]
 mod apperror;
+mod asc;
+mod backup;
+mod bundle;
+mod cli;
 mod config;
+mod history;
 mod komo_interop;
+mod lint;
+mod matching;
+mod platform;
+mod profile;
 mod screen;
 mod settings;
+#[cfg(test)]
+mod testing;
 mod utils;
 mod whkd;
 mod widget;
 
 use crate::apperror::{AppError, AppErrorKind};
 use crate::config::DEFAULT_CONFIG;
+use crate::history::History;
+use crate::platform::{KomorebiIpc, Native};
 use crate::screen::{
-    ConfigState, ConfigType, Configuration, Screen, View, animation, border, general, home,
-    live_debug, monitors, rules, sidebar, stackbar, theme, transparency,
+    ConfigState, ConfigType, Configuration, Screen, View, animation, applications, border, general,
+    home, import, live_debug, monitor, monitors, palette, rule, rules, sidebar, stackbar, theme,
+    transparency, workspace,
+};
+use crate::widget::{
+    button_with_icon, icons,
+    opt_helpers::{self, to_description_text},
 };
-use crate::widget::{button_with_icon, icons, opt_helpers::to_description_text};
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use iced::{
-    Center, Element, Fill, Font, Right, Subscription, Task, Theme, padding,
+    Center, Element, Fill, Font, Right, Subscription, Task, Theme, keyboard, padding,
     widget::{
         button, checkbox, column, container, rich_text, row, rule, scrollable, space, span, text,
     },
 };
 use lazy_static::lazy_static;
 
+/// How often the autosave checks whether the config stopped changing so it can be saved.
+const AUTOSAVE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(750);
+
 lazy_static! {
     static ref KOMOREBI_VERSION: &'static str = "v0.1.39";
     static ref DEFAULT_FONT: Font = Font::with_name("Segoe UI");
@@ -45,11 +66,19 @@ lazy_static! {
         f
     };
     static ref NONE_STR: Arc<str> = Arc::from("[None]");
-    static ref SCREENS_BACK_TO_START: [Screen; 3] =
-        [Screen::Rules, Screen::Transparency, Screen::LiveDebug];
+    static ref SCREENS_BACK_TO_START: [Screen; 4] = [
+        Screen::Rules,
+        Screen::Applications,
+        Screen::Transparency,
+        Screen::LiveDebug,
+    ];
 }
 
 fn main() -> iced::Result {
+    if let Some(code) = cli::run(std::env::args().skip(1).collect()) {
+        std::process::exit(code);
+    }
+
     iced::application(Komorice::initialize, Komorice::update, Komorice::view)
         .title("Komorice")
         .subscription(Komorice::subscription)
@@ -77,18 +106,22 @@ fn main() -> iced::Result {
 enum Message {
     // Error Messages
     AppError(AppError),
+    SaveWarning(AppError),
+    CheckedFiles(widget::path_input::FoundFiles),
     OpenErrorsModal,
     CloseErrorsModal,
     ClearErrors,
 
     // View/Screen related Messages
     Home(home::Message),
+    Import(import::Message),
     Animation(animation::Message),
     Border(border::Message),
     General(general::Message),
     LiveDebug(live_debug::Message),
     Monitors(monitors::Message),
     Rules(rules::Message),
+    Applications(applications::Message),
     Sidebar(sidebar::Message),
     Stackbar(stackbar::Message),
     Theme(theme::Message),
@@ -97,7 +130,14 @@ enum Message {
     Whkd(whkd::Message),
 
     // Config related Messages
-    LoadedConfig(Arc<komorebi_client::StaticConfig>),
+    LoadedConfig(
+        Arc<komorebi_client::StaticConfig>,
+        Vec<config::schema::Violation>,
+    ),
+    ConfigChangedOnDisk(
+        Arc<komorebi_client::StaticConfig>,
+        Vec<config::schema::Violation>,
+    ),
     FailedToLoadConfig(AppError),
     ConfigFileWatcherTx(smol::channel::Sender<config::Input>),
     ConfigWatcherError(AppError),
@@ -105,7 +145,37 @@ enum Message {
     TrySave,
     ToggleSaveModal,
     Save,
-    Saved,
+    Saved(Arc<komorebi_client::StaticConfig>),
+    ConfirmDowngrade(config::migration::Version, Vec<String>),
+    SaveDowngraded,
+    CancelDowngrade,
+    RevertChange(usize, usize),
+    AutosaveTick,
+
+    // Config folders related Messages
+    CheckConfigHomes,
+    CreateConfigHome(ConfigType),
+    PickConfigHome(ConfigType),
+    PickedConfigHome(ConfigType, Option<PathBuf>),
+    DismissHomeProblems,
+
+    // Conflicts related Messages
+    ResolveConflict(usize, config::merge::Side),
+    ResolveAllConflicts(config::merge::Side),
+
+    // History related Messages
+    Undo,
+    Redo,
+    JumpHistory(usize),
+    ToggleHistoryModal,
+
+    // Problems related Messages
+    ToggleProblemsModal,
+    GoToProblem(lint::Location),
+
+    // Palette related Messages
+    OpenPalette,
+    Palette(palette::Message),
 }
 
 struct Komorice {
@@ -114,6 +184,8 @@ struct Komorice {
     display_info: HashMap<usize, monitors::DisplayInfo>,
     sidebar: sidebar::Sidebar,
     home: home::Home,
+    import: import::Import,
+    palette: Option<palette::Palette>,
     monitors: monitors::Monitors,
     border: border::Border,
     general: general::General,
@@ -122,16 +194,46 @@ struct Komorice {
     animation: animation::Animation,
     theme_screen: theme::Theme,
     rules: rules::Rules,
+    applications: applications::Applications,
     live_debug: live_debug::LiveDebug,
     settings: settings::Settings,
     whkd: whkd::Whkd,
     config: komorebi_client::StaticConfig,
     loaded_config: Arc<komorebi_client::StaticConfig>,
     is_dirty: bool,
+    history: History<komorebi_client::StaticConfig>,
+    history_config: komorebi_client::StaticConfig,
     config_watcher_tx: Option<smol::channel::Sender<config::Input>>,
     errors: Vec<AppError>,
+    pending_changes: Vec<config::diff::SectionChanges>,
+    problems: Vec<lint::Problem>,
+    /// The places where the loaded `komorebi.json` file doesn't follow the komorebi schema.
+    schema_violations: Vec<config::schema::Violation>,
+    conflicts: Vec<config::merge::Conflict>,
+    /// The folders of the configs that can't be used, found when starting.
+    home_problems: Vec<(ConfigType, config::HomePathError)>,
+    /// The configs as they were on the last autosave tick, which are only saved once they stop
+    /// changing between ticks.
+    autosave_snapshot: Option<(komorebi_client::StaticConfig, whkd::Whkdrc)>,
+    /// The configs that were backed up by an autosave already. Autosaves only back up each config
+    /// once per session, so that the backups aren't all taken by small changes.
+    autosave_backups: Vec<ConfigType>,
+    /// The changes that saving for an older komorebi version makes, waiting to be confirmed.
+    pending_downgrade: Option<(config::migration::Version, Vec<String>)>,
+    /// Whether saving for the older komorebi version selected on the settings was confirmed or
+    /// cancelled this session, so that autosaves don't ask again on every change.
+    downgrade_answer: Option<(config::migration::TargetVersion, bool)>,
+    /// The titles of the warnings about saving that were shown already. Each one is only shown
+    /// once per session, since autosaves would repeat them on every change.
+    save_warnings_shown: Vec<String>,
+    /// The paths shown on the path inputs, as last checked for `found_files`.
+    checked_paths: BTreeSet<String>,
+    /// Whether the file of each of the `checked_paths` exists.
+    found_files: widget::path_input::FoundFiles,
     show_save_modal: bool,
     show_errors_modal: bool,
+    show_history_modal: bool,
+    show_problems_modal: bool,
 }
 
 impl Default for Komorice {
@@ -142,6 +244,8 @@ impl Default for Komorice {
             sidebar: Default::default(),
             display_info: Default::default(),
             home: Default::default(),
+            import: Default::default(),
+            palette: None,
             monitors: monitors::Monitors::new(&DEFAULT_CONFIG),
             border: Default::default(),
             general: Default::default(),
@@ -150,16 +254,33 @@ impl Default for Komorice {
             animation: Default::default(),
             theme_screen: Default::default(),
             rules: Default::default(),
+            applications: Default::default(),
             live_debug: Default::default(),
             settings: Default::default(),
             whkd: Default::default(),
             config: DEFAULT_CONFIG.clone(),
             loaded_config: Arc::new(DEFAULT_CONFIG.clone()),
             is_dirty: Default::default(),
+            history: Default::default(),
+            history_config: DEFAULT_CONFIG.clone(),
             config_watcher_tx: Default::default(),
             errors: Default::default(),
+            pending_changes: Default::default(),
+            problems: Default::default(),
+            schema_violations: Vec::new(),
+            conflicts: Default::default(),
+            home_problems: Vec::new(),
+            autosave_snapshot: None,
+            autosave_backups: Vec::new(),
+            pending_downgrade: None,
+            downgrade_answer: None,
+            save_warnings_shown: Vec::new(),
+            checked_paths: BTreeSet::new(),
+            found_files: Default::default(),
             show_save_modal: Default::default(),
             show_errors_modal: Default::default(),
+            show_history_modal: Default::default(),
+            show_problems_modal: Default::default(),
         }
     }
 }
@@ -172,20 +293,21 @@ impl Komorice {
         config::fill_monitors(&mut config, &display_info);
         let monitors = monitors::Monitors::new(&config);
         let (whkd, whkd_task) = whkd::Whkd::init();
-        let init = Komorice {
+        let mut init = Komorice {
             display_info,
+            history_config: config.clone(),
             config,
             loaded_config,
             monitors,
             whkd,
             ..Default::default()
         };
+        let load_configs = init.check_config_homes();
         (
             init,
             Task::batch([
                 settings::load_task().map(Message::Settings),
-                config::load_task(config::config_path()),
-                whkd::load_task(whkd::config_path()).map(Message::Whkd),
+                load_configs,
                 whkd::load_commands().map(Message::Whkd),
                 whkd_task.map(Message::Whkd),
             ]),
@@ -193,8 +315,29 @@ impl Komorice {
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
+        let task = self.handle(message);
+        let paths = self.path_input_values();
+        if paths == self.checked_paths {
+            return task;
+        }
+        self.checked_paths = paths.clone();
+        let check = Task::perform(
+            smol::unblock(move || widget::path_input::check_files(paths)),
+            Message::CheckedFiles,
+        );
+        Task::batch([task, check])
+    }
+
+    fn handle(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::AppError(apperror) => self.add_error(apperror),
+            Message::CheckedFiles(found_files) => self.found_files = found_files,
+            Message::SaveWarning(warning) => {
+                if !self.save_warnings_shown.contains(&warning.title) {
+                    self.save_warnings_shown.push(warning.title.clone());
+                    self.add_error(warning);
+                }
+            }
             Message::OpenErrorsModal => self.show_errors_modal = true,
             Message::CloseErrorsModal => self.show_errors_modal = false,
             Message::ClearErrors => {
@@ -202,7 +345,12 @@ impl Komorice {
                 self.show_errors_modal = false;
             }
             Message::Home(message) => {
-                let (action, task) = self.home.update(message, &mut self.configuration);
+                let (action, task) = self.home.update(
+                    message,
+                    &mut self.configuration,
+                    self.settings.backups_to_keep,
+                    self.settings.active_profile.as_deref(),
+                );
                 let action_task = match action {
                     home::Action::None => Task::none(),
                     home::Action::ContinueEdit => {
@@ -236,6 +384,9 @@ impl Komorice {
                                     self.loaded_config = Arc::new(self.config.clone());
                                     self.monitors = monitors::Monitors::new(&self.config);
                                     self.is_dirty = false;
+                                    self.schema_violations.clear();
+                                    self.reset_history();
+                                    self.refresh_problems();
                                     Task::none()
                                 }
                             },
@@ -255,9 +406,61 @@ impl Komorice {
                         self.show_errors_modal = true;
                         Task::none()
                     }
+                    home::Action::RestoredBackup(config_type) => {
+                        match (config_type, self.configuration.path_for(config_type)) {
+                            (ConfigType::Komorebi, Ok(path)) => config::load_task(path),
+                            (ConfigType::Whkd, Ok(path)) => {
+                                whkd::load_task(path).map(Message::Whkd)
+                            }
+                            (_, Err(error)) => Task::done(Message::AppError(error.into())),
+                        }
+                    }
+                    home::Action::ActivatedProfile(name) => {
+                        self.settings.active_profile = Some(name);
+                        Task::batch([
+                            settings::save_task(self.settings.clone()).map(Message::Settings),
+                            self.reload_active_configs(),
+                        ])
+                    }
+                    home::Action::ChangedActiveProfile(active_profile) => {
+                        self.settings.active_profile = active_profile;
+                        settings::save_task(self.settings.clone()).map(Message::Settings)
+                    }
+                    home::Action::ExportBundle => {
+                        home::export_bundle(self.config.clone(), self.whkd.whkdrc.clone())
+                            .map(Message::Home)
+                    }
+                    home::Action::ImportedBundle => self.reload_active_configs(),
+                    home::Action::Error(apperror) => {
+                        self.add_error(apperror);
+                        Task::none()
+                    }
                 };
                 return Task::batch([task.map(Message::Home), action_task]);
             }
+            Message::Import(message) => {
+                let (action, task) = self.import.update(message, &self.config);
+                match action {
+                    import::Action::None => {}
+                    import::Action::Import(config) => {
+                        let update_display_info = config.display_index_preferences
+                            != self.config.display_index_preferences;
+                        let update_monitors = config.monitors != self.config.monitors;
+                        self.config = config;
+                        if update_display_info {
+                            self.display_info = monitors::get_display_information(
+                                &self.config.display_index_preferences,
+                            );
+                        }
+                        if update_monitors {
+                            self.monitors = monitors::Monitors::new(&self.config);
+                        }
+                        self.check_changes();
+                    }
+                    import::Action::Error(apperror) => self.add_error(apperror),
+                }
+                return task.map(Message::Import);
+            }
             Message::General(message) => {
                 let (action, task) = self.general.update(message, &mut self.config);
                 let action_task = match action {
@@ -282,6 +485,13 @@ impl Komorice {
                         self.add_error(apperror);
                         Task::none()
                     }
+                    live_debug::Action::AddRule(location, rule) => {
+                        if let Some(rules) = lint::rule_list_mut(&mut self.config, &location) {
+                            rules.get_or_insert_with(Vec::new).push(rule);
+                            self.check_changes();
+                        }
+                        Task::none()
+                    }
                 };
                 return Task::batch([task.map(Message::LiveDebug), action_task]);
             }
@@ -325,6 +535,11 @@ impl Komorice {
                 let (action, task) = self.settings.update(message);
                 let action_task = match action {
                     settings::Action::None => Task::none(),
+                    settings::Action::ChangedConfigHomes => {
+                        config::set_home_override(self.settings.komorebi_config_home.clone());
+                        whkd::set_home_override(self.settings.whkd_config_home.clone());
+                        self.check_config_homes()
+                    }
                     settings::Action::Error(apperror) => {
                         self.add_error(apperror);
                         Task::none()
@@ -338,7 +553,13 @@ impl Komorice {
                     whkd::Action::None => Task::none(),
                     whkd::Action::SavedWhkdrc => {
                         self.configuration.saved_new_whkd = true;
-                        Task::none()
+                        if self.settings.autosave
+                            && matches!(self.configuration.whkd_state, ConfigState::Active)
+                        {
+                            self.whkd.restart_whkd().map(Message::Whkd)
+                        } else {
+                            Task::none()
+                        }
                     }
                     whkd::Action::LoadedWhkdrc => {
                         self.configuration.has_loaded_whkd = true;
@@ -389,10 +610,24 @@ impl Komorice {
                 let (action, task) = self.rules.update(message, &mut self.config);
                 let action_task = match action {
                     rules::Action::None => Task::none(),
+                    rules::Action::GoToRule(location) => self.go_to_location(location),
                 };
                 self.check_changes();
                 return Task::batch([task.map(Message::Rules), action_task]);
             }
+            Message::Applications(message) => {
+                let (action, task) = self
+                    .applications
+                    .update(message, self.settings.backups_to_keep);
+                let action_task = match action {
+                    applications::Action::None => Task::none(),
+                    applications::Action::Error(apperror) => {
+                        self.add_error(apperror);
+                        Task::none()
+                    }
+                };
+                return Task::batch([task.map(Message::Applications), action_task]);
+            }
             Message::Sidebar(message) => {
                 let (action, task) = self
                     .sidebar
@@ -409,12 +644,19 @@ impl Komorice {
                         }
                         self.main_screen = screen;
                         self.screen_to_start();
-                        Task::none()
+                        if matches!(self.main_screen, Screen::Applications) {
+                            self.applications
+                                .open(&self.config)
+                                .map(Message::Applications)
+                        } else {
+                            Task::none()
+                        }
                     }
                 };
                 return Task::batch([task.map(Message::Sidebar), action_task]);
             }
-            Message::LoadedConfig(config) => {
+            Message::LoadedConfig(config, violations) => {
+                self.schema_violations = violations;
                 if let Some(config) = Arc::into_inner(config) {
                     // println!("Config Loaded: {config:#?}");
                     let config = config::merge_default(config);
@@ -428,7 +670,30 @@ impl Komorice {
                             .selected_screen(&self.configuration.config_type);
                     }
                     self.loaded_config = Arc::new(config);
-                    //TODO: show message on app to load external changes
+                    self.conflicts.clear();
+                    self.reset_history();
+                    self.refresh_problems();
+                }
+            }
+            Message::ConfigChangedOnDisk(config, violations) => {
+                if !self.is_dirty {
+                    return self.update(Message::LoadedConfig(config, violations));
+                }
+                self.schema_violations = violations;
+                let disk = config::merge_default(Arc::unwrap_or_clone(config));
+                // The current config had its monitors filled when it was loaded, so the ones on
+                // disk need to be filled the same way to merge them.
+                let mut filled = disk.clone();
+                config::fill_monitors(&mut filled, &self.display_info);
+                match config::merge::merge(&self.loaded_config, &self.config, &filled) {
+                    Ok(merged) => {
+                        self.loaded_config = Arc::new(disk);
+                        self.record_history();
+                        self.replace_config(merged.config);
+                        self.check_changes();
+                        self.conflicts = merged.conflicts;
+                    }
+                    Err(apperror) => self.add_error(apperror),
                 }
             }
             Message::FailedToLoadConfig(apperror) => {
@@ -444,51 +709,72 @@ impl Komorice {
             Message::TrySave => {
                 if self.settings.show_save_warning {
                     self.show_save_modal = true;
+                    self.refresh_pending_changes();
                 } else {
                     match self.configuration.config_type {
-                        ConfigType::Komorebi => {
-                            self.configuration.saved_new_komorebi = true;
-                            return config::save_task(
-                                self.config.clone(),
-                                self.configuration.path(),
-                            );
-                        }
-                        ConfigType::Whkd => {
-                            self.configuration.saved_new_whkd = true;
-                            return whkd::save_task(
-                                self.whkd.whkdrc.clone(),
-                                self.configuration.path(),
-                            )
-                            .map(Message::Whkd);
-                        }
+                        ConfigType::Komorebi => self.configuration.saved_new_komorebi = true,
+                        ConfigType::Whkd => self.configuration.saved_new_whkd = true,
                     }
+                    return self.save(
+                        self.settings.backups_to_keep,
+                        self.settings.save_version,
+                        false,
+                    );
                 }
             }
             Message::ToggleSaveModal => {
                 self.show_save_modal = !self.show_save_modal;
+                if self.show_save_modal {
+                    self.refresh_pending_changes();
+                }
             }
             Message::Save => {
                 self.show_save_modal = false;
-                match self.configuration.config_type {
-                    ConfigType::Komorebi => {
-                        return config::save_task(self.config.clone(), self.configuration.path());
-                    }
-                    ConfigType::Whkd => {
-                        return whkd::save_task(
-                            self.whkd.whkdrc.clone(),
-                            self.configuration.path(),
-                        )
-                        .map(Message::Whkd);
-                    }
-                }
+                return self.save(
+                    self.settings.backups_to_keep,
+                    self.settings.save_version,
+                    false,
+                );
             }
-            Message::Saved => {
+            Message::Saved(saved) => {
                 if let Some(sender) = &self.config_watcher_tx {
                     let _ = sender.try_send(config::Input::IgnoreNextEvent);
                 }
-                self.loaded_config = Arc::new(self.config.clone());
-                self.is_dirty = false;
+                // The config might have changed while it was being saved
+                self.loaded_config = saved;
+                self.is_dirty = self.config != *self.loaded_config;
                 self.configuration.saved_new_komorebi = true;
+                if self.settings.autosave
+                    && matches!(self.configuration.komorebi_state, ConfigState::Active)
+                    && let Ok(path) = self.configuration.path()
+                {
+                    return config::reload_task(path);
+                }
+            }
+            Message::ConfirmDowngrade(version, changes) => {
+                self.pending_downgrade = Some((version, changes));
+            }
+            Message::SaveDowngraded => {
+                if let Some((version, _)) = self.pending_downgrade.take() {
+                    self.downgrade_answer = Some((self.settings.save_version, true));
+                    return self.save(
+                        self.settings.backups_to_keep,
+                        config::migration::TargetVersion::Version(version),
+                        true,
+                    );
+                }
+            }
+            Message::CancelDowngrade => {
+                self.pending_downgrade = None;
+                self.downgrade_answer = Some((self.settings.save_version, false));
+            }
+            Message::AutosaveTick => {
+                let snapshot = (self.config.clone(), self.whkd.whkdrc.clone());
+                if self.autosave_snapshot.as_ref() == Some(&snapshot) {
+                    self.autosave_snapshot = None;
+                    return self.autosave();
+                }
+                self.autosave_snapshot = Some(snapshot);
             }
             Message::DiscardChanges => match self.configuration.config_type {
                 ConfigType::Komorebi => {
@@ -496,14 +782,167 @@ impl Komorice {
                         != self.loaded_config.display_index_preferences;
                     self.config = (*self.loaded_config).clone();
                     self.is_dirty = false;
+                    self.conflicts.clear();
+                    self.applications.discard_changes();
                     if update_display_info {
                         self.display_info = monitors::get_display_information(
                             &self.config.display_index_preferences,
                         );
                     }
+                    self.record_history();
                 }
                 ConfigType::Whkd => self.whkd.discard_changes(),
             },
+            Message::RevertChange(section_idx, change_idx) => {
+                let Some(section) = self.pending_changes.get(section_idx) else {
+                    return Task::none();
+                };
+                let Some(change) = section.changes.get(change_idx) else {
+                    return Task::none();
+                };
+                match config::diff::revert(&self.config, change) {
+                    Ok(config) => {
+                        let update_display_info = config.display_index_preferences
+                            != self.config.display_index_preferences;
+                        let update_monitors = matches!(
+                            section.section,
+                            config::diff::Section::Monitor(_)
+                                | config::diff::Section::Workspace(..)
+                        );
+                        self.config = config;
+                        if update_display_info {
+                            self.display_info = monitors::get_display_information(
+                                &self.config.display_index_preferences,
+                            );
+                        }
+                        if update_monitors {
+                            self.monitors = monitors::Monitors::new(&self.config);
+                        }
+                        self.check_changes();
+                        self.refresh_pending_changes();
+                    }
+                    Err(apperror) => self.add_error(apperror),
+                }
+            }
+            Message::CheckConfigHomes => return self.check_config_homes(),
+            Message::CreateConfigHome(config_type) => {
+                let path = self
+                    .home_problems
+                    .iter()
+                    .find(|(ct, _)| *ct == config_type)
+                    .and_then(|(_, error)| error.path())
+                    .map(PathBuf::from);
+                if let Some(path) = path {
+                    return Task::perform(smol::fs::create_dir_all(path.clone()), move |res| {
+                        match res {
+                            Ok(_) => Message::CheckConfigHomes,
+                            Err(error) => Message::AppError(AppError {
+                                title: format!("Failed to create '{}'", path.display()),
+                                description: Some(error.to_string()),
+                                kind: AppErrorKind::Error,
+                            }),
+                        }
+                    });
+                }
+            }
+            Message::PickConfigHome(config_type) => {
+                return Task::future(async { rfd::FileDialog::new().pick_folder() })
+                    .map(move |path| Message::PickedConfigHome(config_type, path));
+            }
+            Message::PickedConfigHome(config_type, path) => {
+                if let Some(path) = path {
+                    let message = match config_type {
+                        ConfigType::Komorebi => settings::Message::ChangedKomorebiConfigHome,
+                        ConfigType::Whkd => settings::Message::ChangedWhkdConfigHome,
+                    };
+                    return self.update(Message::Settings(message(Some(path))));
+                }
+            }
+            Message::DismissHomeProblems => self.home_problems.clear(),
+            Message::ResolveConflict(idx, side) => match self.configuration.config_type {
+                ConfigType::Komorebi => {
+                    if idx < self.conflicts.len() {
+                        let conflict = self.conflicts.remove(idx);
+                        match config::merge::resolve(&self.config, &[conflict], side) {
+                            Ok(config) => {
+                                self.replace_config(config);
+                                self.check_changes();
+                            }
+                            Err(apperror) => self.add_error(apperror),
+                        }
+                    }
+                }
+                ConfigType::Whkd => self.whkd.resolve_conflict(idx, side),
+            },
+            Message::ResolveAllConflicts(side) => match self.configuration.config_type {
+                ConfigType::Komorebi => {
+                    let conflicts = std::mem::take(&mut self.conflicts);
+                    match config::merge::resolve(&self.config, &conflicts, side) {
+                        Ok(config) => {
+                            self.replace_config(config);
+                            self.check_changes();
+                        }
+                        Err(apperror) => self.add_error(apperror),
+                    }
+                }
+                ConfigType::Whkd => self.whkd.resolve_all_conflicts(side),
+            },
+            Message::Undo => match self.configuration.config_type {
+                ConfigType::Komorebi => {
+                    if let Some(config) = self.history.undo(self.config.clone()) {
+                        self.apply_history(config);
+                    }
+                }
+                ConfigType::Whkd => self.whkd.undo(),
+            },
+            Message::Redo => match self.configuration.config_type {
+                ConfigType::Komorebi => {
+                    if let Some(config) = self.history.redo(self.config.clone()) {
+                        self.apply_history(config);
+                    }
+                }
+                ConfigType::Whkd => self.whkd.redo(),
+            },
+            Message::JumpHistory(position) => match self.configuration.config_type {
+                ConfigType::Komorebi => {
+                    if let Some(config) = self.history.jump(position, self.config.clone()) {
+                        self.apply_history(config);
+                    }
+                }
+                ConfigType::Whkd => self.whkd.jump_history(position),
+            },
+            Message::ToggleHistoryModal => {
+                self.show_history_modal = !self.show_history_modal;
+            }
+            Message::ToggleProblemsModal => {
+                self.show_problems_modal = !self.show_problems_modal;
+            }
+            Message::GoToProblem(location) => {
+                self.show_problems_modal = false;
+                return self.go_to_location(location);
+            }
+            Message::OpenPalette => {
+                let (palette, task) = palette::Palette::new(self.search_entries());
+                self.palette = Some(palette);
+                return task.map(Message::Palette);
+            }
+            Message::Palette(message) => {
+                if let Some(palette) = &mut self.palette {
+                    let (action, task) = palette.update(message);
+                    let action_task = match action {
+                        palette::Action::None => Task::none(),
+                        palette::Action::GoTo(target) => {
+                            self.palette = None;
+                            self.go_to_target(target)
+                        }
+                        palette::Action::Close => {
+                            self.palette = None;
+                            Task::none()
+                        }
+                    };
+                    return Task::batch([task.map(Message::Palette), action_task]);
+                }
+            }
         }
         Task::none()
     }
@@ -512,12 +951,15 @@ impl Komorice {
         let main_screen: View<Message> = match self.main_screen {
             Screen::Home => self
                 .home
-                .view(&self.configuration, !self.errors.is_empty())
-                .map(Message::Home)
-                .into(),
+                .view(
+                    &self.configuration,
+                    !self.errors.is_empty(),
+                    self.settings.active_profile.as_deref(),
+                )
+                .map(Message::Home),
             Screen::General => self
                 .general
-                .view(&self.config, self.settings.show_advanced)
+                .view(&self.config, self.settings.show_advanced, &self.found_files)
                 .map(Message::General),
             Screen::Monitors => {
                 if let Some(monitors_config) = &self.config.monitors {
@@ -526,6 +968,8 @@ impl Komorice {
                             monitors_config,
                             &self.display_info,
                             &self.config.display_index_preferences,
+                            &self.problems,
+                            &self.found_files,
                         )
                         .map(Message::Monitors)
                 } else {
@@ -555,10 +999,15 @@ impl Komorice {
                 .into(),
             Screen::Rules => self
                 .rules
-                .view(&self.config, self.settings.show_advanced)
+                .view(&self.config, &self.problems, self.settings.show_advanced)
                 .map(Message::Rules)
                 .into(),
-            Screen::LiveDebug => self.live_debug.view().map(Message::LiveDebug).into(),
+            Screen::Applications => self.applications.view().map(Message::Applications),
+            Screen::LiveDebug => self
+                .live_debug
+                .view(&self.config)
+                .map(Message::LiveDebug)
+                .into(),
             Screen::Settings => self.settings.view().map(Message::Settings).into(),
             Screen::Whkd | Screen::WhkdBindings | Screen::WhkdAppBindings => {
                 self.whkd.view(&self.settings.theme).map(Message::Whkd)
@@ -606,8 +1055,55 @@ impl Komorice {
 
         let modal_content = self.show_save_modal.then(|| self.save_warning());
         let main_modal = widget::modal(main_content, modal_content, Message::ToggleSaveModal);
+        let history_modal_content = self.show_history_modal.then(|| self.history_modal());
+        let history_modal = widget::modal(
+            main_modal,
+            history_modal_content,
+            Message::ToggleHistoryModal,
+        );
+        let problems_modal_content = self.show_problems_modal.then(|| self.problems_modal());
+        let problems_modal = widget::modal(
+            history_modal,
+            problems_modal_content,
+            Message::ToggleProblemsModal,
+        );
+        let conflicts_modal = widget::modal(
+            problems_modal,
+            self.has_conflicts().then(|| self.conflicts_modal()),
+            Message::ResolveAllConflicts(config::merge::Side::Disk),
+        );
+        let downgrade_modal = widget::modal(
+            conflicts_modal,
+            self.pending_downgrade
+                .as_ref()
+                .map(|(version, changes)| self.downgrade_modal(*version, changes)),
+            Message::CancelDowngrade,
+        );
+        let import_modal = widget::modal(
+            downgrade_modal,
+            self.import
+                .view(&self.config)
+                .map(|content| content.map(Message::Import)),
+            Message::Import(import::Message::Close),
+        );
+        let palette_modal = widget::modal(
+            import_modal,
+            self.palette
+                .as_ref()
+                .map(|palette| palette.view().map(Message::Palette)),
+            Message::Palette(palette::Message::Close),
+        );
+        let home_problems_modal = widget::modal(
+            palette_modal,
+            (!self.home_problems.is_empty()).then(|| self.home_problems_modal()),
+            Message::DismissHomeProblems,
+        );
         let errors_modal_content = self.show_errors_modal.then(|| self.errors_modal());
-        widget::modal(main_modal, errors_modal_content, Message::CloseErrorsModal)
+        widget::modal(
+            home_problems_modal,
+            errors_modal_content,
+            Message::CloseErrorsModal,
+        )
     }
 
     pub fn subscription(&self) -> Subscription<Message> {
@@ -623,6 +1119,7 @@ impl Komorice {
             Screen::Monitors => self.monitors.subscription().map(Message::Monitors),
             Screen::Transparency => self.transparency.subscription().map(Message::Transparency),
             Screen::Rules => self.rules.subscription().map(Message::Rules),
+            Screen::Applications => self.applications.subscription().map(Message::Applications),
             Screen::Whkd | Screen::WhkdBindings | Screen::WhkdAppBindings => self
                 .whkd
                 .subscription(&self.configuration)
@@ -632,17 +1129,48 @@ impl Komorice {
         let worker = if matches!(self.configuration.config_type, ConfigType::Komorebi)
             && (!matches!(self.configuration.komorebi_state, ConfigState::New(_))
                 || self.configuration.saved_new_komorebi)
+            && let Ok(path) = self.configuration.path()
         {
             // Only start the worker if has the config_type as `Komorebi` and in case the komorebi state is
             // `New` the worker should only run if it has already been saved once at least.
-            config::worker(self.configuration.path())
+            config::worker(path)
         } else {
             Subscription::none()
         };
 
+        let autosave = if self.settings.autosave
+            && self.is_dirty()
+            && !self.has_conflicts()
+            && !matches!(self.main_screen, Screen::Home)
+        {
+            iced::time::every(AUTOSAVE_INTERVAL).map(|_| Message::AutosaveTick)
+        } else {
+            Subscription::none()
+        };
+
+        let shortcut_keys = if !matches!(self.main_screen, Screen::Home)
+            && !(matches!(self.configuration.config_type, ConfigType::Whkd)
+                && self.whkd.is_binding_keys())
+            && self.palette.is_none()
+        {
+            Subscription::batch([history_keys(), palette_keys()])
+        } else {
+            Subscription::none()
+        };
+
+        let palette = self
+            .palette
+            .as_ref()
+            .map_or_else(Subscription::none, |palette| {
+                palette.subscription().map(Message::Palette)
+            });
+
         Subscription::batch([
-            komo_interop::connect().map(Message::LiveDebug),
+            Native::notifications().map(Message::LiveDebug),
             worker,
+            autosave,
+            shortcut_keys,
+            palette,
             settings::worker().map(Message::Settings),
             screen_subscription,
         ])
@@ -665,6 +1193,397 @@ impl Komorice {
 
     fn check_changes(&mut self) {
         self.is_dirty = self.config != *self.loaded_config;
+        self.record_history();
+        self.refresh_problems();
+    }
+
+    /// Saves the config that is being edited, without asking for confirmation. The komorebi
+    /// config isn't saved while it has errors, and saving it for an older version is only asked
+    /// once per session: after a cancel it isn't autosaved for that version anymore.
+    fn autosave(&mut self) -> Task<Message> {
+        if !self.is_dirty()
+            || self.has_conflicts()
+            || self.pending_downgrade.is_some()
+            || self.configuration.path().is_err()
+        {
+            return Task::none();
+        }
+        let config_type = self.configuration.config_type;
+        let mut applications = Task::none();
+        let mut downgrade_confirmed = false;
+        match config_type {
+            ConfigType::Komorebi => {
+                applications = self
+                    .applications
+                    .autosave(self.settings.backups_to_keep)
+                    .map(Message::Applications);
+                if !self.is_dirty
+                    || self
+                        .problems
+                        .iter()
+                        .any(|problem| problem.error.kind == AppErrorKind::Error)
+                {
+                    return applications;
+                }
+                if let Some((version, confirmed)) = self.downgrade_answer
+                    && version == self.settings.save_version
+                {
+                    if !confirmed {
+                        return applications;
+                    }
+                    downgrade_confirmed = true;
+                }
+                self.configuration.saved_new_komorebi = true;
+            }
+            ConfigType::Whkd => self.configuration.saved_new_whkd = true,
+        }
+        let backups_to_keep = if self.autosave_backups.contains(&config_type) {
+            0
+        } else {
+            self.autosave_backups.push(config_type);
+            self.settings.backups_to_keep
+        };
+        Task::batch([
+            self.save(
+                backups_to_keep,
+                self.settings.save_version,
+                downgrade_confirmed,
+            ),
+            applications,
+        ])
+    }
+
+    /// Saves the file being edited, see `config::save_task` for the `version` and
+    /// `downgrade_confirmed` of the komorebi config. Nothing is saved while the folder of the
+    /// active file can't be used.
+    fn save(
+        &mut self,
+        backups_to_keep: usize,
+        version: config::migration::TargetVersion,
+        downgrade_confirmed: bool,
+    ) -> Task<Message> {
+        let path = match self.configuration.path() {
+            Ok(path) => path,
+            Err(error) => {
+                self.add_error(error.into());
+                return Task::none();
+            }
+        };
+        match self.configuration.config_type {
+            ConfigType::Komorebi => config::save_task(
+                self.config.clone(),
+                path,
+                backups_to_keep,
+                version,
+                downgrade_confirmed,
+            ),
+            ConfigType::Whkd => {
+                whkd::save_task(self.whkd.whkdrc.clone(), path, backups_to_keep).map(Message::Whkd)
+            }
+        }
+    }
+
+    /// Checks the folders of the configs, keeping the problems found to show them, and loads the
+    /// active configs from the folders that can be used.
+    fn check_config_homes(&mut self) -> Task<Message> {
+        self.home_problems.clear();
+        let mut tasks = Vec::new();
+        match config::config_path() {
+            Ok(path) => {
+                if matches!(self.configuration.komorebi_state, ConfigState::Active) {
+                    tasks.push(config::load_task(path));
+                }
+            }
+            Err(error) => self.home_problems.push((ConfigType::Komorebi, error)),
+        }
+        match whkd::config_path() {
+            Ok(path) => {
+                if matches!(self.configuration.whkd_state, ConfigState::Active) {
+                    tasks.push(whkd::load_task(path).map(Message::Whkd));
+                }
+            }
+            Err(error) => self.home_problems.push((ConfigType::Whkd, error)),
+        }
+        Task::batch(tasks)
+    }
+
+    fn refresh_problems(&mut self) {
+        self.problems = lint::lint(&self.config);
+        self.problems
+            .extend(lint::schema_problems(&self.schema_violations));
+    }
+
+    /// Reloads the configs that are being edited from the active files, after they have been
+    /// replaced, and restarts whkd so it uses the new whkdrc.
+    fn reload_active_configs(&self) -> Task<Message> {
+        let mut tasks = vec![self.whkd.restart_whkd().map(Message::Whkd)];
+        if matches!(self.configuration.komorebi_state, ConfigState::Active)
+            && let Ok(path) = config::config_path()
+        {
+            tasks.push(config::load_task(path));
+        }
+        if matches!(self.configuration.whkd_state, ConfigState::Active)
+            && let Ok(path) = whkd::config_path()
+        {
+            tasks.push(whkd::load_task(path).map(Message::Whkd));
+        }
+        Task::batch(tasks)
+    }
+
+    /// Opens the `sub_screen` of the monitor with index `idx` on the monitors screen.
+    fn go_to_monitor(&mut self, idx: usize, sub_screen: monitor::SubScreen) -> Task<Message> {
+        self.monitors.monitor_to_config = Some(idx);
+        if let Some(monitor) = self.monitors.monitors.get_mut(&idx) {
+            monitor.set_subscreen(sub_screen).map(move |message| {
+                Message::Monitors(monitors::Message::MonitorConfigChanged(idx, message))
+            })
+        } else {
+            Task::none()
+        }
+    }
+
+    /// Opens the screen where the problems at `location` can be fixed.
+    fn go_to_location(&mut self, location: lint::Location) -> Task<Message> {
+        let screen = match &location {
+            lint::Location::General => Screen::General,
+            lint::Location::Monitors
+            | lint::Location::Monitor(_)
+            | lint::Location::Workspace(_, _)
+            | lint::Location::WorkspaceRules(_, _)
+            | lint::Location::InitialWorkspaceRules(_, _) => Screen::Monitors,
+            lint::Location::Rules(_) => Screen::Rules,
+            lint::Location::TransparencyIgnoreRules => Screen::Transparency,
+        };
+        self.sidebar.komorebi_selected = screen.clone();
+        self.main_screen = screen;
+        match location {
+            lint::Location::General => {}
+            lint::Location::Monitors => self.monitors.monitor_to_config = None,
+            lint::Location::Monitor(m) => {
+                return self.go_to_monitor(m, monitor::SubScreen::Monitor);
+            }
+            lint::Location::Workspace(m, w) => {
+                return self.go_to_monitor(m, monitor::SubScreen::Workspace(w));
+            }
+            lint::Location::WorkspaceRules(m, w) => {
+                return self.go_to_monitor(m, monitor::SubScreen::WorkspaceRules(w));
+            }
+            lint::Location::InitialWorkspaceRules(m, w) => {
+                return self.go_to_monitor(m, monitor::SubScreen::InitialWorkspaceRules(w));
+            }
+            lint::Location::Rules(screen) => {
+                self.rules.rule_screen = Some((rule::Rule::new(), screen));
+            }
+            lint::Location::TransparencyIgnoreRules => {
+                self.transparency.rule = rule::Rule::new();
+                self.transparency.screen = transparency::Screen::TransparencyIgnoreRules;
+            }
+        }
+        Task::none()
+    }
+
+    /// Opens `screen` the same way as selecting it on the sidebar.
+    fn open_screen(&mut self, screen: Screen) {
+        match self.configuration.config_type {
+            ConfigType::Komorebi => self.sidebar.komorebi_selected = screen.clone(),
+            ConfigType::Whkd => {
+                self.sidebar.whkd_selected = screen.clone();
+                self.whkd.screen = screen.clone();
+            }
+        }
+        self.main_screen = screen;
+        self.screen_to_start();
+    }
+
+    /// Opens the screen with the `target` of a palette entry and scrolls to it.
+    fn go_to_target(&mut self, target: palette::Target) -> Task<Message> {
+        match target {
+            palette::Target::Option {
+                screen,
+                monitor,
+                name,
+            } => {
+                self.open_screen(screen);
+                if let Some((m, sub_screen)) = monitor {
+                    self.go_to_monitor(m, sub_screen)
+                        .chain(opt_helpers::scroll_to_option(monitors::SCROLLABLE_ID, name))
+                } else {
+                    opt_helpers::scroll_to_option(opt_helpers::SECTION_ID, name)
+                }
+            }
+            palette::Target::Rule(location) => self.go_to_location(location),
+            palette::Target::Binding { screen, idx } => {
+                let id = if matches!(screen, Screen::WhkdAppBindings) {
+                    crate::screen::whkd::app_bindings::binding_id(idx)
+                } else {
+                    crate::screen::whkd::bindings::binding_id(idx)
+                };
+                self.open_screen(screen);
+                widget::scroll_to(
+                    iced::widget::Id::new(crate::screen::whkd::bindings::SCROLLABLE_ID),
+                    id,
+                )
+            }
+        }
+    }
+
+    /// All the options, bindings and rules that can be searched for on the palette. The options
+    /// are the ones declared by each screen.
+    fn search_entries(&self) -> Vec<palette::Entry> {
+        let show_advanced = self.settings.show_advanced;
+        let options = |options, screen: Screen| {
+            palette::option_entries(options, &screen.to_string(), screen, None, show_advanced)
+        };
+
+        let mut entries = Vec::new();
+        match self.configuration.config_type {
+            ConfigType::Komorebi => {
+                entries.extend(options(general::OPTIONS, Screen::General));
+                entries.extend(options(border::OPTIONS, Screen::Border));
+                entries.extend(options(stackbar::OPTIONS, Screen::Stackbar));
+                entries.extend(options(transparency::OPTIONS, Screen::Transparency));
+                entries.extend(options(animation::OPTIONS, Screen::Animations));
+                entries.extend(options(theme::OPTIONS, Screen::Theme));
+                entries.extend(options(rules::OPTIONS, Screen::Rules));
+                entries.extend(options(settings::OPTIONS, Screen::Settings));
+                entries.extend(self.monitor_entries());
+                entries.extend(palette::rule_entries(&self.config));
+            }
+            ConfigType::Whkd => {
+                entries.extend(options(settings::OPTIONS, Screen::Settings));
+                entries.extend(options(crate::screen::whkd::OPTIONS, Screen::Whkd));
+                entries.extend(palette::binding_entries(&self.whkd.whkdrc));
+            }
+        }
+        entries
+    }
+
+    /// The palette entries with the options of a monitor and its first workspace, for the monitor
+    /// being configured or the first one.
+    fn monitor_entries(&self) -> Vec<palette::Entry> {
+        let m = self.monitors.monitor_to_config.unwrap_or(0);
+        let Some(monitor_config) = self.config.monitors.as_ref().and_then(|ms| ms.get(m)) else {
+            return Vec::new();
+        };
+        let location = format!("{} > Monitor [{m}]", Screen::Monitors);
+
+        let mut entries = palette::option_entries(
+            monitor::OPTIONS,
+            &location,
+            Screen::Monitors,
+            Some((m, monitor::SubScreen::Monitor)),
+            self.settings.show_advanced,
+        );
+        if !monitor_config.workspaces.is_empty() {
+            entries.extend(palette::option_entries(
+                workspace::OPTIONS,
+                &format!("{location} > Workspace"),
+                Screen::Monitors,
+                Some((m, monitor::SubScreen::Workspace(0))),
+                self.settings.show_advanced,
+            ));
+        }
+        entries
+    }
+
+    /// Adds an entry to the history if the config changed since the last recorded state.
+    fn record_history(&mut self) {
+        if self.config != self.history_config {
+            let label = config::diff::describe(&self.history_config, &self.config);
+            let previous = std::mem::replace(&mut self.history_config, self.config.clone());
+            self.history.push(label, previous);
+        }
+    }
+
+    fn reset_history(&mut self) {
+        self.history.clear();
+        self.history_config = self.config.clone();
+    }
+
+    /// Sets `config` as the current config after moving on the history.
+    fn apply_history(&mut self, config: komorebi_client::StaticConfig) {
+        self.replace_config(config);
+        self.history_config = self.config.clone();
+        self.is_dirty = self.config != *self.loaded_config;
+        self.refresh_problems();
+    }
+
+    /// Replaces the whole current config with `config`, updating the display information and
+    /// the monitors screen when they depend on what changed.
+    fn replace_config(&mut self, config: komorebi_client::StaticConfig) {
+        let update_display_info =
+            config.display_index_preferences != self.config.display_index_preferences;
+        let update_monitors = config
+            .monitors
+            .as_ref()
+            .map(|ms| ms.iter().map(|m| m.workspaces.len()).collect::<Vec<_>>())
+            != self
+                .config
+                .monitors
+                .as_ref()
+                .map(|ms| ms.iter().map(|m| m.workspaces.len()).collect::<Vec<_>>());
+        self.config = config;
+        if update_display_info {
+            self.display_info =
+                monitors::get_display_information(&self.config.display_index_preferences);
+        }
+        if update_monitors {
+            self.monitors = monitors::Monitors::new(&self.config);
+        }
+    }
+
+    /// Recomputes the pending changes between the loaded config and the current config.
+    fn refresh_pending_changes(&mut self) {
+        self.pending_changes = if matches!(self.configuration.config_type, ConfigType::Komorebi) {
+            config::diff::diff(&self.loaded_config, &self.config)
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// The paths of the config that are edited on path inputs, along with the new paths being
+    /// typed, see `path_input::check_files`.
+    fn path_input_values(&self) -> BTreeSet<String> {
+        let asc_paths = match &self.config.app_specific_configuration_path {
+            Some(komorebi_client::AppSpecificConfigurationPath::Single(path)) => {
+                std::slice::from_ref(path)
+            }
+            Some(komorebi_client::AppSpecificConfigurationPath::Multiple(paths)) => {
+                paths.as_slice()
+            }
+            None => &[],
+        };
+        let bar_paths = self.config.bar_configurations.iter().flatten();
+        let wallpaper_paths = self
+            .config
+            .monitors
+            .iter()
+            .flatten()
+            .flat_map(|monitor| {
+                let workspaces = monitor.workspaces.iter();
+                monitor
+                    .wallpaper
+                    .iter()
+                    .chain(workspaces.flat_map(|workspace| &workspace.wallpaper))
+            })
+            .map(|wallpaper| &wallpaper.path);
+        asc_paths
+            .iter()
+            .chain(bar_paths)
+            .chain(wallpaper_paths)
+            .filter_map(|path| path.to_str())
+            .chain([
+                self.general.new_asc_path.as_str(),
+                self.general.new_bar_config_path.as_str(),
+            ])
+            .map(String::from)
+            .collect()
+    }
+
+    /// Whether the file being edited has something to save and it can be saved, which isn't the
+    /// case for the active files while their folder can't be used.
+    fn can_save(&self) -> bool {
+        self.is_unsaved() && self.configuration.path().is_ok()
     }
 
     fn is_unsaved(&self) -> bool {
@@ -682,7 +1601,7 @@ impl Komorice {
 
     fn is_dirty(&self) -> bool {
         match self.configuration.config_type {
-            ConfigType::Komorebi => self.is_dirty,
+            ConfigType::Komorebi => self.is_dirty || self.applications.is_dirty(),
             ConfigType::Whkd => self.whkd.is_dirty,
         }
     }
@@ -703,6 +1622,7 @@ impl Komorice {
                 Screen::Animations => self.animation = animation::Animation,
                 Screen::Theme => self.theme_screen = theme::Theme::default(),
                 Screen::Rules => self.rules = rules::Rules::default(),
+                Screen::Applications => self.applications.goto_start_screen(),
                 Screen::LiveDebug => self.live_debug.goto_start_screen(),
                 Screen::Settings => {
                     unreachable!("should never try to reset settings screen!")
@@ -715,7 +1635,7 @@ impl Komorice {
     }
 
     fn save_warning(&self) -> container::Container<'_, Message> {
-        let save = button("Save").on_press_maybe(self.is_unsaved().then_some(Message::Save));
+        let save = button("Save").on_press_maybe(self.can_save().then_some(Message::Save));
         let cancel = button("Cancel")
             .on_press(Message::ToggleSaveModal)
             .style(button::secondary);
@@ -740,7 +1660,17 @@ impl Komorice {
                 .font(*BOLD_FONT),
         ]
         .on_link_click(iced::never);
-        let content = column![title, description, row![stop_showing, buttons]].spacing(20);
+        let changes = matches!(self.configuration.config_type, ConfigType::Komorebi).then(|| {
+            column![
+                text("Pending Changes:").size(18).font(*BOLD_FONT),
+                config::diff::view(&self.pending_changes),
+            ]
+            .spacing(10)
+        });
+        let content = column![title, description]
+            .push(changes)
+            .push(row![stop_showing, buttons])
+            .spacing(20);
         container(
             container(content)
                 .padding(20)
@@ -802,6 +1732,255 @@ impl Komorice {
             .style(widget::modal::red)
     }
 
+    fn history_modal(&self) -> container::Container<'_, Message> {
+        let history = match self.configuration.config_type {
+            ConfigType::Komorebi => self.history.view(Message::JumpHistory),
+            ConfigType::Whkd => self.whkd.history.view(Message::JumpHistory),
+        };
+        let title = row![
+            text("History").size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::ToggleHistoryModal)
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = to_description_text(text(
+            "Click on any entry to go back or forward to that point. \
+            You can also use 'Ctrl+Z' to undo and 'Ctrl+Shift+Z' to redo.",
+        ));
+
+        container(column![title, description, history].spacing(10))
+            .padding(20)
+            .max_width(850.0)
+            .center(iced::Fill)
+            .height(iced::Shrink)
+            .style(widget::modal::default)
+    }
+
+    fn problems_modal(&self) -> container::Container<'_, Message> {
+        let title = row![
+            text("Problems").size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::ToggleProblemsModal)
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = to_description_text(text(
+            "These are settings that komorebi will not handle the way you might expect. \
+            Use 'Go to' to open the screen where each of them can be fixed.",
+        ));
+
+        container(column![title, description, lint::view(&self.problems)].spacing(10))
+            .padding(20)
+            .max_width(850.0)
+            .center(iced::Fill)
+            .height(iced::Shrink)
+            .style(widget::modal::default)
+    }
+
+    fn home_problems_modal(&self) -> container::Container<'_, Message> {
+        let title = row![
+            text("Config Folders").size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::DismissHomeProblems)
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = to_description_text(text(
+            "Some config folders can't be used. Create the missing folder or pick another one, \
+            which is kept on the settings. You can also continue with the default config.",
+        ));
+
+        let problems = column(self.home_problems.iter().map(|(config_type, error)| {
+            let config_type = *config_type;
+            let apperror = error.to_app_error();
+            let name = match config_type {
+                ConfigType::Komorebi => "Komorebi",
+                ConfigType::Whkd => "whkd",
+            };
+            let buttons = row![
+                error.path().map(|path| {
+                    button(text!("Create '{}'", path.display()))
+                        .on_press(Message::CreateConfigHome(config_type))
+                }),
+                button(text("Pick Folder…"))
+                    .on_press(Message::PickConfigHome(config_type))
+                    .style(button::secondary),
+            ]
+            .spacing(10);
+            column![
+                text(name).size(18.0),
+                text(apperror.title).style(text::danger),
+                apperror.description.map(|d| to_description_text(text(d))),
+                buttons,
+            ]
+            .spacing(10)
+            .into()
+        }))
+        .spacing(20);
+        let continue_button = row![
+            space::horizontal(),
+            button(text("Continue with Defaults"))
+                .on_press(Message::DismissHomeProblems)
+                .style(button::secondary),
+        ];
+
+        container(column![title, description, problems, continue_button].spacing(10))
+            .padding(20)
+            .max_width(850.0)
+            .center(iced::Fill)
+            .height(iced::Shrink)
+            .style(widget::modal::default)
+    }
+
+    fn downgrade_modal<'a>(
+        &self,
+        version: config::migration::Version,
+        changes: &'a [String],
+    ) -> container::Container<'a, Message> {
+        let title = row![
+            text!("Save for komorebi {version}").size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::CancelDowngrade)
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = to_description_text(text!(
+            "Komorebi {version} is older than {}, so saving 'komorebi.json' for it makes these \
+            changes on the config. The whole file is written again, so any comments on it are \
+            lost. You can change the version to save for on the settings.",
+            *KOMOREBI_VERSION,
+        ));
+        let changes = container(scrollable(changes.iter().fold(
+            column![].spacing(2).padding(padding::right(20)),
+            |col, change| col.push(text!("• {change}")),
+        )))
+        .max_height(300.0);
+        let buttons = row![
+            space::horizontal(),
+            button(text!("Save for {version}"))
+                .on_press(Message::SaveDowngraded)
+                .style(button::danger),
+            button("Cancel")
+                .on_press(Message::CancelDowngrade)
+                .style(button::secondary),
+        ]
+        .spacing(10);
+
+        container(column![title, description, changes, buttons].spacing(10))
+            .padding(20)
+            .max_width(850.0)
+            .center(iced::Fill)
+            .height(iced::Shrink)
+            .style(widget::modal::default)
+    }
+
+    fn has_conflicts(&self) -> bool {
+        match self.configuration.config_type {
+            ConfigType::Komorebi => !self.conflicts.is_empty(),
+            ConfigType::Whkd => !self.whkd.conflicts.is_empty(),
+        }
+    }
+
+    fn conflicts_modal(&self) -> container::Container<'_, Message> {
+        use config::merge::Side;
+
+        let title = row![
+            text("Conflicts").size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::ResolveAllConflicts(Side::Disk))
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = to_description_text(text(
+            "The file changed on disk while you had unsaved changes. All the changes that didn't \
+            conflict were merged, while these options were changed on both sides, so they are \
+            using the version on disk for now. You can use 'Undo' to go back to your changes \
+            from before the merge.",
+        ));
+
+        let conflicts: Vec<(String, String, String)> = match self.configuration.config_type {
+            ConfigType::Komorebi => self
+                .conflicts
+                .iter()
+                .map(|c| (c.label(), c.mine_text(), c.disk_text()))
+                .collect(),
+            ConfigType::Whkd => self
+                .whkd
+                .conflicts
+                .iter()
+                .map(|c| (c.label(), c.mine_text(), c.disk_text()))
+                .collect(),
+        };
+        let rows = column(
+            conflicts
+                .into_iter()
+                .enumerate()
+                .map(|(idx, (label, mine, disk))| {
+                    let versions = row![
+                        column![to_description_text(text("Mine")), text(mine)]
+                            .spacing(5)
+                            .width(Fill),
+                        column![to_description_text(text("On Disk")), text(disk)]
+                            .spacing(5)
+                            .width(Fill),
+                    ]
+                    .spacing(10);
+                    let buttons = row![
+                        button(text("Keep Mine"))
+                            .on_press(Message::ResolveConflict(idx, Side::Mine))
+                            .style(button::secondary),
+                        button(text("Keep Disk"))
+                            .on_press(Message::ResolveConflict(idx, Side::Disk))
+                            .style(button::secondary),
+                    ]
+                    .spacing(10);
+                    column![text(label).size(18.0), versions, buttons]
+                        .spacing(10)
+                        .into()
+                }),
+        )
+        .spacing(20);
+        let all_buttons = row![
+            space::horizontal(),
+            button(text("Keep All Mine")).on_press(Message::ResolveAllConflicts(Side::Mine)),
+            button(text("Keep All From Disk"))
+                .on_press(Message::ResolveAllConflicts(Side::Disk))
+                .style(button::secondary),
+        ]
+        .spacing(10);
+
+        container(
+            column![
+                title,
+                description,
+                scrollable(rows).height(iced::Shrink),
+                all_buttons
+            ]
+            .spacing(10),
+        )
+        .padding(20)
+        .max_width(850.0)
+        .center(iced::Fill)
+        .height(iced::Shrink)
+        .style(widget::modal::default)
+    }
+
     fn save_buttons(&self) -> row::Row<'_, Message> {
         let mut save_buttons = row![]
             .spacing(10)
@@ -813,12 +1992,66 @@ impl Komorice {
                 .on_press(Message::OpenErrorsModal)
                 .style(button::danger)
         }));
+        let (can_undo, can_redo) = match self.configuration.config_type {
+            ConfigType::Komorebi => (self.history.can_undo(), self.history.can_redo()),
+            ConfigType::Whkd => (self.whkd.history.can_undo(), self.whkd.history.can_redo()),
+        };
+        save_buttons = save_buttons.extend([
+            button(text("Undo"))
+                .on_press_maybe(can_undo.then_some(Message::Undo))
+                .style(button::secondary)
+                .into(),
+            button(text("History"))
+                .on_press(Message::ToggleHistoryModal)
+                .style(button::secondary)
+                .into(),
+            button(text("Redo"))
+                .on_press_maybe(can_redo.then_some(Message::Redo))
+                .style(button::secondary)
+                .into(),
+        ]);
+        save_buttons = save_buttons.push(
+            matches!(self.configuration.config_type, ConfigType::Komorebi).then(|| {
+                let has_errors = self
+                    .problems
+                    .iter()
+                    .any(|problem| problem.error.kind == AppErrorKind::Error);
+                button(text!("Problems ({})", self.problems.len()))
+                    .on_press(Message::ToggleProblemsModal)
+                    .style(move |t, s| {
+                        if has_errors {
+                            button::danger(t, s)
+                        } else {
+                            button::secondary(t, s)
+                        }
+                    })
+            }),
+        );
+        save_buttons = save_buttons.push(
+            matches!(self.configuration.config_type, ConfigType::Komorebi).then(|| {
+                button(text("Import from file…"))
+                    .on_press(Message::Import(import::Message::PickFile))
+                    .style(button::secondary)
+            }),
+        );
         save_buttons = save_buttons.extend([
             space::horizontal().into(),
-            to_description_text(text!("{}", self.configuration.path().display())).into(),
+            to_description_text(text(match self.configuration.path() {
+                Ok(path) => path.display().to_string(),
+                Err(error) => error.to_app_error().title,
+            }))
+            .into(),
             space::horizontal().into(),
+            button("Review Changes")
+                .on_press_maybe(
+                    (matches!(self.configuration.config_type, ConfigType::Komorebi)
+                        && self.is_dirty)
+                        .then_some(Message::ToggleSaveModal),
+                )
+                .style(button::secondary)
+                .into(),
             button("Save")
-                .on_press_maybe(self.is_unsaved().then_some(Message::TrySave))
+                .on_press_maybe(self.can_save().then_some(Message::TrySave))
                 .into(),
             button("Discard Changes")
                 .on_press_maybe(self.is_dirty().then_some(Message::DiscardChanges))
@@ -828,3 +2061,33 @@ impl Komorice {
         save_buttons
     }
 }
+
+/// Listens for the undo (`Ctrl+Z`) and redo (`Ctrl+Shift+Z`) shortcuts.
+fn history_keys() -> Subscription<Message> {
+    iced::event::listen_with(|event, _status, _id| match event {
+        iced::Event::Keyboard(keyboard::Event::KeyPressed {
+            key: keyboard::Key::Character(c),
+            modifiers,
+            ..
+        }) if modifiers.command() && c.eq_ignore_ascii_case("z") => {
+            if modifiers.shift() {
+                Some(Message::Redo)
+            } else {
+                Some(Message::Undo)
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Listens for the shortcut that opens the palette (`Ctrl+K`).
+fn palette_keys() -> Subscription<Message> {
+    iced::event::listen_with(|event, _status, _id| match event {
+        iced::Event::Keyboard(keyboard::Event::KeyPressed {
+            key: keyboard::Key::Character(c),
+            modifiers,
+            ..
+        }) if modifiers.command() && c.eq_ignore_ascii_case("k") => Some(Message::OpenPalette),
+        _ => None,
+    })
+}
@@ -0,0 +1,181 @@
+//! Checks rules against a window the same way komorebi does, so that they can be tested without
+//! having to reload komorebi and open the application.
+
+use crate::lint::{self, Location};
+
+use komorebi_client::{
+    ApplicationIdentifier, IdWithIdentifier, MatchingRule, MatchingStrategy, StaticConfig,
+};
+
+/// The properties of a window that rules can match.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Window {
+    pub exe: String,
+    pub title: String,
+    pub class: String,
+    pub path: String,
+}
+
+impl Window {
+    pub fn is_empty(&self) -> bool {
+        self.exe.is_empty()
+            && self.title.is_empty()
+            && self.class.is_empty()
+            && self.path.is_empty()
+    }
+
+    pub fn property(&self, kind: &ApplicationIdentifier) -> &str {
+        match kind {
+            ApplicationIdentifier::Exe => &self.exe,
+            ApplicationIdentifier::Title => &self.title,
+            ApplicationIdentifier::Class => &self.class,
+            ApplicationIdentifier::Path => &self.path,
+        }
+    }
+}
+
+/// Whether `rule` matches `window`. Composite rules only match when all of their identifiers do.
+pub fn matches(rule: &MatchingRule, window: &Window) -> bool {
+    match rule {
+        MatchingRule::Simple(id) => matches_id(id, window),
+        MatchingRule::Composite(ids) => {
+            !ids.is_empty() && ids.iter().all(|id| matches_id(id, window))
+        }
+    }
+}
+
+/// Whether the identifier `id` matches `window`. An empty property is taken as unknown, so it
+/// never matches, not even with the `DoesNot*` strategies.
+pub fn matches_id(id: &IdWithIdentifier, window: &Window) -> bool {
+    let property = window.property(&id.kind);
+    if property.is_empty() {
+        return false;
+    }
+    let value = id.id.as_str();
+    match id.matching_strategy {
+        None | Some(MatchingStrategy::Legacy) => match id.kind {
+            ApplicationIdentifier::Title | ApplicationIdentifier::Class => {
+                property.starts_with(value) || property.ends_with(value)
+            }
+            ApplicationIdentifier::Exe | ApplicationIdentifier::Path => property == value,
+        },
+        Some(MatchingStrategy::Equals) => property == value,
+        Some(MatchingStrategy::StartsWith) => property.starts_with(value),
+        Some(MatchingStrategy::EndsWith) => property.ends_with(value),
+        Some(MatchingStrategy::Contains) => property.contains(value),
+        Some(MatchingStrategy::Regex) => {
+            regex::Regex::new(value).is_ok_and(|regex| regex.is_match(property))
+        }
+        Some(MatchingStrategy::DoesNotEndWith) => !property.ends_with(value),
+        Some(MatchingStrategy::DoesNotStartWith) => !property.starts_with(value),
+        Some(MatchingStrategy::DoesNotEqual) => property != value,
+        Some(MatchingStrategy::DoesNotContain) => !property.contains(value),
+    }
+}
+
+/// All the rules on `config` that match `window`, each with the place where it is.
+pub fn matching_rules<'a>(
+    config: &'a StaticConfig,
+    window: &Window,
+) -> Vec<(Location, &'a MatchingRule)> {
+    lint::rule_lists(config)
+        .into_iter()
+        .flat_map(|(rules, location)| {
+            rules
+                .iter()
+                .flatten()
+                .filter(|rule| matches(rule, window))
+                .map(move |rule| (location.clone(), rule))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window() -> Window {
+        Window {
+            exe: "firefox.exe".into(),
+            title: "Picture-in-Picture".into(),
+            class: "MozillaDialogClass".into(),
+            path: r"C:\Program Files\Mozilla Firefox\firefox.exe".into(),
+        }
+    }
+
+    fn id(
+        kind: ApplicationIdentifier,
+        id: &str,
+        strategy: Option<MatchingStrategy>,
+    ) -> MatchingRule {
+        MatchingRule::Simple(IdWithIdentifier {
+            kind,
+            id: id.into(),
+            matching_strategy: strategy,
+        })
+    }
+
+    #[test]
+    fn follows_each_matching_strategy() {
+        use ApplicationIdentifier::*;
+        use MatchingStrategy::*;
+
+        let window = window();
+        let cases = [
+            (id(Exe, "firefox.exe", None), true),
+            (id(Exe, "firefox", Some(Legacy)), false),
+            (id(Title, "Picture", Some(Legacy)), true),
+            (id(Title, "Picture", Some(Equals)), false),
+            (id(Class, "Mozilla", Some(StartsWith)), true),
+            (id(Class, "Class", Some(EndsWith)), true),
+            (id(Title, "-in-", Some(Contains)), true),
+            (id(Path, r"Mozilla Firefox\\.*\.exe$", Some(Regex)), true),
+            (id(Title, "(", Some(Regex)), false),
+            (id(Exe, "chrome.exe", Some(DoesNotEqual)), true),
+            (id(Exe, "fire", Some(DoesNotStartWith)), false),
+            (id(Exe, ".exe", Some(DoesNotEndWith)), false),
+            (id(Title, "Firefox", Some(DoesNotContain)), true),
+        ];
+        for (rule, expected) in cases {
+            assert_eq!(matches(&rule, &window), expected, "{rule:?}");
+        }
+    }
+
+    #[test]
+    fn composite_rules_need_all_identifiers_to_match() {
+        let window = window();
+        let exe = IdWithIdentifier {
+            kind: ApplicationIdentifier::Exe,
+            id: "firefox.exe".into(),
+            matching_strategy: Some(MatchingStrategy::Equals),
+        };
+        let mut title = IdWithIdentifier {
+            kind: ApplicationIdentifier::Title,
+            id: "Picture-in-Picture".into(),
+            matching_strategy: Some(MatchingStrategy::Equals),
+        };
+        assert!(matches(
+            &MatchingRule::Composite(vec![exe.clone(), title.clone()]),
+            &window
+        ));
+        title.id = "Library".into();
+        assert!(!matches(
+            &MatchingRule::Composite(vec![exe, title]),
+            &window
+        ));
+    }
+
+    #[test]
+    fn unknown_properties_never_match() {
+        let window = Window {
+            exe: "firefox.exe".into(),
+            ..Default::default()
+        };
+        let rule = id(
+            ApplicationIdentifier::Title,
+            "Library",
+            Some(MatchingStrategy::DoesNotEqual),
+        );
+        assert!(!matches(&rule, &window));
+    }
+}
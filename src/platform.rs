@@ -0,0 +1,121 @@
+//! Everything that works differently depending on the operating system.
+//!
+//! Each concern has its own trait, implemented by the `Native` type of the backend for the
+//! current target: `windows` on Windows and `portable` everywhere else. The portable backend lets
+//! Komorice edit configs and run its tests on other systems, while the things that only make
+//! sense on Windows, like finding the connected displays, do nothing there.
+
+#[cfg(not(windows))]
+mod portable;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(not(windows))]
+pub use portable::Native;
+#[cfg(windows)]
+pub use windows::Native;
+
+use crate::matching;
+use crate::screen::live_debug;
+use crate::screen::monitors::DisplayInfo;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use iced::Subscription;
+use iced::keyboard::key::{Key, Physical};
+use komorebi_client::SocketMessage;
+use notify_debouncer_mini::{DebounceEventHandler, Debouncer, notify};
+use smol::process::Command;
+
+/// The file watcher used by the config workers.
+pub type Watcher = <Native as FileWatching>::Watcher;
+
+/// Starting and stopping other programs, like whkd.
+pub trait ProcessControl {
+    /// Creates a command to run `program` without opening a console window for it.
+    fn command(program: &str) -> Command;
+
+    /// Whether some process of the `program` executable is running.
+    fn is_running(program: &str) -> impl Future<Output = bool> + Send;
+
+    /// Kills all the processes of the `program` executable. Returns whether it succeeded.
+    fn kill(program: &str) -> impl Future<Output = bool> + Send;
+
+    /// Starts `program` on the background, without it being tied to Komorice. Returns whether it
+    /// succeeded.
+    fn start_detached(program: &str) -> impl Future<Output = bool> + Send;
+
+    /// The name of each running process mapped to its description, if they can be found.
+    fn process_names() -> impl Future<Output = Option<HashMap<String, String>>> + Send;
+}
+
+/// Finding the connected displays.
+pub trait Displays {
+    /// The displays connected right now, in the order the system reports them.
+    fn connected_displays() -> Vec<DisplayInfo>;
+}
+
+/// Access to the text on the clipboard.
+pub trait Clipboard {
+    fn get_text() -> Option<String>;
+
+    /// Replaces the text on the clipboard. Returns whether it succeeded.
+    fn set_text(text: &str) -> bool;
+}
+
+/// Mapping the keys pressed on Komorice to the key names used by whkd.
+pub trait KeyMapping {
+    /// The whkd name of the logical `key`, like `return` or `a`.
+    fn key_name(key: &Key) -> Option<String>;
+
+    /// The whkd name of the physical `key`. It is used for the numpad keys, whose logical key
+    /// depends on the state of num lock.
+    fn physical_key_name(key: &Physical) -> Option<String>;
+}
+
+/// Reading the properties of the windows reported by komorebi, which only come with their handle.
+pub trait WindowProperties {
+    /// The exe, title, class and path of the window with the `hwnd` handle, or `None` if it
+    /// doesn't exist anymore.
+    fn window_properties(hwnd: isize) -> Option<matching::Window>;
+}
+
+/// Talking to a running komorebi through its socket.
+pub trait KomorebiIpc {
+    /// Sends `message` to komorebi.
+    fn send_message(message: &SocketMessage) -> std::io::Result<()>;
+
+    /// The notifications komorebi sends every time its state changes.
+    fn notifications() -> Subscription<live_debug::Message>;
+}
+
+/// Watching the config files for changes made outside of Komorice.
+pub trait FileWatching {
+    type Watcher: notify::Watcher;
+
+    /// Creates a debouncer that calls `handler` with the events of its watcher at most once
+    /// every `timeout`.
+    fn debouncer<F: DebounceEventHandler>(
+        timeout: Duration,
+        handler: F,
+    ) -> notify::Result<Debouncer<Self::Watcher>> {
+        notify_debouncer_mini::new_debouncer_opt(
+            notify_debouncer_mini::Config::default().with_timeout(timeout),
+            handler,
+        )
+    }
+}
+
+/// Creates the debouncer of the current platform, see `FileWatching::debouncer`.
+pub fn new_debouncer<F: DebounceEventHandler>(
+    timeout: Duration,
+    handler: F,
+) -> notify::Result<Debouncer<Watcher>> {
+    Native::debouncer(timeout, handler)
+}
+
+/// The file name of the executable of `program`, like `whkd.exe` on Windows.
+pub fn executable(program: &str) -> String {
+    format!("{program}{}", std::env::consts::EXE_SUFFIX)
+}
@@ -0,0 +1,249 @@
+//! The backend for every system other than Windows. Komorebi and whkd only run on Windows, so
+//! this backend is mostly meant to edit configs and to run the tests of Komorice elsewhere.
+
+use super::{
+    Clipboard, Displays, FileWatching, KeyMapping, KomorebiIpc, ProcessControl, WindowProperties,
+};
+
+use crate::matching;
+use crate::screen::live_debug;
+use crate::screen::monitors::DisplayInfo;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use iced::Subscription;
+use iced::keyboard::key::{Code, Key, Named, Physical};
+use komorebi_client::SocketMessage;
+use notify_debouncer_mini::notify::RecommendedWatcher;
+use smol::process::{Command, Stdio};
+
+/// The text copied on Komorice. There is no system clipboard to use without extra dependencies,
+/// so copying and pasting only works within Komorice.
+static CLIPBOARD: Mutex<Option<String>> = Mutex::new(None);
+
+pub struct Native;
+
+impl ProcessControl for Native {
+    fn command(program: &str) -> Command {
+        Command::new(program)
+    }
+
+    async fn is_running(program: &str) -> bool {
+        Native::command("pgrep")
+            .args(["-x", program])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .is_ok_and(|status| status.success())
+    }
+
+    async fn kill(program: &str) -> bool {
+        Native::command("pkill")
+            .args(["-x", program])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .is_ok_and(|status| status.success())
+    }
+
+    async fn start_detached(program: &str) -> bool {
+        Native::command(program)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .is_ok()
+    }
+
+    async fn process_names() -> Option<HashMap<String, String>> {
+        let output = Native::command("ps")
+            .args(["-e", "-o", "comm="])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .output()
+            .await
+            .ok()
+            .filter(|output| output.status.success())?;
+        // There are no descriptions, so each process is described by its own name.
+        let process_names = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| (name.to_string(), name.to_string()))
+            .collect();
+        Some(process_names)
+    }
+}
+
+impl Displays for Native {
+    fn connected_displays() -> Vec<DisplayInfo> {
+        // Komorebi identifies the displays by their Windows device ids, which don't exist here,
+        // so only the monitors already on the config are used.
+        Vec::new()
+    }
+}
+
+impl Clipboard for Native {
+    fn get_text() -> Option<String> {
+        CLIPBOARD.lock().ok()?.clone()
+    }
+
+    fn set_text(text: &str) -> bool {
+        CLIPBOARD
+            .lock()
+            .map(|mut clipboard| *clipboard = Some(text.to_string()))
+            .is_ok()
+    }
+}
+
+impl WindowProperties for Native {
+    fn window_properties(_hwnd: isize) -> Option<matching::Window> {
+        // The handles reported by komorebi are Windows window handles.
+        None
+    }
+}
+
+impl KeyMapping for Native {
+    fn key_name(key: &Key) -> Option<String> {
+        match key {
+            Key::Named(named) => named_key_name(*named).map(String::from),
+            Key::Character(c) => character_key_name(c),
+            Key::Unidentified => None,
+        }
+    }
+
+    fn physical_key_name(key: &Physical) -> Option<String> {
+        let name = match key {
+            Physical::Code(code) => match code {
+                Code::NumLock => "numlock",
+                Code::Numpad0 => "numpad0",
+                Code::Numpad1 => "numpad1",
+                Code::Numpad2 => "numpad2",
+                Code::Numpad3 => "numpad3",
+                Code::Numpad4 => "numpad4",
+                Code::Numpad5 => "numpad5",
+                Code::Numpad6 => "numpad6",
+                Code::Numpad7 => "numpad7",
+                Code::Numpad8 => "numpad8",
+                Code::Numpad9 => "numpad9",
+                Code::NumpadAdd => "add",
+                Code::NumpadBackspace => "back",
+                Code::NumpadClear | Code::NumpadClearEntry => "clear",
+                Code::NumpadComma => "separator",
+                Code::NumpadDecimal => "decimal",
+                Code::NumpadDivide => "divide",
+                Code::NumpadEnter | Code::NumpadEqual => "return",
+                Code::NumpadMultiply | Code::NumpadStar => "multiply",
+                Code::NumpadSubtract => "subtract",
+                _ => return None,
+            },
+            Physical::Unidentified(_) => return None,
+        };
+        Some(name.to_string())
+    }
+}
+
+impl KomorebiIpc for Native {
+    fn send_message(_message: &SocketMessage) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "komorebi only runs on Windows",
+        ))
+    }
+
+    /// There is no komorebi to subscribe to.
+    fn notifications() -> Subscription<live_debug::Message> {
+        Subscription::none()
+    }
+}
+
+impl FileWatching for Native {
+    type Watcher = RecommendedWatcher;
+}
+
+/// The whkd names of the named keys, the same ones the Windows backend gets from the virtual key
+/// codes.
+fn named_key_name(named: Named) -> Option<&'static str> {
+    let name = match named {
+        Named::Backspace => "back",
+        Named::Tab => "tab",
+        Named::Enter => "return",
+        Named::Shift => "shift",
+        Named::Control => "control",
+        Named::Alt => "menu",
+        Named::CapsLock => "capital",
+        Named::Escape => "escape",
+        Named::PageUp => "prior",
+        Named::PageDown => "next",
+        Named::End => "end",
+        Named::Home => "home",
+        Named::ArrowLeft => "left",
+        Named::ArrowUp => "up",
+        Named::ArrowRight => "right",
+        Named::ArrowDown => "down",
+        Named::Delete => "delete",
+        Named::Insert => "insert",
+        Named::AltGraph => "rmenu",
+        Named::NumLock => "numlock",
+        Named::ScrollLock => "scroll",
+        Named::Meta | Named::Hyper | Named::Super => "lwin",
+        Named::Space => "space",
+        Named::Clear => "clear",
+        Named::ContextMenu => "apps",
+        Named::Pause => "pause",
+        Named::PrintScreen => "snapshot",
+        Named::F1 => "f1",
+        Named::F2 => "f2",
+        Named::F3 => "f3",
+        Named::F4 => "f4",
+        Named::F5 => "f5",
+        Named::F6 => "f6",
+        Named::F7 => "f7",
+        Named::F8 => "f8",
+        Named::F9 => "f9",
+        Named::F10 => "f10",
+        Named::F11 => "f11",
+        Named::F12 => "f12",
+        Named::F13 => "f13",
+        Named::F14 => "f14",
+        Named::F15 => "f15",
+        Named::F16 => "f16",
+        Named::F17 => "f17",
+        Named::F18 => "f18",
+        Named::F19 => "f19",
+        Named::F20 => "f20",
+        Named::F21 => "f21",
+        Named::F22 => "f22",
+        Named::F23 => "f23",
+        Named::F24 => "f24",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// The whkd name of the key that types `c` on a US keyboard layout.
+fn character_key_name(c: &str) -> Option<String> {
+    let mut chars = c.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return None;
+    };
+    let name = match c.to_ascii_lowercase() {
+        c if c.is_ascii_alphanumeric() => return Some(c.to_string()),
+        ';' | ':' => "oem_1",
+        '/' | '?' => "oem_2",
+        '`' | '~' => "oem_3",
+        '[' | '{' => "oem_4",
+        '\\' | '|' => "oem_5",
+        ']' | '}' => "oem_6",
+        '\'' | '"' => "oem_7",
+        '=' | '+' => "oem_plus",
+        ',' | '<' => "oem_comma",
+        '-' | '_' => "oem_minus",
+        '.' | '>' => "oem_period",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
@@ -0,0 +1,474 @@
+use crate::{
+    apperror::{AppError, AppErrorKind},
+    backup, config,
+    screen::ConfigType,
+    utils, whkd,
+};
+
+use std::path::{Path, PathBuf};
+
+const KOMOREBI_FILE: &str = "komorebi.json";
+const WHKD_FILE: &str = "whkdrc";
+const APPLICATIONS_FILE: &str = "applications.json";
+
+/// A named set of config files that can be made the active one.
+///
+/// It always has a `komorebi.json` and a `whkdrc` and it can also have an `applications.json`
+/// and the bar configs (`komorebi.bar*.json`) found on the komorebi config folder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    pub path: PathBuf,
+    /// The names of the files this profile has.
+    pub files: Vec<String>,
+}
+
+/// The folder where the profiles are kept, each one on its own folder.
+pub fn profiles_dir() -> Result<PathBuf, AppError> {
+    Ok(utils::data_dir()?.join("profiles"))
+}
+
+/// Lists all the profiles sorted by name.
+pub async fn list() -> Result<Vec<Profile>, AppError> {
+    use smol::stream::StreamExt;
+
+    let dir = profiles_dir()?;
+    if !is_dir(&dir).await {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = smol::fs::read_dir(&dir).await.map_err(|e| AppError {
+        title: "Error reading profiles folder".into(),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    })?;
+
+    let mut profiles = Vec::new();
+    while let Some(entry) = entries.next().await {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        if !is_dir(&path).await {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|n| n.to_str()).map(String::from) else {
+            continue;
+        };
+        let files = profile_files(&path).await;
+        profiles.push(Profile { name, path, files });
+    }
+    profiles.sort_by_key(|profile| profile.name.to_lowercase());
+
+    Ok(profiles)
+}
+
+/// Creates a new profile named `name` with a copy of the currently active files.
+pub async fn create(name: String) -> Result<(), AppError> {
+    let dir = new_profile_dir(&name).await?;
+    copy_active_files(&dir, &name).await
+}
+
+/// Copies the currently active files to the folder `dir` of the profile `name`, replacing the
+/// ones it already has.
+async fn copy_active_files(dir: &Path, name: &str) -> Result<(), AppError> {
+    let komorebi_home = config::home_path()?;
+
+    let mut sources = vec![(config::config_path()?, KOMOREBI_FILE.to_string())];
+    // There is no whkdrc to copy while its folder can't be used
+    if let Ok(whkd_path) = whkd::config_path() {
+        sources.push((whkd_path, WHKD_FILE.to_string()));
+    }
+    sources.extend(
+        optional_files(&komorebi_home)
+            .await
+            .into_iter()
+            .map(|file| (komorebi_home.join(&file), file)),
+    );
+
+    for (source, file) in sources {
+        if !is_file(&source).await {
+            continue;
+        }
+        smol::fs::copy(&source, dir.join(&file))
+            .await
+            .map_err(|e| AppError {
+                title: format!("Error copying '{}' to profile '{name}'", source.display()),
+                description: Some(e.to_string()),
+                kind: AppErrorKind::Error,
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Creates a new profile named `name` with a copy of the files from `profile`.
+pub async fn clone(profile: Profile, name: String) -> Result<(), AppError> {
+    let dir = new_profile_dir(&name).await?;
+    for file in &profile.files {
+        smol::fs::copy(profile.path.join(file), dir.join(file))
+            .await
+            .map_err(|e| AppError {
+                title: format!(
+                    "Error copying '{file}' from profile '{}' to profile '{name}'",
+                    profile.name
+                ),
+                description: Some(e.to_string()),
+                kind: AppErrorKind::Error,
+            })?;
+    }
+    Ok(())
+}
+
+pub async fn rename(profile: Profile, name: String) -> Result<(), AppError> {
+    validate_name(&name)?;
+    let target = profiles_dir()?.join(&name);
+    if smol::fs::metadata(&target).await.is_ok() {
+        return Err(already_exists(&name));
+    }
+    smol::fs::rename(&profile.path, &target)
+        .await
+        .map_err(|e| AppError {
+            title: format!("Error renaming profile '{}' to '{name}'", profile.name),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })
+}
+
+pub async fn delete(profile: Profile) -> Result<(), AppError> {
+    smol::fs::remove_dir_all(&profile.path)
+        .await
+        .map_err(|e| AppError {
+            title: format!("Error deleting profile '{}'", profile.name),
+            description: Some(e.to_string()),
+            kind: AppErrorKind::Error,
+        })
+}
+
+/// Makes `profile` the active one by writing its files over the active config files. The active
+/// files are saved to the `previous` active profile first, so that the changes made to them
+/// while it was active aren't lost, and the active `komorebi.json` and `whkdrc` are backed up.
+///
+/// Every target is resolved and every file of the profile is read and verified before anything
+/// is written, so that the profile isn't left half active when one of them can't be used. The
+/// extra files of the previous profile that this one doesn't have are removed once they are saved
+/// to it.
+pub async fn activate(
+    profile: Profile,
+    previous: Option<Profile>,
+    keep: usize,
+) -> Result<(), AppError> {
+    let komorebi_path = config::config_path()?;
+    let whkd_path = whkd::config_path().map_err(AppError::from);
+    let komorebi_home = config::home_path()?;
+    let targets = targets(&profile.files, &komorebi_path, &whkd_path, &komorebi_home)?;
+
+    let mut files = Vec::new();
+    for (file, target) in targets {
+        let source = profile.path.join(&file);
+        let read_error = |description: String| AppError {
+            title: format!("Error reading '{file}' from profile '{}'", profile.name),
+            description: Some(description),
+            kind: AppErrorKind::Error,
+        };
+        let contents = smol::fs::read(&source)
+            .await
+            .map_err(|e| read_error(e.to_string()))?;
+        let name = file.clone();
+        smol::unblock(move || verify(&name, source))
+            .await
+            .map_err(read_error)?;
+        files.push((file, target, contents));
+    }
+
+    let previous = previous.filter(|previous| previous.path != profile.path);
+    if let Some(previous) = &previous {
+        copy_active_files(&previous.path, &previous.name).await?;
+    }
+
+    backup::create(ConfigType::Komorebi, &komorebi_path, keep).await?;
+    if let Ok(whkd_path) = &whkd_path {
+        backup::create(ConfigType::Whkd, whkd_path, keep).await?;
+    }
+
+    for (file, target, contents) in &files {
+        if let Some(parent) = target.parent() {
+            let _ = smol::fs::create_dir_all(parent).await;
+        }
+        write_file(file, target, contents).await?;
+    }
+
+    // Without a previous profile the extra files weren't saved anywhere, so they are kept.
+    if previous.is_some() {
+        let active = optional_files(&komorebi_home).await;
+        for file in stale_files(&active, &profile.files) {
+            smol::fs::remove_file(komorebi_home.join(&file))
+                .await
+                .map_err(|e| AppError {
+                    title: format!("Error removing '{file}' from the komorebi config folder"),
+                    description: Some(e.to_string()),
+                    kind: AppErrorKind::Error,
+                })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The active path that each of the profile `files` is written to. Fails if some file can't be
+/// written, like the `whkdrc` while its folder can't be used.
+fn targets(
+    files: &[String],
+    komorebi_path: &Path,
+    whkd_path: &Result<PathBuf, AppError>,
+    komorebi_home: &Path,
+) -> Result<Vec<(String, PathBuf)>, AppError> {
+    files
+        .iter()
+        .map(|file| {
+            let target = match file.as_str() {
+                KOMOREBI_FILE => komorebi_path.to_path_buf(),
+                WHKD_FILE => whkd_path.clone()?,
+                _ => komorebi_home.join(file),
+            };
+            Ok((file.clone(), target))
+        })
+        .collect()
+}
+
+/// The `active` extra files that a profile with `files` doesn't have.
+fn stale_files(active: &[String], files: &[String]) -> Vec<String> {
+    active
+        .iter()
+        .filter(|file| !files.contains(file))
+        .cloned()
+        .collect()
+}
+
+/// Checks that the profile `file` at `path` can be read back by komorebi or whkd.
+fn verify(file: &str, path: PathBuf) -> Result<(), String> {
+    match file {
+        KOMOREBI_FILE => utils::verify_config(ConfigType::Komorebi, path),
+        WHKD_FILE => utils::verify_config(ConfigType::Whkd, path),
+        _ => {
+            let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            serde_json::from_str::<serde_json::Value>(&contents)
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Writes the profile `file` with `contents` to the active `target` without ever leaving it half
+/// written.
+async fn write_file(file: &str, target: &Path, contents: &[u8]) -> Result<(), AppError> {
+    let name = file.to_string();
+    utils::write_verified(target, contents, file, move |temp_path| {
+        verify(&name, temp_path)
+    })
+    .await
+}
+
+/// Checks that `name` can be used as a profile name, which is also used as its folder name.
+pub fn validate_name(name: &str) -> Result<(), AppError> {
+    const INVALID_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+    let description = if name.trim().is_empty() {
+        "The name can't be empty.".to_string()
+    } else if name.trim() != name {
+        "The name can't start or end with spaces.".to_string()
+    } else if name.ends_with('.') {
+        "The name can't end with a '.'.".to_string()
+    } else if let Some(c) = name
+        .chars()
+        .find(|c| INVALID_CHARS.contains(c) || c.is_control())
+    {
+        format!("The name can't have the character '{c}'.")
+    } else {
+        return Ok(());
+    };
+
+    Err(AppError {
+        title: format!("Invalid profile name '{name}'"),
+        description: Some(description),
+        kind: AppErrorKind::Warning,
+    })
+}
+
+/// Validates `name` and creates an empty folder for a new profile with it.
+async fn new_profile_dir(name: &str) -> Result<PathBuf, AppError> {
+    validate_name(name)?;
+    let dir = profiles_dir()?.join(name);
+    if smol::fs::metadata(&dir).await.is_ok() {
+        return Err(already_exists(name));
+    }
+    smol::fs::create_dir_all(&dir).await.map_err(|e| AppError {
+        title: format!("Error creating folder for profile '{name}'"),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    })?;
+    Ok(dir)
+}
+
+fn already_exists(name: &str) -> AppError {
+    AppError {
+        title: format!("Profile '{name}' already exists"),
+        description: Some("Choose a different name for the profile.".into()),
+        kind: AppErrorKind::Warning,
+    }
+}
+
+/// The files on `dir` that belong to a profile.
+async fn profile_files(dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    for file in [KOMOREBI_FILE, WHKD_FILE] {
+        if is_file(&dir.join(file)).await {
+            files.push(file.to_string());
+        }
+    }
+    files.extend(optional_files(dir).await);
+    files
+}
+
+/// The `applications.json` and bar config files on `dir`.
+async fn optional_files(dir: &Path) -> Vec<String> {
+    use smol::stream::StreamExt;
+
+    let Ok(mut entries) = smol::fs::read_dir(dir).await else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next().await {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let Some(name) = entry.file_name().to_str().map(String::from) else {
+            continue;
+        };
+        let is_optional = name == APPLICATIONS_FILE
+            || (name.starts_with("komorebi.bar") && name.ends_with(".json"));
+        if is_optional && is_file(&entry.path()).await {
+            files.push(name);
+        }
+    }
+    files.sort();
+    files
+}
+
+async fn is_file(path: &Path) -> bool {
+    matches!(smol::fs::metadata(path).await, Ok(m) if m.is_file())
+}
+
+async fn is_dir(path: &Path) -> bool {
+    matches!(smol::fs::metadata(path).await, Ok(m) if m.is_dir())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn names_must_be_usable_as_folder_names() {
+        assert!(validate_name("Work").is_ok());
+        assert!(validate_name("Gaming 2").is_ok());
+        for name in [
+            "",
+            "  ",
+            " Work",
+            "Work ",
+            "Work.",
+            "Work/Home",
+            "a:b",
+            "tab\t",
+        ] {
+            assert!(validate_name(name).is_err(), "'{name}' should be invalid");
+        }
+    }
+
+    #[test]
+    fn every_target_is_resolved_before_writing() {
+        let komorebi_path = PathBuf::from("home/komorebi.json");
+        let komorebi_home = PathBuf::from("home");
+        let whkd_path = Ok(PathBuf::from("whkd/whkdrc"));
+        assert_eq!(
+            targets(
+                &files(&[KOMOREBI_FILE, WHKD_FILE, APPLICATIONS_FILE]),
+                &komorebi_path,
+                &whkd_path,
+                &komorebi_home,
+            )
+            .unwrap(),
+            vec![
+                (KOMOREBI_FILE.to_string(), komorebi_path.clone()),
+                (WHKD_FILE.to_string(), PathBuf::from("whkd/whkdrc")),
+                (
+                    APPLICATIONS_FILE.to_string(),
+                    komorebi_home.join(APPLICATIONS_FILE)
+                ),
+            ]
+        );
+
+        let whkd_path = Err(AppError {
+            title: "No whkd folder".into(),
+            description: None,
+            kind: AppErrorKind::Error,
+        });
+        assert!(
+            targets(
+                &files(&[KOMOREBI_FILE, WHKD_FILE]),
+                &komorebi_path,
+                &whkd_path,
+                &komorebi_home,
+            )
+            .is_err()
+        );
+        assert!(
+            targets(
+                &files(&[KOMOREBI_FILE]),
+                &komorebi_path,
+                &whkd_path,
+                &komorebi_home,
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn extra_files_missing_from_the_profile_are_stale() {
+        let active = files(&[APPLICATIONS_FILE, "komorebi.bar.json", "komorebi.bar2.json"]);
+        assert_eq!(
+            stale_files(&active, &files(&[KOMOREBI_FILE, "komorebi.bar.json"])),
+            files(&[APPLICATIONS_FILE, "komorebi.bar2.json"])
+        );
+    }
+
+    #[test]
+    fn invalid_extra_files_are_not_written() {
+        let dir = std::env::temp_dir().join("komorice-profile-test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join(APPLICATIONS_FILE);
+        std::fs::write(&target, "{}").unwrap();
+
+        smol::block_on(async {
+            assert!(
+                write_file(APPLICATIONS_FILE, &target, b"{\"Firefox\": ")
+                    .await
+                    .is_err()
+            );
+            assert_eq!(std::fs::read_to_string(&target).unwrap(), "{}");
+
+            write_file(APPLICATIONS_FILE, &target, b"{\"Firefox\": {}}")
+                .await
+                .unwrap();
+            assert_eq!(
+                std::fs::read_to_string(&target).unwrap(),
+                "{\"Firefox\": {}}"
+            );
+        });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,10 +1,13 @@
 pub mod animation;
+pub mod applications;
 pub mod border;
 pub mod general;
 pub mod home;
+pub mod import;
 pub mod live_debug;
 pub mod monitor;
 pub mod monitors;
+pub mod palette;
 pub mod rule;
 pub mod rules;
 pub mod sidebar;
@@ -15,6 +18,8 @@ pub mod wallpaper;
 pub mod whkd;
 pub mod workspace;
 
+use crate::config::HomePathError;
+
 use std::fmt::{Display, Formatter};
 use std::path::PathBuf;
 
@@ -32,6 +37,7 @@ pub enum Screen {
     Animations,
     Theme,
     Rules,
+    Applications,
     LiveDebug,
     Settings,
     Whkd,
@@ -51,6 +57,7 @@ impl Display for Screen {
             Screen::Animations => write!(f, "Animations"),
             Screen::Theme => write!(f, "Theme"),
             Screen::Rules => write!(f, "Rules"),
+            Screen::Applications => write!(f, "Applications"),
             Screen::LiveDebug => write!(f, "Live Debug"),
             Screen::Settings => write!(f, "Settings"),
             Screen::Whkd => write!(f, "Whkd"),
@@ -84,15 +91,21 @@ pub struct Configuration {
 }
 
 impl Configuration {
-    pub fn path(&self) -> PathBuf {
-        match self.config_type {
+    pub fn path(&self) -> Result<PathBuf, HomePathError> {
+        self.path_for(self.config_type)
+    }
+
+    /// The path of the file being edited for `config_type`, even if it isn't the current type.
+    /// The active files have no path while their folder can't be used.
+    pub fn path_for(&self, config_type: ConfigType) -> Result<PathBuf, HomePathError> {
+        match config_type {
             ConfigType::Komorebi => match &self.komorebi_state {
                 ConfigState::Active => crate::config::config_path(),
-                ConfigState::Loaded(path_buf) | ConfigState::New(path_buf) => path_buf.clone(),
+                ConfigState::Loaded(path_buf) | ConfigState::New(path_buf) => Ok(path_buf.clone()),
             },
             ConfigType::Whkd => match &self.whkd_state {
                 ConfigState::Active => crate::whkd::config_path(),
-                ConfigState::Loaded(path_buf) | ConfigState::New(path_buf) => path_buf.clone(),
+                ConfigState::Loaded(path_buf) | ConfigState::New(path_buf) => Ok(path_buf.clone()),
             },
         }
     }
@@ -144,6 +157,14 @@ impl ConfigType {
             ConfigType::Whkd => "Whkd",
         }
     }
+
+    /// The name of the config file.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ConfigType::Komorebi => "komorebi.json",
+            ConfigType::Whkd => "whkdrc",
+        }
+    }
 }
 
 impl std::fmt::Display for ConfigType {
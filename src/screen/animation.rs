@@ -1,5 +1,6 @@
 use crate::{
     config::DEFAULT_CONFIG,
+    screen::palette::SearchOption,
     widget::{
         number_input,
         opt_helpers::{self, DisableArgs},
@@ -17,6 +18,35 @@ use komorebi_client::{
 };
 use lazy_static::lazy_static;
 
+/// The options on the animations screen, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new(
+        "Enable",
+        "Enable or disable all animations or per type of animation",
+    )
+    .children(&[
+        SearchOption::new("Enable Movement Animations", ""),
+        SearchOption::new("Enable Transparency Animations", ""),
+    ]),
+    SearchOption::new(
+        "Duration",
+        "Set the animation duration in ms for all animations or per type of animation (default: 250)",
+    )
+    .children(&[
+        SearchOption::new("Set Duration for Movement Animations", ""),
+        SearchOption::new("Set Duration for Transparency Animations", ""),
+    ]),
+    SearchOption::new(
+        "Style",
+        "Set the animation style for all animations or per type of animation (default: Linear)",
+    )
+    .children(&[
+        SearchOption::new("Set Style for Movement Animations", ""),
+        SearchOption::new("Set Style for Transparency Animations", ""),
+    ]),
+    SearchOption::new("FPS", "Set the animation FPS for all animations"),
+];
+
 lazy_static! {
     static ref ALL_ANIMATIONS_STYLES: [AnimationStyle; 30] = [
         AnimationStyle::Linear,
@@ -0,0 +1,439 @@
+use super::rule::{self, Rule};
+
+use crate::apperror::AppError;
+use crate::asc::{self, RuleKind};
+use crate::widget::{self, button_with_icon, icons, opt_helpers};
+use crate::{BOLD_FONT, ITALIC_FONT};
+
+use std::path::PathBuf;
+
+use iced::{
+    Center, Element, Fill, Subscription, Task, padding,
+    widget::{Column, button, column, container, row, rule as ruler, scrollable, space, text},
+};
+use komorebi_client::StaticConfig;
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    Load,
+    Loaded(Vec<(PathBuf, Result<asc::Applications, AppError>)>),
+    SelectFile(usize),
+    ChangedFilter(String),
+    ChangedNewApplication(String),
+    AddApplication,
+    RemoveApplication(String),
+    EditRules(String, RuleKind),
+    CloseRules,
+    Rule(rule::Message),
+    Save,
+    Saved(PathBuf, Result<asc::Applications, AppError>),
+    DiscardChanges,
+}
+
+#[derive(Clone, Debug)]
+pub enum Action {
+    None,
+    Error(AppError),
+}
+
+/// An application specific configuration file, as it is being edited.
+#[derive(Debug)]
+pub struct File {
+    /// The path of the file as written on the config.
+    pub path: PathBuf,
+    pub applications: asc::Applications,
+    /// The contents of the file when it was last loaded or saved.
+    pub loaded: asc::Applications,
+    /// Why the file couldn't be loaded, if it couldn't.
+    pub error: Option<AppError>,
+}
+
+impl File {
+    pub fn is_dirty(&self) -> bool {
+        self.applications != self.loaded
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Applications {
+    pub files: Vec<File>,
+    pub selected: usize,
+    pub loading: bool,
+    pub filter: String,
+    pub new_application: String,
+    pub rule_screen: Option<(String, RuleKind, Rule)>,
+    /// The files that an autosave has already backed up this session.
+    pub autosave_backups: Vec<PathBuf>,
+}
+
+impl Applications {
+    pub fn update(&mut self, message: Message, backups_to_keep: usize) -> (Action, Task<Message>) {
+        match message {
+            Message::Load => {
+                let paths = self.files.iter().map(|f| f.path.clone()).collect();
+                return (Action::None, self.load(paths));
+            }
+            Message::Loaded(files) => {
+                self.loading = false;
+                self.files = files
+                    .into_iter()
+                    .map(|(path, res)| {
+                        let (applications, error) = match res {
+                            Ok(applications) => (applications, None),
+                            Err(error) => (asc::Applications::default(), Some(error)),
+                        };
+                        File {
+                            path,
+                            loaded: applications.clone(),
+                            applications,
+                            error,
+                        }
+                    })
+                    .collect();
+                if self.selected >= self.files.len() {
+                    self.selected = 0;
+                }
+                self.rule_screen = None;
+            }
+            Message::SelectFile(idx) => {
+                self.selected = idx;
+                self.rule_screen = None;
+            }
+            Message::ChangedFilter(filter) => {
+                self.filter = filter;
+            }
+            Message::ChangedNewApplication(name) => {
+                self.new_application = name;
+            }
+            Message::AddApplication => {
+                let name = self.new_application.trim().to_string();
+                if let Some(file) = self.selected_file_mut()
+                    && !name.is_empty()
+                    && !file.applications.applications.contains_key(&name)
+                {
+                    file.applications
+                        .applications
+                        .insert(name, asc::ApplicationRules::default());
+                    self.new_application.clear();
+                }
+            }
+            Message::RemoveApplication(name) => {
+                if let Some(file) = self.selected_file_mut() {
+                    file.applications.applications.remove(&name);
+                }
+            }
+            Message::EditRules(name, kind) => {
+                self.rule_screen = Some((name, kind, Rule::new()));
+            }
+            Message::CloseRules => {
+                self.rule_screen = None;
+            }
+            Message::Rule(message) => {
+                let selected = self.selected;
+                if let Some((name, kind, rule)) = &mut self.rule_screen
+                    && let Some(file) = self.files.get_mut(selected)
+                    && let Some(app) = file.applications.applications.get_mut(name)
+                {
+                    let (action, task) = rule.update(app.rules_mut(*kind), message);
+                    app.remove_empty();
+                    let action_task = match action {
+                        rule::Action::None => Task::none(),
+                    };
+                    return (
+                        Action::None,
+                        Task::batch([task.map(Message::Rule), action_task]),
+                    );
+                }
+            }
+            Message::Save => {
+                if let Some(file) = self.files.get(self.selected)
+                    && file.error.is_none()
+                {
+                    return (Action::None, save_task(file, backups_to_keep));
+                }
+            }
+            Message::Saved(path, res) => match res {
+                Ok(saved) => {
+                    // The files might have been reloaded while saving, so the saved file is found
+                    // by its path.
+                    if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+                        file.loaded = saved;
+                    }
+                }
+                Err(error) => return (Action::Error(error), Task::none()),
+            },
+            Message::DiscardChanges => {
+                if let Some(file) = self.selected_file_mut() {
+                    file.applications = file.loaded.clone();
+                }
+                self.rule_screen = None;
+            }
+        }
+        (Action::None, Task::none())
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        if let Some((name, kind, rule)) = &self.rule_screen {
+            let title = row![
+                nav_button(text!("{}", self.title()), Message::CloseRules),
+                text!(" > {name} > {kind}:").size(20).font(*BOLD_FONT)
+            ];
+            let rules = self
+                .files
+                .get(self.selected)
+                .and_then(|file| file.applications.applications.get(name))
+                .and_then(|app| app.rules(*kind).as_ref());
+            let content = rule.view(rules).map(Message::Rule);
+            return column![
+                title,
+                ruler::horizontal(2.0),
+                opt_helpers::description_text(kind.description()),
+                container(content)
+                    .width(Fill)
+                    .padding(padding::top(10).bottom(10))
+            ]
+            .spacing(10)
+            .into();
+        }
+
+        let title = text!("{}:", self.title()).size(20).font(*BOLD_FONT);
+        let content: Element<_> = if self.loading {
+            text("Loading...").into()
+        } else if self.files.is_empty() {
+            opt_helpers::description_text(
+                "The config doesn't point to any application specific configuration file. \
+                You can add one on 'General > App Specific Configuration Path'.",
+            )
+            .into()
+        } else {
+            self.files_view()
+        };
+        column![title, ruler::horizontal(2.0), content]
+            .spacing(10)
+            .into()
+    }
+
+    fn files_view(&self) -> Element<'_, Message> {
+        let file_buttons = self.files.iter().enumerate().map(|(idx, file)| {
+            let label = if file.is_dirty() {
+                text!("{}*", file.path.display())
+            } else {
+                text!("{}", file.path.display())
+            };
+            button(label)
+                .on_press(Message::SelectFile(idx))
+                .style(if idx == self.selected {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .into()
+        });
+        let Some(file) = self.files.get(self.selected) else {
+            return space().into();
+        };
+        let is_dirty = file.is_dirty();
+        let file_actions = row![
+            row(file_buttons).spacing(5).wrap(),
+            space::horizontal(),
+            // Every file is reloaded, so none of them can have changes.
+            button("Reload")
+                .on_press_maybe((!self.is_dirty()).then_some(Message::Load))
+                .style(button::secondary),
+            button("Discard Changes")
+                .on_press_maybe(is_dirty.then_some(Message::DiscardChanges))
+                .style(button::secondary),
+            button("Save")
+                .on_press_maybe((is_dirty && file.error.is_none()).then_some(Message::Save)),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        if let Some(error) = &file.error {
+            let description = error.description.as_deref().unwrap_or_default();
+            return column![
+                file_actions,
+                text(&error.title).style(text::danger),
+                opt_helpers::description_text(description),
+            ]
+            .spacing(10)
+            .into();
+        }
+
+        let filter = widget::input(
+            "Filter applications...",
+            &self.filter,
+            Message::ChangedFilter,
+            None,
+        );
+        let add_application = row![
+            widget::input(
+                "New application name",
+                &self.new_application,
+                Message::ChangedNewApplication,
+                Some(Message::AddApplication),
+            ),
+            button_with_icon(icons::plus(), "Add Application").on_press_maybe(
+                (!self.new_application.trim().is_empty()
+                    && !file
+                        .applications
+                        .applications
+                        .contains_key(self.new_application.trim()))
+                .then_some(Message::AddApplication)
+            ),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let filter_lower = self.filter.to_lowercase();
+        let applications = file
+            .applications
+            .applications
+            .iter()
+            .filter(|(name, _)| name.to_lowercase().contains(&filter_lower))
+            .map(|(name, rules)| application_view(name, rules));
+
+        column![
+            file_actions,
+            row![filter, add_application].spacing(10),
+            scrollable(
+                Column::with_children(applications)
+                    .spacing(10)
+                    .width(Fill)
+                    .padding(padding::top(10).bottom(10).right(20))
+            )
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    pub fn title(&self) -> &str {
+        "Applications"
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.files.iter().any(File::is_dirty)
+    }
+
+    /// Loads the application specific configuration files of `config` when they aren't the ones
+    /// already loaded, unless there are unsaved changes on them.
+    pub fn open(&mut self, config: &StaticConfig) -> Task<Message> {
+        let paths = asc::paths(config);
+        let loaded = self.files.iter().map(|f| &f.path).eq(paths.iter());
+        if loaded || self.is_dirty() {
+            Task::none()
+        } else {
+            self.load(paths)
+        }
+    }
+
+    pub fn goto_start_screen(&mut self) {
+        self.rule_screen = None;
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match &self.rule_screen {
+            Some((_, _, rule)) => rule.subscription().map(Message::Rule),
+            None => Subscription::none(),
+        }
+    }
+
+    fn load(&mut self, paths: Vec<PathBuf>) -> Task<Message> {
+        self.loading = true;
+        Task::perform(
+            async move {
+                let mut files = Vec::with_capacity(paths.len());
+                for path in paths {
+                    let res = asc::load(path.clone()).await;
+                    files.push((path, res));
+                }
+                files
+            },
+            Message::Loaded,
+        )
+    }
+
+    /// Discards the changes of every file.
+    pub fn discard_changes(&mut self) {
+        for file in &mut self.files {
+            file.applications = file.loaded.clone();
+        }
+        self.rule_screen = None;
+    }
+
+    /// Saves every file with changes. Like the autosave of the config, only the first autosave of
+    /// each file in a session creates backups.
+    pub fn autosave(&mut self, backups_to_keep: usize) -> Task<Message> {
+        let tasks = self
+            .files
+            .iter()
+            .filter(|file| file.is_dirty() && file.error.is_none())
+            .map(|file| {
+                let backups_to_keep = if self.autosave_backups.contains(&file.path) {
+                    0
+                } else {
+                    self.autosave_backups.push(file.path.clone());
+                    backups_to_keep
+                };
+                save_task(file, backups_to_keep)
+            })
+            .collect::<Vec<_>>();
+        Task::batch(tasks)
+    }
+
+    fn selected_file_mut(&mut self) -> Option<&mut File> {
+        self.files.get_mut(self.selected)
+    }
+}
+
+fn save_task(file: &File, backups_to_keep: usize) -> Task<Message> {
+    let path = file.path.clone();
+    let applications = file.applications.clone();
+    let saved = applications.clone();
+    Task::perform(
+        asc::save(path.clone(), applications, backups_to_keep),
+        move |res| Message::Saved(path.clone(), res.map(|()| saved.clone())),
+    )
+}
+
+fn application_view<'a>(name: &'a str, rules: &'a asc::ApplicationRules) -> Element<'a, Message> {
+    let header = row![
+        text(name).font(*BOLD_FONT),
+        space::horizontal(),
+        button(icons::delete())
+            .on_press(Message::RemoveApplication(name.to_string()))
+            .style(button::danger),
+    ]
+    .align_y(Center);
+    let kinds = RuleKind::ALL.into_iter().map(|kind| {
+        let count = rules.count(kind);
+        button(text!("{kind} ({count})").size(13))
+            .on_press(Message::EditRules(name.to_string(), kind))
+            .style(if count > 0 {
+                button::secondary
+            } else {
+                button::text
+            })
+            .into()
+    });
+    let other = (!rules.other.is_empty()).then(|| {
+        let names = rules.other.keys().cloned().collect::<Vec<_>>().join(", ");
+        text!("Other options: {names}").size(12).font(*ITALIC_FONT)
+    });
+    opt_helpers::opt_box(
+        column![header, row(kinds).spacing(5).wrap()]
+            .push(other)
+            .spacing(5),
+    )
+    .into()
+}
+
+fn nav_button<'a>(
+    content: impl Into<iced::widget::Text<'a>>,
+    on_press: Message,
+) -> iced::widget::Button<'a, Message> {
+    button(content.into().size(20).font(*BOLD_FONT))
+        .on_press(on_press)
+        .padding(0)
+        .style(button::text)
+}
@@ -1,3 +1,4 @@
+use crate::screen::palette::SearchOption;
 use crate::widget::opt_helpers::description_text as t;
 use crate::{
     BOLD_FONT, ITALIC_FONT,
@@ -13,6 +14,48 @@ use komorebi_client::{
     BorderColours, BorderImplementation, BorderStyle, Colour, Rgb, StaticConfig,
 };
 
+/// The options on the border screen, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new(
+        "Enable Border",
+        "Display an active window border (default: true)",
+    ),
+    SearchOption::new("Border Width", "Width of the window border. (default: 8)"),
+    SearchOption::new("Border Offset", "Offset of the window border (default: -1)"),
+    SearchOption::new(
+        "Border Style",
+        "Active window border style (default: System)",
+    ),
+    SearchOption::new(
+        "Border Implementation",
+        "Active window border implementation (default: Komorebi)",
+    ),
+    SearchOption::new(
+        "Single Border Colour",
+        "Border colour when the container contains a single window and is focused",
+    ),
+    SearchOption::new(
+        "Stack Border Colour",
+        "Border colour when the container contains multiple windows and is focused",
+    ),
+    SearchOption::new(
+        "Monocle Border Colour",
+        "Border colour when the container is in monocle mode",
+    ),
+    SearchOption::new(
+        "Floating Border Colour",
+        "Border colour when the container is in floating mode and focused",
+    ),
+    SearchOption::new(
+        "Unfocused Border Colour",
+        "Border colour when the container is unfocused",
+    ),
+    SearchOption::new(
+        "Unfocused Locked Border Colour",
+        "Border colour when the container is unfocused and locked in place",
+    ),
+];
+
 #[derive(Clone, Debug)]
 pub enum Message {
     ConfigChange(ConfigChange),
@@ -441,3 +484,44 @@ fn into_color(colour: Colour) -> iced::Color {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::{Harness, empty_config};
+
+    #[test]
+    fn toggling_border_changes_config() {
+        let mut ui = Harness::new(Border::default(), DEFAULT_CONFIG.clone());
+        ui.click("On");
+        assert_eq!(ui.state.border, Some(false));
+        assert!(ui.contains("Off"));
+    }
+
+    #[test]
+    fn changing_one_colour_keeps_the_others() {
+        let mut ui = Harness::new(Border::default(), empty_config());
+        ui.send([
+            Message::ToggleSinglePicker(true),
+            Message::ConfigChange(ConfigChange::SingleColor(Some(iced::Color::BLACK))),
+            Message::ConfigChange(ConfigChange::StackColor(Some(iced::Color::WHITE))),
+        ]);
+        let colours = ui.state.border_colours.as_ref().expect("colours were set");
+        assert_eq!(colours.single, Some(Colour::Rgb(Rgb::new(0, 0, 0))));
+        assert_eq!(colours.stack, Some(Colour::Rgb(Rgb::new(255, 255, 255))));
+        assert_eq!(colours.monocle, None);
+        assert!(!ui.screen.show_single_picker);
+    }
+
+    #[test]
+    fn numbers_change_config() {
+        let mut ui = Harness::new(Border::default(), empty_config());
+        ui.send([
+            Message::ConfigChange(ConfigChange::BorderWidth(4)),
+            Message::ConfigChange(ConfigChange::BorderOffset(2)),
+        ]);
+        assert_eq!(ui.state.border_width, Some(4));
+        assert_eq!(ui.state.border_offset, Some(2));
+    }
+}
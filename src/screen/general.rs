@@ -1,10 +1,10 @@
-use crate::screen::View;
+use crate::screen::{View, palette::SearchOption};
 use crate::widget::opt_helpers::description_text as t;
-use crate::widget::{self, icons};
+use crate::widget::{self, icons, path_input::FoundFiles};
 use crate::{
     BOLD_FONT, ITALIC_FONT,
     config::DEFAULT_CONFIG,
-    utils::DisplayOption,
+    utils::{self, DisplayOption},
     widget::opt_helpers::{self, DisableArgs},
 };
 
@@ -29,6 +29,111 @@ lazy_static! {
     ];
 }
 
+/// The options on the general screen, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new(
+        "App Specific Configuration Path",
+        "Path to applications.json from komorebi-application-specific-configurations (default: None)",
+    ),
+    SearchOption::new(
+        "Bar Configurations",
+        "Komorebi status bar configuration files for multiple instances on different monitors. (default: None)",
+    ),
+    SearchOption::new(
+        "Mouse Follows Focus",
+        "Enable or disable mouse follows focus (default: true)",
+    ),
+    SearchOption::new("Resize Delta", "Delta to resize windows by (default 50)"),
+    SearchOption::new(
+        "Default Container Padding",
+        "Global default container padding (default: 10)",
+    ),
+    SearchOption::new(
+        "Default Workspace Padding",
+        "Global default workspace padding (default: 10)",
+    ),
+    SearchOption::new(
+        "Global Work Area Offset",
+        "Global work area (space used for tiling) offset (default: None)",
+    )
+    .children(OFFSET_CHILDREN),
+    SearchOption::new(
+        "Cross Boundary Behaviour",
+        "Determine what happens when an action is called on a window at a monitor boundary (default: Monitor)",
+    ),
+    SearchOption::new(
+        "Cross Monitor Move Behaviour",
+        "Determine what happens when a window is moved across a monitor boundary (default: Swap)",
+    ),
+    SearchOption::new(
+        "Unmanaged Window Behaviour",
+        "Determine what happens when commands are sent while an unmanaged window is in the foreground (default: Op)",
+    ),
+    SearchOption::new(
+        "Window Container Behaviour",
+        "Determine what happens when a new window is opened (default: Create)",
+    ),
+    SearchOption::new(
+        "Window Hiding Behaviour",
+        "Which Windows signal to use when hiding windows (default: Cloak)",
+    ),
+    SearchOption::new(
+        "Window Handling Behaviour",
+        "Which Windows API behaviour to use when manipulating windows (default: Sync)",
+    ),
+    SearchOption::new(
+        "Floating Layer Behaviour",
+        "Determines what happens to a new window when on the `FloatingLayer` (default: Tile)",
+    ),
+    SearchOption::new(
+        "Float Override",
+        "Enable or disable float override, which makes it so every new window opens in floating mode (default: false)",
+    ),
+    SearchOption::new(
+        "Floating Window Aspect Ratio",
+        "Aspect ratio to resize with when toggling floating mode for a window. (default: Standard (4:3))",
+    )
+    .children(&[
+        SearchOption::new("width", ""),
+        SearchOption::new("height", ""),
+    ]),
+    SearchOption::new(
+        "Toggle Float Placement",
+        "Determines the placement of a window when toggling to float (default: CenterAndResize)",
+    ),
+    SearchOption::new(
+        "Floating Layer Placement",
+        "Determines the `Placement` to be used when spawning a window on the floating layer with the \
+        `FloatingLayerBehaviour` set to `FloatingLayerBehaviour::Float` (default: Center)",
+    ),
+    SearchOption::new(
+        "Float Override Placement",
+        "Determines the `Placement` to be used when spawning a window with float override active (default: None)",
+    ),
+    SearchOption::new(
+        "Float Rule Placement",
+        "Determines the `Placement` to be used when spawning a window that matches a 'floating_applications' rule (default: None)",
+    ),
+    SearchOption::new(
+        "Focus Follows Mouse",
+        "END OF LIFE FEATURE: Determine focus follows mouse implementation (default: None)",
+    )
+    .advanced(),
+    SearchOption::new(
+        "Slow Application Compensation Time",
+        "How long to wait when compensating for slow applications, in milliseconds (default: 20)",
+    )
+    .advanced(),
+];
+
+/// The sides of a work area offset, shown inside of its option.
+pub const OFFSET_CHILDREN: &[SearchOption] = &[
+    SearchOption::new("left", ""),
+    SearchOption::new("top", ""),
+    SearchOption::new("bottom", ""),
+    SearchOption::new("right", ""),
+];
+
 #[derive(Clone, Debug)]
 pub enum Message {
     ConfigChange(ConfigChange),
@@ -406,13 +511,18 @@ impl General {
         (Action::None, Task::none())
     }
 
-    pub fn view<'a>(&'a self, config: &'a StaticConfig, show_advanced: bool) -> View<'a, Message> {
+    pub fn view<'a>(
+        &'a self,
+        config: &'a StaticConfig,
+        show_advanced: bool,
+        found_files: &'a FoundFiles,
+    ) -> View<'a, Message> {
         let asc_path = opt_helpers::expandable(
             "App Specific Configuration Path",
             Some(
                 "Path to applications.json from komorebi-application-specific-configurations (default: None)",
             ),
-            || self.asc_children(&config.app_specific_configuration_path),
+            || self.asc_children(&config.app_specific_configuration_path, found_files),
             config.app_specific_configuration_path
                 != DEFAULT_CONFIG.app_specific_configuration_path,
             Message::ConfigChange(ConfigChange::AppSpecificConfigurationPath(
@@ -425,7 +535,7 @@ impl General {
             Some(
                 "Komorebi status bar configuration files for multiple instances on different monitors. (default: None)",
             ),
-            || self.bar_configurations_children(&config.bar_configurations),
+            || self.bar_configurations_children(&config.bar_configurations, found_files),
             config.bar_configurations != DEFAULT_CONFIG.bar_configurations,
             Message::ConfigChange(ConfigChange::BarConfigurations(
                 DEFAULT_CONFIG.bar_configurations.clone(),
@@ -879,45 +989,42 @@ impl General {
     fn asc_children<'a>(
         &'a self,
         asc_path: &'a Option<AppSpecificConfigurationPath>,
+        found_files: &'a FoundFiles,
     ) -> Vec<Element<'a, Message>> {
         let mut elements = Vec::new();
         elements.push(text("Current paths:").into());
         if let Some(asc) = asc_path {
             match asc {
                 AppSpecificConfigurationPath::Single(path_buf) => {
-                    let file_picker = button(icons::folder())
-                        .style(subtle)
-                        .on_press(Message::PickAscFile(AscKind::ExistingSingle));
-                    let path_input = widget::input(
-                        "",
-                        path_buf.to_str().unwrap_or_default(),
-                        |v| Message::ConfigChange(ConfigChange::AscPathChange(0, v)),
-                        None,
-                    );
                     let remove = button(icons::delete().style(text::danger))
                         .style(subtle)
                         .on_press(Message::ConfigChange(ConfigChange::RemoveAscPath(
                             AscKind::ExistingSingle,
                         )));
-                    elements.push(row![file_picker, path_input, remove].spacing(10).into());
+                    let path_input = widget::path_input(
+                        path_buf.to_str().unwrap_or_default(),
+                        found_files,
+                        |v| Message::ConfigChange(ConfigChange::AscPathChange(0, v)),
+                        Message::PickAscFile(AscKind::ExistingSingle),
+                    )
+                    .push(remove);
+                    elements.push(path_input.into());
                 }
                 AppSpecificConfigurationPath::Multiple(paths) => {
                     for (idx, path_buf) in paths.iter().enumerate() {
-                        let file_picker = button(icons::folder())
-                            .style(subtle)
-                            .on_press(Message::PickAscFile(AscKind::ExistingMultiple(idx)));
-                        let path_input = widget::input(
-                            "",
-                            path_buf.to_str().unwrap_or_default(),
-                            move |v| Message::ConfigChange(ConfigChange::AscPathChange(idx, v)),
-                            None,
-                        );
                         let remove = button(icons::delete().style(text::danger))
                             .style(subtle)
                             .on_press(Message::ConfigChange(ConfigChange::RemoveAscPath(
                                 AscKind::ExistingMultiple(idx),
                             )));
-                        elements.push(row![file_picker, path_input, remove].spacing(10).into());
+                        let path_input = widget::path_input(
+                            path_buf.to_str().unwrap_or_default(),
+                            found_files,
+                            move |v| Message::ConfigChange(ConfigChange::AscPathChange(idx, v)),
+                            Message::PickAscFile(AscKind::ExistingMultiple(idx)),
+                        )
+                        .push(remove);
+                        elements.push(path_input.into());
                     }
                 }
             }
@@ -926,15 +1033,6 @@ impl General {
             .then_some(Message::ConfigChange(ConfigChange::AddNewAscPath));
         let is_enabled = add_new_msg.is_some();
 
-        let file_picker = button(icons::folder())
-            .style(subtle)
-            .on_press(Message::PickAscFile(AscKind::New));
-        let new_path = widget::input(
-            "",
-            &self.new_asc_path,
-            |v| Message::ConfigChange(ConfigChange::NewAscPathChange(v)),
-            add_new_msg.clone(),
-        );
         let add_button = button(icons::plus().style(move |t| {
             let color = if is_enabled {
                 t.palette().primary.into()
@@ -943,9 +1041,16 @@ impl General {
             };
             text::Style { color }
         }))
-        .on_press_maybe(add_new_msg)
+        .on_press_maybe(add_new_msg.clone())
         .style(button::text);
-        let new_path_row = row![file_picker, new_path, add_button].spacing(10);
+        let new_path_row = widget::path_input(
+            &self.new_asc_path,
+            found_files,
+            |v| Message::ConfigChange(ConfigChange::NewAscPathChange(v)),
+            Message::PickAscFile(AscKind::New),
+        )
+        .on_submit_maybe(add_new_msg)
+        .push(add_button);
 
         let new_path_col = column![rule::horizontal(2), text("Add new path:"), new_path_row]
             .spacing(10)
@@ -957,39 +1062,29 @@ impl General {
     fn bar_configurations_children<'a>(
         &'a self,
         bar_configs: &'a Option<Vec<PathBuf>>,
+        found_files: &'a FoundFiles,
     ) -> Vec<Element<'a, Message>> {
         let mut elements = Vec::new();
         elements.push(text("Current configurations:").into());
         for (idx, path_buf) in bar_configs.iter().flatten().enumerate() {
-            let file_picker = button(icons::folder())
-                .style(subtle)
-                .on_press(Message::PickBarConfigFile(BarConfigKind::Existing(idx)));
-            let path_input = widget::input(
-                "",
-                path_buf.to_str().unwrap_or_default(),
-                move |v| Message::ConfigChange(ConfigChange::BarConfigPathChange(idx, v)),
-                None,
-            );
             let remove = button(icons::delete().style(text::danger))
                 .style(subtle)
                 .on_press(Message::ConfigChange(ConfigChange::RemoveBarConfigPath(
                     idx,
                 )));
-            elements.push(row![file_picker, path_input, remove].spacing(10).into());
+            let path_input = widget::path_input(
+                path_buf.to_str().unwrap_or_default(),
+                found_files,
+                move |v| Message::ConfigChange(ConfigChange::BarConfigPathChange(idx, v)),
+                Message::PickBarConfigFile(BarConfigKind::Existing(idx)),
+            )
+            .push(remove);
+            elements.push(path_input.into());
         }
         let add_new_msg = (!self.new_bar_config_path.is_empty())
             .then_some(Message::ConfigChange(ConfigChange::AddNewBarConfigPath));
         let is_enabled = add_new_msg.is_some();
 
-        let file_picker = button(icons::folder())
-            .style(subtle)
-            .on_press(Message::PickBarConfigFile(BarConfigKind::New));
-        let new_path = widget::input(
-            "",
-            &self.new_bar_config_path,
-            |v| Message::ConfigChange(ConfigChange::NewBarConfigPathChange(v)),
-            add_new_msg.clone(),
-        );
         let add_button = button(icons::plus().style(move |t| {
             let color = if is_enabled {
                 t.palette().primary.into()
@@ -998,9 +1093,16 @@ impl General {
             };
             text::Style { color }
         }))
-        .on_press_maybe(add_new_msg)
+        .on_press_maybe(add_new_msg.clone())
         .style(button::text);
-        let new_path_row = row![file_picker, new_path, add_button].spacing(10);
+        let new_path_row = widget::path_input(
+            &self.new_bar_config_path,
+            found_files,
+            |v| Message::ConfigChange(ConfigChange::NewBarConfigPathChange(v)),
+            Message::PickBarConfigFile(BarConfigKind::New),
+        )
+        .on_submit_maybe(add_new_msg)
+        .push(add_button);
 
         let new_path_col = column![
             rule::horizontal(2),
@@ -1050,11 +1152,10 @@ fn title(title: &str) -> container::Container<'_, Message> {
 }
 
 fn pick_file() -> Task<Message> {
-    let (home_dir, _) = crate::config::home_path();
+    let home_dir = crate::config::home_path().ok();
     Task::future(async move {
-        rfd::FileDialog::new()
+        utils::file_dialog(home_dir)
             .add_filter("json", &["json"])
-            .set_directory(home_dir.as_path())
             .pick_file()
     })
     .map(|res| match res {
@@ -1074,3 +1175,90 @@ fn subtle(theme: &iced::Theme, status: button::Status) -> button::Style {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::{Harness, empty_config};
+
+    fn change(change: ConfigChange) -> Message {
+        Message::ConfigChange(change)
+    }
+
+    #[test]
+    fn asc_path_switches_between_single_and_multiple() {
+        let mut ui = Harness::new(General::default(), empty_config());
+        ui.send([
+            change(ConfigChange::NewAscPathChange("a.json".into())),
+            change(ConfigChange::AddNewAscPath),
+        ]);
+        assert_eq!(
+            ui.state.app_specific_configuration_path,
+            Some(AppSpecificConfigurationPath::Single("a.json".into()))
+        );
+
+        ui.send([
+            change(ConfigChange::NewAscPathChange("b.json".into())),
+            change(ConfigChange::AddNewAscPath),
+        ]);
+        assert_eq!(
+            ui.state.app_specific_configuration_path,
+            Some(AppSpecificConfigurationPath::Multiple(vec![
+                "a.json".into(),
+                "b.json".into()
+            ]))
+        );
+        assert!(ui.screen.new_asc_path.is_empty());
+
+        ui.send([change(ConfigChange::AscPathChange(0, String::new()))]);
+        assert_eq!(
+            ui.state.app_specific_configuration_path,
+            Some(AppSpecificConfigurationPath::Single("b.json".into()))
+        );
+
+        ui.send([change(ConfigChange::RemoveAscPath(AscKind::ExistingSingle))]);
+        assert_eq!(ui.state.app_specific_configuration_path, None);
+    }
+
+    #[test]
+    fn removing_last_bar_config_restores_default() {
+        let mut ui = Harness::new(General::default(), empty_config());
+        ui.send([
+            change(ConfigChange::NewBarConfigPathChange("bar.json".into())),
+            change(ConfigChange::AddNewBarConfigPath),
+        ]);
+        assert_eq!(ui.state.bar_configurations, Some(vec!["bar.json".into()]));
+
+        ui.send([change(ConfigChange::RemoveBarConfigPath(0))]);
+        assert_eq!(
+            ui.state.bar_configurations,
+            DEFAULT_CONFIG.bar_configurations
+        );
+    }
+
+    #[test]
+    fn work_area_offset_sides_fill_the_missing_ones() {
+        let mut ui = Harness::new(General::default(), empty_config());
+        ui.send([
+            change(ConfigChange::GlobalWorkAreaOffsetTop(10)),
+            change(ConfigChange::GlobalWorkAreaOffsetLeft(5)),
+        ]);
+        assert_eq!(
+            ui.state.global_work_area_offset,
+            Some(Rect {
+                left: 5,
+                top: 10,
+                right: 0,
+                bottom: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn view_shows_the_options() {
+        let ui = Harness::new(General::default(), DEFAULT_CONFIG.clone());
+        assert!(ui.contains("App Specific Configuration Path"));
+        assert!(ui.contains("Cross Boundary Behaviour"));
+    }
+}
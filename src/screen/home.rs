@@ -1,10 +1,26 @@
-use super::{ConfigState, ConfigType, Configuration};
-use crate::{EMOJI_FONT, config, whkd, widget::icons};
+use super::{ConfigState, ConfigType, Configuration, View};
+use crate::{
+    EMOJI_FONT,
+    apperror::{AppError, AppErrorKind},
+    backup::{self, Backup, DiffLine},
+    bundle::{self, Bundle},
+    config,
+    profile::{self, Profile},
+    utils, whkd,
+    widget::{button_with_icon, icons, modal, opt_helpers},
+};
+
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use iced::{
-    Center, Element, Fill, Shrink, Task,
-    widget::{button, center, column, container, image, opaque, row, space, stack, text},
+    Center, Element, Fill, Shrink, Task, padding,
+    widget::{
+        button, center, column, container, image, opaque, row, scrollable, space, stack, text,
+        text_input,
+    },
 };
+use komorebi_client::StaticConfig;
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -14,6 +30,37 @@ pub enum Message {
     ChangeConfiguration(ConfigType, ConfigState),
     ClosedDialog,
     OpenErrorsModal,
+    OpenBackups(ConfigType),
+    CloseBackups,
+    LoadedBackups(Vec<Backup>),
+    SelectBackup(usize),
+    LoadedBackupDiff(usize, Vec<DiffLine>),
+    RestoreBackup(usize),
+    RestoredBackup(ConfigType),
+    BackupError(AppError),
+    OpenProfiles,
+    CloseProfiles,
+    LoadedProfiles(Vec<Profile>),
+    SelectProfile(usize),
+    ChangedProfileName(String),
+    CreateProfile,
+    CloneProfile(usize),
+    RenameProfile(usize),
+    DeleteProfile(usize),
+    ActivateProfile(usize),
+    CreatedProfile,
+    RenamedProfile(String, String),
+    DeletedProfile(String),
+    ActivatedProfile(String),
+    ProfileError(AppError),
+    ExportBundle,
+    ExportedBundle(Vec<PathBuf>),
+    ImportBundle,
+    ReadBundle(Arc<Bundle>),
+    ConfirmImportBundle,
+    CloseImportBundle,
+    ImportedBundle,
+    BundleError(AppError),
 }
 
 #[derive(Debug, Clone)]
@@ -22,12 +69,47 @@ pub enum Action {
     ContinueEdit,
     ChangedConfiguration,
     OpenErrorsModal,
+    RestoredBackup(ConfigType),
+    ActivatedProfile(String),
+    ChangedActiveProfile(Option<String>),
+    ExportBundle,
+    ImportedBundle,
+    Error(AppError),
 }
 
 #[derive(Debug, Default, Clone)]
 pub struct Home {
     dialog_opened: bool,
     pub loading: Option<ConfigType>,
+    backups: Option<Backups>,
+    profiles: Option<Profiles>,
+    bundle_import: Option<BundleImport>,
+}
+
+#[derive(Debug, Clone)]
+struct Backups {
+    config_type: ConfigType,
+    /// The file the backups are of.
+    path: PathBuf,
+    list: Vec<Backup>,
+    selected: Option<usize>,
+    diff: Option<Vec<DiffLine>>,
+    restoring: bool,
+}
+
+/// A bundle that was read and is waiting for the user to confirm the import.
+#[derive(Debug, Clone)]
+struct BundleImport {
+    bundle: Arc<Bundle>,
+    importing: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Profiles {
+    list: Vec<Profile>,
+    selected: Option<usize>,
+    name: String,
+    busy: bool,
 }
 
 impl Home {
@@ -35,6 +117,8 @@ impl Home {
         &mut self,
         message: Message,
         configuration: &mut Configuration,
+        backups_to_keep: usize,
+        active_profile: Option<&str>,
     ) -> (Action, Task<Message>) {
         match message {
             Message::EditCurrent(config_type) => {
@@ -93,11 +177,286 @@ impl Home {
             Message::OpenErrorsModal => {
                 return (Action::OpenErrorsModal, Task::none());
             }
+            Message::OpenBackups(config_type) => {
+                let path = match configuration.path_for(config_type) {
+                    Ok(path) => path,
+                    Err(error) => return (Action::Error(error.into()), Task::none()),
+                };
+                self.backups = Some(Backups {
+                    config_type,
+                    path: path.clone(),
+                    list: Vec::new(),
+                    selected: None,
+                    diff: None,
+                    restoring: false,
+                });
+                return (Action::None, list_backups(config_type, path));
+            }
+            Message::CloseBackups => self.backups = None,
+            Message::LoadedBackups(list) => {
+                if let Some(backups) = &mut self.backups {
+                    backups.list = list;
+                    backups.selected = None;
+                    backups.diff = None;
+                }
+            }
+            Message::SelectBackup(idx) => {
+                if let Some(backups) = &mut self.backups
+                    && let Some(backup) = backups.list.get(idx)
+                {
+                    backups.selected = Some(idx);
+                    backups.diff = None;
+                    let task = Task::perform(
+                        backup::diff(backup.clone(), backups.path.clone()),
+                        move |res| match res {
+                            Ok(diff) => Message::LoadedBackupDiff(idx, diff),
+                            Err(apperror) => Message::BackupError(apperror),
+                        },
+                    );
+                    return (Action::None, task);
+                }
+            }
+            Message::LoadedBackupDiff(idx, diff) => {
+                if let Some(backups) = &mut self.backups
+                    && backups.selected == Some(idx)
+                {
+                    backups.diff = Some(diff);
+                }
+            }
+            Message::RestoreBackup(idx) => {
+                if let Some(backups) = &mut self.backups
+                    && let Some(backup) = backups.list.get(idx)
+                {
+                    backups.restoring = true;
+                    let config_type = backups.config_type;
+                    let task = Task::perform(
+                        backup::restore(backup.clone(), backups.path.clone(), backups_to_keep),
+                        move |res| match res {
+                            Ok(_) => Message::RestoredBackup(config_type),
+                            Err(apperror) => Message::BackupError(apperror),
+                        },
+                    );
+                    return (Action::None, task);
+                }
+            }
+            Message::RestoredBackup(config_type) => {
+                self.backups = None;
+                return (Action::RestoredBackup(config_type), Task::none());
+            }
+            Message::BackupError(apperror) => {
+                if let Some(backups) = &mut self.backups {
+                    backups.restoring = false;
+                }
+                return (Action::Error(apperror), Task::none());
+            }
+            Message::OpenProfiles => {
+                self.profiles = Some(Profiles::default());
+                return (Action::None, list_profiles());
+            }
+            Message::CloseProfiles => self.profiles = None,
+            Message::LoadedProfiles(list) => {
+                if let Some(profiles) = &mut self.profiles {
+                    profiles.list = list;
+                    profiles.selected = None;
+                    profiles.busy = false;
+                }
+            }
+            Message::SelectProfile(idx) => {
+                if let Some(profiles) = &mut self.profiles
+                    && let Some(profile) = profiles.list.get(idx)
+                {
+                    profiles.selected = Some(idx);
+                    profiles.name = profile.name.clone();
+                }
+            }
+            Message::ChangedProfileName(name) => {
+                if let Some(profiles) = &mut self.profiles {
+                    profiles.name = name;
+                }
+            }
+            Message::CreateProfile => {
+                if let Some(profiles) = &mut self.profiles {
+                    profiles.busy = true;
+                    let task =
+                        Task::perform(profile::create(profiles.name.clone()), |res| match res {
+                            Ok(_) => Message::CreatedProfile,
+                            Err(apperror) => Message::ProfileError(apperror),
+                        });
+                    return (Action::None, task);
+                }
+            }
+            Message::CloneProfile(idx) => {
+                if let Some(profiles) = &mut self.profiles
+                    && let Some(profile) = profiles.list.get(idx)
+                {
+                    profiles.busy = true;
+                    let task = Task::perform(
+                        profile::clone(profile.clone(), profiles.name.clone()),
+                        |res| match res {
+                            Ok(_) => Message::CreatedProfile,
+                            Err(apperror) => Message::ProfileError(apperror),
+                        },
+                    );
+                    return (Action::None, task);
+                }
+            }
+            Message::RenameProfile(idx) => {
+                if let Some(profiles) = &mut self.profiles
+                    && let Some(profile) = profiles.list.get(idx)
+                {
+                    profiles.busy = true;
+                    let old_name = profile.name.clone();
+                    let new_name = profiles.name.clone();
+                    let task = Task::perform(
+                        profile::rename(profile.clone(), new_name.clone()),
+                        move |res| match res {
+                            Ok(_) => Message::RenamedProfile(old_name.clone(), new_name.clone()),
+                            Err(apperror) => Message::ProfileError(apperror),
+                        },
+                    );
+                    return (Action::None, task);
+                }
+            }
+            Message::DeleteProfile(idx) => {
+                if let Some(profiles) = &mut self.profiles
+                    && let Some(profile) = profiles.list.get(idx)
+                {
+                    profiles.busy = true;
+                    let name = profile.name.clone();
+                    let task =
+                        Task::perform(profile::delete(profile.clone()), move |res| match res {
+                            Ok(_) => Message::DeletedProfile(name.clone()),
+                            Err(apperror) => Message::ProfileError(apperror),
+                        });
+                    return (Action::None, task);
+                }
+            }
+            Message::ActivateProfile(idx) => {
+                if let Some(profiles) = &mut self.profiles
+                    && let Some(profile) = profiles.list.get(idx)
+                {
+                    profiles.busy = true;
+                    let name = profile.name.clone();
+                    let previous = profiles
+                        .list
+                        .iter()
+                        .find(|profile| Some(profile.name.as_str()) == active_profile)
+                        .cloned();
+                    let task = Task::perform(
+                        profile::activate(profile.clone(), previous, backups_to_keep),
+                        move |res| match res {
+                            Ok(_) => Message::ActivatedProfile(name.clone()),
+                            Err(apperror) => Message::ProfileError(apperror),
+                        },
+                    );
+                    return (Action::None, task);
+                }
+            }
+            Message::CreatedProfile => {
+                if let Some(profiles) = &mut self.profiles {
+                    profiles.name.clear();
+                }
+                return (Action::None, list_profiles());
+            }
+            Message::RenamedProfile(old_name, new_name) => {
+                let action = if active_profile == Some(old_name.as_str()) {
+                    Action::ChangedActiveProfile(Some(new_name))
+                } else {
+                    Action::None
+                };
+                return (action, list_profiles());
+            }
+            Message::DeletedProfile(name) => {
+                if let Some(profiles) = &mut self.profiles {
+                    profiles.name.clear();
+                }
+                let action = if active_profile == Some(name.as_str()) {
+                    Action::ChangedActiveProfile(None)
+                } else {
+                    Action::None
+                };
+                return (action, list_profiles());
+            }
+            Message::ActivatedProfile(name) => {
+                if let Some(profiles) = &mut self.profiles {
+                    profiles.busy = false;
+                }
+                return (Action::ActivatedProfile(name), Task::none());
+            }
+            Message::ProfileError(apperror) => {
+                if let Some(profiles) = &mut self.profiles {
+                    profiles.busy = false;
+                }
+                return (Action::Error(apperror), Task::none());
+            }
+            Message::ExportBundle => {
+                self.dialog_opened = true;
+                return (Action::ExportBundle, Task::none());
+            }
+            Message::ExportedBundle(missing) => {
+                self.dialog_opened = false;
+                if !missing.is_empty() {
+                    let apperror = AppError {
+                        title: "Some files referenced by the config weren't found".into(),
+                        description: Some(format!(
+                            "The bundle was exported without these files and the config keeps \
+                            their original paths:\n{}",
+                            missing
+                                .iter()
+                                .map(|path| path.display().to_string())
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        )),
+                        kind: AppErrorKind::Warning,
+                    };
+                    return (Action::Error(apperror), Task::none());
+                }
+            }
+            Message::ImportBundle => {
+                self.dialog_opened = true;
+                return (Action::None, read_bundle());
+            }
+            Message::ReadBundle(bundle) => {
+                self.dialog_opened = false;
+                self.bundle_import = Some(BundleImport {
+                    bundle,
+                    importing: false,
+                });
+            }
+            Message::ConfirmImportBundle => {
+                if let Some(bundle_import) = &mut self.bundle_import {
+                    bundle_import.importing = true;
+                    let bundle = Arc::unwrap_or_clone(bundle_import.bundle.clone());
+                    let task =
+                        Task::perform(bundle::import(bundle, backups_to_keep), |res| match res {
+                            Ok(_) => Message::ImportedBundle,
+                            Err(apperror) => Message::BundleError(apperror),
+                        });
+                    return (Action::None, task);
+                }
+            }
+            Message::CloseImportBundle => self.bundle_import = None,
+            Message::ImportedBundle => {
+                self.bundle_import = None;
+                return (Action::ImportedBundle, Task::none());
+            }
+            Message::BundleError(apperror) => {
+                self.dialog_opened = false;
+                if let Some(bundle_import) = &mut self.bundle_import {
+                    bundle_import.importing = false;
+                }
+                return (Action::Error(apperror), Task::none());
+            }
         }
         (Action::None, Task::none())
     }
 
-    pub fn view(&self, configuration: &Configuration, show_errors: bool) -> Element<'_, Message> {
+    pub fn view<'a>(
+        &'a self,
+        configuration: &Configuration,
+        show_errors: bool,
+        active_profile: Option<&'a str>,
+    ) -> View<'a, Message> {
         let image = center(image("assets/komorice.png").width(256).height(256));
         let title = container(
             row![
@@ -144,11 +503,24 @@ impl Home {
         let buttons_row = row![komorebi_buttons, errors_button, whkd_buttons]
             .spacing(10)
             .height(Shrink);
-        let col = column![title, subtitle, image, buttons_row]
+        let profiles_button = button(text!(
+            "Profiles ({})",
+            active_profile.unwrap_or("none active")
+        ))
+        .on_press_maybe(self.loading.is_none().then_some(Message::OpenProfiles))
+        .style(button::secondary);
+        let export_button = button("Export bundle")
+            .on_press_maybe(self.loading.is_none().then_some(Message::ExportBundle))
+            .style(button::secondary);
+        let import_button = button("Import bundle")
+            .on_press_maybe(self.loading.is_none().then_some(Message::ImportBundle))
+            .style(button::secondary);
+        let bottom_row = row![profiles_button, export_button, import_button].spacing(10);
+        let col = column![title, subtitle, image, buttons_row, bottom_row]
             .spacing(20)
             .align_x(Center);
 
-        stack![
+        let home = stack![
             container(col).padding(20).center_x(Fill).height(Fill),
             self.dialog_opened.then(|| opaque(center("").style(|t| {
                 container::Style {
@@ -156,8 +528,18 @@ impl Home {
                     ..container::dark(t)
                 }
             }))),
-        ]
-        .into()
+        ];
+
+        if let Some(bundle_import) = &self.bundle_import {
+            View::new(home).modal(Some(bundle_import.view()), Message::CloseImportBundle)
+        } else if let Some(profiles) = &self.profiles {
+            View::new(home).modal(Some(profiles.view(active_profile)), Message::CloseProfiles)
+        } else {
+            View::new(home).modal(
+                self.backups.as_ref().map(Backups::view),
+                Message::CloseBackups,
+            )
+        }
     }
 
     fn button_col(
@@ -185,6 +567,10 @@ impl Home {
             .loading
             .is_none_or(|ct| ct != config_type)
             .then_some(new_file);
+        let backups = self
+            .loading
+            .is_none_or(|ct| ct != config_type)
+            .then_some(Message::OpenBackups(config_type));
 
         column![
             fixed_width,
@@ -220,6 +606,11 @@ impl Home {
                 .on_press_maybe(new_file)
                 .style(button::secondary)
             ),
+            container(
+                button(text("Backups").width(Fill).align_x(Center))
+                    .on_press_maybe(backups)
+                    .style(button::secondary)
+            ),
         ]
         .align_x(Center)
         .width(Shrink)
@@ -228,12 +619,343 @@ impl Home {
     }
 }
 
-fn load_komorebi() -> Task<Message> {
-    let (home_dir, _) = config::home_path();
+impl Backups {
+    fn view(&self) -> Element<'_, Message> {
+        let title = row![
+            text!("{} Backups", self.config_type.title()).size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::CloseBackups)
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = opt_helpers::to_description_text(text!(
+            "Backups are created every time a file is saved. Select a backup to see what would \
+            change on '{}' if it was restored. Restoring also creates a backup of the current \
+            file and any unsaved changes will be lost.",
+            self.path.display()
+        ));
+
+        let list: Element<_> = if self.list.is_empty() {
+            opt_helpers::description_text("There are no backups yet.").into()
+        } else {
+            let entries = self.list.iter().enumerate().fold(
+                column![].spacing(5).padding(padding::right(20)),
+                |col, (idx, backup)| {
+                    let is_selected = self.selected == Some(idx);
+                    col.push(
+                        button(
+                            row![
+                                text(backup.date()).width(Fill),
+                                opt_helpers::to_description_text(text!(
+                                    "{:.1} KB",
+                                    backup.size as f64 / 1024.0
+                                )),
+                            ]
+                            .spacing(10),
+                        )
+                        .on_press_maybe((!is_selected).then_some(Message::SelectBackup(idx)))
+                        .style(move |t, s| {
+                            if is_selected {
+                                button::primary(t, s)
+                            } else {
+                                button::subtle(t, s)
+                            }
+                        })
+                        .width(Fill),
+                    )
+                },
+            );
+            scrollable(entries).into()
+        };
+
+        let diff: Element<_> = match (self.selected, &self.diff) {
+            (None, _) => opt_helpers::description_text("Select a backup to compare.").into(),
+            (Some(_), None) => opt_helpers::description_text("Loading...").into(),
+            (Some(idx), Some(diff)) => {
+                let lines: Element<_> = if diff.is_empty() {
+                    text("This backup is the same as the current file.").into()
+                } else {
+                    scrollable(diff.iter().fold(
+                        column![].spacing(2).padding(padding::right(20)),
+                        |col, line| {
+                            col.push(match line {
+                                DiffLine::Added(line) => text!("+ {line}").style(text::success),
+                                DiffLine::Removed(line) => text!("- {line}").style(text::danger),
+                                DiffLine::Changed(line) => text!("~ {line}").style(text::primary),
+                            })
+                        },
+                    ))
+                    .height(Fill)
+                    .into()
+                };
+                let restore = button_with_icon(icons::back(), "Restore")
+                    .on_press_maybe(
+                        (!self.restoring && !diff.is_empty())
+                            .then_some(Message::RestoreBackup(idx)),
+                    )
+                    .style(button::danger);
+                column![lines, restore].spacing(10).into()
+            }
+        };
+
+        container(
+            column![
+                title,
+                description,
+                row![
+                    container(list).width(250),
+                    container(diff).width(Fill).height(Fill)
+                ]
+                .spacing(10)
+                .height(400),
+            ]
+            .spacing(10),
+        )
+        .padding(20)
+        .max_width(850.0)
+        .center(Fill)
+        .height(Shrink)
+        .style(modal::default)
+        .into()
+    }
+}
+
+impl BundleImport {
+    fn view(&self) -> Element<'_, Message> {
+        let title = row![
+            text("Import Bundle").size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::CloseImportBundle)
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = opt_helpers::to_description_text(text!(
+            "Importing '{}' writes these files. The ones marked as replaced already exist, a \
+            backup of each of them is created first. Any unsaved changes to the active files \
+            will be lost.",
+            self.bundle.source.display()
+        ));
+
+        let files = self.bundle.writes.iter().fold(
+            column![].spacing(2).padding(padding::right(20)),
+            |col, (path, exists)| {
+                col.push(
+                    row![
+                        text!("• {}", path.display()).width(Fill),
+                        exists.then(|| text("replaced").style(text::danger)),
+                    ]
+                    .spacing(10),
+                )
+            },
+        );
+
+        let buttons = row![
+            button_with_icon(icons::check(), "Import")
+                .on_press_maybe((!self.importing).then_some(Message::ConfirmImportBundle))
+                .style(button::primary),
+            button("Cancel")
+                .on_press(Message::CloseImportBundle)
+                .style(button::secondary),
+        ]
+        .spacing(10);
+
+        container(
+            column![title, description, scrollable(files).height(Fill), buttons]
+                .spacing(10)
+                .height(400),
+        )
+        .padding(20)
+        .max_width(850.0)
+        .center(Fill)
+        .height(Shrink)
+        .style(modal::default)
+        .into()
+    }
+}
+
+impl Profiles {
+    fn view<'a>(&'a self, active_profile: Option<&'a str>) -> Element<'a, Message> {
+        let title = row![
+            text("Profiles").size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::CloseProfiles)
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = opt_helpers::description_text(
+            "A profile is a named set of a 'komorebi.json' and a 'whkdrc', together with the \
+            'applications.json' and bar configs found on the komorebi config folder. Activating \
+            a profile writes its files over the active ones (a backup of the active \
+            'komorebi.json' and 'whkdrc' is created first) and restarts whkd if it is running. \
+            Any unsaved changes to the active files will be lost.",
+        );
+
+        let list: Element<_> = if self.list.is_empty() {
+            opt_helpers::description_text("There are no profiles yet.").into()
+        } else {
+            let entries = self.list.iter().enumerate().fold(
+                column![].spacing(5).padding(padding::right(20)),
+                |col, (idx, profile)| {
+                    let is_selected = self.selected == Some(idx);
+                    let is_active = active_profile == Some(profile.name.as_str());
+                    col.push(
+                        button(
+                            row![
+                                text(&profile.name).width(Fill),
+                                is_active.then(|| opt_helpers::description_text("active")),
+                            ]
+                            .spacing(10),
+                        )
+                        .on_press_maybe((!is_selected).then_some(Message::SelectProfile(idx)))
+                        .style(move |t, s| {
+                            if is_selected {
+                                button::primary(t, s)
+                            } else {
+                                button::subtle(t, s)
+                            }
+                        })
+                        .width(Fill),
+                    )
+                },
+            );
+            scrollable(entries).into()
+        };
+
+        let can_use_name = !self.busy && profile::validate_name(&self.name).is_ok();
+        let name = text_input("Profile name", &self.name)
+            .on_input(Message::ChangedProfileName)
+            .width(Fill);
+        let create = button_with_icon(icons::plus(), "Create from active files")
+            .on_press_maybe(can_use_name.then_some(Message::CreateProfile))
+            .style(button::secondary);
+
+        let selected: Element<_> = match self
+            .selected
+            .and_then(|idx| self.list.get(idx).map(|profile| (idx, profile)))
+        {
+            None => opt_helpers::description_text(
+                "Select a profile to activate, clone, rename or delete it.",
+            )
+            .into(),
+            Some((idx, profile)) => {
+                let files = profile.files.iter().fold(
+                    column![text!("Files of '{}':", profile.name)].spacing(2),
+                    |col, file| col.push(opt_helpers::to_description_text(text!("• {file}"))),
+                );
+                let name_changed = self.name != profile.name;
+                let buttons = row![
+                    button_with_icon(icons::check(), "Activate")
+                        .on_press_maybe((!self.busy).then_some(Message::ActivateProfile(idx)))
+                        .style(button::primary),
+                    button_with_icon(icons::copy(), "Clone")
+                        .on_press_maybe(
+                            (can_use_name && name_changed).then_some(Message::CloneProfile(idx))
+                        )
+                        .style(button::secondary),
+                    button_with_icon(icons::edit(), "Rename")
+                        .on_press_maybe(
+                            (can_use_name && name_changed).then_some(Message::RenameProfile(idx))
+                        )
+                        .style(button::secondary),
+                    button_with_icon(icons::delete(), "Delete")
+                        .on_press_maybe((!self.busy).then_some(Message::DeleteProfile(idx)))
+                        .style(button::danger),
+                ]
+                .spacing(10);
+                column![scrollable(files).height(Fill), buttons]
+                    .spacing(10)
+                    .into()
+            }
+        };
+
+        container(
+            column![
+                title,
+                description,
+                row![name, create].spacing(10).align_y(Center),
+                row![
+                    container(list).width(250),
+                    container(selected).width(Fill).height(Fill)
+                ]
+                .spacing(10)
+                .height(300),
+            ]
+            .spacing(10),
+        )
+        .padding(20)
+        .max_width(850.0)
+        .center(Fill)
+        .height(Shrink)
+        .style(modal::default)
+        .into()
+    }
+}
+
+fn list_profiles() -> Task<Message> {
+    Task::perform(profile::list(), |res| match res {
+        Ok(list) => Message::LoadedProfiles(list),
+        Err(apperror) => Message::ProfileError(apperror),
+    })
+}
+
+/// Asks where to export the bundle and exports `config` and `whkdrc` to it.
+pub fn export_bundle(config: StaticConfig, whkdrc: whkd::Whkdrc) -> Task<Message> {
     Task::future(async move {
         rfd::FileDialog::new()
+            .add_filter("zip", &["zip"])
+            .set_file_name("komorice-bundle.zip")
+            .save_file()
+    })
+    .then(move |res| match res {
+        Some(file) => Task::perform(
+            bundle::export(config.clone(), whkdrc.clone(), file),
+            |res| match res {
+                Ok(missing) => Message::ExportedBundle(missing),
+                Err(apperror) => Message::BundleError(apperror),
+            },
+        ),
+        None => Task::done(Message::ClosedDialog),
+    })
+}
+
+/// Asks for the bundle to import and reads it, so the files it writes can be shown before
+/// importing it.
+fn read_bundle() -> Task<Message> {
+    Task::future(async move {
+        rfd::FileDialog::new()
+            .add_filter("zip", &["zip"])
+            .pick_file()
+    })
+    .then(|res| match res {
+        Some(file) => Task::perform(bundle::read(file), |res| match res {
+            Ok(bundle) => Message::ReadBundle(Arc::new(bundle)),
+            Err(apperror) => Message::BundleError(apperror),
+        }),
+        None => Task::done(Message::ClosedDialog),
+    })
+}
+
+fn list_backups(config_type: ConfigType, path: PathBuf) -> Task<Message> {
+    Task::perform(backup::list(config_type, path), |res| match res {
+        Ok(list) => Message::LoadedBackups(list),
+        Err(apperror) => Message::BackupError(apperror),
+    })
+}
+
+fn load_komorebi() -> Task<Message> {
+    let home_dir = config::home_path().ok();
+    Task::future(async move {
+        utils::file_dialog(home_dir)
             .add_filter("json", &["json"])
-            .set_directory(home_dir.as_path())
             .pick_file()
     })
     .map(|res| match res {
@@ -243,11 +965,10 @@ fn load_komorebi() -> Task<Message> {
 }
 
 fn new_komorebi() -> Task<Message> {
-    let (home_dir, _) = config::home_path();
+    let home_dir = config::home_path().ok();
     Task::future(async move {
-        rfd::FileDialog::new()
+        utils::file_dialog(home_dir)
             .add_filter("json", &["json"])
-            .set_directory(home_dir.as_path())
             .save_file()
     })
     .map(|res| match res {
@@ -257,26 +978,16 @@ fn new_komorebi() -> Task<Message> {
 }
 
 fn load_whkd() -> Task<Message> {
-    let home_dir = whkd::home_path();
-    Task::future(async move {
-        rfd::FileDialog::new()
-            .set_directory(home_dir.as_path())
-            .pick_file()
-    })
-    .map(|res| match res {
+    let home_dir = whkd::find_home_path().ok();
+    Task::future(async move { utils::file_dialog(home_dir).pick_file() }).map(|res| match res {
         Some(file) => Message::ChangeConfiguration(ConfigType::Whkd, ConfigState::Loaded(file)),
         None => Message::ClosedDialog,
     })
 }
 
 fn new_whkd() -> Task<Message> {
-    let home_dir = whkd::home_path();
-    Task::future(async move {
-        rfd::FileDialog::new()
-            .set_directory(home_dir.as_path())
-            .save_file()
-    })
-    .map(|res| match res {
+    let home_dir = whkd::find_home_path().ok();
+    Task::future(async move { utils::file_dialog(home_dir).save_file() }).map(|res| match res {
         Some(file) => Message::ChangeConfiguration(ConfigType::Whkd, ConfigState::New(file)),
         None => Message::ClosedDialog,
     })
@@ -0,0 +1,499 @@
+use super::rules;
+
+use crate::{
+    EMOJI_FONT,
+    apperror::AppError,
+    config::{self, DEFAULT_MONITOR_CONFIG, DEFAULT_WORKSPACE_CONFIG},
+    utils,
+    widget::{button_with_icon, icons, modal, opt_helpers},
+};
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use iced::{
+    Center, Element, Fill, Shrink, Task, padding,
+    widget::{button, checkbox, column, container, pick_list, row, scrollable, space, text},
+};
+use komorebi_client::{MatchingRule, MonitorConfig, StaticConfig};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    PickFile,
+    ClosedFilePicker,
+    Loaded(PathBuf, Arc<StaticConfig>),
+    FailedToLoad(AppError),
+    ToggleSection(Section, bool),
+    ChangeMode(RuleList, Mode),
+    Import,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    Import(StaticConfig),
+    Error(AppError),
+}
+
+/// A part of a config that can be imported on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Border,
+    Stackbar,
+    Animation,
+    Theme,
+    Rules(RuleList),
+    Monitor(usize),
+    Workspace(usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleList {
+    Rules(rules::Screen),
+    TransparencyIgnoreRules,
+}
+
+impl std::fmt::Display for RuleList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuleList::Rules(screen) => write!(f, "{screen}"),
+            RuleList::TransparencyIgnoreRules => write!(f, "Transparency Ignore Rules"),
+        }
+    }
+}
+
+impl RuleList {
+    const ALL: [RuleList; 9] = [
+        RuleList::Rules(rules::Screen::IgnoreRules),
+        RuleList::Rules(rules::Screen::FloatingApplications),
+        RuleList::Rules(rules::Screen::ManageRules),
+        RuleList::Rules(rules::Screen::TrayAndMultiWindowApplications),
+        RuleList::Rules(rules::Screen::ObjectNameChangeApplications),
+        RuleList::Rules(rules::Screen::SlowApplicationIdentifiers),
+        RuleList::Rules(rules::Screen::LayeredApplications),
+        RuleList::Rules(rules::Screen::BorderOverflowApplications),
+        RuleList::TransparencyIgnoreRules,
+    ];
+
+    fn get(self, config: &StaticConfig) -> &Option<Vec<MatchingRule>> {
+        match self {
+            RuleList::Rules(screen) => rules::get_rules_from_config(config, &screen),
+            RuleList::TransparencyIgnoreRules => &config.transparency_ignore_rules,
+        }
+    }
+
+    fn get_mut(self, config: &mut StaticConfig) -> &mut Option<Vec<MatchingRule>> {
+        match self {
+            RuleList::Rules(screen) => rules::get_rules_from_config_mut(config, &screen),
+            RuleList::TransparencyIgnoreRules => &mut config.transparency_ignore_rules,
+        }
+    }
+}
+
+/// How the imported rules are merged with the existing ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Adds the imported rules that don't exist yet.
+    #[default]
+    Append,
+    /// Replaces the existing rules with the imported ones.
+    Replace,
+}
+
+impl Mode {
+    const ALL: [Mode; 2] = [Mode::Append, Mode::Replace];
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Append => write!(f, "Append"),
+            Mode::Replace => write!(f, "Replace"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Import {
+    source: Option<Source>,
+}
+
+#[derive(Debug)]
+struct Source {
+    path: PathBuf,
+    config: StaticConfig,
+    selected: Vec<Section>,
+    modes: Vec<(RuleList, Mode)>,
+}
+
+impl Import {
+    pub fn update(&mut self, message: Message, config: &StaticConfig) -> (Action, Task<Message>) {
+        match message {
+            Message::PickFile => return (Action::None, pick_file()),
+            Message::ClosedFilePicker => {}
+            Message::Loaded(path, source) => {
+                self.source = Some(Source {
+                    path,
+                    config: config::merge_default(Arc::unwrap_or_clone(source)),
+                    selected: Vec::new(),
+                    modes: Vec::new(),
+                });
+            }
+            Message::FailedToLoad(apperror) => {
+                return (Action::Error(apperror), Task::none());
+            }
+            Message::ToggleSection(section, selected) => {
+                if let Some(source) = &mut self.source {
+                    source.selected.retain(|s| *s != section);
+                    if selected {
+                        source.selected.push(section);
+                    }
+                }
+            }
+            Message::ChangeMode(list, mode) => {
+                if let Some(source) = &mut self.source {
+                    source.modes.retain(|(l, _)| *l != list);
+                    source.modes.push((list, mode));
+                }
+            }
+            Message::Import => {
+                if let Some(source) = self.source.take() {
+                    return (Action::Import(source.merge_into(config)), Task::none());
+                }
+            }
+            Message::Close => self.source = None,
+        }
+        (Action::None, Task::none())
+    }
+
+    /// The modal with the sections of the loaded file, if a file was loaded.
+    pub fn view<'a>(&'a self, config: &'a StaticConfig) -> Option<Element<'a, Message>> {
+        self.source.as_ref().map(|source| source.view(config))
+    }
+}
+
+impl Source {
+    fn is_selected(&self, section: Section) -> bool {
+        self.selected.contains(&section)
+    }
+
+    fn mode(&self, list: RuleList) -> Mode {
+        self.modes
+            .iter()
+            .find_map(|(l, mode)| (*l == list).then_some(*mode))
+            .unwrap_or_default()
+    }
+
+    /// Returns a copy of `config` with the selected sections imported from this source.
+    fn merge_into(&self, config: &StaticConfig) -> StaticConfig {
+        let mut merged = config.clone();
+        let source = &self.config;
+
+        // Whole monitors are imported first so that the selected workspaces of monitors that
+        // don't exist yet can be imported on them.
+        let mut sections = self.selected.clone();
+        sections.sort_by_key(|section| !matches!(section, Section::Monitor(_)));
+        for section in sections {
+            match section {
+                Section::Border => {
+                    merged.border = source.border;
+                    merged.border_width = source.border_width;
+                    merged.border_offset = source.border_offset;
+                    merged.border_style = source.border_style;
+                    merged.border_implementation = source.border_implementation;
+                    merged.border_colours = source.border_colours.clone();
+                    merged.border_z_order = source.border_z_order;
+                }
+                Section::Stackbar => merged.stackbar = source.stackbar.clone(),
+                Section::Animation => merged.animation = source.animation.clone(),
+                Section::Theme => merged.theme = source.theme.clone(),
+                Section::Rules(list) => {
+                    let imported = list.get(source).clone().unwrap_or_default();
+                    let rules = list.get_mut(&mut merged);
+                    match self.mode(list) {
+                        Mode::Replace => *rules = Some(imported),
+                        Mode::Append => {
+                            let rules = rules.get_or_insert_with(Vec::new);
+                            for rule in imported {
+                                if !rules.contains(&rule) {
+                                    rules.push(rule);
+                                }
+                            }
+                        }
+                    }
+                }
+                Section::Monitor(m) => {
+                    if let Some(monitor) = source.monitors.as_ref().and_then(|ms| ms.get(m)) {
+                        *monitor_at(&mut merged, m) = monitor.clone();
+                    }
+                }
+                Section::Workspace(m, w) => {
+                    if self.is_selected(Section::Monitor(m)) {
+                        continue;
+                    }
+                    let workspace = source
+                        .monitors
+                        .as_ref()
+                        .and_then(|ms| ms.get(m))
+                        .and_then(|monitor| monitor.workspaces.get(w));
+                    if let Some(workspace) = workspace {
+                        let workspaces = &mut monitor_at(&mut merged, m).workspaces;
+                        if workspaces.len() <= w {
+                            workspaces.resize(w + 1, DEFAULT_WORKSPACE_CONFIG.clone());
+                        }
+                        workspaces[w] = workspace.clone();
+                    }
+                }
+            }
+        }
+
+        merged
+    }
+
+    fn section_toggle<'a>(&self, section: Section, label: String) -> Element<'a, Message> {
+        checkbox(label, self.is_selected(section))
+            .on_toggle(move |selected| Message::ToggleSection(section, selected))
+            .into()
+    }
+
+    fn view<'a>(&'a self, config: &'a StaticConfig) -> Element<'a, Message> {
+        let title = row![
+            text("Import from file").size(30.0),
+            space::horizontal(),
+            button(text("❌").font(*EMOJI_FONT))
+                .on_press(Message::Close)
+                .style(button::text),
+        ]
+        .spacing(10)
+        .padding([10, 0])
+        .align_y(Center);
+        let description = opt_helpers::to_description_text(text!(
+            "Pick the sections of '{}' to import into the current config. Imported sections \
+            replace the current ones, except for the rule lists which can also be appended, \
+            skipping the rules that already exist. Monitors and workspaces replace the ones with \
+            the same index or are added at the end.",
+            self.path.display()
+        ));
+
+        let mut general = column![text("General").size(18)].spacing(5);
+        general = general.extend([
+            self.section_toggle(Section::Border, "Border".into()),
+            self.section_toggle(Section::Stackbar, "Stackbar".into()),
+            self.section_toggle(Section::Animation, "Animations".into()),
+        ]);
+        if self.config.theme.is_some() {
+            general = general.push(self.section_toggle(Section::Theme, "Theme".into()));
+        }
+
+        let mut rules = column![text("Rules").size(18)].spacing(5);
+        for list in RuleList::ALL {
+            let count = list.get(&self.config).as_ref().map_or(0, Vec::len);
+            if count == 0 {
+                continue;
+            }
+            let section = Section::Rules(list);
+            rules = rules.push(
+                row![
+                    container(self.section_toggle(section, format!("{list} ({count})")))
+                        .width(Fill),
+                    pick_list(Mode::ALL, Some(self.mode(list)), move |mode| {
+                        Message::ChangeMode(list, mode)
+                    }),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            );
+        }
+
+        let mut monitors = column![text("Monitors").size(18)].spacing(5);
+        let current_monitors = config.monitors.as_ref().map_or(0, Vec::len);
+        for (m, monitor) in self.config.monitors.iter().flatten().enumerate() {
+            let monitor_selected = self.is_selected(Section::Monitor(m));
+            monitors = monitors.push(self.section_toggle(
+                Section::Monitor(m),
+                format!("Monitor [{m}] ({} workspaces)", monitor.workspaces.len()),
+            ));
+            // Single workspaces can only be imported into existing monitors.
+            let can_import_workspaces = !monitor_selected && m < current_monitors;
+            let workspaces = monitor.workspaces.iter().enumerate().fold(
+                column![].spacing(5).padding(padding::left(30)),
+                |col, (w, workspace)| {
+                    let label = format!("Workspace [{w}] '{}'", workspace.name);
+                    col.push(if can_import_workspaces {
+                        self.section_toggle(Section::Workspace(m, w), label)
+                    } else {
+                        checkbox(label, monitor_selected).into()
+                    })
+                },
+            );
+            monitors = monitors.push(workspaces);
+        }
+
+        let import = button_with_icon(icons::check(), "Import")
+            .on_press_maybe((!self.selected.is_empty()).then_some(Message::Import));
+
+        container(
+            column![
+                title,
+                description,
+                scrollable(
+                    column![general, rules, monitors]
+                        .spacing(20)
+                        .padding(padding::right(20))
+                )
+                .height(400),
+                import,
+            ]
+            .spacing(10),
+        )
+        .padding(20)
+        .max_width(850.0)
+        .center(Fill)
+        .height(Shrink)
+        .style(modal::default)
+        .into()
+    }
+}
+
+fn pick_file() -> Task<Message> {
+    let home_dir = config::home_path().ok();
+    Task::future(async move {
+        utils::file_dialog(home_dir)
+            .add_filter("json", &["json"])
+            .pick_file()
+    })
+    .then(|res| match res {
+        Some(path) => Task::perform(config::load(path.clone()), move |res| match res {
+            Ok(config) => Message::Loaded(path.clone(), Arc::new(config)),
+            Err(apperror) => Message::FailedToLoad(apperror),
+        }),
+        None => Task::done(Message::ClosedFilePicker),
+    })
+}
+
+/// The monitor at index `m` of `config`. Default monitors are added until there is one at that
+/// index, so that the monitors keep their index when imported.
+fn monitor_at(config: &mut StaticConfig, m: usize) -> &mut MonitorConfig {
+    let monitors = config.monitors.get_or_insert_with(Vec::new);
+    if monitors.len() <= m {
+        monitors.resize(
+            m + 1,
+            MonitorConfig {
+                workspaces: vec![DEFAULT_WORKSPACE_CONFIG.clone()],
+                ..DEFAULT_MONITOR_CONFIG.clone()
+            },
+        );
+    }
+    &mut monitors[m]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn source(config: serde_json::Value, selected: Vec<Section>) -> Source {
+        Source {
+            path: PathBuf::new(),
+            config: serde_json::from_value(config).unwrap(),
+            selected,
+            modes: Vec::new(),
+        }
+    }
+
+    fn workspace_names(config: &StaticConfig) -> Vec<Vec<&str>> {
+        config
+            .monitors
+            .iter()
+            .flatten()
+            .map(|monitor| {
+                monitor
+                    .workspaces
+                    .iter()
+                    .map(|workspace| workspace.name.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn monitors(names: &[&[&str]]) -> serde_json::Value {
+        let monitors = names
+            .iter()
+            .map(|workspaces| {
+                let workspaces = workspaces
+                    .iter()
+                    .map(|name| json!({ "name": name }))
+                    .collect::<Vec<_>>();
+                json!({ "workspaces": workspaces })
+            })
+            .collect::<Vec<_>>();
+        json!({ "monitors": monitors })
+    }
+
+    #[test]
+    fn imports_monitors_on_their_own_index() {
+        let source = source(
+            monitors(&[&["A"], &["B"], &["C"]]),
+            vec![Section::Monitor(2)],
+        );
+        let config = serde_json::from_value(monitors(&[&["I"]])).unwrap();
+
+        let merged = source.merge_into(&config);
+        assert_eq!(
+            workspace_names(&merged),
+            vec![
+                vec!["I"],
+                vec![DEFAULT_WORKSPACE_CONFIG.name.as_str()],
+                vec!["C"]
+            ]
+        );
+    }
+
+    #[test]
+    fn imports_workspaces_on_missing_monitors() {
+        let source = source(
+            monitors(&[&["A"], &["B", "C", "D"]]),
+            vec![Section::Workspace(1, 2)],
+        );
+        let config = serde_json::from_value(monitors(&[&["I", "II"]])).unwrap();
+
+        let merged = source.merge_into(&config);
+        let default = DEFAULT_WORKSPACE_CONFIG.name.as_str();
+        assert_eq!(
+            workspace_names(&merged),
+            vec![vec!["I", "II"], vec![default, default, "D"]]
+        );
+    }
+
+    #[test]
+    fn workspaces_of_imported_monitors_are_not_imported_again() {
+        let source = source(
+            monitors(&[&["A", "B"]]),
+            vec![Section::Workspace(0, 1), Section::Monitor(0)],
+        );
+        let config = serde_json::from_value(monitors(&[&["I", "II", "III"]])).unwrap();
+
+        let merged = source.merge_into(&config);
+        assert_eq!(workspace_names(&merged), vec![vec!["A", "B"]]);
+    }
+
+    #[test]
+    fn appends_only_the_missing_rules() {
+        let rule = |id: &str| json!({ "kind": "Exe", "id": id, "matching_strategy": "Equals" });
+        let list = RuleList::Rules(rules::Screen::IgnoreRules);
+        let mut source = source(
+            json!({ "ignore_rules": [rule("a.exe"), rule("b.exe")] }),
+            vec![Section::Rules(list)],
+        );
+        let config = serde_json::from_value(json!({ "ignore_rules": [rule("b.exe")] })).unwrap();
+
+        let merged = source.merge_into(&config);
+        assert_eq!(merged.ignore_rules.as_ref().map(Vec::len), Some(2));
+
+        source.modes.push((list, Mode::Replace));
+        let merged = source.merge_into(&config);
+        assert_eq!(merged.ignore_rules, source.config.ignore_rules);
+    }
+}
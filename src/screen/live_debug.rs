@@ -1,15 +1,23 @@
+use super::rule::{APPLICATION_IDENTIFIER_OPTIONS, MATCHING_STRATEGY_OPTIONS, regex_error};
+
 use crate::{
     BOLD_FONT,
     apperror::{AppError, AppErrorKind},
+    lint::{self, Location},
+    matching,
     monitors::DisplayInfo,
-    widget::{monitors_viewer, opt_helpers},
+    platform::{KomorebiIpc, Native, WindowProperties},
+    widget::{self, button_with_icon, icons, monitors_viewer, opt_helpers},
 };
 
 use std::{collections::HashMap, sync::Arc};
 
 use iced::{
     Center, Element, Fill, Task, padding,
-    widget::{Id, button, checkbox, column, container, row, rule, scrollable, text},
+    widget::{Id, button, checkbox, column, container, pick_list, row, rule, scrollable, text},
+};
+use komorebi_client::{
+    ApplicationIdentifier, IdWithIdentifier, MatchingRule, MatchingStrategy, StaticConfig,
 };
 
 #[derive(Clone, Debug)]
@@ -22,12 +30,23 @@ pub enum Message {
 
     // Komorebi Command Messages
     ToggleWorkspaceTile(usize, usize, bool),
+
+    // Window browser Messages
+    RefreshWindows,
+    CreateRule(usize, ApplicationIdentifier),
+    ChangeNewRuleKind(ApplicationIdentifier),
+    ChangeNewRuleMatchingStrategy(MatchingStrategy),
+    ChangeNewRuleId(String),
+    ChangeNewRuleTarget(Location),
+    AddNewRule,
+    CancelNewRule,
 }
 
 #[derive(Clone, Debug)]
 pub enum Action {
     None,
     Error(AppError),
+    AddRule(Location, MatchingRule),
 }
 
 #[derive(Clone, Debug, Default)]
@@ -36,6 +55,24 @@ pub enum Screen {
     Main,
     Monitors,
     Notifications,
+    Windows,
+}
+
+/// A window reported by komorebi, with the properties that rules can match.
+#[derive(Clone, Debug)]
+pub struct LiveWindow {
+    pub monitor: usize,
+    pub workspace: usize,
+    pub floating: bool,
+    pub properties: matching::Window,
+}
+
+/// A rule being created from one of the `LiveWindow`s.
+#[derive(Clone, Debug)]
+pub struct NewRule {
+    pub window: usize,
+    pub rule: IdWithIdentifier,
+    pub target: Option<Location>,
 }
 
 #[derive(Default)]
@@ -45,6 +82,11 @@ pub struct LiveDebug {
     pub notifications: Vec<Arc<komorebi_client::NotificationEvent>>,
     pub komorebi_state: Option<Arc<komorebi_client::State>>,
     pub actual_display_info: HashMap<usize, DisplayInfo>,
+    /// The windows on the last state, as they were when the window browser was refreshed.
+    pub windows: Vec<LiveWindow>,
+    pub new_rule: Option<NewRule>,
+    /// Where the last rule created from a window was added.
+    pub added_rule_to: Option<Location>,
 }
 
 impl LiveDebug {
@@ -65,16 +107,77 @@ impl LiveDebug {
                     self.monitor_to_config = Some(idx);
                 }
             }
-            Message::ChangeScreen(screen) => self.screen = screen,
+            Message::ChangeScreen(screen) => {
+                if matches!(screen, Screen::Windows) {
+                    self.refresh_windows();
+                }
+                self.screen = screen;
+            }
             Message::ToggleWorkspaceTile(monitor_idx, workspace_idx, tile) => {
-                let _ = komorebi_client::send_message(
-                    &komorebi_client::SocketMessage::WorkspaceTiling(
-                        monitor_idx,
-                        workspace_idx,
-                        tile,
-                    ),
-                );
+                let _ = Native::send_message(&komorebi_client::SocketMessage::WorkspaceTiling(
+                    monitor_idx,
+                    workspace_idx,
+                    tile,
+                ));
             }
+            Message::RefreshWindows => self.refresh_windows(),
+            Message::CreateRule(window, kind) => {
+                if let Some(live_window) = self.windows.get(window) {
+                    self.new_rule = Some(NewRule {
+                        window,
+                        rule: IdWithIdentifier {
+                            kind,
+                            id: live_window.properties.property(&kind).to_string(),
+                            matching_strategy: Some(suggested_strategy(&kind)),
+                        },
+                        target: self.new_rule.take().and_then(|new_rule| new_rule.target),
+                    });
+                }
+            }
+            Message::ChangeNewRuleKind(kind) => {
+                if let Some(new_rule) = &mut self.new_rule
+                    && let Some(live_window) = self.windows.get(new_rule.window)
+                {
+                    new_rule.rule = IdWithIdentifier {
+                        kind,
+                        id: live_window.properties.property(&kind).to_string(),
+                        matching_strategy: Some(suggested_strategy(&kind)),
+                    };
+                }
+            }
+            Message::ChangeNewRuleMatchingStrategy(matching_strategy) => {
+                if let Some(new_rule) = &mut self.new_rule {
+                    new_rule.rule.matching_strategy = Some(matching_strategy);
+                }
+            }
+            Message::ChangeNewRuleId(id) => {
+                if let Some(new_rule) = &mut self.new_rule {
+                    new_rule.rule.id = id;
+                }
+            }
+            Message::ChangeNewRuleTarget(target) => {
+                if let Some(new_rule) = &mut self.new_rule {
+                    new_rule.target = Some(target);
+                }
+            }
+            Message::AddNewRule => {
+                // Like on the rules screens, invalid regexes never reach the config.
+                if let Some(NewRule {
+                    rule,
+                    target: Some(target),
+                    ..
+                }) = self.new_rule.take_if(|new_rule| {
+                    regex_error(&new_rule.rule.id, new_rule.rule.matching_strategy.as_ref())
+                        .is_none()
+                }) {
+                    self.added_rule_to = Some(target.clone());
+                    return (
+                        Action::AddRule(target, MatchingRule::Simple(rule)),
+                        Task::none(),
+                    );
+                }
+            }
+            Message::CancelNewRule => self.new_rule = None,
             Message::KomorebiNotification(notification) => {
                 if let Some(notification) = Arc::into_inner(notification) {
                     self.notifications.push(Arc::from(notification.event));
@@ -113,11 +216,12 @@ impl LiveDebug {
         (Action::None, Task::none())
     }
 
-    pub fn view(&self) -> Element<'_, Message> {
+    pub fn view<'a>(&'a self, config: &'a StaticConfig) -> Element<'a, Message> {
         match self.screen {
             Screen::Main => self.main_view(),
             Screen::Monitors => self.monitors_view(),
             Screen::Notifications => self.notifications_view(),
+            Screen::Windows => self.windows_view(config),
         }
     }
 
@@ -129,11 +233,17 @@ impl LiveDebug {
             None,
             Message::ChangeScreen(Screen::Notifications),
         );
+        let windows = opt_helpers::opt_button(
+            "Windows",
+            Some("The windows managed by komorebi, to create rules from them."),
+            Message::ChangeScreen(Screen::Windows),
+        );
         column![
             text("Live Debug:").size(20).font(*BOLD_FONT),
             rule::horizontal(2.0),
             monitors,
             notifications,
+            windows,
         ]
         .spacing(10)
         .width(Fill)
@@ -232,7 +342,316 @@ impl LiveDebug {
             .into()
     }
 
+    fn windows_view<'a>(&'a self, config: &'a StaticConfig) -> Element<'a, Message> {
+        let title = row![
+            button(text("Live Debug").size(20).font(*BOLD_FONT))
+                .padding(0)
+                .on_press(Message::ChangeScreen(Screen::Main))
+                .style(button::text),
+            text(" > Windows:").size(20).font(*BOLD_FONT)
+        ];
+        let header = row![
+            opt_helpers::description_text(
+                "Create a rule from any of the windows managed by komorebi, with the exact exe, \
+                title, class or path of the window.",
+            )
+            .width(Fill),
+            button("Refresh")
+                .on_press(Message::RefreshWindows)
+                .style(button::secondary),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let added = self.added_rule_to.as_ref().map(|location| {
+            text!("Rule added to '{location}'. Save the config to apply it.").style(text::success)
+        });
+
+        let windows: Element<_> = if self.komorebi_state.is_none() {
+            opt_helpers::description_text(
+                "Komorebi hasn't reported its state yet. Make sure it is running.",
+            )
+            .into()
+        } else if self.windows.is_empty() {
+            opt_helpers::description_text("There are no windows managed by komorebi.").into()
+        } else {
+            let windows = self
+                .windows
+                .iter()
+                .enumerate()
+                .fold(column![].spacing(10), |col, (idx, window)| {
+                    col.push(self.window_view(idx, window, config))
+                });
+            scrollable(
+                windows
+                    .width(Fill)
+                    .padding(padding::top(10).bottom(10).right(20)),
+            )
+            .into()
+        };
+
+        column![title, rule::horizontal(2.0), header]
+            .push(added)
+            .push(windows)
+            .spacing(10)
+            .width(Fill)
+            .height(Fill)
+            .into()
+    }
+
+    fn window_view<'a>(
+        &'a self,
+        idx: usize,
+        window: &'a LiveWindow,
+        config: &'a StaticConfig,
+    ) -> Element<'a, Message> {
+        let kind = if window.floating {
+            "Floating"
+        } else {
+            "Managed"
+        };
+        let header = row![
+            text(&window.properties.exe).font(*BOLD_FONT).width(Fill),
+            opt_helpers::to_description_text(text!(
+                "{kind} on Monitor [{}] > Workspace [{}]",
+                window.monitor,
+                window.workspace
+            )),
+        ]
+        .spacing(10)
+        .align_y(Center);
+        let properties =
+            APPLICATION_IDENTIFIER_OPTIONS
+                .iter()
+                .fold(column![].spacing(5), |col, kind| {
+                    let value = window.properties.property(kind);
+                    col.push(
+                        row![
+                            text!("{kind}:").width(50),
+                            text(value).width(Fill),
+                            button_with_icon(icons::plus(), "Rule")
+                                .on_press_maybe(
+                                    (!value.is_empty()).then_some(Message::CreateRule(idx, *kind)),
+                                )
+                                .style(button::secondary),
+                        ]
+                        .spacing(10)
+                        .align_y(Center),
+                    )
+                });
+        let new_rule = self
+            .new_rule
+            .as_ref()
+            .filter(|new_rule| new_rule.window == idx)
+            .map(|new_rule| new_rule_view(new_rule, config));
+        opt_helpers::opt_box(column![header, properties].push(new_rule).spacing(10)).into()
+    }
+
+    /// Gets the windows from the last state reported by komorebi, with their properties.
+    fn refresh_windows(&mut self) {
+        self.windows = self
+            .komorebi_state
+            .as_deref()
+            .map(live_windows)
+            .unwrap_or_default();
+        self.new_rule = None;
+        self.added_rule_to = None;
+    }
+
     pub fn goto_start_screen(&mut self) {
         self.screen = Screen::Main;
     }
 }
+
+fn new_rule_view<'a>(new_rule: &'a NewRule, config: &'a StaticConfig) -> Element<'a, Message> {
+    let targets = lint::rule_lists(config)
+        .into_iter()
+        .map(|(_, location)| location)
+        .collect::<Vec<_>>();
+    let fields = row![
+        pick_list(
+            &APPLICATION_IDENTIFIER_OPTIONS[..],
+            Some(new_rule.rule.kind),
+            Message::ChangeNewRuleKind,
+        ),
+        pick_list(
+            &MATCHING_STRATEGY_OPTIONS[..],
+            new_rule.rule.matching_strategy.as_ref(),
+            Message::ChangeNewRuleMatchingStrategy,
+        ),
+        widget::input("", &new_rule.rule.id, Message::ChangeNewRuleId, None),
+    ]
+    .spacing(10)
+    .align_y(Center);
+    let error = regex_error(&new_rule.rule.id, new_rule.rule.matching_strategy.as_ref());
+    let target = row![
+        text("Add to:"),
+        pick_list(
+            targets,
+            new_rule.target.clone(),
+            Message::ChangeNewRuleTarget
+        )
+        .placeholder("Choose a rules list...")
+        .width(Fill),
+        button_with_icon(icons::plus(), "Add").on_press_maybe(
+            (new_rule.target.is_some() && !new_rule.rule.id.is_empty() && error.is_none())
+                .then_some(Message::AddNewRule)
+        ),
+        button("Cancel")
+            .on_press(Message::CancelNewRule)
+            .style(button::secondary),
+    ]
+    .spacing(10)
+    .align_y(Center);
+    column![rule::horizontal(2.0), text("New rule:"), fields]
+        .push(error.map(|error| text(error).size(12).style(text::danger)))
+        .push(target)
+        .spacing(10)
+        .into()
+}
+
+/// The strategy suggested for new rules of `kind`. Titles usually change while the window is
+/// open, so they are better matched by a part of them.
+fn suggested_strategy(kind: &ApplicationIdentifier) -> MatchingStrategy {
+    match kind {
+        ApplicationIdentifier::Title => MatchingStrategy::Contains,
+        ApplicationIdentifier::Exe | ApplicationIdentifier::Class | ApplicationIdentifier::Path => {
+            MatchingStrategy::Equals
+        }
+    }
+}
+
+/// All the managed and floating windows on `state`, skipping the ones that don't exist anymore.
+fn live_windows(state: &komorebi_client::State) -> Vec<LiveWindow> {
+    with_properties(window_handles(state), Native::window_properties)
+}
+
+/// The monitor, workspace and handle of each managed and floating window on `state`, and whether
+/// it is floating.
+fn window_handles(state: &komorebi_client::State) -> Vec<(usize, usize, isize, bool)> {
+    let mut windows = Vec::new();
+    for (m, monitor) in state.monitors.elements().iter().enumerate() {
+        for (w, workspace) in monitor.workspaces().iter().enumerate() {
+            let managed = workspace
+                .containers()
+                .iter()
+                .chain(workspace.monocle_container.as_ref())
+                .flat_map(|container| container.windows().iter())
+                .chain(workspace.maximized_window.as_ref())
+                .map(|window| (window, false));
+            let floating = workspace
+                .floating_windows()
+                .iter()
+                .map(|window| (window, true));
+            windows.extend(
+                managed
+                    .chain(floating)
+                    .map(|(window, floating)| (m, w, window.hwnd, floating)),
+            );
+        }
+    }
+    windows
+}
+
+/// The windows with the `handles` that still have some `properties`.
+fn with_properties(
+    handles: Vec<(usize, usize, isize, bool)>,
+    properties: impl Fn(isize) -> Option<matching::Window>,
+) -> Vec<LiveWindow> {
+    handles
+        .into_iter()
+        .filter_map(|(monitor, workspace, hwnd, floating)| {
+            Some(LiveWindow {
+                monitor,
+                workspace,
+                floating,
+                properties: properties(hwnd)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::screen::rules;
+
+    fn window(exe: &str) -> matching::Window {
+        matching::Window {
+            exe: exe.into(),
+            title: format!("{exe} window"),
+            class: "Class".into(),
+            path: format!("C:\\{exe}"),
+        }
+    }
+
+    fn new_rule(id: &str, matching_strategy: MatchingStrategy) -> NewRule {
+        NewRule {
+            window: 0,
+            rule: IdWithIdentifier {
+                kind: ApplicationIdentifier::Exe,
+                id: id.into(),
+                matching_strategy: Some(matching_strategy),
+            },
+            target: Some(Location::Rules(rules::Screen::IgnoreRules)),
+        }
+    }
+
+    #[test]
+    fn windows_that_were_closed_are_skipped() {
+        let handles = vec![(0, 0, 1, false), (0, 1, 2, false), (1, 0, 3, true)];
+        let windows = with_properties(handles, |hwnd| {
+            (hwnd != 2).then(|| window(&format!("app{hwnd}.exe")))
+        });
+
+        let found = windows
+            .iter()
+            .map(|w| {
+                (
+                    w.monitor,
+                    w.workspace,
+                    w.floating,
+                    w.properties.exe.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![(0, 0, false, "app1.exe"), (1, 0, true, "app3.exe")]
+        );
+    }
+
+    #[test]
+    fn titles_are_matched_by_a_part_of_them() {
+        assert_eq!(
+            suggested_strategy(&ApplicationIdentifier::Title),
+            MatchingStrategy::Contains
+        );
+        for kind in [
+            ApplicationIdentifier::Exe,
+            ApplicationIdentifier::Class,
+            ApplicationIdentifier::Path,
+        ] {
+            assert_eq!(suggested_strategy(&kind), MatchingStrategy::Equals);
+        }
+    }
+
+    #[test]
+    fn invalid_regexes_are_not_added() {
+        let mut live_debug = LiveDebug {
+            new_rule: Some(new_rule("app(", MatchingStrategy::Regex)),
+            ..Default::default()
+        };
+        let (action, _) = live_debug.update(Message::AddNewRule);
+        assert!(matches!(action, Action::None));
+        assert!(live_debug.new_rule.is_some());
+
+        live_debug.new_rule = Some(new_rule("app(", MatchingStrategy::Equals));
+        let (action, _) = live_debug.update(Message::AddNewRule);
+        assert!(matches!(
+            action,
+            Action::AddRule(_, MatchingRule::Simple(rule)) if rule.id == "app("
+        ));
+        assert!(live_debug.new_rule.is_none());
+    }
+}
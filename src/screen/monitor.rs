@@ -1,13 +1,17 @@
+use super::rule;
 use super::workspace::{self, WorkspaceScreen};
 
 use crate::{
     config::{DEFAULT_CONFIG, DEFAULT_MONITOR_CONFIG, DEFAULT_WORKSPACE_CONFIG},
-    monitors::TitleLink,
+    lint,
+    monitors::{self, TitleLink},
     screen::{
-        Modal,
+        Modal, general,
+        palette::SearchOption,
         wallpaper::{self, WallpaperScreen},
     },
     widget::opt_helpers::{self, DisableArgs, description_text as t},
+    widget::path_input::FoundFiles,
 };
 
 use std::collections::HashMap;
@@ -24,6 +28,35 @@ use iced::{
 use komorebi_client::{FloatingLayerBehaviour, MonitorConfig, Rect, Wallpaper, WorkspaceConfig};
 use lazy_static::lazy_static;
 
+/// The options of a monitor, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new("Container Padding", "Container padding (default: global)"),
+    SearchOption::new("Workspace Padding", "Workspace padding (default: global)"),
+    SearchOption::new(
+        "Window Based Work Area Offset",
+        "Window based work area offset (default: global)",
+    )
+    .children(general::OFFSET_CHILDREN),
+    SearchOption::new(
+        "Window Based Work Area Offset Limit",
+        "Open window limit after which the window based work area offset will no longer be applied (default: 1)",
+    ),
+    SearchOption::new(
+        "Work Area Offset",
+        "Monitor-specific work area offset (default: global)",
+    )
+    .children(general::OFFSET_CHILDREN),
+    SearchOption::new(
+        "Floating Layer Behaviour",
+        "Determines what happens to a new window when on the `FloatingLayer` (default: global)",
+    ),
+    SearchOption::new(
+        "Wallpaper",
+        "Specify a wallpaper for this monitor. (default: None)",
+    ),
+    SearchOption::new("Workspaces", ""),
+];
+
 lazy_static! {
     pub static ref DEFAULT_MONITOR: Monitor = Default::default();
 }
@@ -369,20 +402,30 @@ impl Monitor {
         Task::none()
     }
 
-    pub fn view<'a>(&'a self, config: &'a MonitorConfig) -> MonitorView<'a, Message> {
+    pub fn view<'a>(
+        &'a self,
+        config: &'a MonitorConfig,
+        problems: &[lint::Problem],
+        found_files: &FoundFiles,
+    ) -> MonitorView<'a, Message> {
         match self.sub_screen {
             SubScreen::Monitor => self.monitor_view(config),
-            SubScreen::MonitorWallpaper => self.monitor_wallpaper_view(config.wallpaper.as_ref()),
+            SubScreen::MonitorWallpaper => {
+                self.monitor_wallpaper_view(config.wallpaper.as_ref(), found_files)
+            }
             SubScreen::Workspaces => self.workspaces_view(&config.workspaces),
             SubScreen::Workspace(idx) | SubScreen::WorkspaceWallpaper(idx) => {
-                self.workspace_view(idx, &config.workspaces[idx])
+                self.workspace_view(idx, &config.workspaces[idx], problems, found_files)
             }
             SubScreen::WorkspaceRules(idx) => {
-                self.workspace_rules_view(idx, &config.workspaces[idx])
-            }
-            SubScreen::InitialWorkspaceRules(idx) => {
-                self.initial_workspace_rules_view(idx, &config.workspaces[idx])
+                self.workspace_rules_view(idx, &config.workspaces[idx], problems, found_files)
             }
+            SubScreen::InitialWorkspaceRules(idx) => self.initial_workspace_rules_view(
+                idx,
+                &config.workspaces[idx],
+                problems,
+                found_files,
+            ),
         }
     }
 
@@ -587,10 +630,14 @@ impl Monitor {
     pub fn monitor_wallpaper_view<'a>(
         &'a self,
         wp_config: Option<&'a Wallpaper>,
+        found_files: &FoundFiles,
     ) -> MonitorView<'a, Message> {
         let title = self.get_sub_section_title(None);
         if let Some(wp_config) = wp_config {
-            let wp = self.wallpaper.view(wp_config).map(Message::Wallpaper);
+            let wp = self
+                .wallpaper
+                .view(wp_config, found_files)
+                .map(Message::Wallpaper);
             if let Some(modal) = wp.modal {
                 MonitorView::new(title, vec![wp.element]).modal(modal.element, modal.close_message)
             } else {
@@ -630,10 +677,13 @@ impl Monitor {
         &'a self,
         idx: usize,
         workspace: &'a WorkspaceConfig,
+        problems: &[lint::Problem],
+        found_files: &FoundFiles,
     ) -> MonitorView<'a, Message> {
         let title = self.get_sub_section_title(Some(workspace));
+        let findings = workspace::RuleFindings::new(problems, self.index, idx);
         let ws_view = workspace
-            .view(&self.workspaces[&idx])
+            .view(&self.workspaces[&idx], findings, found_files)
             .map(move |m| Message::Workspace(idx, m));
         let contents = vec![ws_view.element];
 
@@ -648,11 +698,14 @@ impl Monitor {
         &'a self,
         idx: usize,
         workspace: &'a WorkspaceConfig,
+        problems: &[lint::Problem],
+        found_files: &FoundFiles,
     ) -> MonitorView<'a, Message> {
         let title = self.get_sub_section_title(Some(workspace));
+        let findings = workspace::RuleFindings::new(problems, self.index, idx);
         let contents = vec![
             workspace
-                .view(&self.workspaces[&idx])
+                .view(&self.workspaces[&idx], findings, found_files)
                 .map(move |m| Message::Workspace(idx, m))
                 .element,
         ];
@@ -664,11 +717,14 @@ impl Monitor {
         &'a self,
         idx: usize,
         workspace: &'a WorkspaceConfig,
+        problems: &[lint::Problem],
+        found_files: &FoundFiles,
     ) -> MonitorView<'a, Message> {
         let title = self.get_sub_section_title(Some(workspace));
+        let findings = workspace::RuleFindings::new(problems, self.index, idx);
         let contents = vec![
             workspace
-                .view(&self.workspaces[&idx])
+                .view(&self.workspaces[&idx], findings, found_files)
                 .map(move |m| Message::Workspace(idx, m))
                 .element,
         ];
@@ -789,12 +845,25 @@ impl Monitor {
     }
 
     pub fn set_subscreen(&mut self, sub_screen: SubScreen) -> Task<Message> {
-        if let SubScreen::Workspace(idx) = &sub_screen {
-            self.workspaces.entry(*idx).or_default().screen = workspace::Screen::Workspace;
+        match &sub_screen {
+            SubScreen::Workspace(idx) => {
+                self.workspaces.entry(*idx).or_default().screen = workspace::Screen::Workspace;
+            }
+            SubScreen::WorkspaceRules(idx) => {
+                let workspace = self.workspaces.entry(*idx).or_default();
+                workspace.rule = rule::Rule::new();
+                workspace.screen = workspace::Screen::WorkspaceRules;
+            }
+            SubScreen::InitialWorkspaceRules(idx) => {
+                let workspace = self.workspaces.entry(*idx).or_default();
+                workspace.rule = rule::Rule::new();
+                workspace.screen = workspace::Screen::InitialWorkspaceRules;
+            }
+            _ => {}
         }
         self.sub_screen = sub_screen;
         operation::scroll_to(
-            Id::new("monitors_scrollable"),
+            Id::new(monitors::SCROLLABLE_ID),
             AbsoluteOffset { x: 0.0, y: 0.0 },
         )
     }
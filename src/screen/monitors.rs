@@ -6,7 +6,10 @@ use super::{
 use crate::{
     BOLD_FONT,
     config::{DEFAULT_MONITOR_CONFIG, DEFAULT_WORKSPACE_CONFIG},
+    lint,
+    platform::{Displays, Native},
     screen::View,
+    widget::path_input::FoundFiles,
     widget::{
         icons, monitors_viewer,
         opt_helpers::{self, DisableArgs},
@@ -23,6 +26,9 @@ use iced::{
 };
 use komorebi_client::{MonitorConfig, Rect};
 
+/// Id of the scrollable with the contents of the monitors screen.
+pub const SCROLLABLE_ID: &str = "monitors_scrollable";
+
 #[derive(Clone, Debug)]
 pub enum Message {
     ConfigMonitor(usize),
@@ -139,15 +145,13 @@ impl Monitors {
             }
             Message::DeleteMonitor(idx) => {
                 monitors_config.remove(idx);
-                if idx < self.monitors.len() - 1 {
-                    for i in (self.monitors.len() - 1)..(idx + 1) {
-                        if let Some(mut m) = self.monitors.remove(&i) {
-                            m.index = i - 1;
-                            self.monitors.insert(i - 1, m);
-                        }
+                self.monitors.remove(&idx);
+                // Move the monitors after the removed one up by one
+                for i in (idx + 1)..=self.monitors.len() {
+                    if let Some(mut m) = self.monitors.remove(&i) {
+                        m.index = i - 1;
+                        self.monitors.insert(i - 1, m);
                     }
-                } else {
-                    self.monitors.remove(&idx);
                 }
             }
             Message::AddMonitorUp(idx) => {
@@ -191,7 +195,7 @@ impl Monitors {
                 let mut previous_m = self.monitors.insert(
                     idx + 1,
                     monitor::Monitor {
-                        index: idx,
+                        index: idx + 1,
                         workspaces: HashMap::from([(0, workspace::Workspace::new(0))]),
                         ..Default::default()
                     },
@@ -296,6 +300,8 @@ impl Monitors {
         monitors_config: &'a [MonitorConfig],
         display_info: &'a HashMap<usize, DisplayInfo>,
         display_index_preferences: &'a Option<HashMap<usize, String>>,
+        problems: &[lint::Problem],
+        found_files: &FoundFiles,
     ) -> View<'a, Message> {
         let mut main_modal = None;
         let mut main_title_spans = vec![
@@ -325,7 +331,7 @@ impl Monitors {
                 contents,
                 modal,
             } = monitor
-                .view(m_config)
+                .view(m_config, problems, found_files)
                 .map(move |message| Message::MonitorConfigChanged(monitor_idx, message));
             main_modal = modal;
             main_title_spans.extend(title);
@@ -381,7 +387,7 @@ impl Monitors {
         });
 
         col = col.push(dip);
-        let contents = scrollable(col).id(Id::new("monitors_scrollable"));
+        let contents = scrollable(col).id(Id::new(SCROLLABLE_ID));
 
         let show_monitors_display = container(
             checkbox("Show Monitors", !self.show_monitors_list)
@@ -532,40 +538,7 @@ pub struct DisplayInfo {
 pub fn get_display_information(
     display_index_preferences: &Option<HashMap<usize, String>>,
 ) -> HashMap<usize, DisplayInfo> {
-    let devices = std::thread::spawn(|| {
-        // Since `win32_display_data` has some `COMLibrary` thing going on it can't be called on
-        // the main thread otherwise it panics with:
-        // ```
-        // OleInitialize failed! Result was: `RPC_E_CHANGED_MODE`. Make sure other crates are not
-        // using multithreaded COM library on the same thread or disable drag and drop support.
-        // ```
-        // To prevent this we spawn a new thread and join it immediately to get the result.
-        komorebi_client::win32_display_data::connected_displays_all()
-            .flatten()
-            .map(|display| {
-                let path = display.device_path.clone();
-
-                let (_device, device_id) = if path.is_empty() {
-                    (String::from("UNKNOWN"), String::from("UNKNOWN"))
-                } else {
-                    let mut split: Vec<_> = path.split('#').collect();
-                    split.remove(0);
-                    split.remove(split.len() - 1);
-                    let device = split[0].to_string();
-                    let device_id = split.join("-");
-                    (device, device_id)
-                };
-
-                DisplayInfo {
-                    device_id,
-                    serial_number_id: display.serial_number_id,
-                    size: display.size.into(),
-                }
-            })
-            .collect::<Vec<_>>()
-    })
-    .join()
-    .unwrap_or_default();
+    let devices = Native::connected_displays();
 
     let configs_with_preference = display_index_preferences
         .as_ref()
@@ -600,3 +573,99 @@ pub fn get_display_information(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::{Harness, empty_config};
+
+    use komorebi_client::StaticConfig;
+
+    /// A config with one monitor for each name, which has a single workspace with that name.
+    fn config_with_monitors(names: &[&str]) -> StaticConfig {
+        let monitors = names
+            .iter()
+            .map(|name| MonitorConfig {
+                workspaces: vec![komorebi_client::WorkspaceConfig {
+                    name: name.to_string(),
+                    ..DEFAULT_WORKSPACE_CONFIG.clone()
+                }],
+                ..DEFAULT_MONITOR_CONFIG.clone()
+            })
+            .collect();
+        StaticConfig {
+            monitors: Some(monitors),
+            ..empty_config()
+        }
+    }
+
+    fn harness(names: &[&str]) -> Harness<Monitors> {
+        let config = config_with_monitors(names);
+        Harness::new(Monitors::new(&config), config)
+    }
+
+    fn workspace_names(config: &StaticConfig) -> Vec<&str> {
+        config
+            .monitors
+            .iter()
+            .flatten()
+            .map(|monitor| monitor.workspaces[0].name.as_str())
+            .collect()
+    }
+
+    /// Checks that there is one monitor screen for each monitor config, with the right index.
+    fn assert_indices(ui: &Harness<Monitors>) {
+        let count = ui.state.monitors.as_ref().map_or(0, Vec::len);
+        assert_eq!(ui.screen.monitors.len(), count);
+        for idx in 0..count {
+            assert_eq!(ui.screen.monitors.get(&idx).map(|m| m.index), Some(idx));
+        }
+    }
+
+    #[test]
+    fn deleting_a_monitor_moves_the_next_ones_up() {
+        let mut ui = harness(&["A", "B", "C"]);
+        ui.send([Message::DeleteMonitor(0)]);
+        assert_eq!(workspace_names(&ui.state), ["B", "C"]);
+        assert_indices(&ui);
+
+        ui.send([Message::DeleteMonitor(1)]);
+        assert_eq!(workspace_names(&ui.state), ["B"]);
+        assert_indices(&ui);
+    }
+
+    #[test]
+    fn adding_monitors_keeps_the_indices() {
+        let mut ui = harness(&["A", "B"]);
+        ui.send([Message::AddMonitorDown(0), Message::AddMonitorUp(0)]);
+        let default_name = DEFAULT_WORKSPACE_CONFIG.name.as_str();
+        assert_eq!(
+            workspace_names(&ui.state),
+            [default_name, "A", default_name, "B"]
+        );
+        assert_indices(&ui);
+    }
+
+    #[test]
+    fn moving_monitors_wraps_around() {
+        let mut ui = harness(&["A", "B", "C"]);
+        ui.send([Message::MoveDownMonitor(0)]);
+        assert_eq!(workspace_names(&ui.state), ["B", "A", "C"]);
+
+        ui.send([Message::MoveUpMonitor(0)]);
+        assert_eq!(workspace_names(&ui.state), ["C", "A", "B"]);
+        assert_indices(&ui);
+    }
+
+    #[test]
+    fn clicking_a_monitor_on_the_list_opens_it() {
+        let mut ui = harness(&["A", "B"]);
+        ui.click("Show Monitors");
+        assert!(ui.screen.show_monitors_list);
+
+        ui.click("Monitor [1] - [Display Not Found]");
+        assert_eq!(ui.screen.monitor_to_config, Some(1));
+        assert!(!ui.contains("Display Index Preferences"));
+    }
+}
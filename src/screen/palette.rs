@@ -0,0 +1,587 @@
+use super::{Screen, monitor};
+
+use crate::{
+    lint,
+    whkd::{SEPARATOR, Whkdrc},
+    widget::{self, modal, opt_helpers},
+};
+
+use iced::{
+    Element, Fill, Shrink, Subscription, Task, keyboard, padding,
+    widget::{
+        Id, button, column, container, operation, row, scrollable, scrollable::RelativeOffset,
+        space, text, text_input,
+    },
+};
+use komorebi_client::StaticConfig;
+
+const INPUT_ID: &str = "PALETTE_INPUT";
+const RESULTS_ID: &str = "PALETTE_RESULTS";
+/// The maximum number of results shown at once.
+const MAX_RESULTS: usize = 50;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ChangedQuery(String),
+    SelectPrevious,
+    SelectNext,
+    Select(usize),
+    Submit,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+pub enum Action {
+    None,
+    GoTo(Target),
+    Close,
+}
+
+/// An option that a screen declares so it can be searched for on the palette.
+#[derive(Debug, Clone, Copy)]
+pub struct SearchOption {
+    /// The label of the option on the screen, which its widget id is made from.
+    pub name: &'static str,
+    /// The first paragraph of the description of the option, if it has one.
+    pub description: &'static str,
+    /// The options shown inside of this one once it is expanded.
+    pub children: &'static [SearchOption],
+    /// Whether the option is only shown when showing the advanced options.
+    pub advanced: bool,
+}
+
+impl SearchOption {
+    pub const fn new(name: &'static str, description: &'static str) -> Self {
+        SearchOption {
+            name,
+            description,
+            children: &[],
+            advanced: false,
+        }
+    }
+
+    pub const fn children(mut self, children: &'static [SearchOption]) -> Self {
+        self.children = children;
+        self
+    }
+
+    pub const fn advanced(mut self) -> Self {
+        self.advanced = true;
+        self
+    }
+}
+
+/// Where the item of an entry can be found.
+#[derive(Debug, Clone)]
+pub enum Target {
+    /// The option with `name` on `screen`, or on the `monitor` sub screen when it is an option of
+    /// a monitor or workspace.
+    Option {
+        screen: Screen,
+        monitor: Option<(usize, monitor::SubScreen)>,
+        name: &'static str,
+    },
+    /// A rule, on the rules list at this location.
+    Rule(lint::Location),
+    /// The binding at `idx` on the bindings or app bindings `screen`.
+    Binding { screen: Screen, idx: usize },
+}
+
+/// Something that can be searched for on the palette.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub title: String,
+    /// Where the entry is, like `General > Border`.
+    pub location: String,
+    pub description: Option<String>,
+    pub target: Target,
+}
+
+#[derive(Debug)]
+pub struct Palette {
+    query: String,
+    entries: Vec<Entry>,
+    /// The indices of the entries matching the query, best match first.
+    results: Vec<usize>,
+    selected: usize,
+}
+
+impl Palette {
+    /// Creates a palette to search `entries`, together with the task that focuses its input.
+    pub fn new(entries: Vec<Entry>) -> (Self, Task<Message>) {
+        let mut palette = Palette {
+            query: String::new(),
+            entries,
+            results: Vec::new(),
+            selected: 0,
+        };
+        palette.search();
+        (palette, operation::focus(INPUT_ID))
+    }
+
+    pub fn update(&mut self, message: Message) -> (Action, Task<Message>) {
+        match message {
+            Message::ChangedQuery(query) => {
+                self.query = query;
+                self.search();
+                (
+                    Action::None,
+                    operation::snap_to(RESULTS_ID, RelativeOffset { x: 0.0, y: 0.0 }),
+                )
+            }
+            Message::SelectPrevious => {
+                self.selected = self.selected.saturating_sub(1);
+                (Action::None, self.snap_to_selected())
+            }
+            Message::SelectNext => {
+                if self.selected + 1 < self.results.len() {
+                    self.selected += 1;
+                }
+                (Action::None, self.snap_to_selected())
+            }
+            Message::Select(idx) => {
+                self.selected = idx;
+                (self.go_to_selected(), Task::none())
+            }
+            Message::Submit => (self.go_to_selected(), Task::none()),
+            Message::Close => (Action::Close, Task::none()),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let input = text_input("Search options, bindings and rules...", &self.query)
+            .id(INPUT_ID)
+            .on_input(Message::ChangedQuery)
+            .on_submit(Message::Submit)
+            .padding(10)
+            .size(18);
+
+        let results: Element<_> = if self.results.is_empty() {
+            container(opt_helpers::description_text("Nothing found."))
+                .padding(10)
+                .into()
+        } else {
+            let results = self.results.iter().enumerate().fold(
+                column![].spacing(2).padding(padding::right(20)),
+                |col, (idx, entry_idx)| {
+                    let entry = &self.entries[*entry_idx];
+                    let mut content = column![
+                        row![
+                            text(&entry.title),
+                            space::horizontal(),
+                            opt_helpers::description_text(&entry.location),
+                        ]
+                        .spacing(10)
+                    ];
+                    if let Some(description) = entry
+                        .description
+                        .as_deref()
+                        .and_then(|description| description.lines().next())
+                    {
+                        content = content.push(opt_helpers::description_text(description).size(12));
+                    }
+                    let selected = idx == self.selected;
+                    col.push(
+                        container(
+                            button(content)
+                                .on_press(Message::Select(idx))
+                                .style(move |t, s| {
+                                    if selected {
+                                        button::primary(t, s)
+                                    } else {
+                                        button::subtle(t, s)
+                                    }
+                                })
+                                .width(Fill),
+                        )
+                        .id(result_id(idx)),
+                    )
+                },
+            );
+            scrollable(results).id(RESULTS_ID).height(400).into()
+        };
+
+        container(column![input, results].spacing(10))
+            .padding(20)
+            .max_width(850.0)
+            .center(Fill)
+            .height(Shrink)
+            .style(modal::default)
+            .into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        // The keys are handled even if the focused input captured them, so the results can be
+        // navigated while typing.
+        iced::event::listen_with(|event, _status, _id| match event {
+            iced::Event::Keyboard(keyboard::Event::KeyPressed {
+                key: keyboard::Key::Named(named),
+                ..
+            }) => match named {
+                keyboard::key::Named::ArrowUp => Some(Message::SelectPrevious),
+                keyboard::key::Named::ArrowDown => Some(Message::SelectNext),
+                keyboard::key::Named::Escape => Some(Message::Close),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    fn search(&mut self) {
+        let query = normalize(self.query.trim());
+        self.selected = 0;
+        if query.is_empty() {
+            self.results = (0..self.entries.len().min(MAX_RESULTS)).collect();
+            return;
+        }
+        let mut scored = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| {
+                // A match on the title always ranks above a match on the location or description.
+                let score = fuzzy_score(&query, &entry.title)
+                    .map(|score| score + 1000)
+                    .or_else(|| fuzzy_score(&query, &entry.location))
+                    .or_else(|| {
+                        entry
+                            .description
+                            .as_deref()
+                            .and_then(|description| fuzzy_score(&query, description))
+                    })?;
+                Some((idx, score))
+            })
+            .collect::<Vec<_>>();
+        // The sort is stable, so entries with the same score keep the order they have on screen.
+        scored.sort_by(|(_, a), (_, b)| b.cmp(a));
+        self.results = scored
+            .into_iter()
+            .take(MAX_RESULTS)
+            .map(|(idx, _)| idx)
+            .collect();
+    }
+
+    fn go_to_selected(&self) -> Action {
+        self.results.get(self.selected).map_or(Action::None, |idx| {
+            Action::GoTo(self.entries[*idx].target.clone())
+        })
+    }
+
+    fn snap_to_selected(&self) -> Task<Message> {
+        widget::scroll_into_view(Id::new(RESULTS_ID), result_id(self.selected))
+    }
+}
+
+/// Creates the entries for the `options` declared by some screen. The advanced options are only
+/// included with `show_advanced`, like on the screen itself.
+pub fn option_entries(
+    options: &'static [SearchOption],
+    location: &str,
+    screen: Screen,
+    monitor: Option<(usize, monitor::SubScreen)>,
+    show_advanced: bool,
+) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for option in options.iter().filter(|o| show_advanced || !o.advanced) {
+        let target = Target::Option {
+            screen: screen.clone(),
+            monitor: monitor.clone(),
+            name: option.name,
+        };
+        entries.push(Entry {
+            title: option.name.to_string(),
+            location: location.to_string(),
+            description: (!option.description.is_empty()).then(|| option.description.to_string()),
+            target: target.clone(),
+        });
+        // The children are only shown once their option is expanded, so they go to it.
+        entries.extend(option.children.iter().map(|child| Entry {
+            title: child.name.to_string(),
+            location: format!("{location} > {}", option.name),
+            description: (!child.description.is_empty()).then(|| child.description.to_string()),
+            target: target.clone(),
+        }));
+    }
+    entries
+}
+
+/// Creates the entries for all the rules on `config`.
+pub fn rule_entries(config: &StaticConfig) -> Vec<Entry> {
+    lint::rule_lists(config)
+        .into_iter()
+        .flat_map(|(rules, location)| {
+            rules.iter().flatten().map(move |rule| Entry {
+                title: lint::describe_rule(rule),
+                location: location.to_string(),
+                description: None,
+                target: Target::Rule(location.clone()),
+            })
+        })
+        .collect()
+}
+
+/// Creates the entries for all the bindings and app bindings on `whkdrc`.
+pub fn binding_entries(whkdrc: &Whkdrc) -> Vec<Entry> {
+    let bindings = whkdrc
+        .bindings
+        .iter()
+        .enumerate()
+        .map(|(idx, binding)| Entry {
+            title: binding.keys.join(SEPARATOR),
+            location: Screen::WhkdBindings.to_string(),
+            description: Some(binding.command.clone()),
+            target: Target::Binding {
+                screen: Screen::WhkdBindings,
+                idx,
+            },
+        });
+    let app_bindings =
+        whkdrc
+            .app_bindings
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, (keys, bindings))| {
+                bindings.iter().map(move |binding| Entry {
+                    title: keys.join(SEPARATOR),
+                    location: format!(
+                        "{} > {}",
+                        Screen::WhkdAppBindings,
+                        binding.process_name.as_deref().unwrap_or("Default")
+                    ),
+                    description: Some(binding.command.clone()),
+                    target: Target::Binding {
+                        screen: Screen::WhkdAppBindings,
+                        idx,
+                    },
+                })
+            });
+    bindings.chain(app_bindings).collect()
+}
+
+/// Id of the widget of the result at `idx`.
+fn result_id(idx: usize) -> Id {
+    Id::from(format!("PALETTE_RESULT_{idx}"))
+}
+
+fn normalize(text: &str) -> Vec<char> {
+    text.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| if c == '_' { ' ' } else { c })
+        .collect()
+}
+
+/// Scores how well `query` matches `text`, or `None` if the characters of `query` don't show up
+/// on `text` in the same order. Matches that are consecutive or at the start of a word score
+/// higher.
+fn fuzzy_score(query: &[char], text: &str) -> Option<u32> {
+    let text = normalize(text);
+    let mut score = 0;
+    let mut chars = query.iter().peekable();
+    let mut previous_matched = false;
+    for (idx, c) in text.iter().enumerate() {
+        let Some(q) = chars.peek() else {
+            break;
+        };
+        if **q == *c {
+            chars.next();
+            score += 1;
+            if previous_matched {
+                score += 3;
+            }
+            if idx == 0 || !text[idx - 1].is_alphanumeric() {
+                score += 2;
+            }
+            previous_matched = true;
+        } else {
+            previous_matched = false;
+        }
+    }
+    chars.peek().is_none().then_some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::config::{DEFAULT_CONFIG, DEFAULT_MONITOR_CONFIG, DEFAULT_WORKSPACE_CONFIG};
+    use crate::screen::{
+        animation, border, general, monitor, rules, stackbar, theme, transparency, whkd, workspace,
+    };
+    use crate::testing::{Harness, TestScreen, empty_config};
+
+    use komorebi_client::{DefaultLayout, WorkspaceConfig};
+
+    const ALL_OPTIONS: &[&[SearchOption]] = &[
+        general::OPTIONS,
+        border::OPTIONS,
+        stackbar::OPTIONS,
+        transparency::OPTIONS,
+        animation::OPTIONS,
+        theme::OPTIONS,
+        rules::OPTIONS,
+        monitor::OPTIONS,
+        workspace::OPTIONS,
+        whkd::OPTIONS,
+        crate::settings::OPTIONS,
+    ];
+
+    #[test]
+    fn options_have_unique_names_on_each_screen() {
+        for options in ALL_OPTIONS {
+            let mut names = options.iter().map(|o| o.name).collect::<Vec<_>>();
+            names.sort_unstable();
+            let count = names.len();
+            names.dedup();
+            assert_eq!(names.len(), count, "duplicated option on {names:?}");
+        }
+    }
+
+    #[test]
+    fn children_go_to_the_option_they_are_in() {
+        let entries = option_entries(general::OPTIONS, "General", Screen::General, None, false);
+        let left = entries
+            .iter()
+            .find(|entry| entry.title == "left")
+            .expect("the offset sides are searchable");
+
+        assert_eq!(left.location, "General > Global Work Area Offset");
+        assert!(matches!(
+            left.target,
+            Target::Option {
+                name: "Global Work Area Offset",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn advanced_options_are_only_found_when_shown() {
+        let has_advanced = |show_advanced| {
+            option_entries(rules::OPTIONS, "Rules", Screen::Rules, None, show_advanced)
+                .iter()
+                .any(|entry| entry.title == "Layered Applications")
+        };
+
+        assert!(!has_advanced(false));
+        assert!(has_advanced(true));
+    }
+
+    /// The `options` that none of the `uis` show. The palette would go to them without anything to
+    /// scroll to.
+    fn hidden_options<S: TestScreen>(
+        options: &[SearchOption],
+        uis: &[Harness<S>],
+    ) -> Vec<&'static str> {
+        options
+            .iter()
+            .filter(|option| !uis.iter().any(|ui| ui.shows_option(option.name)))
+            .map(|option| option.name)
+            .collect()
+    }
+
+    #[test]
+    fn general_options_are_shown() {
+        let ui = Harness::new(general::General::default(), DEFAULT_CONFIG.clone());
+        assert_eq!(hidden_options(general::OPTIONS, &[ui]), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn border_options_are_shown() {
+        let ui = Harness::new(border::Border::default(), DEFAULT_CONFIG.clone());
+        assert_eq!(hidden_options(border::OPTIONS, &[ui]), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn stackbar_options_are_shown() {
+        let ui = Harness::new(stackbar::Stackbar::default(), empty_config());
+        assert_eq!(hidden_options(stackbar::OPTIONS, &[ui]), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn transparency_options_are_shown() {
+        let ui = Harness::new(transparency::Transparency::default(), empty_config());
+        assert_eq!(
+            hidden_options(transparency::OPTIONS, &[ui]),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn animation_options_are_shown() {
+        let ui = Harness::new(animation::Animation, empty_config());
+        assert_eq!(
+            hidden_options(animation::OPTIONS, &[ui]),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn theme_options_are_shown() {
+        // The colours are only shown once a theme is picked.
+        let mut ui = Harness::new(theme::Theme::default(), empty_config());
+        ui.send([theme::Message::ChangeThemeType(Some(
+            theme::ThemeType::Catppuccin,
+        ))]);
+        assert_eq!(hidden_options(theme::OPTIONS, &[ui]), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn rules_options_are_shown() {
+        let ui = Harness::new(rules::Rules::default(), empty_config());
+        assert_eq!(hidden_options(rules::OPTIONS, &[ui]), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn monitor_options_are_shown() {
+        let ui = Harness::new(monitor::Monitor::default(), DEFAULT_MONITOR_CONFIG.clone());
+        assert_eq!(hidden_options(monitor::OPTIONS, &[ui]), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn workspace_options_are_shown() {
+        // Some layout options are only shown for their layout.
+        let uis = [
+            None,
+            Some(DefaultLayout::Grid),
+            Some(DefaultLayout::Scrolling),
+        ]
+        .map(|layout| {
+            let config = WorkspaceConfig {
+                layout,
+                ..DEFAULT_WORKSPACE_CONFIG.clone()
+            };
+            Harness::new(workspace::Workspace::new(0), config)
+        });
+        assert_eq!(hidden_options(workspace::OPTIONS, &uis), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn whkd_options_are_shown() {
+        let ui = Harness::new(crate::whkd::Whkd::default(), ());
+        assert_eq!(hidden_options(whkd::OPTIONS, &[ui]), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn settings_options_are_shown() {
+        let ui = Harness::new(crate::settings::Settings::default(), ());
+        assert_eq!(
+            hidden_options(crate::settings::OPTIONS, &[ui]),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn titles_rank_above_descriptions() {
+        let (mut palette, _) = Palette::new(option_entries(
+            general::OPTIONS,
+            "General",
+            Screen::General,
+            None,
+            true,
+        ));
+        let _ = palette.update(Message::ChangedQuery("slow application".into()));
+
+        let first = &palette.entries[palette.results[0]];
+        assert_eq!(first.title, "Slow Application Compensation Time");
+    }
+}
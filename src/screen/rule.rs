@@ -1,22 +1,23 @@
+use crate::platform::{Clipboard, Native};
 use crate::widget::{self, button_with_icon, icons, opt_helpers};
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use iced::{
-    Center, Element, Fill, Right, Subscription, Task, Top, padding,
-    widget::{Row, button, column, container, pick_list, row, space, text, text_input},
+    Border, Center, Element, Fill, Right, Subscription, Task, Top, padding,
+    widget::{Column, button, column, container, pick_list, row, space, text, text_input},
 };
 use komorebi_client::{ApplicationIdentifier, IdWithIdentifier, MatchingRule, MatchingStrategy};
 use lazy_static::lazy_static;
 
 lazy_static! {
-    static ref APPLICATION_IDENTIFIER_OPTIONS: [ApplicationIdentifier; 4] = [
+    pub static ref APPLICATION_IDENTIFIER_OPTIONS: [ApplicationIdentifier; 4] = [
         ApplicationIdentifier::Exe,
         ApplicationIdentifier::Title,
         ApplicationIdentifier::Class,
         ApplicationIdentifier::Path,
     ];
-    static ref MATCHING_STRATEGY_OPTIONS: [MatchingStrategy; 10] = [
+    pub static ref MATCHING_STRATEGY_OPTIONS: [MatchingStrategy; 10] = [
         MatchingStrategy::Legacy,
         MatchingStrategy::Equals,
         MatchingStrategy::StartsWith,
@@ -56,6 +57,17 @@ pub enum Message {
     CopyNewRule,
     PasteRule,
     ClipboardHasRule(bool),
+
+    ChangeRegexSample(RuleLine, String),
+}
+
+/// A line of a rule, which is one of its identifiers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RuleLine {
+    /// A line of the new rule, by its index.
+    New(usize),
+    /// A line of an existing rule, by the index of the rule and the index of the line on it.
+    Existing(usize, usize),
 }
 
 #[derive(Clone, Debug)]
@@ -69,6 +81,11 @@ pub struct Rule {
     pub new_rule: Vec<IdWithIdentifier>,
     pub rules_editing: HashSet<usize>,
     pub clipboard_has_rule: bool,
+    /// The texts typed to test the regex of each line.
+    pub regex_samples: HashMap<RuleLine, String>,
+    /// The ids typed on the lines of existing rules that aren't valid regexes, which are kept here
+    /// instead of on the config until they are.
+    pub pending_ids: HashMap<RuleLine, String>,
 }
 
 impl Rule {
@@ -78,6 +95,8 @@ impl Rule {
             new_rule: Vec::new(),
             rules_editing: HashSet::new(),
             clipboard_has_rule: false,
+            regex_samples: HashMap::new(),
+            pending_ids: HashMap::new(),
         }
     }
 
@@ -110,6 +129,13 @@ impl Rule {
                 self.show_new_rule = !self.show_new_rule;
             }
             Message::AddNewRule => {
+                if self
+                    .new_rule
+                    .iter()
+                    .any(|rule| regex_error(&rule.id, rule.matching_strategy.as_ref()).is_some())
+                {
+                    return (Action::None, Task::none());
+                }
                 if self.new_rule.len() == 1 {
                     if let Some(rules) = rules {
                         let rule = MatchingRule::Simple(self.new_rule.remove(0));
@@ -138,6 +164,8 @@ impl Rule {
                     self.rules_editing.insert(idx);
                 } else {
                     self.rules_editing.remove(&idx);
+                    self.pending_ids
+                        .retain(|line, _| !matches!(line, RuleLine::Existing(i, _) if *i == idx));
                 }
             }
             Message::ChangeRuleKind(idx, sub_idx, kind) => {
@@ -155,30 +183,26 @@ impl Rule {
                 }
             }
             Message::ChangeRuleId(idx, sub_idx, id) => {
-                if let Some(rule) = rules.as_mut().and_then(|rls| rls.get_mut(idx)) {
-                    match rule {
-                        MatchingRule::Simple(rule) => {
-                            rule.id = id;
-                        }
-                        MatchingRule::Composite(rules) => {
-                            if let Some(rule) = rules.get_mut(sub_idx) {
-                                rule.id = id;
-                            }
-                        }
+                if let Some(rule) = identifier_mut(rules, idx, sub_idx) {
+                    let line = RuleLine::Existing(idx, sub_idx);
+                    if regex_error(&id, rule.matching_strategy.as_ref()).is_some() {
+                        self.pending_ids.insert(line, id);
+                    } else {
+                        self.pending_ids.remove(&line);
+                        rule.id = id;
                     }
                 }
             }
             Message::ChangeRuleMatchingStrategy(idx, sub_idx, matching_strategy) => {
-                if let Some(rule) = rules.as_mut().and_then(|rls| rls.get_mut(idx)) {
-                    match rule {
-                        MatchingRule::Simple(rule) => {
-                            rule.matching_strategy = matching_strategy;
-                        }
-                        MatchingRule::Composite(rules) => {
-                            if let Some(rule) = rules.get_mut(sub_idx) {
-                                rule.matching_strategy = matching_strategy;
-                            }
-                        }
+                if let Some(rule) = identifier_mut(rules, idx, sub_idx) {
+                    // An id that isn't a valid regex can only be used with the new strategy if it
+                    // isn't the regex one.
+                    let line = RuleLine::Existing(idx, sub_idx);
+                    let id = self.pending_ids.get(&line).unwrap_or(&rule.id);
+                    if regex_error(id, matching_strategy.as_ref()).is_none() {
+                        rule.id = id.clone();
+                        rule.matching_strategy = matching_strategy;
+                        self.pending_ids.remove(&line);
                     }
                 }
             }
@@ -202,6 +226,7 @@ impl Rule {
                         MatchingRule::Simple(_) => rule,
                         MatchingRule::Composite(mut rls) => {
                             rls.remove(sub_idx);
+                            self.remove_lines(idx, Some(sub_idx));
                             if rls.len() == 1 {
                                 MatchingRule::Simple(rls.remove(0))
                             } else {
@@ -217,14 +242,19 @@ impl Rule {
                     && rules.get(idx).is_some()
                 {
                     rules.remove(idx);
+                    self.remove_lines(idx, None);
+                    self.rules_editing = std::mem::take(&mut self.rules_editing)
+                        .into_iter()
+                        .filter(|i| *i != idx)
+                        .map(|i| if i > idx { i - 1 } else { i })
+                        .collect();
                 }
-                self.rules_editing.remove(&idx);
             }
             Message::CopyRule(idx) => {
                 if let Some(rule) = rules.as_mut().and_then(|rls| rls.get_mut(idx))
                     && let Ok(rule_str) = serde_json::to_string_pretty(&rule)
                 {
-                    let _ = clipboard_win::set_clipboard_string(&rule_str);
+                    Native::set_text(&rule_str);
                 }
             }
             Message::CopyNewRule => {
@@ -235,11 +265,11 @@ impl Rule {
                 };
 
                 if let Ok(rule_str) = serde_json::to_string_pretty(&rule) {
-                    let _ = clipboard_win::set_clipboard_string(&rule_str);
+                    Native::set_text(&rule_str);
                 }
             }
             Message::PasteRule => {
-                if let Ok(content) = clipboard_win::get_clipboard_string()
+                if let Some(content) = Native::get_text()
                     && let Ok(rule) = serde_json::from_str::<MatchingRule>(&content)
                 {
                     match rule {
@@ -251,11 +281,24 @@ impl Rule {
             Message::ClipboardHasRule(has_rule) => {
                 self.clipboard_has_rule = has_rule;
             }
+            Message::ChangeRegexSample(line, sample) => {
+                self.regex_samples.insert(line, sample);
+            }
         }
         (Action::None, Task::none())
     }
 
     pub fn view<'a>(&'a self, rules: Option<&'a Vec<MatchingRule>>) -> Element<'a, Message> {
+        self.view_with_findings(rules, Vec::new())
+    }
+
+    /// Same as `view` but with a badge under each rule for each of its `findings`, given as the
+    /// index of the rule and a short summary.
+    pub fn view_with_findings<'a>(
+        &'a self,
+        rules: Option<&'a Vec<MatchingRule>>,
+        findings: Vec<(usize, String)>,
+    ) -> Element<'a, Message> {
         let add_new_rule_button = widget::button_with_icon(icons::plus(), text("Add New Rule"))
             .on_press(Message::ToggleShowNewRule)
             .style(button::secondary);
@@ -279,10 +322,17 @@ impl Rule {
                             } else {
                                 None
                             },
+                            &rule.id,
+                            self.regex_sample(RuleLine::New(idx)),
+                            move |v| Message::ChangeRegexSample(RuleLine::New(idx), v),
                         ))
                     });
+            let is_valid = self
+                .new_rule
+                .iter()
+                .all(|rule| regex_error(&rule.id, rule.matching_strategy.as_ref()).is_none());
             let add_rule_button = button_with_icon(icons::plus(), "Add")
-                .on_press(Message::AddNewRule)
+                .on_press_maybe(is_valid.then_some(Message::AddNewRule))
                 .width(77);
             let copy_button = button(icons::copy())
                 .on_press_maybe((!self.new_rule[0].id.is_empty()).then_some(Message::CopyNewRule))
@@ -317,6 +367,7 @@ impl Rule {
                     MatchingRule::Simple(rule) => col.push(
                         self.matching_rule_view(
                             idx,
+                            &findings,
                             column!["Match any window where:"]
                                 .push(rule_view(
                                     rule,
@@ -327,6 +378,11 @@ impl Rule {
                                     move |v| Message::ChangeRuleId(idx, 0, v),
                                     Message::ComposingAddToRule(idx),
                                     None,
+                                    self.rule_id(RuleLine::Existing(idx, 0), rule),
+                                    self.regex_sample(RuleLine::Existing(idx, 0)),
+                                    move |v| {
+                                        Message::ChangeRegexSample(RuleLine::Existing(idx, 0), v)
+                                    },
                                 ))
                                 .spacing(10)
                                 .into(),
@@ -335,6 +391,7 @@ impl Rule {
                     MatchingRule::Composite(rules) => col.push(
                         self.matching_rule_view(
                             idx,
+                            &findings,
                             rules
                                 .iter()
                                 .enumerate()
@@ -360,6 +417,14 @@ impl Rule {
                                             } else {
                                                 None
                                             },
+                                            self.rule_id(RuleLine::Existing(idx, i), r),
+                                            self.regex_sample(RuleLine::Existing(idx, i)),
+                                            move |v| {
+                                                Message::ChangeRegexSample(
+                                                    RuleLine::Existing(idx, i),
+                                                    v,
+                                                )
+                                            },
                                         ))
                                     },
                                 )
@@ -384,8 +449,14 @@ impl Rule {
     fn matching_rule_view<'a>(
         &'a self,
         idx: usize,
+        findings: &[(usize, String)],
         content: Element<'a, Message>,
     ) -> Element<'a, Message> {
+        let badges = findings
+            .iter()
+            .filter(|(rule, _)| *rule == idx)
+            .map(|(_, summary)| finding_badge(summary.clone()));
+        let content = Column::with_children(std::iter::once(content).chain(badges)).spacing(10);
         iced::widget::hover(
             container(opt_helpers::opt_box(content))
                 .max_width(685 + 90)
@@ -423,11 +494,41 @@ impl Rule {
         )
     }
 
+    /// The id to show on the `line` of the existing `rule`, which is the one being typed if it
+    /// isn't a valid regex yet.
+    fn rule_id<'a>(&'a self, line: RuleLine, rule: &'a IdWithIdentifier) -> &'a str {
+        self.pending_ids.get(&line).unwrap_or(&rule.id)
+    }
+
+    /// Drops the regex samples and pending ids of the removed rule `idx`, or only of its line
+    /// `sub_idx` if given, and moves the ones of the following lines so they stay on their lines.
+    fn remove_lines(&mut self, idx: usize, sub_idx: Option<usize>) {
+        let reindex = |line: RuleLine| match (line, sub_idx) {
+            (RuleLine::Existing(i, _), None) if i == idx => None,
+            (RuleLine::Existing(i, j), None) if i > idx => Some(RuleLine::Existing(i - 1, j)),
+            (RuleLine::Existing(i, j), Some(removed)) if i == idx && j == removed => None,
+            (RuleLine::Existing(i, j), Some(removed)) if i == idx && j > removed => {
+                Some(RuleLine::Existing(i, j - 1))
+            }
+            _ => Some(line),
+        };
+        for lines in [&mut self.regex_samples, &mut self.pending_ids] {
+            *lines = std::mem::take(lines)
+                .into_iter()
+                .filter_map(|(line, value)| Some((reindex(line)?, value)))
+                .collect();
+        }
+    }
+
+    fn regex_sample(&self, line: RuleLine) -> &str {
+        self.regex_samples.get(&line).map_or("", String::as_str)
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
         if self.show_new_rule {
             iced::time::every(std::time::Duration::from_millis(250))
                 .map(|_| {
-                    if let Ok(content) = clipboard_win::get_clipboard_string() {
+                    if let Some(content) = Native::get_text() {
                         return serde_json::from_str::<MatchingRule>(&content).is_ok();
                     }
                     false
@@ -439,6 +540,48 @@ impl Rule {
     }
 }
 
+/// Shows the number of findings about a list of rules next to the `button` that opens it, if
+/// there are any.
+pub fn with_findings_badge<'a, Message: 'a>(
+    button: Element<'a, Message>,
+    count: usize,
+) -> Element<'a, Message> {
+    if count == 0 {
+        return button;
+    }
+    let summary = if count == 1 {
+        "1 finding".to_string()
+    } else {
+        format!("{count} findings")
+    };
+    row![container(button).width(Fill), finding_badge(summary)]
+        .spacing(10)
+        .align_y(Center)
+        .into()
+}
+
+/// A small warning badge with `summary`, used to point out the findings about some rules.
+pub fn finding_badge<'a, Message: 'a>(summary: String) -> Element<'a, Message> {
+    container(
+        row![
+            icons::warning().size(12).color(*widget::YELLOW),
+            text(summary).size(12),
+        ]
+        .spacing(5)
+        .align_y(Center),
+    )
+    .padding(padding::left(8).right(8).top(2).bottom(2))
+    .style(|t| container::Style {
+        border: Border {
+            color: *widget::YELLOW,
+            width: 1.0,
+            radius: 10.0.into(),
+        },
+        ..container::transparent(t)
+    })
+    .into()
+}
+
 #[allow(clippy::too_many_arguments)]
 fn rule_view<'a>(
     rule: &'a IdWithIdentifier,
@@ -449,7 +592,10 @@ fn rule_view<'a>(
     change_id: impl Fn(String) -> Message + 'a,
     composing_add: Message,
     composing_remove: Option<Message>,
-) -> Row<'a, Message> {
+    id: &'a str,
+    regex_sample: &'a str,
+    change_regex_sample: impl Fn(String) -> Message + 'a,
+) -> Column<'a, Message> {
     let kind: Element<_> = if edit {
         pick_list(
             &APPLICATION_IDENTIFIER_OPTIONS[..],
@@ -493,10 +639,7 @@ fn rule_view<'a>(
         .into()
     };
     let id: Element<_> = if edit {
-        text_input("", &rule.id)
-            .on_input(change_id)
-            .width(Fill)
-            .into()
+        text_input("", id).on_input(change_id).width(Fill).into()
     } else {
         container(text(&rule.id))
             .padding(5)
@@ -534,11 +677,79 @@ fn rule_view<'a>(
         )
         .flatten();
 
-    row![kind, matching_strategy, id]
+    let line = row![kind, matching_strategy, id]
         .push(composing_add_button)
         .push(delete_rule_line_button)
         .spacing(10)
-        .align_y(Center)
+        .align_y(Center);
+
+    let is_regex = matches!(rule.matching_strategy, Some(MatchingStrategy::Regex));
+    let error = regex_error(id, rule.matching_strategy.as_ref())
+        .map(|error| text(error).size(12).style(text::danger));
+    let preview = (edit && is_regex).then(|| {
+        let result = regex::Regex::new(id)
+            .ok()
+            .filter(|_| !regex_sample.is_empty())
+            .map(|regex| regex_preview(&regex, regex_sample));
+        column![
+            row![
+                text("Test:").size(12),
+                text_input("Type some text to test the regex...", regex_sample)
+                    .on_input(change_regex_sample)
+                    .size(12)
+                    .width(Fill),
+            ]
+            .spacing(10)
+            .align_y(Center),
+        ]
+        .push(result)
+        .spacing(5)
+    });
+
+    column![line].push(error).push(preview).spacing(5)
+}
+
+/// The error of the regex `id`, if the `matching_strategy` is the regex one and `id` isn't a valid
+/// regex.
+pub fn regex_error(id: &str, matching_strategy: Option<&MatchingStrategy>) -> Option<String> {
+    if !matches!(matching_strategy, Some(MatchingStrategy::Regex)) {
+        return None;
+    }
+    regex::Regex::new(id).err().map(|error| error.to_string())
+}
+
+/// The identifier on the line `sub_idx` of the rule `idx`, where simple rules only have one line.
+fn identifier_mut(
+    rules: &mut Option<Vec<MatchingRule>>,
+    idx: usize,
+    sub_idx: usize,
+) -> Option<&mut IdWithIdentifier> {
+    match rules.as_mut()?.get_mut(idx)? {
+        MatchingRule::Simple(rule) => Some(rule),
+        MatchingRule::Composite(rules) => rules.get_mut(sub_idx),
+    }
+}
+
+/// Shows whether `regex` matches `sample` and what each of its groups captures.
+fn regex_preview<'a>(regex: &regex::Regex, sample: &str) -> Column<'a, Message> {
+    let Some(captures) = regex.captures(sample) else {
+        return column![text("Doesn't match").size(12).style(text::danger)];
+    };
+    let groups = regex
+        .capture_names()
+        .enumerate()
+        .skip(1)
+        .map(|(idx, name)| {
+            let name = name.map_or_else(|| idx.to_string(), |name| format!("'{name}'"));
+            let group = match captures.get(idx) {
+                Some(capture) => text!("Group {name}: \"{}\"", capture.as_str()),
+                None => text!("Group {name}: not captured"),
+            };
+            group.size(12).into()
+        });
+    column![text("Matches").size(12).style(text::success)]
+        .extend(groups)
+        .spacing(2)
 }
 
 fn default_rule() -> IdWithIdentifier {
@@ -548,3 +759,107 @@ fn default_rule() -> IdWithIdentifier {
         matching_strategy: Some(MatchingStrategy::Equals),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_regexes_are_not_added() {
+        let mut rule = Rule::new();
+        let mut rules = None;
+        rule.new_rule = vec![IdWithIdentifier {
+            kind: ApplicationIdentifier::Title,
+            id: "Picture-in-(Picture".into(),
+            matching_strategy: Some(MatchingStrategy::Regex),
+        }];
+        let _ = rule.update(&mut rules, Message::AddNewRule);
+        assert!(rules.is_none());
+
+        let _ = rule.update(
+            &mut rules,
+            Message::ChangeNewRuleId(0, "Picture-in-(Picture)?".into()),
+        );
+        let _ = rule.update(&mut rules, Message::AddNewRule);
+        assert_eq!(rules.map(|rules| rules.len()), Some(1));
+    }
+
+    fn regex_rule(id: &str) -> MatchingRule {
+        MatchingRule::Simple(IdWithIdentifier {
+            kind: ApplicationIdentifier::Title,
+            id: id.into(),
+            matching_strategy: Some(MatchingStrategy::Regex),
+        })
+    }
+
+    #[test]
+    fn invalid_regexes_are_kept_off_the_config_until_they_are_valid() {
+        let mut rule = Rule::new();
+        let mut rules = Some(vec![regex_rule("Picture")]);
+
+        let _ = rule.update(&mut rules, Message::ChangeRuleId(0, 0, "Picture-(".into()));
+        assert_eq!(rules, Some(vec![regex_rule("Picture")]));
+        assert_eq!(
+            rule.rule_id(RuleLine::Existing(0, 0), &default_rule()),
+            "Picture-("
+        );
+
+        let _ = rule.update(&mut rules, Message::ChangeRuleId(0, 0, "Picture-()".into()));
+        assert_eq!(rules, Some(vec![regex_rule("Picture-()")]));
+        assert!(rule.pending_ids.is_empty());
+
+        let mut rules = Some(vec![MatchingRule::Simple(IdWithIdentifier {
+            kind: ApplicationIdentifier::Title,
+            id: "Picture-(".into(),
+            matching_strategy: Some(MatchingStrategy::Equals),
+        })]);
+        let before = rules.clone();
+        let _ = rule.update(
+            &mut rules,
+            Message::ChangeRuleMatchingStrategy(0, 0, Some(MatchingStrategy::Regex)),
+        );
+        assert_eq!(rules, before);
+    }
+
+    #[test]
+    fn regex_samples_stay_on_their_lines_when_rules_are_removed() {
+        let mut rule = Rule::new();
+        let mut rules = Some(vec![
+            regex_rule("a"),
+            MatchingRule::Composite(vec![
+                IdWithIdentifier {
+                    kind: ApplicationIdentifier::Exe,
+                    id: "b".into(),
+                    matching_strategy: Some(MatchingStrategy::Regex),
+                },
+                IdWithIdentifier {
+                    kind: ApplicationIdentifier::Title,
+                    id: "c".into(),
+                    matching_strategy: Some(MatchingStrategy::Regex),
+                },
+            ]),
+        ]);
+        for (line, sample) in [
+            (RuleLine::Existing(0, 0), "a"),
+            (RuleLine::Existing(1, 0), "b"),
+            (RuleLine::Existing(1, 1), "c"),
+        ] {
+            let _ = rule.update(&mut rules, Message::ChangeRegexSample(line, sample.into()));
+        }
+
+        let _ = rule.update(&mut rules, Message::RemoveRule(0));
+        assert_eq!(
+            rule.regex_samples,
+            HashMap::from([
+                (RuleLine::Existing(0, 0), "b".to_string()),
+                (RuleLine::Existing(0, 1), "c".to_string()),
+            ])
+        );
+
+        let _ = rule.update(&mut rules, Message::ComposingRemoveFromRule(0, 0));
+        assert_eq!(
+            rule.regex_samples,
+            HashMap::from([(RuleLine::Existing(0, 0), "c".to_string())])
+        );
+    }
+}
@@ -1,26 +1,84 @@
-use super::rule::{self, Rule};
+use super::{
+    palette::SearchOption,
+    rule::{self, Rule},
+};
 
-use crate::{BOLD_FONT, ITALIC_FONT, widget::opt_helpers};
+use crate::lint::{self, Location};
+use crate::matching;
+use crate::widget::{self, opt_helpers};
+use crate::{BOLD_FONT, EMOJI_FONT, ITALIC_FONT};
 
 use iced::{
     Center, Element, Fill, Subscription, Task, padding,
-    widget::{Column, button, column, container, row, rule as ruler, scrollable, text},
+    widget::{Column, Row, button, column, container, row, rule as ruler, scrollable, space, text},
 };
 use komorebi_client::{MatchingRule, StaticConfig};
 
+/// The options on the rules screen, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new(
+        "Ignore Rules",
+        "Individual window ignore rules. Windows ignored by komorebi will not be hidden and will \
+        show on all workspaces.",
+    ),
+    SearchOption::new(
+        "Floating Applications Rules",
+        "Identify applications which should be managed as floating windows.",
+    ),
+    SearchOption::new(
+        "Manage Rules",
+        "Individual window force-manage rules. You can use this to try to force manage some \
+        window that is not being managed by komorebi.",
+    ),
+    SearchOption::new(
+        "Tray and Multi Window Applications",
+        "Identify tray and multi-window applications.",
+    )
+    .advanced(),
+    SearchOption::new(
+        "Object Name Change Applications",
+        "Identify applications that send EVENT_OBJECT_NAME_CHANGE on launch (very rare).",
+    )
+    .advanced(),
+    SearchOption::new(
+        "Slow Application Identifiers",
+        "Identify applications which are slow to send initial event notifications.",
+    )
+    .advanced(),
+    SearchOption::new(
+        "Layered Applications",
+        "Identify applications that have the WS_EXLAYERED extended window style.",
+    )
+    .advanced(),
+    SearchOption::new(
+        "Border Overflow Applications",
+        "Identify border overflow applications.",
+    )
+    .advanced(),
+];
+
 #[derive(Clone, Debug)]
 pub enum Message {
     SetScreen(Screen),
     SetMainRulesScreen,
     Rule(rule::Message),
+
+    // Rule tester Messages
+    ChangedSampleExe(String),
+    ChangedSampleTitle(String),
+    ChangedSampleClass(String),
+    ChangedSamplePath(String),
+    ClearSample,
+    GoToRule(Location),
 }
 
 #[derive(Clone, Debug)]
 pub enum Action {
     None,
+    GoToRule(Location),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Screen {
     IgnoreRules,
     FloatingApplications,
@@ -33,6 +91,17 @@ pub enum Screen {
 }
 
 impl Screen {
+    pub const ALL: [Screen; 8] = [
+        Screen::IgnoreRules,
+        Screen::FloatingApplications,
+        Screen::ManageRules,
+        Screen::TrayAndMultiWindowApplications,
+        Screen::ObjectNameChangeApplications,
+        Screen::SlowApplicationIdentifiers,
+        Screen::LayeredApplications,
+        Screen::BorderOverflowApplications,
+    ];
+
     fn to_str(&self) -> &'static str {
         match self {
             Screen::IgnoreRules => "Ignore Rules",
@@ -67,6 +136,8 @@ impl std::fmt::Display for Screen {
 #[derive(Debug, Default)]
 pub struct Rules {
     pub rule_screen: Option<(Rule, Screen)>,
+    /// The window that the rules are tested against.
+    pub sample: matching::Window,
 }
 
 impl Rules {
@@ -96,6 +167,12 @@ impl Rules {
                     );
                 }
             }
+            Message::ChangedSampleExe(exe) => self.sample.exe = exe,
+            Message::ChangedSampleTitle(title) => self.sample.title = title,
+            Message::ChangedSampleClass(class) => self.sample.class = class,
+            Message::ChangedSamplePath(path) => self.sample.path = path,
+            Message::ClearSample => self.sample = matching::Window::default(),
+            Message::GoToRule(location) => return (Action::GoToRule(location), Task::none()),
         }
         (Action::None, Task::none())
     }
@@ -103,6 +180,7 @@ impl Rules {
     pub fn view<'a>(
         &'a self,
         config: &'a StaticConfig,
+        problems: &[lint::Problem],
         show_advanced: bool,
     ) -> Element<'a, Message> {
         if let Some((rule, screen)) = &self.rule_screen {
@@ -111,7 +189,10 @@ impl Rules {
                 text!(" > {}:", screen).size(20).font(*BOLD_FONT)
             ];
             let rules = get_rules_from_config(config, screen);
-            let content = rule.view(rules.as_ref()).map(Message::Rule);
+            let findings = lint::rule_findings(problems, &Location::Rules(*screen));
+            let content = rule
+                .view_with_findings(rules.as_ref(), findings)
+                .map(Message::Rule);
             column![
                 title,
                 ruler::horizontal(2.0),
@@ -122,63 +203,93 @@ impl Rules {
             .spacing(10)
             .into()
         } else {
-            let ignore_rules_button = opt_helpers::opt_button(
-                Screen::IgnoreRules.to_str(),
-                Some(
-                    "Individual window ignore rules. Windows ignored \
-                    by komorebi will not be hidden and will show on all workspaces.",
+            let with_findings = |screen: Screen, button: Element<'a, Message>| {
+                let count = lint::rule_findings(problems, &Location::Rules(screen)).len();
+                rule::with_findings_badge(button, count)
+            };
+            let ignore_rules_button = with_findings(
+                Screen::IgnoreRules,
+                opt_helpers::opt_button(
+                    Screen::IgnoreRules.to_str(),
+                    Some(
+                        "Individual window ignore rules. Windows ignored \
+                        by komorebi will not be hidden and will show on all workspaces.",
+                    ),
+                    Message::SetScreen(Screen::IgnoreRules),
                 ),
-                Message::SetScreen(Screen::IgnoreRules),
             );
-            let floating_applications_button = opt_helpers::opt_button(
-                Screen::FloatingApplications.to_str(),
-                Some(
-                    "Identify applications which should be managed \
-                    as floating windows. Floating windows can be tiled later using the \
-                    command `toggle-float`, they are held by the workspace and can be \
-                    moved to specific workspaces using workspace rules.",
+            let floating_applications_button = with_findings(
+                Screen::FloatingApplications,
+                opt_helpers::opt_button(
+                    Screen::FloatingApplications.to_str(),
+                    Some(
+                        "Identify applications which should be managed \
+                        as floating windows. Floating windows can be tiled later using the \
+                        command `toggle-float`, they are held by the workspace and can be \
+                        moved to specific workspaces using workspace rules.",
+                    ),
+                    Message::SetScreen(Screen::FloatingApplications),
                 ),
-                Message::SetScreen(Screen::FloatingApplications),
             );
-            let manage_rules_button = opt_helpers::opt_button(
-                Screen::ManageRules.to_str(),
-                Some(
-                    "Individual window force-manage rules. You can use this \
-                    to try to force manage some window that is not being managed by komorebi.",
+            let manage_rules_button = with_findings(
+                Screen::ManageRules,
+                opt_helpers::opt_button(
+                    Screen::ManageRules.to_str(),
+                    Some(
+                        "Individual window force-manage rules. You can use this \
+                        to try to force manage some window that is not being managed by komorebi.",
+                    ),
+                    Message::SetScreen(Screen::ManageRules),
                 ),
-                Message::SetScreen(Screen::ManageRules),
             );
-            let tray_and_multi_window_applications_button = opt_helpers::opt_button(
-                Screen::TrayAndMultiWindowApplications.to_str(),
-                Some(
-                    "Identify tray and multi-window applications. You can try to \
-                    use this for windows that close/minimize to tray or apps that open \
-                    multiple windows if they are not behaving correctly.",
+            let tray_and_multi_window_applications_button = with_findings(
+                Screen::TrayAndMultiWindowApplications,
+                opt_helpers::opt_button(
+                    Screen::TrayAndMultiWindowApplications.to_str(),
+                    Some(
+                        "Identify tray and multi-window applications. You can try to \
+                        use this for windows that close/minimize to tray or apps that open \
+                        multiple windows if they are not behaving correctly.",
+                    ),
+                    Message::SetScreen(Screen::TrayAndMultiWindowApplications),
                 ),
-                Message::SetScreen(Screen::TrayAndMultiWindowApplications),
             );
-            let object_name_change_apps_button = opt_helpers::opt_button(
-                Screen::ObjectNameChangeApplications.to_str(),
-                Some(
-                    "Identify applications that send EVENT_OBJECT_NAME_CHANGE \
-                    on launch (very rare).",
+            let object_name_change_apps_button = with_findings(
+                Screen::ObjectNameChangeApplications,
+                opt_helpers::opt_button(
+                    Screen::ObjectNameChangeApplications.to_str(),
+                    Some(
+                        "Identify applications that send EVENT_OBJECT_NAME_CHANGE \
+                        on launch (very rare).",
+                    ),
+                    Message::SetScreen(Screen::ObjectNameChangeApplications),
                 ),
-                Message::SetScreen(Screen::ObjectNameChangeApplications),
             );
-            let slow_application_identifiers_button = opt_helpers::opt_button(
-                Screen::SlowApplicationIdentifiers.to_str(),
-                Some("Identify applications which are slow to send initial event notifications."),
-                Message::SetScreen(Screen::SlowApplicationIdentifiers),
+            let slow_application_identifiers_button = with_findings(
+                Screen::SlowApplicationIdentifiers,
+                opt_helpers::opt_button(
+                    Screen::SlowApplicationIdentifiers.to_str(),
+                    Some(
+                        "Identify applications which are slow to send initial event notifications.",
+                    ),
+                    Message::SetScreen(Screen::SlowApplicationIdentifiers),
+                ),
             );
-            let layered_applications_button = opt_helpers::opt_button(
-                Screen::LayeredApplications.to_str(),
-                Some("Identify applications that have the WS_EXLAYERED extended window style."),
-                Message::SetScreen(Screen::LayeredApplications),
+            let layered_applications_button = with_findings(
+                Screen::LayeredApplications,
+                opt_helpers::opt_button(
+                    Screen::LayeredApplications.to_str(),
+                    Some("Identify applications that have the WS_EXLAYERED extended window style."),
+                    Message::SetScreen(Screen::LayeredApplications),
+                ),
             );
-            let border_overflow_applications_button = opt_helpers::opt_button(
-                Screen::BorderOverflowApplications.to_str(),
-                Some("Identify border overflow applications."),
-                Message::SetScreen(Screen::BorderOverflowApplications),
+            let border_overflow_applications_button = with_findings(
+                Screen::BorderOverflowApplications,
+                opt_helpers::opt_button(
+                    Screen::BorderOverflowApplications.to_str(),
+                    Some("Identify border overflow applications."),
+                    Message::SetScreen(Screen::BorderOverflowApplications),
+                ),
             );
             let mut children = vec![
                 ignore_rules_button,
@@ -205,6 +316,15 @@ impl Rules {
                     border_overflow_applications_button,
                 ]);
             }
+            children.extend([
+                text("Test Rules:")
+                    .size(18)
+                    .font(*BOLD_FONT)
+                    .width(Fill)
+                    .into(),
+                ruler::horizontal(2.0).into(),
+                self.tester_view(config),
+            ]);
             column![
                 text!("{}:", self.title()).size(20).font(*BOLD_FONT),
                 ruler::horizontal(2.0),
@@ -214,6 +334,7 @@ impl Rules {
                         .width(Fill)
                         .padding(padding::top(10).bottom(10).right(20))
                 )
+                .id(opt_helpers::SECTION_ID)
             ]
             .spacing(10)
             .into()
@@ -224,6 +345,55 @@ impl Rules {
         "Rules"
     }
 
+    /// The panel to check which rules match a window with the properties typed on it.
+    fn tester_view<'a>(&'a self, config: &'a StaticConfig) -> Element<'a, Message> {
+        let fields = column![
+            opt_helpers::description_text(
+                "Type the properties of a window to see every rule that matches it, on any \
+                list. Properties left empty never match.",
+            ),
+            sample_field("Exe:", &self.sample.exe, Message::ChangedSampleExe),
+            sample_field("Title:", &self.sample.title, Message::ChangedSampleTitle),
+            sample_field("Class:", &self.sample.class, Message::ChangedSampleClass),
+            sample_field("Path:", &self.sample.path, Message::ChangedSamplePath),
+            button("Clear")
+                .on_press_maybe((!self.sample.is_empty()).then_some(Message::ClearSample))
+                .style(button::secondary),
+        ]
+        .spacing(10);
+
+        let results: Element<_> = if self.sample.is_empty() {
+            space().into()
+        } else {
+            let matching = matching::matching_rules(config, &self.sample);
+            if matching.is_empty() {
+                opt_helpers::description_text("No rule matches this window.").into()
+            } else {
+                let title = text!("Matching rules ({}):", matching.len()).font(*BOLD_FONT);
+                matching
+                    .into_iter()
+                    .fold(column![title].spacing(5), |col, (location, rule)| {
+                        col.push(
+                            row![
+                                column![
+                                    text(lint::describe_rule(rule)),
+                                    opt_helpers::to_description_text(text!("{location}")),
+                                ]
+                                .width(Fill),
+                                button(text("›").font(*EMOJI_FONT).size(25))
+                                    .on_press(Message::GoToRule(location))
+                                    .style(button::text),
+                            ]
+                            .align_y(Center),
+                        )
+                    })
+                    .into()
+            }
+        };
+
+        opt_helpers::opt_box(column![fields, results].spacing(20)).into()
+    }
+
     pub fn subscription(&self) -> Subscription<Message> {
         match &self.rule_screen {
             Some((rule, _)) => rule.subscription().map(Message::Rule),
@@ -232,9 +402,9 @@ impl Rules {
     }
 }
 
-fn get_rules_from_config<'a>(
+pub fn get_rules_from_config<'a>(
     config: &'a StaticConfig,
-    screen: &'a Screen,
+    screen: &Screen,
 ) -> &'a Option<Vec<MatchingRule>> {
     match screen {
         Screen::IgnoreRules => &config.ignore_rules,
@@ -248,9 +418,9 @@ fn get_rules_from_config<'a>(
     }
 }
 
-fn get_rules_from_config_mut<'a>(
+pub fn get_rules_from_config_mut<'a>(
     config: &'a mut StaticConfig,
-    screen: &'a Screen,
+    screen: &Screen,
 ) -> &'a mut Option<Vec<MatchingRule>> {
     match screen {
         Screen::IgnoreRules => &mut config.ignore_rules,
@@ -264,6 +434,19 @@ fn get_rules_from_config_mut<'a>(
     }
 }
 
+fn sample_field<'a>(
+    name: &'a str,
+    value: &'a str,
+    on_input: fn(String) -> Message,
+) -> Row<'a, Message> {
+    row![
+        text(name).width(50),
+        widget::input("", value, on_input, None)
+    ]
+    .spacing(10)
+    .align_y(Center)
+}
+
 fn nav_button<'a>(
     content: impl Into<iced::widget::Text<'a>>,
     on_press: Message,
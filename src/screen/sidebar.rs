@@ -113,6 +113,7 @@ impl Sidebar {
                 Screen::Animations,
                 Screen::Theme,
                 Screen::Rules,
+                Screen::Applications,
                 Screen::LiveDebug,
                 Screen::Settings,
             ]
@@ -1,5 +1,6 @@
 use crate::{
     BOLD_FONT, ITALIC_FONT,
+    screen::palette::SearchOption,
     widget::opt_helpers::{self, DisableArgs},
 };
 
@@ -12,6 +13,31 @@ use iced::{
 use komorebi_client::{Colour, Rgb, StackbarConfig, StackbarLabel, StackbarMode, TabsConfig};
 use komorebi_themes::{Base16Value, Base16Wrapper, CatppuccinValue};
 
+/// The options on the stackbar screen, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new("Stackbar Height", "Stackbar height. (default: 40)"),
+    SearchOption::new("Stackbar Label", "Stackbar label. (default: \"Title\")"),
+    SearchOption::new("Stackbar Mode", "Stackbar mode. (default \"OnStack\")"),
+    SearchOption::new("Stackbar Tabs Width", "Tabs width. (default: 200)"),
+    SearchOption::new(
+        "Stackbar Font Family",
+        "Tabs font family name. (default: empty string)",
+    ),
+    SearchOption::new("Stackbar Font Size", "Tabs font size. (default: '0')"),
+    SearchOption::new(
+        "Stackbar Background Colour",
+        "Tab background colour. (default: '0x333333')",
+    ),
+    SearchOption::new(
+        "Stackbar Focused Text Colour",
+        "Focused Tab text colour. (default: '0xFFFFFF')",
+    ),
+    SearchOption::new(
+        "Stackbar Unfocused Text Colour",
+        "Unfocused Tab text colour. (default: '0xB3B3B3')",
+    ),
+];
+
 #[derive(Clone, Debug)]
 pub enum Message {
     ConfigChange(ConfigChange),
@@ -1,4 +1,5 @@
 use crate::config::{DEFAULT_BASE16_THEME, DEFAULT_CATPPUCCIN_THEME, DEFAULT_CUSTOM_THEME};
+use crate::screen::palette::SearchOption;
 use crate::widget::opt_helpers::{self, DisableArgs};
 
 use std::collections::HashMap;
@@ -10,6 +11,47 @@ use komorebi_themes::{
 };
 use lazy_static::lazy_static;
 
+/// The options on the theme screen, in the order they are shown. The colours are the same for
+/// every type of theme.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new(
+        "Theme Type",
+        "Set a Theme to define all colours (default: None)",
+    ),
+    SearchOption::new("Theme Name", "The Theme variant to use"),
+    SearchOption::new(
+        "Single Border",
+        "Border colour when the container contains a single window",
+    ),
+    SearchOption::new(
+        "Stack Border",
+        "Border colour when the container contains multiple windows",
+    ),
+    SearchOption::new(
+        "Monocle Border",
+        "Border colour when the container is in monocle mode",
+    ),
+    SearchOption::new(
+        "Floating Border",
+        "Border colour when the window is floating",
+    ),
+    SearchOption::new(
+        "Unfocused Border",
+        "Border colour when the container is unfocused",
+    ),
+    SearchOption::new(
+        "Unfocused Locked Border",
+        "Border colour when the container is unfocused and locked",
+    ),
+    SearchOption::new("Stackbar Focused Text", "Stackbar focused tab text colour"),
+    SearchOption::new(
+        "Stackbar Unfocused Text",
+        "Stackbar unfocused tab text colour",
+    ),
+    SearchOption::new("Stackbar Background", "Stackbar tab background colour"),
+    SearchOption::new("Bar Accent", "Komorebi status bar accent"),
+];
+
 lazy_static! {
     static ref CATPPUCCIN_OPTIONS: [Catppuccin; 4] = [
         Catppuccin::Frappe,
@@ -1,4 +1,7 @@
-use super::rule::{self, Rule};
+use super::{
+    palette::SearchOption,
+    rule::{self, Rule},
+};
 
 use crate::config::DEFAULT_CONFIG;
 use crate::{
@@ -12,6 +15,23 @@ use iced::{
 };
 use komorebi_client::{MatchingRule, StaticConfig};
 
+/// The options on the transparency screen, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new(
+        "Transparency",
+        "Add transparency to unfocused windows (default: false)",
+    ),
+    SearchOption::new(
+        "Transparency Alpha",
+        "Alpha value for unfocused window transparency [[0-255]] (default: 200)",
+    ),
+    SearchOption::new(
+        "Transparency Ignore Rules",
+        "Individual window transparency ignore rules. Windows matched by these rules won't get \
+        transparency applied to them.",
+    ),
+];
+
 #[derive(Clone, Debug)]
 pub enum Message {
     ConfigChange(ConfigChange),
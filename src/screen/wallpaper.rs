@@ -2,9 +2,10 @@ use std::path::PathBuf;
 
 use crate::{
     screen::View,
+    utils,
     widget::{
-        icons,
         opt_helpers::{self, DisableArgs},
+        path_input::FoundFiles,
     },
 };
 
@@ -12,7 +13,7 @@ use std::sync::LazyLock;
 
 use iced::{
     Fill, Task,
-    widget::{button, center, opaque, row, space},
+    widget::{center, opaque, space},
 };
 use komorebi_client::{ThemeOptions, Wallpaper};
 use komorebi_themes::{Base16Value, ThemeVariant};
@@ -200,24 +201,24 @@ impl WallpaperScreen {
         Task::none()
     }
 
-    pub fn view<'a>(&self, wallpaper: &'a Wallpaper) -> View<'a, Message> {
+    pub fn view<'a>(
+        &self,
+        wallpaper: &'a Wallpaper,
+        found_files: &FoundFiles,
+    ) -> View<'a, Message> {
         let mut contents = vec![
             opt_helpers::opt_custom_el_disable_default(
                 "Path",
-                Some("Full path to the wallpaper image file"),
-                row![
-                    button(icons::folder())
-                        .style(subtle)
-                        .on_press(Message::PickFile),
-                    crate::widget::input(
-                        "",
-                        wallpaper.path.to_str().unwrap_or_default(),
-                        Message::Path,
-                        None,
-                    )
-                    .on_input(Message::Path)
-                ]
-                .spacing(10),
+                Some(
+                    "Path to the wallpaper image file. It can use environment variables, like \
+                    `$Env:USERPROFILE`.",
+                ),
+                crate::widget::path_input(
+                    wallpaper.path.to_str().unwrap_or_default(),
+                    found_files,
+                    Message::Path,
+                    Message::PickFile,
+                ),
                 !wallpaper.path.to_str().unwrap_or_default().is_empty(),
                 Some(Message::Path(String::new())),
                 DisableArgs::none(),
@@ -394,9 +395,9 @@ impl WallpaperScreen {
 }
 
 fn pick_file() -> Task<Message> {
-    let (home_dir, _) = crate::config::home_path();
+    let home_dir = crate::config::home_path().ok();
     Task::future(async move {
-        rfd::FileDialog::new()
+        utils::file_dialog(home_dir)
             .add_filter(
                 "image",
                 &[
@@ -405,7 +406,6 @@ fn pick_file() -> Task<Message> {
                     "jxr", "jxl",
                 ],
             )
-            .set_directory(home_dir.as_path())
             .pick_file()
     })
     .map(|res| match res {
@@ -413,15 +413,3 @@ fn pick_file() -> Task<Message> {
         None => Message::ClosedFilePicker,
     })
 }
-
-fn subtle(theme: &iced::Theme, status: button::Status) -> button::Style {
-    match status {
-        button::Status::Active => button::Style {
-            background: None,
-            ..button::subtle(theme, status)
-        },
-        button::Status::Hovered | button::Status::Pressed | button::Status::Disabled => {
-            button::subtle(theme, status)
-        }
-    }
-}
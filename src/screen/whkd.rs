@@ -7,7 +7,7 @@ pub use bindings::Bindings;
 pub use helpers::{get_vk_key_mods, modal_content};
 
 use crate::{
-    screen::View,
+    screen::{View, palette::SearchOption},
     whkd::{DEFAULT_WHKDRC, MODIFIERS, SEPARATOR, Shell, UNPADDED_SEPARATOR, WhkdBinary, Whkdrc},
     widget::{
         self, hover, icons,
@@ -26,6 +26,19 @@ use iced::{
     },
 };
 
+/// The options on the whkd screen, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new("Shell", "The Shell you want whkd to use."),
+    SearchOption::new(
+        "Pause Binding",
+        "Can be any hotkey combo to toggle all other hotkeys on/off.",
+    ),
+    SearchOption::new(
+        "Pause Hook",
+        "A command that should run when the keybind above is triggered.",
+    ),
+];
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Message {
     Shell(Shell),
@@ -205,14 +218,16 @@ impl Whkd {
             Some(Message::PauseBinding(None)),
             DisableArgs::none(),
         );
-        let pause_hook = hook_custom(
+        let pause_hook: Element<_> = container(hook_custom(
             &self.pause_hook_state,
             &self.pause_hook_content,
             &whkdrc.pause_hook,
             commands,
             commands_desc,
             theme,
-        );
+        ))
+        .id(opt_helpers::option_id("Pause Hook"))
+        .into();
 
         let content = opt_helpers::section_view("Whkd:", [shell, pause_binding, pause_hook]);
 
@@ -277,6 +292,11 @@ impl Whkd {
         Subscription::batch([navigation, keys])
     }
 
+    /// Checks if this screen is currently waiting for the user to press the keys of a binding.
+    pub fn is_binding_keys(&self) -> bool {
+        self.bind_key
+    }
+
     pub fn load_new_commands(&mut self, commands: &[String]) {
         self.pause_hook_state = combo_box::State::new(commands.to_vec());
     }
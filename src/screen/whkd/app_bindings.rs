@@ -2,6 +2,7 @@ use super::{MODIFIERS, SEPARATOR, UNPADDED_SEPARATOR, WhkdBinary, get_vk_key_mod
 
 use crate::{
     BOLD_FONT,
+    platform::{Native, ProcessControl},
     screen::View,
     whkd::{HotkeyBinding, Whkdrc},
     widget::{
@@ -20,10 +21,13 @@ use iced::{
         right_center, row, rule, scrollable, space, stack, text, text_editor,
     },
 };
-use smol::process::{Command, Stdio, windows::CommandExt};
 
-const SCROLLABLE_ID: &str = "BINDINGS_SCROLLABLE";
-const CREATE_NO_WINDOW: u32 = 0x08000000;
+pub const SCROLLABLE_ID: &str = "BINDINGS_SCROLLABLE";
+
+/// Id of the widget of the app binding at `idx`, so it can be scrolled to.
+pub fn binding_id(idx: usize) -> iced::widget::Id {
+    iced::widget::Id::from(format!("APP_BINDING_{idx}"))
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Message {
@@ -986,7 +990,8 @@ impl AppBindings {
                                 ..container::bordered_box(t)
                             }),
                         )
-                        .padding(padding::top(5).bottom(5)),
+                        .padding(padding::top(5).bottom(5))
+                        .id(binding_id(idx)),
                     )
                 } else {
                     let keys_count = app_binding.0.len();
@@ -1083,7 +1088,7 @@ impl AppBindings {
                         .padding(padding::right(5)),
                     );
 
-                    col.push(b)
+                    col.push(container(b).id(binding_id(idx)))
                 }
             })
             .into();
@@ -1183,6 +1188,11 @@ impl AppBindings {
         }
     }
 
+    /// Checks if this screen is currently waiting for the user to press the keys of a binding.
+    pub fn is_binding_keys(&self) -> bool {
+        self.modal_opened.is_some()
+    }
+
     pub fn load_new_commands(&mut self, commands: &[String]) {
         self.new_binding_state =
             vec![combo_box::State::new(commands.to_vec()); self.new_binding.1.len()];
@@ -1466,43 +1476,10 @@ fn split_keys(keys: &[String]) -> SplitBinding<'_> {
 
 fn get_current_process_names() -> Task<Message> {
     Task::perform(
-        async {
-            Command::new("powershell")
-                .arg("-NoProfile")
-                .arg("-C")
-                .raw_arg("\"Get-Process | Select ProcessName, Description\"")
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .creation_flags(CREATE_NO_WINDOW)
-                .output()
-                .await
-        },
-        |output| match output {
-            Ok(output) => {
-                if output.status.success() {
-                    let output = String::from_utf8_lossy(&output.stdout);
-                    let mut process_names = HashMap::new();
-                    dbg!(&output);
-                    output.trim().lines().skip(2).for_each(|line| {
-                        let trimmed_line = line.trim();
-                        let process_name =
-                            trimmed_line.split_whitespace().next().unwrap_or_default();
-                        if !process_name.is_empty() {
-                            let (_, process_app_name) =
-                                trimmed_line.split_at(process_name.chars().count());
-                            let process_app_name = process_app_name.trim();
-                            if !process_app_name.is_empty() {
-                                process_names
-                                    .insert(process_name.to_string(), process_app_name.to_string());
-                            }
-                        }
-                    });
-                    Message::GotProcessNames(process_names)
-                } else {
-                    Message::FailedToGetProcessNames
-                }
-            }
-            _ => Message::FailedToGetProcessNames,
+        Native::process_names(),
+        |process_names| match process_names {
+            Some(process_names) => Message::GotProcessNames(process_names),
+            None => Message::FailedToGetProcessNames,
         },
     )
 }
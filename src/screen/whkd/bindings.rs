@@ -20,7 +20,12 @@ use iced::{
     },
 };
 
-const SCROLLABLE_ID: &str = "BINDINGS_SCROLLABLE";
+pub const SCROLLABLE_ID: &str = "BINDINGS_SCROLLABLE";
+
+/// Id of the widget of the binding at `idx`, so it can be scrolled to.
+pub fn binding_id(idx: usize) -> iced::widget::Id {
+    iced::widget::Id::from(format!("BINDING_{idx}"))
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Message {
@@ -482,7 +487,8 @@ impl Bindings {
                                 ..container::bordered_box(t)
                             }),
                         )
-                        .padding(padding::top(5).bottom(5)),
+                        .padding(padding::top(5).bottom(5))
+                        .id(binding_id(idx)),
                     )
                 } else {
                     let keys_count = binding.keys.len();
@@ -531,7 +537,7 @@ impl Bindings {
                         .align_y(Center),
                     );
 
-                    col.push(b)
+                    col.push(container(b).id(binding_id(idx)))
                 }
             })
             .into();
@@ -617,6 +623,11 @@ impl Bindings {
         }
     }
 
+    /// Checks if this screen is currently waiting for the user to press the keys of a binding.
+    pub fn is_binding_keys(&self) -> bool {
+        self.modal_opened.is_some()
+    }
+
     pub fn load_new_commands(&mut self, commands: &[String]) {
         self.new_binding_state = combo_box::State::new(commands.to_vec());
     }
@@ -4,13 +4,15 @@ use crate::config::{DEFAULT_CONFIG, DEFAULT_WORKSPACE_CONFIG};
 use crate::komo_interop::layout::{
     LAYOUT_FLIP_OPTIONS, LAYOUT_OPTIONS, LAYOUT_OPTIONS_WITHOUT_NONE, Layout,
 };
+use crate::lint::{self, Location};
 use crate::screen::{
-    View,
+    View, general, monitors,
+    palette::SearchOption,
     wallpaper::{self, WallpaperScreen},
 };
 use crate::utils::{DisplayOption, DisplayOptionCustom};
 use crate::widget::opt_helpers::{DisableArgs, description_text as t, to_description_text as td};
-use crate::widget::{ICONS, icons, opt_helpers};
+use crate::widget::{ICONS, icons, opt_helpers, path_input::FoundFiles};
 
 use std::collections::{BTreeMap, HashMap};
 
@@ -25,6 +27,73 @@ use komorebi_client::{
     Rect, ScrollingLayoutOptions, Wallpaper, WindowContainerBehaviour, WorkspaceConfig,
 };
 
+/// The options of a workspace, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new("Name", "Name of the workspace. Should be unique."),
+    SearchOption::new("Layout", "Layout (default: BSP)"),
+    SearchOption::new(
+        "Grid Layout Rows",
+        "Maximum number of rows per grid column. (default: None)",
+    ),
+    SearchOption::new(
+        "Scrolling Layout Columns",
+        "Desired number of visible columns (default: 3)",
+    ),
+    SearchOption::new(
+        "Scrolling Layout Center Focused Column",
+        "With an odd number of visible columns, keep the focused window column centered (default: false)",
+    ),
+    SearchOption::new(
+        "Layout Flip",
+        "Specify an axis on which to flip the selected layout (default: None)",
+    ),
+    SearchOption::new(
+        "Apply Window Based Work Area Offset",
+        "Apply this monitor's window-based work area offset (default: true)",
+    ),
+    SearchOption::new("Container Padding", "Container padding (default: global)"),
+    SearchOption::new(
+        "Float Override",
+        "Enable or disable float override, which makes it so every new window opens in floating mode (default: global)",
+    ),
+    SearchOption::new(
+        "Floating Layer Behaviour",
+        "Determine what happens to a new window when the Floating workspace layer is active (default: global)",
+    ),
+    SearchOption::new("Layout Rules", "Layout rules (default: None)"),
+    SearchOption::new(
+        "Tile Workspace",
+        "Enable or disable tiling for the workspace (default: true)",
+    ),
+    SearchOption::new(
+        "Window Container Behaviour",
+        "Determine what happens when a new window is opened (default: global)",
+    ),
+    SearchOption::new(
+        "Window Container Behaviour Rules",
+        "Window Container Behaviour rules (default: None)",
+    ),
+    SearchOption::new("Workspace Padding", "Workspace padding (default: global)"),
+    SearchOption::new(
+        "Work Area Offset",
+        "Workspace-specific work area offset (default: global)",
+    )
+    .children(general::OFFSET_CHILDREN),
+    SearchOption::new(
+        "Wallpaper",
+        "Specify a wallpaper which will be set when switching to this workspace. (default: None)",
+    ),
+    SearchOption::new(
+        "Initial Workspace Rules",
+        "Initial workspace application rules. The matched windows only move to this worksapace \
+        once, after that you can freely move them anywhere.",
+    ),
+    SearchOption::new(
+        "Workspace Rules",
+        "Permanent workspace application rules. The matched windows will always move to this workspace.",
+    ),
+];
+
 #[derive(Clone, Debug)]
 pub enum Message {
     SetScreen(Screen),
@@ -108,10 +177,39 @@ pub struct Workspace {
     pub new_behaviour_rule_behaviour: WindowContainerBehaviour,
 }
 
+/// The findings about the rules of a workspace, as the index of each rule and a short summary.
+#[derive(Clone, Debug, Default)]
+pub struct RuleFindings {
+    pub workspace_rules: Vec<(usize, String)>,
+    pub initial_workspace_rules: Vec<(usize, String)>,
+}
+
+impl RuleFindings {
+    /// The findings from `problems` about the rules of the workspace `ws_idx` on the monitor
+    /// `m_idx`.
+    pub fn new(problems: &[lint::Problem], m_idx: usize, ws_idx: usize) -> Self {
+        Self {
+            workspace_rules: lint::rule_findings(
+                problems,
+                &Location::WorkspaceRules(m_idx, ws_idx),
+            ),
+            initial_workspace_rules: lint::rule_findings(
+                problems,
+                &Location::InitialWorkspaceRules(m_idx, ws_idx),
+            ),
+        }
+    }
+}
+
 pub trait WorkspaceScreen {
     fn update(&mut self, workspace: &mut Workspace, message: Message) -> (Action, Task<Message>);
 
-    fn view<'a>(&'a self, workspace: &'a Workspace) -> View<'a, Message>;
+    fn view<'a>(
+        &'a self,
+        workspace: &'a Workspace,
+        findings: RuleFindings,
+        found_files: &FoundFiles,
+    ) -> View<'a, Message>;
 }
 
 impl WorkspaceScreen for WorkspaceConfig {
@@ -126,7 +224,7 @@ impl WorkspaceScreen for WorkspaceConfig {
                 }
                 workspace.screen = screen.clone();
                 let task = operation::scroll_to(
-                    Id::new("monitors_scrollable"),
+                    Id::new(monitors::SCROLLABLE_ID),
                     AbsoluteOffset { x: 0.0, y: 0.0 },
                 );
                 return (Action::ScreenChange(screen), task);
@@ -454,19 +552,38 @@ impl WorkspaceScreen for WorkspaceConfig {
         (Action::None, Task::none())
     }
 
-    fn view<'a>(&'a self, workspace: &'a Workspace) -> View<'a, Message> {
+    fn view<'a>(
+        &'a self,
+        workspace: &'a Workspace,
+        findings: RuleFindings,
+        found_files: &FoundFiles,
+    ) -> View<'a, Message> {
         match workspace.screen {
-            Screen::Workspace => workspace.workspace_view(self).into(),
+            Screen::Workspace => workspace.workspace_view(self, &findings).into(),
             Screen::WorkspaceWallpaper => {
                 if let Some(wp_config) = self.wallpaper.as_ref() {
-                    workspace.wallpaper.view(wp_config).map(Message::Wallpaper)
+                    workspace
+                        .wallpaper
+                        .view(wp_config, found_files)
+                        .map(Message::Wallpaper)
                 } else {
                     View::new(space())
                 }
             }
-            Screen::WorkspaceRules | Screen::InitialWorkspaceRules => workspace
+            Screen::WorkspaceRules => workspace
                 .rule
-                .view(get_rules_from_config(self, &workspace.screen))
+                .view_with_findings(
+                    get_rules_from_config(self, &workspace.screen),
+                    findings.workspace_rules,
+                )
+                .map(Message::Rule)
+                .into(),
+            Screen::InitialWorkspaceRules => workspace
+                .rule
+                .view_with_findings(
+                    get_rules_from_config(self, &workspace.screen),
+                    findings.initial_workspace_rules,
+                )
                 .map(Message::Rule)
                 .into(),
         }
@@ -481,7 +598,11 @@ impl Workspace {
         }
     }
 
-    fn workspace_view<'a>(&'a self, ws_config: &'a WorkspaceConfig) -> Element<'a, Message> {
+    fn workspace_view<'a>(
+        &'a self,
+        ws_config: &'a WorkspaceConfig,
+        findings: &RuleFindings,
+    ) -> Element<'a, Message> {
         let name = opt_helpers::input(
             "Name",
             Some("Name of the workspace. Should be unique."),
@@ -804,20 +925,26 @@ impl Workspace {
                 },
             )),
         );
-        let initial_workspace_rules_button = opt_helpers::opt_button(
-            "Initial Workspace Rules",
-            Some(
-                "Initial workspace application rules. The matched windows only move to this worksapace once, \
-                after that you can freely move them anywhere.",
+        let initial_workspace_rules_button = rule::with_findings_badge(
+            opt_helpers::opt_button(
+                "Initial Workspace Rules",
+                Some(
+                    "Initial workspace application rules. The matched windows only move to this worksapace once, \
+                    after that you can freely move them anywhere.",
+                ),
+                Message::SetScreen(Screen::InitialWorkspaceRules),
             ),
-            Message::SetScreen(Screen::InitialWorkspaceRules),
+            findings.initial_workspace_rules.len(),
         );
-        let workspace_rules_button = opt_helpers::opt_button(
-            "Workspace Rules",
-            Some(
-                "Permanent workspace application rules. The matched windows will always move to this workspace.",
+        let workspace_rules_button = rule::with_findings_badge(
+            opt_helpers::opt_button(
+                "Workspace Rules",
+                Some(
+                    "Permanent workspace application rules. The matched windows will always move to this workspace.",
+                ),
+                Message::SetScreen(Screen::WorkspaceRules),
             ),
-            Message::SetScreen(Screen::WorkspaceRules),
+            findings.workspace_rules.len(),
         );
         column![
             name,
@@ -1127,3 +1254,59 @@ fn layout_options_descriptions<'a>() -> Vec<Element<'a, Message>> {
         .into(),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::Harness;
+
+    fn harness() -> Harness<Workspace> {
+        Harness::new(Workspace::new(0), DEFAULT_WORKSPACE_CONFIG.clone())
+    }
+
+    #[test]
+    fn config_changes_apply_to_the_workspace() {
+        let mut ui = harness();
+        ui.send([
+            Message::ConfigChange(ConfigChange::Name("Work".into())),
+            Message::ConfigChange(ConfigChange::Layout(Some(Layout::Columns))),
+            Message::ConfigChange(ConfigChange::ContainerPadding(Some(4))),
+        ]);
+        assert_eq!(ui.state.name, "Work");
+        assert_eq!(ui.state.layout, Some(DefaultLayout::Columns));
+        assert_eq!(ui.state.container_padding, Some(4));
+    }
+
+    #[test]
+    fn layout_rules_are_added_and_removed() {
+        let mut ui = harness();
+        ui.send([
+            Message::ChangeNewLayoutRuleLimit(3),
+            Message::ChangeNewLayoutRuleLayout(Layout::Grid),
+            Message::AddNewLayoutRule,
+        ]);
+        assert_eq!(
+            ui.state.layout_rules,
+            Some(HashMap::from([(3, DefaultLayout::Grid)]))
+        );
+        assert_eq!(ui.screen.new_layout_rule_limit, 0);
+
+        ui.send([Message::ConfigChange(ConfigChange::LayoutRuleLimit((3, 5)))]);
+        assert_eq!(
+            ui.state.layout_rules,
+            Some(HashMap::from([(5, DefaultLayout::Grid)]))
+        );
+
+        ui.send([Message::RemoveLayoutRule(5)]);
+        assert_eq!(ui.state.layout_rules, Some(HashMap::new()));
+    }
+
+    #[test]
+    fn clicking_rules_button_opens_the_rules() {
+        let mut ui = harness();
+        ui.click("Workspace Rules");
+        assert!(matches!(ui.screen.screen, Screen::WorkspaceRules));
+        assert!(!ui.contains("Initial Workspace Rules"));
+    }
+}
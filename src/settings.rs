@@ -1,6 +1,10 @@
 use crate::BOLD_FONT;
 use crate::apperror::{AppError, AppErrorKind};
-use crate::widget::opt_helpers;
+use crate::config::migration::{self, TargetVersion};
+use crate::platform::{self, new_debouncer};
+use crate::screen::palette::SearchOption;
+use crate::utils;
+use crate::widget::opt_helpers::{self, DisableArgs};
 
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -13,12 +17,46 @@ use iced::{
     widget::{column, rule, text},
 };
 use notify_debouncer_mini::{
-    DebounceEventResult, DebouncedEvent, DebouncedEventKind, Debouncer, new_debouncer,
-    notify::{ReadDirectoryChangesWatcher, RecursiveMode},
+    DebounceEventResult, DebouncedEvent, DebouncedEventKind, Debouncer, notify::RecursiveMode,
 };
 use serde::{Deserialize, Serialize};
 use smol::channel::{self, Receiver};
 
+/// The options on the settings screen, in the order they are shown.
+pub const OPTIONS: &[SearchOption] = &[
+    SearchOption::new("Theme", "Theme for the Komorice app"),
+    SearchOption::new(
+        "Show advanced options",
+        "By default Komorice tries to be as simple as possible for new users by showing only the \
+        simpler options that should be required to use and configure komorebi.",
+    ),
+    SearchOption::new(
+        "Show Save Warning",
+        "Show overwrite warning message on config save.",
+    ),
+    SearchOption::new(
+        "Backups to keep",
+        "Amount of backups kept for each config file.",
+    ),
+    SearchOption::new(
+        "Save for komorebi version",
+        "Komorebi version that 'komorebi.json' is saved for.",
+    ),
+    SearchOption::new(
+        "Autosave and apply changes",
+        "Save the config automatically a moment after each change, without asking for \
+        confirmation.",
+    ),
+    SearchOption::new(
+        "Komorebi config folder",
+        "Folder used for 'komorebi.json' instead of the one on $Env:KOMOREBI_CONFIG_HOME.",
+    ),
+    SearchOption::new(
+        "whkd config folder",
+        "Folder used for 'whkdrc' instead of the one on $Env:WHKD_CONFIG_HOME.",
+    ),
+];
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Settings {
@@ -26,6 +64,14 @@ pub struct Settings {
     pub theme: Theme,
     pub show_advanced: bool,
     pub show_save_warning: bool,
+    pub backups_to_keep: usize,
+    pub active_profile: Option<String>,
+    pub save_version: TargetVersion,
+    pub autosave: bool,
+    /// The folder to use for the komorebi config instead of `KOMOREBI_CONFIG_HOME`.
+    pub komorebi_config_home: Option<PathBuf>,
+    /// The folder to use for the whkdrc instead of `WHKD_CONFIG_HOME`.
+    pub whkd_config_home: Option<PathBuf>,
     #[serde(skip)]
     settings_watcher_tx: Option<smol::channel::Sender<Input>>,
 }
@@ -36,6 +82,12 @@ impl Default for Settings {
             theme: Theme::TokyoNightStorm,
             show_advanced: false,
             show_save_warning: true,
+            backups_to_keep: 10,
+            active_profile: None,
+            save_version: TargetVersion::Detected,
+            autosave: false,
+            komorebi_config_home: None,
+            whkd_config_home: None,
             settings_watcher_tx: None,
         }
     }
@@ -50,12 +102,19 @@ pub enum Message {
     ChangedTheme(Theme),
     ChangedShowAdvanced(bool),
     ChangedShowSaveWarning(bool),
+    ChangedBackupsToKeep(usize),
+    ChangedSaveVersion(TargetVersion),
+    ChangedAutosave(bool),
+    ChangedKomorebiConfigHome(Option<PathBuf>),
+    ChangedWhkdConfigHome(Option<PathBuf>),
     SettingsFileWatcherTx(smol::channel::Sender<Input>),
 }
 
 #[derive(Debug, Clone)]
 pub enum Action {
     None,
+    /// The folders to use for the configs have changed.
+    ChangedConfigHomes,
     Error(AppError),
 }
 
@@ -67,8 +126,14 @@ impl Settings {
             }
             Message::LoadedSettings(settings) => {
                 let sender = self.settings_watcher_tx.take();
+                let changed_config_homes = settings.komorebi_config_home
+                    != self.komorebi_config_home
+                    || settings.whkd_config_home != self.whkd_config_home;
                 *self = settings;
                 self.settings_watcher_tx = sender;
+                if changed_config_homes {
+                    return (Action::ChangedConfigHomes, Task::none());
+                }
             }
             Message::FailedToLoadSettings(apperror) => {
                 return (Action::Error(apperror), Task::none());
@@ -91,6 +156,26 @@ impl Settings {
                 self.show_save_warning = show_save_warning;
                 return (Action::None, save_task(self.clone()));
             }
+            Message::ChangedBackupsToKeep(backups_to_keep) => {
+                self.backups_to_keep = backups_to_keep;
+                return (Action::None, save_task(self.clone()));
+            }
+            Message::ChangedSaveVersion(save_version) => {
+                self.save_version = save_version;
+                return (Action::None, save_task(self.clone()));
+            }
+            Message::ChangedAutosave(autosave) => {
+                self.autosave = autosave;
+                return (Action::None, save_task(self.clone()));
+            }
+            Message::ChangedKomorebiConfigHome(path) => {
+                self.komorebi_config_home = path;
+                return (Action::ChangedConfigHomes, save_task(self.clone()));
+            }
+            Message::ChangedWhkdConfigHome(path) => {
+                self.whkd_config_home = path;
+                return (Action::ChangedConfigHomes, save_task(self.clone()));
+            }
         }
         (Action::None, Task::none())
     }
@@ -120,9 +205,73 @@ impl Settings {
             self.show_save_warning,
             Message::ChangedShowSaveWarning,
         );
-        let col = column![theme, show_advanced, show_save_warning]
-            .spacing(10)
-            .padding(padding::top(10).bottom(10).right(20));
+        let backups_to_keep = opt_helpers::number(
+            "Backups to keep:",
+            Some(
+                "Amount of backups kept for each config file. Every time a file is saved the \
+                previous version is copied to the backups folder first and the oldest backups \
+                are removed. You can restore a backup from the home screen.\n\n\
+                Set it to 0 to disable backups.",
+            ),
+            self.backups_to_keep,
+            Message::ChangedBackupsToKeep,
+        );
+        let save_version = opt_helpers::choose(
+            "Save for komorebi version:",
+            Some(
+                "Komorebi version that 'komorebi.json' is saved for. 'Detected' uses the version \
+                of the installed komorebic.\n\n\
+                Saving for an older version removes the options it doesn't have and writes the \
+                whole file again, so any comments on it are lost. The changes are shown and have \
+                to be confirmed before the file is written.",
+            ),
+            [TargetVersion::Detected]
+                .into_iter()
+                .chain(migration::SAVE_VERSIONS.iter().copied())
+                .collect::<Vec<_>>(),
+            Some(self.save_version),
+            Message::ChangedSaveVersion,
+        );
+        let autosave = opt_helpers::toggle(
+            "Autosave and apply changes",
+            Some(
+                "Save the config automatically a moment after each change, without asking for \
+                confirmation. When editing the active config, komorebi is told to reload it and \
+                whkd is restarted, so the changes are applied right away.\n\n\
+                Changes are not saved while there are conflicts with the file on disk to resolve \
+                or errors on 'Problems'. Saving for an older komorebi version is only confirmed \
+                once per session, and if it is cancelled the config isn't autosaved for that \
+                version anymore.",
+            ),
+            self.autosave,
+            Message::ChangedAutosave,
+        );
+        let komorebi_config_home = config_home(
+            "Komorebi config folder:",
+            "Folder used for 'komorebi.json' instead of the one on $Env:KOMOREBI_CONFIG_HOME. \
+            It is set when picking another folder because the usual one couldn't be used.",
+            self.komorebi_config_home.as_ref(),
+            Message::ChangedKomorebiConfigHome(None),
+        );
+        let whkd_config_home = config_home(
+            "whkd config folder:",
+            "Folder used for 'whkdrc' instead of the one on $Env:WHKD_CONFIG_HOME. It is set \
+            when picking another folder because the usual one couldn't be used.",
+            self.whkd_config_home.as_ref(),
+            Message::ChangedWhkdConfigHome(None),
+        );
+        let col = column![
+            theme,
+            show_advanced,
+            show_save_warning,
+            backups_to_keep,
+            save_version,
+            autosave,
+            komorebi_config_home,
+            whkd_config_home
+        ]
+        .spacing(10)
+        .padding(padding::top(10).bottom(10).right(20));
         column![title, rule::horizontal(2.0), col]
             .spacing(10)
             .width(Fill)
@@ -131,13 +280,31 @@ impl Settings {
     }
 }
 
+fn config_home<'a>(
+    name: &'a str,
+    description: &'a str,
+    path: Option<&PathBuf>,
+    reset_message: Message,
+) -> Element<'a, Message> {
+    let is_dirty = path.is_some();
+    let path = path.map_or_else(|| String::from("[None]"), |path| path.display().to_string());
+    opt_helpers::opt_custom_el_disable_default(
+        name,
+        Some(description),
+        text(path),
+        is_dirty,
+        Some(reset_message),
+        DisableArgs::none(),
+    )
+}
+
 enum State {
     Starting,
     Ready(Data),
 }
 
 struct Data {
-    debouncer: Debouncer<ReadDirectoryChangesWatcher>,
+    debouncer: Debouncer<platform::Watcher>,
     receiver: Receiver<Input>,
     ignore_event: usize,
 }
@@ -182,7 +349,18 @@ pub fn worker() -> Subscription<Message> {
 
                         match debouncer_res {
                             Ok(mut debouncer) => {
-                                let path = config_path();
+                                let path = match config_path() {
+                                    Ok(path) => path,
+                                    Err(apperror) => {
+                                        if let Err(e) =
+                                            output.send(Message::AppError(apperror)).await
+                                        {
+                                            println!("Error trying to send error:\n{e:?}");
+                                        }
+                                        smol::Timer::after(Duration::from_secs(60)).await;
+                                        continue;
+                                    }
+                                };
                                 if matches!(std::fs::exists(&path), Ok(false) | Err(_)) {
                                     // If the path doesn't exist, we save the default version to create it
                                     if let Err(apperror) = save(Settings::default()).await {
@@ -335,7 +513,7 @@ pub async fn load() -> Result<Settings, AppError> {
 
     let mut contents = String::new();
 
-    let file_open_res = smol::fs::File::open(config_path()).await;
+    let file_open_res = smol::fs::File::open(config_path()?).await;
 
     let mut file = match file_open_res {
         Ok(file) => file,
@@ -380,7 +558,7 @@ pub async fn save(settings: Settings) -> Result<(), AppError> {
         kind: AppErrorKind::Error,
     })?;
 
-    let path = config_path();
+    let path = config_path()?;
 
     if let Some(dir) = path.parent() {
         smol::fs::create_dir_all(dir).await.map_err(|e| AppError {
@@ -410,11 +588,8 @@ pub async fn save(settings: Settings) -> Result<(), AppError> {
     Ok(())
 }
 
-pub fn config_path() -> PathBuf {
-    dirs::data_local_dir()
-        .expect("there is no local data directory")
-        .join("komorice")
-        .join("settings.json")
+pub fn config_path() -> Result<PathBuf, AppError> {
+    utils::data_dir().map(|dir| dir.join("settings.json"))
 }
 
 /// A built-in theme.
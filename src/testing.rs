@@ -0,0 +1,299 @@
+//! A harness to test the screens without running the app.
+//!
+//! A `Harness` owns a screen and the in-memory config it edits. Messages can be sent to it
+//! directly, like the app does, or produced by clicking on the widgets of its view, which is laid
+//! out with the headless renderer of `iced_test`. The tasks returned by `update` aren't run, so
+//! only the changes made by `update` itself can be checked.
+
+use crate::screen::{
+    View, animation, border, general, monitor, monitors, rules, stackbar, theme, transparency,
+    workspace,
+};
+use crate::widget::{self, icons, path_input::FoundFiles};
+use crate::{settings, whkd};
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use iced::{Size, Theme, widget::Column};
+use iced_test::Simulator;
+use komorebi_client::{MonitorConfig, StaticConfig, WorkspaceConfig};
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref NO_DISPLAYS: HashMap<usize, monitors::DisplayInfo> = HashMap::new();
+    static ref THEME: Theme = Theme::Dark;
+}
+
+static NO_FOUND_FILES: FoundFiles = FoundFiles::new();
+
+/// The size of the window the views are laid out on. It is tall enough to fit the longest
+/// screens, so that every option can be clicked without having to scroll.
+const WINDOW_SIZE: Size = Size::new(1024.0, 4096.0);
+
+/// A screen that can be driven by a `Harness`.
+pub trait TestScreen {
+    /// The config edited by the screen.
+    type State;
+    type Message: Clone + Debug + 'static;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State);
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message>;
+}
+
+pub struct Harness<S: TestScreen> {
+    pub screen: S,
+    pub state: S::State,
+}
+
+impl<S: TestScreen> Harness<S> {
+    pub fn new(screen: S, state: S::State) -> Self {
+        Harness { screen, state }
+    }
+
+    /// Sends each of the `messages` to the screen, in order.
+    pub fn send(&mut self, messages: impl IntoIterator<Item = S::Message>) -> &mut Self {
+        for message in messages {
+            self.screen.update(message, &mut self.state);
+        }
+        self
+    }
+
+    /// Clicks on the widget showing `text` and sends the messages it produced to the screen.
+    ///
+    /// Panics if there is no such widget on the view.
+    pub fn click(&mut self, text: &str) -> &mut Self {
+        let messages = {
+            let mut ui = self.simulator();
+            if let Err(error) = ui.click(text) {
+                panic!("couldn't click on {text:?}: {error:?}");
+            }
+            ui.into_messages().collect::<Vec<_>>()
+        };
+        self.send(messages)
+    }
+
+    /// Whether some widget on the view shows `text`.
+    pub fn contains(&self, text: &str) -> bool {
+        self.simulator().find(text).is_ok()
+    }
+
+    /// Whether the view shows the option with `name`, whose label might end with a colon.
+    pub fn shows_option(&self, name: &str) -> bool {
+        self.contains(name) || self.contains(&format!("{name}:"))
+    }
+
+    fn simulator(&self) -> Simulator<'_, S::Message> {
+        let view = self.screen.view(&self.state);
+        let element = match view.modal {
+            Some(modal) => widget::modal(view.element, modal.element, modal.close_message),
+            None => view.element,
+        };
+        let settings = iced::Settings {
+            fonts: vec![icons::FONT.into()],
+            ..Default::default()
+        };
+        Simulator::with_size(settings, WINDOW_SIZE, element)
+    }
+}
+
+impl TestScreen for general::General {
+    type State = StaticConfig;
+    type Message = general::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let _ = general::General::update(self, message, state);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        general::General::view(self, state, true, &NO_FOUND_FILES)
+    }
+}
+
+impl TestScreen for border::Border {
+    type State = StaticConfig;
+    type Message = border::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let _ = border::Border::update(self, message, state);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        border::Border::view(self, state).into()
+    }
+}
+
+impl TestScreen for stackbar::Stackbar {
+    type State = StaticConfig;
+    type Message = stackbar::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let stackbar_config = state
+            .stackbar
+            .get_or_insert_with(stackbar::default_stackbar_config);
+        let _ = stackbar::Stackbar::update(self, message, stackbar_config);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        stackbar::Stackbar::view(self, state.stackbar.as_ref(), state.theme.as_ref()).into()
+    }
+}
+
+impl TestScreen for transparency::Transparency {
+    type State = StaticConfig;
+    type Message = transparency::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let _ = transparency::Transparency::update(self, message, state);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        transparency::Transparency::view(self, state).into()
+    }
+}
+
+impl TestScreen for animation::Animation {
+    type State = StaticConfig;
+    type Message = animation::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let animation_config = state
+            .animation
+            .get_or_insert_with(animation::default_animations_config);
+        let _ = animation::Animation::update(self, message, animation_config);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        animation::Animation::view(self, state.animation.as_ref()).into()
+    }
+}
+
+impl TestScreen for theme::Theme {
+    type State = StaticConfig;
+    type Message = theme::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let _ = theme::Theme::update(self, message, state);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        theme::Theme::view(self, state).into()
+    }
+}
+
+/// The rules screen is shown with the advanced rules and without any problems on them.
+impl TestScreen for rules::Rules {
+    type State = StaticConfig;
+    type Message = rules::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let _ = rules::Rules::update(self, message, state);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        rules::Rules::view(self, state, &[], true).into()
+    }
+}
+
+/// The monitor screen shows its contents in a column, like the monitors screen does.
+impl TestScreen for monitor::Monitor {
+    type State = MonitorConfig;
+    type Message = monitor::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let _ = monitor::Monitor::update(self, message, state);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        let monitor::MonitorView {
+            contents, modal, ..
+        } = monitor::Monitor::view(self, state, &[], &NO_FOUND_FILES);
+        View {
+            element: Column::with_children(contents).spacing(10).into(),
+            modal,
+        }
+    }
+}
+
+/// The monitors screen edits the monitors of the config, without any connected display.
+impl TestScreen for monitors::Monitors {
+    type State = StaticConfig;
+    type Message = monitors::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let monitors_config = state
+            .monitors
+            .as_mut()
+            .expect("the monitors screen is only tested with configs that have monitors");
+        let _ = monitors::Monitors::update(
+            self,
+            message,
+            monitors_config,
+            &mut state.display_index_preferences,
+            &mut HashMap::new(),
+        );
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        monitors::Monitors::view(
+            self,
+            state.monitors.as_deref().unwrap_or_default(),
+            &NO_DISPLAYS,
+            &state.display_index_preferences,
+            &[],
+            &NO_FOUND_FILES,
+        )
+    }
+}
+
+impl TestScreen for workspace::Workspace {
+    type State = WorkspaceConfig;
+    type Message = workspace::Message;
+
+    fn update(&mut self, message: Self::Message, state: &mut Self::State) {
+        let _ = workspace::WorkspaceScreen::update(state, self, message);
+    }
+
+    fn view<'a>(&'a self, state: &'a Self::State) -> View<'a, Self::Message> {
+        workspace::WorkspaceScreen::view(
+            state,
+            self,
+            workspace::RuleFindings::default(),
+            &NO_FOUND_FILES,
+        )
+    }
+}
+
+/// The whkd screens keep their own whkdrc, on `Whkd::whkdrc`, so they have no state.
+impl TestScreen for whkd::Whkd {
+    type State = ();
+    type Message = whkd::Message;
+
+    fn update(&mut self, message: Self::Message, _state: &mut Self::State) {
+        let _ = whkd::Whkd::update(self, message);
+    }
+
+    fn view<'a>(&'a self, _state: &'a Self::State) -> View<'a, Self::Message> {
+        whkd::Whkd::view(self, &THEME)
+    }
+}
+
+/// The settings are their own state, like the whkdrc on the whkd screens.
+impl TestScreen for settings::Settings {
+    type State = ();
+    type Message = settings::Message;
+
+    fn update(&mut self, message: Self::Message, _state: &mut Self::State) {
+        let _ = settings::Settings::update(self, message);
+    }
+
+    fn view<'a>(&'a self, _state: &'a Self::State) -> View<'a, Self::Message> {
+        settings::Settings::view(self).into()
+    }
+}
+
+/// The config with no options set, which is what a new komorebi.json looks like.
+pub fn empty_config() -> StaticConfig {
+    serde_json::from_str("{}").expect("an empty object is a valid config")
+}
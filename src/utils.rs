@@ -1,6 +1,9 @@
 use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
 
 use crate::NONE_STR;
+use crate::apperror::{AppError, AppErrorKind};
+use crate::screen::ConfigType;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct DisplayOption<T>(pub Option<T>);
@@ -25,3 +28,149 @@ impl<T: Display> Display for DisplayOptionCustom<T> {
         }
     }
 }
+
+/// The folder where komorice keeps its own data, like the settings, backups and profiles.
+pub fn data_dir() -> Result<PathBuf, AppError> {
+    dirs::data_local_dir()
+        .map(|dir| dir.join("komorice"))
+        .ok_or_else(|| AppError {
+            title: "There is no local data folder".into(),
+            description: Some(
+                "Komorice couldn't find the local data folder of this user, where it keeps its \
+                settings, backups and profiles."
+                    .into(),
+            ),
+            kind: AppErrorKind::Error,
+        })
+}
+
+/// A file dialog that starts on the `dir` folder, if there is one.
+pub fn file_dialog(dir: Option<PathBuf>) -> rfd::FileDialog {
+    match dir {
+        Some(dir) => rfd::FileDialog::new().set_directory(dir),
+        None => rfd::FileDialog::new(),
+    }
+}
+
+/// Checks that the `config_type` file at `path` can be read back. To be used as the `verify` of
+/// `write_verified`.
+pub fn verify_config(config_type: ConfigType, path: PathBuf) -> Result<(), String> {
+    match config_type {
+        ConfigType::Komorebi => {
+            let contents = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            serde_json::from_str::<komorebi_client::StaticConfig>(&contents)
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }
+        ConfigType::Whkd => whkd_parser::load(&path)
+            .map(|_| ())
+            .map_err(|e| format!("{e:#?}")),
+    }
+}
+
+/// Writes the `config_type` file `contents` to `path` with `write_verified`, making sure they can
+/// be read back as a config.
+pub async fn write_config(
+    config_type: ConfigType,
+    path: &Path,
+    contents: &[u8],
+) -> Result<(), AppError> {
+    write_verified(path, contents, config_type.file_name(), move |temp_path| {
+        verify_config(config_type, temp_path)
+    })
+    .await
+}
+
+/// Writes `contents` to `path` without ever leaving it half written.
+///
+/// The contents are first written to a temporary file on the same folder, which is then checked
+/// with `verify`. Only if it succeeds is the temporary file renamed over `path`, otherwise the
+/// original file is kept untouched and the temporary file is removed. The `file_name` is only used
+/// on the error messages.
+pub async fn write_verified<F>(
+    path: &Path,
+    contents: &[u8],
+    file_name: &str,
+    verify: F,
+) -> Result<(), AppError>
+where
+    F: FnOnce(PathBuf) -> Result<(), String> + Send + 'static,
+{
+    let temp_path = path.with_file_name(format!(
+        ".{}.komorice.tmp",
+        path.file_name()
+            .map_or_else(|| file_name.into(), |name| name.to_string_lossy())
+    ));
+
+    let res = write_and_verify(&temp_path, contents, file_name, verify).await;
+    let res = match res {
+        Ok(()) => smol::fs::rename(&temp_path, path)
+            .await
+            .map_err(|e| AppError {
+                title: format!("Error replacing '{file_name}' file"),
+                description: Some(format!(
+                    "The new contents were saved and verified on '{}' but it failed to replace \
+                    the original file, which was kept unchanged.\n\n{e}",
+                    temp_path.display(),
+                )),
+                kind: AppErrorKind::Error,
+            }),
+        Err(apperror) => Err(apperror),
+    };
+
+    if res.is_err() {
+        let _ = smol::fs::remove_file(&temp_path).await;
+    }
+
+    res
+}
+
+async fn write_and_verify<F>(
+    temp_path: &Path,
+    contents: &[u8],
+    file_name: &str,
+    verify: F,
+) -> Result<(), AppError>
+where
+    F: FnOnce(PathBuf) -> Result<(), String> + Send + 'static,
+{
+    use smol::prelude::*;
+
+    let mut file = smol::fs::File::create(temp_path)
+        .await
+        .map_err(|e| AppError {
+            title: format!("Error creating temporary file for '{file_name}'"),
+            description: Some(format!("{}\n\n{e}", temp_path.display())),
+            kind: AppErrorKind::Error,
+        })?;
+
+    file.write_all(contents).await.map_err(|e| AppError {
+        title: format!("Error saving '{file_name}' file"),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    })?;
+
+    file.sync_all().await.map_err(|e| AppError {
+        title: format!("Error saving '{file_name}' file"),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    })?;
+
+    file.close().await.map_err(|e| AppError {
+        title: format!("Error closing '{file_name}' file"),
+        description: Some(e.to_string()),
+        kind: AppErrorKind::Error,
+    })?;
+
+    let path = temp_path.to_path_buf();
+    smol::unblock(move || verify(path))
+        .await
+        .map_err(|e| AppError {
+            title: format!("Error verifying '{file_name}' file"),
+            description: Some(format!(
+                "The saved contents couldn't be read back, so the original file was kept \
+                unchanged. This is most likely a bug, please report it.\n\n{e}"
+            )),
+            kind: AppErrorKind::Error,
+        })
+}
@@ -1,15 +1,21 @@
+pub mod merge;
 pub mod unparser;
 
 use crate::{
     KOMOREBI_VERSION,
     apperror::{AppError, AppErrorKind},
+    backup,
+    config::{self, merge::Side},
+    history::History,
+    platform::{self, Native, ProcessControl, new_debouncer},
     screen::{self, ConfigState, ConfigType, Configuration, Screen, View},
+    utils,
 };
 
 use std::{
     collections::HashMap,
     path::PathBuf,
-    sync::{Arc, LazyLock},
+    sync::{Arc, LazyLock, RwLock},
     time::Duration,
 };
 
@@ -20,16 +26,14 @@ use iced::{
     widget::{markdown, space},
 };
 use notify_debouncer_mini::{
-    DebounceEventResult, DebouncedEvent, DebouncedEventKind, Debouncer, new_debouncer,
-    notify::{ReadDirectoryChangesWatcher, RecursiveMode},
+    DebounceEventResult, DebouncedEvent, DebouncedEventKind, Debouncer, notify::RecursiveMode,
 };
 use smol::channel::{self, Receiver, Sender};
-use smol::process::{Command, ExitStatus, Output, Stdio, windows::CommandExt};
+use smol::process::Stdio;
 pub use whkd_core::{HotkeyBinding, Shell, Whkdrc};
 
 pub static MODIFIERS: [&str; 4] = ["CTRL", "SHIFT", "ALT", "WIN"];
 
-const CREATE_NO_WINDOW: u32 = 0x08000000;
 pub const SEPARATOR: &str = " + ";
 pub const UNPADDED_SEPARATOR: &str = "+";
 
@@ -287,8 +291,9 @@ pub enum Message {
     WhkdrcFileWatcherTx(Sender<Input>),
     FileWatcherError(AppError),
     LoadedWhkdrc(Arc<Whkdrc>),
+    ChangedOnDisk(Arc<Whkdrc>),
     FailedToLoadWhkdrc(AppError),
-    SavedWhkdrc,
+    SavedWhkdrc(Arc<Whkdrc>),
     AppError(AppError),
 
     // Messages related to CLI commands fetch
@@ -327,6 +332,10 @@ pub struct Whkd {
     pub whkdrc: Whkdrc,
     pub loaded_whkdrc: Arc<Whkdrc>,
     pub is_dirty: bool,
+    pub history: History<Whkdrc>,
+    history_whkdrc: Whkdrc,
+    /// The conflicts left from merging the changes on disk, which are yet to be resolved.
+    pub conflicts: Vec<merge::Conflict>,
     pub whkd: screen::whkd::Whkd,
     pub bindings: screen::whkd::Bindings,
     pub app_bindings: screen::whkd::AppBindings,
@@ -344,6 +353,9 @@ impl Default for Whkd {
             whkdrc: DEFAULT_WHKDRC.clone(),
             loaded_whkdrc: Arc::new(DEFAULT_WHKDRC.clone()),
             is_dirty: false,
+            history: Default::default(),
+            history_whkdrc: DEFAULT_WHKDRC.clone(),
+            conflicts: Vec::new(),
             whkd: Default::default(),
             bindings: Default::default(),
             app_bindings: Default::default(),
@@ -361,6 +373,15 @@ impl Whkd {
         (Self::default(), Task::batch([find_whkd(), whkd_status()]))
     }
 
+    /// Restarts whkd, if it is running, so that it reads the active whkdrc again.
+    pub fn restart_whkd(&self) -> Task<Message> {
+        if self.whkd_bin.found && self.whkd_bin.running_current {
+            stop_whkd().chain(restart_whkd())
+        } else {
+            Task::none()
+        }
+    }
+
     pub fn update(&mut self, message: Message) -> (Action, Task<Message>) {
         match message {
             Message::WhkdrcFileWatcherTx(sender) => self.whkdrc_watcher_tx = Some(sender),
@@ -372,20 +393,35 @@ impl Whkd {
                     // println!("Whkdrc Loaded: {whkdrc:#?}");
                     self.whkdrc = whkdrc.clone();
                     self.loaded_whkdrc = Arc::new(whkdrc);
+                    self.conflicts.clear();
+                    self.reset_history();
                     self.refresh();
-                    //TODO: show message on app to load external changes
                     return (Action::LoadedWhkdrc, Task::none());
                 }
             }
+            Message::ChangedOnDisk(whkdrc) => {
+                if !self.is_dirty {
+                    return self.update(Message::LoadedWhkdrc(whkdrc));
+                }
+                let whkdrc = Arc::unwrap_or_clone(whkdrc);
+                let merged = merge::merge(&self.loaded_whkdrc, &self.whkdrc, &whkdrc);
+                self.record_history();
+                self.whkdrc = merged.whkdrc;
+                self.loaded_whkdrc = Arc::new(whkdrc);
+                self.conflicts = merged.conflicts;
+                self.check_changes();
+                self.refresh();
+            }
             Message::FailedToLoadWhkdrc(app_error) => {
                 return (Action::FailedToLoadWhkdrc(app_error), Task::none());
             }
-            Message::SavedWhkdrc => {
+            Message::SavedWhkdrc(saved) => {
                 if let Some(sender) = &self.whkdrc_watcher_tx {
                     let _ = sender.try_send(Input::IgnoreNextEvent);
                 }
-                self.loaded_whkdrc = Arc::new(self.whkdrc.clone());
-                self.is_dirty = false;
+                // The whkdrc might have changed while it was being saved
+                self.loaded_whkdrc = saved;
+                self.is_dirty = self.whkdrc != *self.loaded_whkdrc;
                 return (Action::SavedWhkdrc, Task::none());
             }
             Message::AppError(app_error) => {
@@ -569,10 +605,11 @@ impl Whkd {
         let worker = if matches!(configuration.config_type, ConfigType::Whkd)
             && (!matches!(configuration.whkd_state, ConfigState::New(_))
                 || configuration.saved_new_whkd)
+            && let Ok(path) = configuration.path()
         {
             // Only start the worker if has the config_type as `Whkd` and in case the whkd state is
             // `New` the worker should only run if it has already been saved once at least.
-            worker(configuration.path())
+            worker(path)
         } else {
             Subscription::none()
         };
@@ -583,11 +620,87 @@ impl Whkd {
     pub fn discard_changes(&mut self) {
         self.whkdrc = (*self.loaded_whkdrc).clone();
         self.is_dirty = false;
+        self.conflicts.clear();
+        self.record_history();
         self.refresh();
     }
 
+    /// Resolves the conflict at `idx` by keeping the version on `side`.
+    pub fn resolve_conflict(&mut self, idx: usize, side: Side) {
+        if idx < self.conflicts.len() {
+            let conflict = self.conflicts.remove(idx);
+            if matches!(side, Side::Mine) {
+                conflict.keep_mine(&mut self.whkdrc);
+                self.check_changes();
+                self.refresh();
+            }
+        }
+    }
+
+    /// Resolves all the remaining conflicts by keeping the version on `side`.
+    pub fn resolve_all_conflicts(&mut self, side: Side) {
+        let conflicts = std::mem::take(&mut self.conflicts);
+        if matches!(side, Side::Mine) {
+            for conflict in conflicts {
+                conflict.keep_mine(&mut self.whkdrc);
+            }
+            self.check_changes();
+            self.refresh();
+        }
+    }
+
     fn check_changes(&mut self) {
         self.is_dirty = self.whkdrc != *self.loaded_whkdrc;
+        self.record_history();
+    }
+
+    /// Adds an entry to the history if the whkdrc changed since the last recorded state.
+    fn record_history(&mut self) {
+        if self.whkdrc != self.history_whkdrc {
+            let label = describe_change(&self.history_whkdrc, &self.whkdrc);
+            let previous = std::mem::replace(&mut self.history_whkdrc, self.whkdrc.clone());
+            self.history.push(label, previous);
+        }
+    }
+
+    fn reset_history(&mut self) {
+        self.history.clear();
+        self.history_whkdrc = self.whkdrc.clone();
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(whkdrc) = self.history.undo(self.whkdrc.clone()) {
+            self.apply_history(whkdrc);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(whkdrc) = self.history.redo(self.whkdrc.clone()) {
+            self.apply_history(whkdrc);
+        }
+    }
+
+    pub fn jump_history(&mut self, position: usize) {
+        if let Some(whkdrc) = self.history.jump(position, self.whkdrc.clone()) {
+            self.apply_history(whkdrc);
+        }
+    }
+
+    fn apply_history(&mut self, whkdrc: Whkdrc) {
+        self.bindings.clear_editing();
+        self.app_bindings.clear_editing();
+        self.whkdrc = whkdrc;
+        self.history_whkdrc = self.whkdrc.clone();
+        self.is_dirty = self.whkdrc != *self.loaded_whkdrc;
+        self.refresh();
+    }
+
+    /// Checks if any of the screens is currently waiting for the user to press some keys to
+    /// create a binding.
+    pub fn is_binding_keys(&self) -> bool {
+        self.whkd.is_binding_keys()
+            || self.bindings.is_binding_keys()
+            || self.app_bindings.is_binding_keys()
     }
 
     pub fn load_default(&mut self) {
@@ -599,6 +712,8 @@ impl Whkd {
         self.whkdrc = DEFAULT_WHKDRC.clone();
         self.loaded_whkdrc = Arc::new(DEFAULT_WHKDRC.clone());
         self.is_dirty = false;
+        self.conflicts.clear();
+        self.reset_history();
 
         self.refresh();
     }
@@ -659,6 +774,42 @@ impl Whkd {
     }
 }
 
+/// Returns a short description of the changes between the `old` and `new` whkdrc, to be used as a
+/// label for the change (e.g. on the undo history).
+fn describe_change(old: &Whkdrc, new: &Whkdrc) -> String {
+    if old.shell != new.shell {
+        String::from("Shell")
+    } else if old.pause_binding != new.pause_binding {
+        String::from("Pause binding")
+    } else if old.pause_hook != new.pause_hook {
+        String::from("Pause hook")
+    } else if old.bindings.len() < new.bindings.len() {
+        String::from("Added binding")
+    } else if old.bindings.len() > new.bindings.len() {
+        String::from("Removed binding")
+    } else if let Some(binding) = new
+        .bindings
+        .iter()
+        .zip(old.bindings.iter())
+        .find_map(|(n, o)| (n != o).then_some(n))
+    {
+        format!("Binding: {}", binding.keys.join(SEPARATOR))
+    } else if old.app_bindings.len() < new.app_bindings.len() {
+        String::from("Added app binding")
+    } else if old.app_bindings.len() > new.app_bindings.len() {
+        String::from("Removed app binding")
+    } else if let Some((keys, _)) = new
+        .app_bindings
+        .iter()
+        .zip(old.app_bindings.iter())
+        .find_map(|(n, o)| (n != o).then_some(n))
+    {
+        format!("App binding: {}", keys.join(SEPARATOR))
+    } else {
+        String::from("Edit")
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct WhkdBinary {
     pub found: bool,
@@ -669,11 +820,10 @@ pub struct WhkdBinary {
 fn find_whkd() -> Task<Message> {
     Task::perform(
         async {
-            Command::new("whkd.exe")
+            Native::command(&platform::executable("whkd"))
                 .arg("--version")
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
-                .creation_flags(CREATE_NO_WINDOW)
                 .output()
                 .await
         },
@@ -691,106 +841,34 @@ fn find_whkd() -> Task<Message> {
 }
 
 fn whkd_status() -> Task<Message> {
-    Task::future(async {
-        Command::new("tasklist")
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .arg("/fi")
-            .raw_arg(r#""imagename eq whkd.exe""#)
-            .creation_flags(CREATE_NO_WINDOW)
-            .spawn()
-    })
-    .then(|output| match output {
-        Ok(output) => Task::perform(
-            async {
-                if let Some(stdout) = output.stdout
-                    && let Ok(stdout) = stdout.into_stdio().await
-                {
-                    Command::new("find")
-                        .args(["/I", "/N", "/C"])
-                        .raw_arg(r#""whkd.exe""#)
-                        .stdin(stdout)
-                        .stdout(Stdio::piped())
-                        .stderr(Stdio::piped())
-                        .creation_flags(CREATE_NO_WINDOW)
-                        .output()
-                        .await
-                } else {
-                    Ok(Output {
-                        status: ExitStatus::default(),
-                        stdout: Vec::new(),
-                        stderr: Vec::new(),
-                    })
-                }
-            },
-            |res| match res {
-                Ok(output) => {
-                    if output.status.success()
-                        && String::from_utf8_lossy(&output.stdout).trim() == "1"
-                    {
-                        Message::WhkdStatus(true)
-                    } else {
-                        Message::WhkdStatus(false)
-                    }
-                }
-                _ => Message::WhkdStatus(false),
-            },
-        ),
-        Err(_e) => Task::done(Message::WhkdStatus(false)),
-    })
+    Task::perform(
+        async { Native::is_running(&platform::executable("whkd")).await },
+        Message::WhkdStatus,
+    )
 }
 
 fn stop_whkd() -> Task<Message> {
     Task::perform(
-        async {
-            Command::new("taskkill")
-                .args(["/f", "/im", "whkd.exe"])
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .creation_flags(CREATE_NO_WINDOW)
-                .status()
-                .await
-        },
-        |res| match res {
-            Ok(status) if status.success() => Message::StoppedWhkd,
-            _ => Message::FailedToStopWhkd,
+        async { Native::kill(&platform::executable("whkd")).await },
+        |stopped| {
+            if stopped {
+                Message::StoppedWhkd
+            } else {
+                Message::FailedToStopWhkd
+            }
         },
     )
 }
 
 fn restart_whkd() -> Task<Message> {
     Task::perform(
-        async move {
-            Command::new("cmd")
-                .args([
-                    "/b",
-                    "/c",
-                    "start",
-                    "/b",
-                    "powershell.exe",
-                    "-NoProfile",
-                    "-NoLogo",
-                    "-C",
-                    "Start-Process",
-                    "whkd.exe",
-                    "-WindowStyle",
-                    "Hidden",
-                ])
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .creation_flags(CREATE_NO_WINDOW)
-                .status()
-                .await
-        },
-        |res| match res {
-            Ok(output) => {
-                if output.success() {
-                    Message::StartedWhkd
-                } else {
-                    Message::FailedToStartWhkd
-                }
+        async { Native::start_detached(&platform::executable("whkd")).await },
+        |started| {
+            if started {
+                Message::StartedWhkd
+            } else {
+                Message::FailedToStartWhkd
             }
-            _ => Message::FailedToStartWhkd,
         },
     )
 }
@@ -841,7 +919,7 @@ enum State {
 }
 
 struct Data {
-    debouncer: Debouncer<ReadDirectoryChangesWatcher>,
+    debouncer: Debouncer<platform::Watcher>,
     receiver: Receiver<Input>,
     ignore_event: usize,
 }
@@ -887,10 +965,12 @@ pub fn worker(path: PathBuf) -> Subscription<Message> {
 
                         match debouncer_res {
                             Ok(mut debouncer) => {
-                                if matches!(std::fs::exists(config_path()), Ok(false) | Err(_)) {
+                                if let Ok(default_path) = config_path()
+                                    && matches!(std::fs::exists(&default_path), Ok(false) | Err(_))
+                                {
                                     // If the default path doesn't exist, we save the default version to create it
                                     if let Err(apperror) =
-                                        save(DEFAULT_WHKDRC.clone(), config_path()).await
+                                        save(DEFAULT_WHKDRC.clone(), default_path, 0).await
                                     {
                                         match output.send(Message::AppError(apperror)).await {
                                             Ok(_) => {}
@@ -1022,7 +1102,7 @@ fn handle_event(
                 match load(path).await {
                     Ok(loaded_whkdrc) => {
                         let _ = output
-                            .send(Message::LoadedWhkdrc(Arc::new(loaded_whkdrc)))
+                            .send(Message::ChangedOnDisk(Arc::new(loaded_whkdrc)))
                             .await;
                     }
                     Err(e) => {
@@ -1055,16 +1135,15 @@ pub async fn load(path: PathBuf) -> Result<Whkdrc, AppError> {
     .await
 }
 
-pub fn save_task(whkdrc: Whkdrc, path: PathBuf) -> Task<Message> {
-    Task::future(save(whkdrc, path)).map(|res| match res {
-        Ok(_) => Message::SavedWhkdrc,
+pub fn save_task(whkdrc: Whkdrc, path: PathBuf, backups_to_keep: usize) -> Task<Message> {
+    let saved = Arc::new(whkdrc.clone());
+    Task::future(save(whkdrc, path, backups_to_keep)).map(move |res| match res {
+        Ok(_) => Message::SavedWhkdrc(saved.clone()),
         Err(apperror) => Message::AppError(apperror),
     })
 }
 
-pub async fn save(whkdrc: Whkdrc, path: PathBuf) -> Result<(), AppError> {
-    use smol::prelude::*;
-
+pub async fn save(whkdrc: Whkdrc, path: PathBuf, backups_to_keep: usize) -> Result<(), AppError> {
     let str = smol::unblock(move || unparser::unparse_whkdrc(&whkdrc)).await;
 
     if let Some(dir) = path.parent() {
@@ -1075,23 +1154,9 @@ pub async fn save(whkdrc: Whkdrc, path: PathBuf) -> Result<(), AppError> {
         })?;
     }
 
-    let mut file = smol::fs::File::create(path).await.map_err(|e| AppError {
-        title: "Error creating 'whkdrc' file.".into(),
-        description: Some(e.to_string()),
-        kind: AppErrorKind::Error,
-    })?;
-
-    file.write_all(str.as_bytes()).await.map_err(|e| AppError {
-        title: "Error saving 'whkdrc' file".into(),
-        description: Some(e.to_string()),
-        kind: AppErrorKind::Error,
-    })?;
+    backup::create(ConfigType::Whkd, &path, backups_to_keep).await?;
 
-    file.close().await.map_err(|e| AppError {
-        title: "Error closing 'whkdrc' file".into(),
-        description: Some(e.to_string()),
-        kind: AppErrorKind::Error,
-    })?;
+    utils::write_config(ConfigType::Whkd, &path, str.as_bytes()).await?;
 
     // This is a simple way to save at most once every couple seconds
     // smol::Timer::after(std::time::Duration::from_secs(2)).await;
@@ -1099,29 +1164,95 @@ pub async fn save(whkdrc: Whkdrc, path: PathBuf) -> Result<(), AppError> {
     Ok(())
 }
 
-pub fn home_path() -> PathBuf {
-    std::env::var("WHKD_CONFIG_HOME").map_or_else(
-        |_| {
-            dirs::home_dir()
-                .expect("there is no home directory")
-                .join(".config")
-        },
-        |home_path| {
-            let home = PathBuf::from(&home_path);
+/// The folder picked on Komorice to use instead of `WHKD_CONFIG_HOME`.
+static HOME_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
 
-            if home.as_path().is_dir() {
-                home
-            } else {
-                panic!(
-                    "$Env:WHKD_CONFIG_HOME is set to '{home_path}', which is not a valid directory"
-                );
-            }
-        },
-    )
+/// Sets the folder to use instead of `WHKD_CONFIG_HOME`, if any.
+pub fn set_home_override(path: Option<PathBuf>) {
+    if let Ok(mut home_override) = HOME_OVERRIDE.write() {
+        *home_override = path;
+    }
+}
+
+/// Finds the folder of the whkdrc.
+pub fn find_home_path() -> Result<PathBuf, config::HomePathError> {
+    config::find_config_home(&HOME_OVERRIDE, "WHKD_CONFIG_HOME", |home| {
+        home.join(".config")
+    })
+    .map(|(path, _)| path)
+}
+
+/// The path of the active whkdrc, which can't be read or written while its folder can't be used.
+pub fn config_path() -> Result<PathBuf, config::HomePathError> {
+    find_home_path().map(|home_dir| home_dir.join("whkdrc"))
 }
 
-pub fn config_path() -> PathBuf {
-    let home_dir = home_path();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    home_dir.join("whkdrc")
+    use crate::screen::whkd::bindings::Message as BindingsMessage;
+    use crate::testing::Harness;
+
+    fn bindings_harness() -> Harness<Whkd> {
+        let whkd = Whkd {
+            screen: Screen::WhkdBindings,
+            ..Default::default()
+        };
+        Harness::new(whkd, ())
+    }
+
+    #[test]
+    fn view_shows_the_whkd_options() {
+        let ui = Harness::new(Whkd::default(), ());
+        assert!(ui.contains("Shell"));
+    }
+
+    #[test]
+    fn new_binding_is_added_from_the_form() {
+        let mut ui = bindings_harness();
+        assert!(!ui.contains("Keybind"));
+        ui.click("Add New Binding");
+        assert!(ui.contains("Keybind"));
+
+        ui.send([
+            Message::Bindings(BindingsMessage::ChangeNewBindingMod(0, "alt".into())),
+            Message::Bindings(BindingsMessage::ChangeNewBindingKey("q".into())),
+            Message::Bindings(BindingsMessage::ChangeNewBindingCommand(
+                "komorebic close".into(),
+            )),
+        ]);
+        ui.click("Add");
+        let binding = ui.screen.whkdrc.bindings.last().expect("binding was added");
+        assert_eq!(binding.keys, ["alt", "q"]);
+        assert_eq!(binding.command, "komorebic close");
+        assert!(!ui.contains("Keybind"));
+    }
+
+    #[test]
+    fn duplicated_binding_cannot_be_added() {
+        let mut ui = bindings_harness();
+        let count = ui.screen.whkdrc.bindings.len();
+        ui.click("Add New Binding");
+        // `alt + o` is already used by the default whkdrc
+        ui.send([
+            Message::Bindings(BindingsMessage::ChangeNewBindingMod(0, "alt".into())),
+            Message::Bindings(BindingsMessage::ChangeNewBindingKey("o".into())),
+            Message::Bindings(BindingsMessage::ChangeNewBindingCommand(
+                "komorebic close".into(),
+            )),
+        ]);
+        ui.click("Add");
+        assert_eq!(ui.screen.whkdrc.bindings.len(), count);
+    }
+
+    #[test]
+    fn removing_a_binding_changes_whkdrc() {
+        let mut ui = bindings_harness();
+        let count = ui.screen.whkdrc.bindings.len();
+        let second = ui.screen.whkdrc.bindings[1].command.clone();
+        ui.send([Message::Bindings(BindingsMessage::RemoveBinding(0))]);
+        assert_eq!(ui.screen.whkdrc.bindings.len(), count - 1);
+        assert_eq!(ui.screen.whkdrc.bindings[0].command, second);
+    }
 }
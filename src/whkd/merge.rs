@@ -0,0 +1,378 @@
+//! Three-way merge of whkdrc files, used when the whkdrc changes on disk while there are unsaved
+//! changes on Komorice.
+//!
+//! Bindings are matched by their keys, so each binding is merged as a whole: a binding changed on
+//! only one side keeps that change, while a binding changed on both sides in different ways
+//! becomes a conflict that uses the version on disk until the user resolves it.
+
+use super::SEPARATOR;
+use crate::config::merge::Side;
+
+use whkd_core::{HotkeyBinding, Shell, Whkdrc};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+    Shell {
+        mine: Shell,
+        disk: Shell,
+    },
+    PauseBinding {
+        mine: Option<Vec<String>>,
+        disk: Option<Vec<String>>,
+    },
+    PauseHook {
+        mine: Option<String>,
+        disk: Option<String>,
+    },
+    Binding {
+        keys: Vec<String>,
+        mine: Option<HotkeyBinding>,
+        disk: Option<HotkeyBinding>,
+    },
+    AppBinding {
+        keys: Vec<String>,
+        mine: Option<Vec<HotkeyBinding>>,
+        disk: Option<Vec<HotkeyBinding>>,
+    },
+}
+
+impl Conflict {
+    pub fn label(&self) -> String {
+        match self {
+            Conflict::Shell { .. } => "Shell".into(),
+            Conflict::PauseBinding { .. } => "Pause Binding".into(),
+            Conflict::PauseHook { .. } => "Pause Hook".into(),
+            Conflict::Binding { keys, .. } => format!("Binding {}", keys.join(SEPARATOR)),
+            Conflict::AppBinding { keys, .. } => format!("App Binding {}", keys.join(SEPARATOR)),
+        }
+    }
+
+    pub fn mine_text(&self) -> String {
+        self.text(Side::Mine)
+    }
+
+    pub fn disk_text(&self) -> String {
+        self.text(Side::Disk)
+    }
+
+    /// Applies the version of this conflict from Komorice on `whkdrc`.
+    pub fn keep_mine(&self, whkdrc: &mut Whkdrc) {
+        match self {
+            Conflict::Shell { mine, .. } => whkdrc.shell = mine.clone(),
+            Conflict::PauseBinding { mine, .. } => whkdrc.pause_binding = mine.clone(),
+            Conflict::PauseHook { mine, .. } => whkdrc.pause_hook = mine.clone(),
+            Conflict::Binding { keys, mine, .. } => {
+                let position = whkdrc
+                    .bindings
+                    .iter()
+                    .position(|binding| same_keys(&binding.keys, keys));
+                replace(&mut whkdrc.bindings, position, mine.clone());
+            }
+            Conflict::AppBinding { keys, mine, .. } => {
+                let position = whkdrc
+                    .app_bindings
+                    .iter()
+                    .position(|(app_keys, _)| same_keys(app_keys, keys));
+                let mine = mine.clone().map(|bindings| (keys.clone(), bindings));
+                replace(&mut whkdrc.app_bindings, position, mine);
+            }
+        }
+    }
+
+    fn text(&self, side: Side) -> String {
+        fn pick<T>(side: Side, mine: T, disk: T) -> T {
+            match side {
+                Side::Mine => mine,
+                Side::Disk => disk,
+            }
+        }
+        let text = match self {
+            Conflict::Shell { mine, disk } => Some(pick(side, mine, disk).to_string()),
+            Conflict::PauseBinding { mine, disk } => pick(side, mine, disk)
+                .as_ref()
+                .map(|keys| keys.join(SEPARATOR)),
+            Conflict::PauseHook { mine, disk } => pick(side, mine, disk).clone(),
+            Conflict::Binding { mine, disk, .. } => pick(side, mine, disk)
+                .as_ref()
+                .map(|binding| binding.command.clone()),
+            Conflict::AppBinding { mine, disk, .. } => {
+                pick(side, mine, disk).as_ref().map(|bindings| {
+                    bindings
+                        .iter()
+                        .map(|binding| {
+                            format!(
+                                "{}: {}",
+                                binding.process_name.as_deref().unwrap_or("Default"),
+                                binding.command
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+            }
+        };
+        text.unwrap_or_else(|| String::from("[None]"))
+    }
+}
+
+#[derive(Debug)]
+pub struct Merged {
+    /// The whkdrc on disk with all the changes from Komorice that don't conflict with it.
+    pub whkdrc: Whkdrc,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Merges the changes made on Komorice (`mine`) and on disk (`disk`) since `base` was loaded.
+pub fn merge(base: &Whkdrc, mine: &Whkdrc, disk: &Whkdrc) -> Merged {
+    let mut conflicts = Vec::new();
+    let mut whkdrc = disk.clone();
+
+    match merge_item(&base.shell, &mine.shell, &disk.shell) {
+        Ok(shell) => whkdrc.shell = shell,
+        Err((mine, disk)) => conflicts.push(Conflict::Shell { mine, disk }),
+    }
+    match merge_item(
+        &base.pause_binding,
+        &mine.pause_binding,
+        &disk.pause_binding,
+    ) {
+        Ok(pause_binding) => whkdrc.pause_binding = pause_binding,
+        Err((mine, disk)) => conflicts.push(Conflict::PauseBinding { mine, disk }),
+    }
+    match merge_item(&base.pause_hook, &mine.pause_hook, &disk.pause_hook) {
+        Ok(pause_hook) => whkdrc.pause_hook = pause_hook,
+        Err((mine, disk)) => conflicts.push(Conflict::PauseHook { mine, disk }),
+    }
+
+    let keyed = |bindings: &[HotkeyBinding]| {
+        bindings
+            .iter()
+            .map(|binding| (binding.keys.clone(), binding.clone()))
+            .collect::<Vec<_>>()
+    };
+    whkdrc.bindings = merge_list(
+        &keyed(&base.bindings),
+        &keyed(&mine.bindings),
+        &keyed(&disk.bindings),
+        |keys, mine, disk| conflicts.push(Conflict::Binding { keys, mine, disk }),
+    )
+    .into_iter()
+    .map(|(_, binding)| binding)
+    .collect();
+    whkdrc.app_bindings = merge_list(
+        &base.app_bindings,
+        &mine.app_bindings,
+        &disk.app_bindings,
+        |keys, mine, disk| conflicts.push(Conflict::AppBinding { keys, mine, disk }),
+    );
+
+    Merged { whkdrc, conflicts }
+}
+
+/// Merges a single item, returning both versions if they conflict.
+fn merge_item<T: Clone + PartialEq>(base: &T, mine: &T, disk: &T) -> Result<T, (T, T)> {
+    if mine == base || mine == disk {
+        Ok(disk.clone())
+    } else if disk == base {
+        Ok(mine.clone())
+    } else {
+        Err((mine.clone(), disk.clone()))
+    }
+}
+
+/// Merges lists of items identified by their keys. The items keep the order they have on disk,
+/// followed by the ones only added on Komorice.
+fn merge_list<T: Clone + PartialEq>(
+    base: &[(Vec<String>, T)],
+    mine: &[(Vec<String>, T)],
+    disk: &[(Vec<String>, T)],
+    mut on_conflict: impl FnMut(Vec<String>, Option<T>, Option<T>),
+) -> Vec<(Vec<String>, T)> {
+    let find = |list: &[(Vec<String>, T)], keys: &[String]| {
+        list.iter()
+            .find(|(k, _)| same_keys(k, keys))
+            .map(|(_, item)| item.clone())
+    };
+    let added_on_komorice = mine
+        .iter()
+        .filter(|(keys, _)| find(disk, keys).is_none())
+        .map(|(keys, _)| keys);
+    let all_keys = disk
+        .iter()
+        .map(|(keys, _)| keys)
+        .chain(added_on_komorice)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    let mut merged = Vec::new();
+    for keys in all_keys {
+        let (base, mine, disk) = (find(base, &keys), find(mine, &keys), find(disk, &keys));
+        let item = match merge_item(&base, &mine, &disk) {
+            Ok(item) => item,
+            Err((mine, disk)) => {
+                on_conflict(keys.clone(), mine, disk.clone());
+                disk
+            }
+        };
+        if let Some(item) = item {
+            merged.push((keys, item));
+        }
+    }
+    merged
+}
+
+/// Whether both key combinations are the same, regardless of their order or case.
+fn same_keys(a: &[String], b: &[String]) -> bool {
+    let normalize = |keys: &[String]| {
+        let mut keys = keys.iter().map(|k| k.to_lowercase()).collect::<Vec<_>>();
+        keys.sort();
+        keys
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Replaces the item at `position` with `item`, removing it if there is no `item` or adding it if
+/// there is no `position`.
+fn replace<T>(list: &mut Vec<T>, position: Option<usize>, item: Option<T>) {
+    match (position, item) {
+        (Some(idx), Some(item)) => list[idx] = item,
+        (Some(idx), None) => {
+            list.remove(idx);
+        }
+        (None, Some(item)) => list.push(item),
+        (None, None) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::whkd::DEFAULT_WHKDRC;
+
+    fn binding(keys: &[&str], command: &str) -> HotkeyBinding {
+        HotkeyBinding {
+            keys: keys.iter().map(|k| k.to_string()).collect(),
+            command: command.into(),
+            process_name: None,
+        }
+    }
+
+    #[test]
+    fn bindings_changed_on_either_side_are_kept() {
+        let base = DEFAULT_WHKDRC.clone();
+        let mut mine = base.clone();
+        mine.bindings
+            .push(binding(&["alt", "q"], "komorebic close"));
+        let mut disk = base.clone();
+        disk.bindings.remove(0);
+        disk.shell = Shell::Cmd;
+
+        let merged = merge(&base, &mine, &disk);
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.whkdrc.shell, Shell::Cmd);
+        assert_eq!(merged.whkdrc.bindings.len(), base.bindings.len());
+        assert_eq!(merged.whkdrc.bindings[0], base.bindings[1]);
+        assert_eq!(
+            merged.whkdrc.bindings.last(),
+            Some(&binding(&["alt", "q"], "komorebic close"))
+        );
+    }
+
+    #[test]
+    fn binding_changed_on_both_sides_conflicts() {
+        let base = DEFAULT_WHKDRC.clone();
+        let keys = base.bindings[0]
+            .keys
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let mut mine = base.clone();
+        mine.bindings[0] = binding(&keys, "komorebic mine");
+        let mut disk = base.clone();
+        disk.bindings[0] = binding(&keys, "komorebic disk");
+
+        let mut merged = merge(&base, &mine, &disk);
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].mine_text(), "komorebic mine");
+        assert_eq!(merged.whkdrc.bindings[0].command, "komorebic disk");
+
+        merged.conflicts[0].keep_mine(&mut merged.whkdrc);
+        assert_eq!(merged.whkdrc, mine);
+    }
+
+    #[test]
+    fn settings_changed_on_both_sides_conflict() {
+        let base = DEFAULT_WHKDRC.clone();
+        let mut mine = base.clone();
+        mine.shell = Shell::Cmd;
+        mine.pause_binding = Some(vec!["alt".into(), "p".into()]);
+        mine.pause_hook = Some("komorebic toggle-pause".into());
+        let mut disk = base.clone();
+        disk.shell = Shell::Powershell;
+        disk.pause_binding = Some(vec!["alt".into(), "shift".into(), "p".into()]);
+        disk.pause_hook = Some("komorebic stop".into());
+
+        let mut merged = merge(&base, &mine, &disk);
+        let labels = merged
+            .conflicts
+            .iter()
+            .map(Conflict::label)
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["Shell", "Pause Binding", "Pause Hook"]);
+        assert_eq!(merged.whkdrc, disk);
+
+        for conflict in &merged.conflicts {
+            conflict.keep_mine(&mut merged.whkdrc);
+        }
+        assert_eq!(merged.whkdrc, mine);
+    }
+
+    #[test]
+    fn binding_removed_on_one_side_and_changed_on_the_other_conflicts() {
+        let base = DEFAULT_WHKDRC.clone();
+        let mut mine = base.clone();
+        let removed = mine.bindings.remove(0);
+        let mut disk = base.clone();
+        disk.bindings[0].command = "komorebic disk".into();
+
+        let mut merged = merge(&base, &mine, &disk);
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].mine_text(), "[None]");
+        assert_eq!(merged.whkdrc.bindings[0].keys, removed.keys);
+
+        merged.conflicts[0].keep_mine(&mut merged.whkdrc);
+        assert_eq!(merged.whkdrc, mine);
+    }
+
+    #[test]
+    fn app_binding_added_on_both_sides_conflicts() {
+        let base = DEFAULT_WHKDRC.clone();
+        let keys = vec!["alt".to_string(), "n".to_string()];
+        let app_binding = |process_name: &str, command: &str| {
+            let hotkey = HotkeyBinding {
+                process_name: Some(process_name.into()),
+                ..binding(&["alt", "n"], command)
+            };
+            (keys.clone(), vec![hotkey])
+        };
+        let mut mine = base.clone();
+        mine.app_bindings
+            .push(app_binding("firefox.exe", "komorebic mine"));
+        let mut disk = base.clone();
+        disk.app_bindings
+            .push(app_binding("firefox.exe", "komorebic disk"));
+
+        let mut merged = merge(&base, &mine, &disk);
+        assert_eq!(merged.conflicts.len(), 1);
+        assert_eq!(merged.conflicts[0].label(), "App Binding alt + n");
+        assert_eq!(
+            merged.conflicts[0].mine_text(),
+            "firefox.exe: komorebic mine"
+        );
+        assert_eq!(merged.whkdrc.app_bindings, disk.app_bindings);
+
+        merged.conflicts[0].keep_mine(&mut merged.whkdrc);
+        assert_eq!(merged.whkdrc, mine);
+    }
+}
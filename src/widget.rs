@@ -9,6 +9,7 @@ pub mod number_input;
 pub mod opaque_maybe;
 pub mod opt_button;
 pub mod opt_helpers;
+pub mod path_input;
 pub mod text_input;
 
 pub use helpers::*;
@@ -16,6 +17,7 @@ pub use hover::hover;
 pub use modal::modal;
 pub use opaque_maybe::opaque_maybe;
 pub use opt_button::opt_button;
+pub use path_input::path_input;
 
 use std::fmt::Display;
 use std::str::FromStr;
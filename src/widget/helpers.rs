@@ -1,9 +1,14 @@
 #![allow(dead_code)]
 use crate::widget::text_input::TextInput;
+use iced::advanced::widget::{
+    Operation,
+    operation::{Outcome, Scrollable},
+};
+use iced::widget::Id;
 use iced::widget::{
     Button, Row, Rule, Text, button, container, row, rule, text::IntoFragment, tooltip,
 };
-use iced::{Center, Element, Task, Theme};
+use iced::{Center, Element, Rectangle, Task, Theme, Vector};
 
 pub fn label<'a, Message>(text: impl Into<Text<'a>>) -> Row<'a, Message> {
     Row::new().push(text.into()).align_y(Center).height(30.0)
@@ -68,3 +73,89 @@ pub fn unfocus<M: Send + 'static>() -> Task<M> {
 
     widget::operate(widget::operation::focusable::unfocus())
 }
+
+/// A task that scrolls the scrollable with id `scrollable` until the widget with id `target`,
+/// somewhere on its contents, is at the top of it. Nothing happens if either of them isn't on the
+/// current view.
+pub fn scroll_to<M: Send + 'static>(scrollable: Id, target: Id) -> Task<M> {
+    find_offset(scrollable, target, false)
+}
+
+/// A task that scrolls the scrollable with id `scrollable` just enough for the widget with id
+/// `target` to be fully visible, if it isn't already.
+pub fn scroll_into_view<M: Send + 'static>(scrollable: Id, target: Id) -> Task<M> {
+    find_offset(scrollable, target, true)
+}
+
+fn find_offset<M: Send + 'static>(scrollable: Id, target: Id, only_if_hidden: bool) -> Task<M> {
+    use iced::advanced::widget;
+    use iced::widget::operation::{self, AbsoluteOffset};
+
+    widget::operate(FindOffset {
+        scrollable: scrollable.clone(),
+        target,
+        only_if_hidden,
+        viewport: None,
+        found: false,
+        offset: None,
+    })
+    .then(move |y| operation::scroll_to(scrollable.clone(), AbsoluteOffset { x: 0.0, y }))
+}
+
+/// Finds the offset the `scrollable` needs for the `target` widget on its contents to be visible.
+struct FindOffset {
+    scrollable: Id,
+    target: Id,
+    /// Whether to leave the scrollable as it is when the target is already visible, and to only
+    /// scroll as much as needed otherwise, instead of placing the target at the top.
+    only_if_hidden: bool,
+    /// The top of the contents of the scrollable, along with the height and offset it has.
+    viewport: Option<(f32, f32, f32)>,
+    found: bool,
+    offset: Option<f32>,
+}
+
+impl Operation<f32> for FindOffset {
+    fn traverse(&mut self, operate: &mut dyn FnMut(&mut dyn Operation<f32>)) {
+        operate(self);
+    }
+
+    fn scrollable(
+        &mut self,
+        id: Option<&Id>,
+        bounds: Rectangle,
+        content_bounds: Rectangle,
+        translation: Vector,
+        _state: &mut dyn Scrollable,
+    ) {
+        if id == Some(&self.scrollable) {
+            self.viewport = Some((content_bounds.y, bounds.height, translation.y));
+        }
+    }
+
+    fn container(&mut self, id: Option<&Id>, bounds: Rectangle) {
+        // Only the first widget with the id inside of the scrollable counts.
+        if self.found || id != Some(&self.target) {
+            return;
+        }
+        let Some((content_top, height, current)) = self.viewport else {
+            return;
+        };
+        self.found = true;
+        let top = (bounds.y - content_top).max(0.0);
+        let bottom = top + bounds.height;
+        self.offset = if !self.only_if_hidden {
+            Some(top)
+        } else if top < current {
+            Some(top)
+        } else if bottom > current + height {
+            Some(bottom - height)
+        } else {
+            None
+        };
+    }
+
+    fn finish(&self) -> Outcome<f32> {
+        self.offset.map_or(Outcome::None, Outcome::Some)
+    }
+}
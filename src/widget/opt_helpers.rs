@@ -12,7 +12,7 @@ use std::fmt::Display;
 use std::str::FromStr;
 
 use iced::{
-    Background, Center, Color, Element, Fill, border, padding,
+    Background, Center, Color, Element, Fill, Task, border, padding,
     widget::{
         Button, Column, Container, Row, Text, button, checkbox, column, combo_box, container,
         mouse_area, pick_list, row, rule, scrollable, space, text, toggler,
@@ -20,6 +20,22 @@ use iced::{
 };
 use num_traits::{Bounded, Num, NumAssignOps};
 
+/// Id of the scrollable on `section_view`.
+pub const SECTION_ID: &str = "SECTION_SCROLLABLE";
+
+/// Id of the widget of the option with `name`, so it can be scrolled to.
+pub fn option_id(name: &str) -> iced::widget::Id {
+    iced::widget::Id::from(format!("OPTION_{}", name.trim_end_matches(':')))
+}
+
+/// A task that scrolls the scrollable with id `scrollable` to the option with `name`.
+pub fn scroll_to_option<Message: Send + 'static>(
+    scrollable: impl Into<iced::widget::Id>,
+    name: &str,
+) -> Task<Message> {
+    widget::scroll_to(scrollable.into(), option_id(name))
+}
+
 #[derive(Debug, Clone)]
 pub struct DisableArgs<'a, Message, F>
 where
@@ -234,7 +250,7 @@ pub fn label_with_description<'a, Message: 'a>(
 
 ///Wraps an element `el` with `name` as label and a description on an opt_box
 pub fn opt_custom_el<'a, Message: 'a + Clone>(
-    name: impl Into<Text<'a>>,
+    name: &'a str,
     description: Option<&'a str>,
     element: impl Into<Element<'a, Message>>,
 ) -> Element<'a, Message> {
@@ -248,7 +264,7 @@ pub fn opt_custom_el_disable_default<
     Message: 'a + Clone,
     F: Fn(bool) -> Message + Clone + 'a,
 >(
-    name: impl Into<Text<'a>>,
+    name: &'a str,
     description: Option<impl Into<Description<'a, Message>>>,
     element: impl Into<Element<'a, Message>>,
     is_dirty: bool,
@@ -256,12 +272,12 @@ pub fn opt_custom_el_disable_default<
     disable_args: Option<DisableArgs<'a, Message, F>>,
 ) -> Element<'a, Message> {
     let label = if is_dirty {
-        row![name.into(), reset_button(reset_message)]
+        row![text(name), reset_button(reset_message)]
             .spacing(5)
             .height(30)
             .align_y(Center)
     } else {
-        row![name.into()].height(30).align_y(Center)
+        row![text(name)].height(30).align_y(Center)
     };
     let element = row![label_element_with_description(label, description)]
         .push(disable_checkbox(disable_args.as_ref()))
@@ -269,7 +285,7 @@ pub fn opt_custom_el_disable_default<
         .spacing(10)
         .align_y(Center);
 
-    opt_box(element).into()
+    opt_box(element).id(option_id(name)).into()
 }
 
 ///Creates a `button` with `name` as label and with a custom element as the button itself.
@@ -285,12 +301,15 @@ where
     F: Fn(bool) -> I + 'a,
     I: Into<Element<'a, Message>> + 'a,
 {
-    opt_button_internal::OptButton::<Message, F, I, fn(bool) -> Message>::with(
-        name,
-        description,
-        on_press,
-        element,
-    )
+    let name = name.into_fragment();
+    let id = option_id(&name);
+    container(opt_button_internal::OptButton::<
+        Message,
+        F,
+        I,
+        fn(bool) -> Message,
+    >::with(name, description, on_press, element))
+    .id(id)
     .into()
 }
 
@@ -366,11 +385,12 @@ pub fn opt_button_disable_default<
             }
         });
 
-    if !disabled {
-        button.on_press(on_press_c).into()
+    let button = if !disabled {
+        button.on_press(on_press_c)
     } else {
-        button.into()
-    }
+        button
+    };
+    container(button).id(option_id(name)).into()
 }
 
 ///Creates a `button` with `name` as label with "Delete", "Move Up" and "Move Down" buttons.
@@ -1288,7 +1308,9 @@ where
     I: IntoIterator<Item = Element<'a, Message>> + 'a,
     F: Fn(bool) -> Message + Clone + 'a,
 {
-    Expandable::with(
+    let name = name.into_fragment();
+    let id = option_id(&name);
+    container(Expandable::with(
         Some(name),
         description,
         None,
@@ -1298,7 +1320,8 @@ where
         disable_args,
         right_element,
         children,
-    )
+    ))
+    .id(id)
     .into()
 }
 
@@ -1344,6 +1367,7 @@ pub fn section_view<'a, Message: 'a>(
                 .spacing(10)
                 .width(Fill)
         )
+        .id(SECTION_ID)
     ]
     .spacing(10)
     .into()
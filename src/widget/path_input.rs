@@ -0,0 +1,149 @@
+use crate::config::paths;
+use crate::widget::{self, icons, opt_helpers::to_description_text};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use iced::widget::{Column, button, column, row, text};
+use iced::{Center, Element, padding};
+
+/// Whether the file of each value shown on the path inputs exists, as found by `check_files`, so
+/// that showing the inputs doesn't touch the file system.
+pub type FoundFiles = BTreeMap<String, bool>;
+
+/// Checks whether the files of the `values` shown on path inputs exist. It touches the file
+/// system, so it is meant to run on a `Task` whenever the values change.
+pub fn check_files(values: BTreeSet<String>) -> FoundFiles {
+    values
+        .into_iter()
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            let found = paths::resolve(&value).path.is_file();
+            (value, found)
+        })
+        .collect()
+}
+
+/// Creates a new [`PathInput`].
+///
+/// Path inputs display fields that can be filled with a file path, which can use environment
+/// variables like `$Env:USERPROFILE`, with a button to pick the file instead. Whether the file
+/// exists is looked up on `found_files`.
+pub fn path_input<'a, Message: Clone + 'a>(
+    value: &'a str,
+    found_files: &FoundFiles,
+    on_input: impl Fn(String) -> Message + 'a,
+    on_pick: Message,
+) -> PathInput<'a, Message> {
+    PathInput {
+        value,
+        found: found_files.get(value).copied(),
+        on_input: Box::new(on_input),
+        on_pick,
+        on_submit: None,
+        trailing: Vec::new(),
+    }
+}
+
+/// A field to edit a file path.
+///
+/// Below the field it shows the path with its variables resolved, whether the file exists and
+/// buttons to write an absolute path with any of the known variables instead.
+pub struct PathInput<'a, Message> {
+    value: &'a str,
+    /// Whether the file exists, if it was checked.
+    found: Option<bool>,
+    on_input: Box<dyn Fn(String) -> Message + 'a>,
+    on_pick: Message,
+    on_submit: Option<Message>,
+    trailing: Vec<Element<'a, Message>>,
+}
+
+impl<'a, Message: Clone + 'a> PathInput<'a, Message> {
+    /// Sets the message to be sent when the field is submitted, if any.
+    pub fn on_submit_maybe(mut self, on_submit: Option<Message>) -> Self {
+        self.on_submit = on_submit;
+        self
+    }
+
+    /// Adds an `element` to the right of the field.
+    pub fn push(mut self, element: impl Into<Element<'a, Message>>) -> Self {
+        self.trailing.push(element.into());
+        self
+    }
+
+    fn details(&self) -> Option<Column<'a, Message>> {
+        if self.value.is_empty() {
+            return None;
+        }
+        let resolved = paths::resolve(self.value);
+        let resolved_text = resolved.path.display().to_string();
+        let preview = (resolved_text != self.value)
+            .then(|| to_description_text(text!("Resolves to: {resolved_text}")).size(12));
+        let status = if !resolved.missing.is_empty() {
+            let missing = resolved
+                .missing
+                .iter()
+                .map(|name| format!("'{name}'"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(
+                text!("Variables not set: {missing}")
+                    .size(12)
+                    .style(text::danger),
+            )
+        } else {
+            self.found.map(|found| {
+                if found {
+                    text("File found").size(12).style(text::success)
+                } else {
+                    text("File not found").size(12).style(text::danger)
+                }
+            })
+        };
+        let suggestions = paths::express(self.value).into_iter().map(|path| {
+            button(text!("Use {path}").size(12))
+                .padding(0)
+                .style(button::text)
+                .on_press((self.on_input)(path))
+                .into()
+        });
+        Some(column![
+            preview,
+            status,
+            row(suggestions).spacing(10).align_y(Center).wrap()
+        ])
+    }
+}
+
+impl<'a, Message: Clone + 'a> From<PathInput<'a, Message>> for Element<'a, Message> {
+    fn from(path_input: PathInput<'a, Message>) -> Self {
+        let details = path_input.details();
+        let PathInput {
+            value,
+            found: _,
+            on_input,
+            on_pick,
+            on_submit,
+            trailing,
+        } = path_input;
+        let file_picker = button(icons::folder()).style(subtle).on_press(on_pick);
+        let input = widget::input("", value, on_input, on_submit);
+        let field = row![file_picker, input].extend(trailing).spacing(10);
+        column![field]
+            .push(details.map(|details| details.spacing(2).padding(padding::left(40))))
+            .spacing(5)
+            .into()
+    }
+}
+
+fn subtle(theme: &iced::Theme, status: button::Status) -> button::Style {
+    match status {
+        button::Status::Active => button::Style {
+            background: None,
+            ..button::subtle(theme, status)
+        },
+        button::Status::Hovered | button::Status::Pressed | button::Status::Disabled => {
+            button::subtle(theme, status)
+        }
+    }
+}